@@ -0,0 +1,144 @@
+// Renders a frame from an anim's diffuse layer, tinting the areas specified by its teamcolor layer
+// with the owning player's color. If lighting is enabled, the image is also lit by its emissive,
+// normal, specular and ao_depth layers and any dynamic lights nearby.
+
+#import bevy_sprite::{
+    mesh2d_functions as mesh_functions,
+    mesh2d_vertex_output::VertexOutput,
+}
+#import "shaders/image_effects.wgsl"::{apply_effect, EFFECT_OUTLINE}
+
+struct HdImageParams {
+    // The area of the frame within the layer textures (x, y, width, height), in texels
+    frame: vec4<f32>,
+    anchor: vec2<f32>,
+    flip_x: u32,
+    effect: u32,
+    color: vec4<f32>,
+    team_color: vec4<f32>,
+    lighting: u32,
+}
+
+@group(2) @binding(0) var<uniform> params: HdImageParams;
+@group(2) @binding(1) var diffuse: texture_2d<f32>;
+@group(2) @binding(2) var diffuse_sampler: sampler;
+@group(2) @binding(3) var mask: texture_2d<f32>;
+@group(2) @binding(4) var mask_sampler: sampler;
+@group(2) @binding(5) var emissive: texture_2d<f32>;
+@group(2) @binding(6) var emissive_sampler: sampler;
+@group(2) @binding(7) var normal: texture_2d<f32>;
+@group(2) @binding(8) var normal_sampler: sampler;
+@group(2) @binding(9) var specular: texture_2d<f32>;
+@group(2) @binding(10) var specular_sampler: sampler;
+@group(2) @binding(11) var ao_depth: texture_2d<f32>;
+@group(2) @binding(12) var ao_depth_sampler: sampler;
+@group(2) @binding(13) var lights: texture_2d<f32>;
+
+const MAX_LIGHTS: i32 = 8;
+// How much light images receive when there are no lights nearby
+const AMBIENT_LIGHT: f32 = 0.85;
+// How high above the image lights are, relative to their radius
+const LIGHT_HEIGHT: f32 = 0.25;
+const SPECULAR_POWER: f32 = 16.0;
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+
+    // The mesh is a unit quad, so it gets scaled to the size of the frame and positioned the same
+    // way Bevy positions sprites with anchors
+    let size = params.frame.zw;
+    let position = (vertex.position.xy - params.anchor) * size;
+    var uv = vertex.uv;
+    if params.flip_x != 0u {
+        uv.x = 1.0 - uv.x;
+    }
+    out.uv = params.frame.xy + uv * size;
+
+    let world_from_local = mesh_functions::get_world_from_local(vertex.instance_index);
+    out.world_position = mesh_functions::mesh2d_position_local_to_world(
+        world_from_local,
+        vec4<f32>(position, 0.0, 1.0)
+    );
+    out.position = mesh_functions::mesh2d_position_world_to_clip(out.world_position);
+    return out;
+}
+
+// Returns the alpha of the diffuse layer at `texel`, treating anything outside of the frame as
+// transparent.
+fn alpha_at(texel: vec2<f32>) -> f32 {
+    if any(texel < params.frame.xy) || any(texel >= params.frame.xy + params.frame.zw) {
+        return 0.0;
+    }
+    let uv = (texel + vec2<f32>(0.5)) / vec2<f32>(textureDimensions(diffuse));
+    return textureSampleLevel(diffuse, diffuse_sampler, uv, 0.0).a;
+}
+
+// Applies the image's lighting layers and any nearby dynamic lights to `color`. The lighting layers
+// are the same size as the diffuse layer, so they can be sampled with the same `uv`.
+fn apply_lighting(color: vec3<f32>, uv: vec2<f32>, world_position: vec2<f32>) -> vec3<f32> {
+    var surface_normal = textureSample(normal, normal_sampler, uv).rgb * 2.0 - vec3<f32>(1.0);
+    if params.flip_x != 0u {
+        surface_normal.x = -surface_normal.x;
+    }
+    surface_normal = normalize(vec3<f32>(surface_normal.xy, max(surface_normal.z, 0.001)));
+    let shininess = textureSample(specular, specular_sampler, uv).r;
+    let occlusion = textureSample(ao_depth, ao_depth_sampler, uv).r;
+    let glow = textureSample(emissive, emissive_sampler, uv).rgb;
+
+    var light = vec3<f32>(AMBIENT_LIGHT * mix(1.0, occlusion, 0.5));
+    for (var i = 0; i < MAX_LIGHTS; i++) {
+        let position = textureLoad(lights, vec2<i32>(i, 0), 0);
+        let radius = position.z;
+        if radius <= 0.0 {
+            continue;
+        }
+        let light_color = textureLoad(lights, vec2<i32>(i, 1), 0);
+        let to_light = position.xy - world_position;
+        let falloff = clamp(1.0 - length(to_light) / radius, 0.0, 1.0);
+        let attenuation = falloff * falloff * light_color.a;
+        let direction = normalize(vec3<f32>(to_light, radius * LIGHT_HEIGHT));
+
+        let diffuse_amount = max(dot(surface_normal, direction), 0.0);
+        let reflected = reflect(-direction, surface_normal);
+        let specular_amount = pow(max(reflected.z, 0.0), SPECULAR_POWER) * shininess;
+        light += light_color.rgb * attenuation * (diffuse_amount + specular_amount);
+    }
+
+    return color * light + glow;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let base = textureSample(diffuse, diffuse_sampler, in.uv / vec2<f32>(textureDimensions(diffuse)));
+    let amount = textureSample(mask, mask_sampler, in.uv / vec2<f32>(textureDimensions(mask))).r;
+    let tint = mix(vec3<f32>(1.0), params.team_color.rgb, amount);
+    var color = vec4<f32>(base.rgb * tint, base.a);
+    if params.lighting != 0u {
+        let texel_uv = in.uv / vec2<f32>(textureDimensions(diffuse));
+        color = vec4<f32>(apply_lighting(color.rgb, texel_uv, in.world_position.xy), color.a);
+    }
+
+    var edge = false;
+    if params.effect == EFFECT_OUTLINE && base.a < 0.5 {
+        let texel = floor(in.uv);
+        edge = alpha_at(texel + vec2<f32>(1.0, 0.0)) >= 0.5
+            || alpha_at(texel - vec2<f32>(1.0, 0.0)) >= 0.5
+            || alpha_at(texel + vec2<f32>(0.0, 1.0)) >= 0.5
+            || alpha_at(texel - vec2<f32>(0.0, 1.0)) >= 0.5;
+    }
+
+    let pos = in.uv - params.frame.xy;
+    let result = apply_effect(color, params.effect, pos, params.frame.zw, edge, params.team_color)
+        * params.color;
+    if result.a <= 0.0 {
+        discard;
+    }
+    return result;
+}
@@ -0,0 +1,83 @@
+// Visual effects for images, selected by their render style. The effect values must match the
+// `ImageEffect` enum in `src/render/effects.rs`.
+
+#import bevy_sprite::mesh2d_view_bindings::globals
+
+const EFFECT_NORMAL: u32 = 0u;
+const EFFECT_HIDDEN: u32 = 1u;
+const EFFECT_SHADOW: u32 = 2u;
+const EFFECT_CLOAKED: u32 = 3u;
+const EFFECT_CLOAK_DISTORTION: u32 = 4u;
+const EFFECT_HALLUCINATION: u32 = 5u;
+const EFFECT_WARP_FLASH: u32 = 6u;
+const EFFECT_EMP_SHOCKWAVE: u32 = 7u;
+const EFFECT_OUTLINE: u32 = 8u;
+const EFFECT_BOUNDING_RECT: u32 = 9u;
+
+const SHADOW_ALPHA: f32 = 0.5;
+// How far (in texels) from the edge of the frame shadows start fading out
+const SHADOW_SOFTNESS: f32 = 4.0;
+
+// Applies `effect` to the image's `color` at this pixel. `pos` is the position of the pixel within
+// the frame and `size` is the size of the frame (both in texels). `edge` specifies whether this
+// pixel is transparent but borders a non-transparent one, and `effect_color` is the color used for
+// outlines.
+fn apply_effect(
+    color: vec4<f32>,
+    effect: u32,
+    pos: vec2<f32>,
+    size: vec2<f32>,
+    edge: bool,
+    effect_color: vec4<f32>,
+) -> vec4<f32> {
+    let time = globals.time;
+    switch effect {
+        case EFFECT_HIDDEN: {
+            return vec4<f32>(0.0);
+        }
+        case EFFECT_SHADOW: {
+            let distances = min(pos, size - pos);
+            let softness = clamp(min(distances.x, distances.y) / SHADOW_SOFTNESS, 0.5, 1.0);
+            return vec4<f32>(0.0, 0.0, 0.0, color.a * SHADOW_ALPHA * softness);
+        }
+        case EFFECT_CLOAKED: {
+            let shimmer = 0.5 + 0.5 * sin(time * 4.0 + pos.y * 0.5);
+            let tinted = mix(color.rgb, vec3<f32>(0.6, 0.7, 1.0), 0.3);
+            return vec4<f32>(tinted, color.a * (0.35 + 0.15 * shimmer));
+        }
+        case EFFECT_CLOAK_DISTORTION: {
+            // TODO(tec27): This should actually distort what's behind the image, which needs a copy
+            // of the screen to sample from
+            let wave = 0.5 + 0.5 * sin(time * 6.0 + pos.x * 0.7 + pos.y * 0.3);
+            return vec4<f32>(0.8, 0.85, 1.0, color.a * 0.12 * wave);
+        }
+        case EFFECT_HALLUCINATION: {
+            let luma = dot(color.rgb, vec3<f32>(0.299, 0.587, 0.114));
+            return vec4<f32>(luma * vec3<f32>(0.35, 0.55, 1.0) + vec3<f32>(0.05, 0.1, 0.25), color.a);
+        }
+        case EFFECT_WARP_FLASH: {
+            let pulse = 0.5 + 0.5 * sin(time * 10.0);
+            return vec4<f32>(mix(color.rgb, vec3<f32>(0.9, 0.95, 1.0), 0.6 + 0.3 * pulse), color.a);
+        }
+        case EFFECT_EMP_SHOCKWAVE: {
+            let ripple = sin(time * 8.0 + length(pos - size * 0.5) * 0.3);
+            let tinted = mix(color.rgb, vec3<f32>(0.4, 0.6, 1.0), 0.5);
+            return vec4<f32>(tinted, color.a * (0.6 + 0.2 * ripple));
+        }
+        case EFFECT_OUTLINE: {
+            if edge {
+                return effect_color;
+            }
+            return vec4<f32>(0.0);
+        }
+        case EFFECT_BOUNDING_RECT: {
+            if any(pos < vec2<f32>(1.0)) || any(pos >= size - vec2<f32>(1.0)) {
+                return effect_color;
+            }
+            return vec4<f32>(0.0);
+        }
+        default: {
+            return color;
+        }
+    }
+}
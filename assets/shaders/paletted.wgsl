@@ -0,0 +1,101 @@
+// Renders a frame from a texture of palette indices (as loaded from a GRP), applying a remapping
+// table (e.g. for team colors) before looking up the final color in the palette.
+
+#import bevy_sprite::{
+    mesh2d_functions as mesh_functions,
+    mesh2d_vertex_output::VertexOutput,
+}
+#import "shaders/image_effects.wgsl"::{apply_effect, EFFECT_CLOAKED, EFFECT_OUTLINE, EFFECT_SHADOW}
+
+struct PalettedParams {
+    // The area of the frame within the indices texture (x, y, width, height), in texels
+    frame: vec4<f32>,
+    anchor: vec2<f32>,
+    flip_x: u32,
+    remap_row: u32,
+    color: vec4<f32>,
+    effect: u32,
+    effect_color: vec4<f32>,
+    // How much to darken what's behind the image, for shadows and visible cloaked images
+    darkness: f32,
+}
+
+@group(2) @binding(0) var<uniform> params: PalettedParams;
+@group(2) @binding(1) var indices: texture_2d<u32>;
+@group(2) @binding(2) var palette: texture_2d<f32>;
+@group(2) @binding(3) var remap: texture_2d<u32>;
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+
+    // The mesh is a unit quad, so it gets scaled to the size of the frame and positioned the same
+    // way Bevy positions sprites with anchors
+    let size = params.frame.zw;
+    let position = (vertex.position.xy - params.anchor) * size;
+    var uv = vertex.uv;
+    if params.flip_x != 0u {
+        uv.x = 1.0 - uv.x;
+    }
+    out.uv = params.frame.xy + uv * size;
+
+    let world_from_local = mesh_functions::get_world_from_local(vertex.instance_index);
+    out.world_position = mesh_functions::mesh2d_position_local_to_world(
+        world_from_local,
+        vec4<f32>(position, 0.0, 1.0)
+    );
+    out.position = mesh_functions::mesh2d_position_world_to_clip(out.world_position);
+    return out;
+}
+
+// Returns the palette index at `texel`, treating anything outside of the frame as transparent.
+fn index_at(texel: vec2<i32>) -> u32 {
+    let min_texel = vec2<i32>(params.frame.xy);
+    let max_texel = vec2<i32>(params.frame.xy + params.frame.zw);
+    if any(texel < min_texel) || any(texel >= max_texel) {
+        return 0u;
+    }
+    return textureLoad(indices, texel, 0).r;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let max_texel = params.frame.xy + params.frame.zw - vec2<f32>(1.0);
+    let texel = vec2<i32>(clamp(floor(in.uv), params.frame.xy, max_texel));
+    let index = index_at(texel);
+
+    // BW draws these by remapping the colors of what's behind the image through the tileset's
+    // darkening table, so we draw black at whatever opacity darkens things by the same amount
+    if params.effect == EFFECT_SHADOW || params.effect == EFFECT_CLOAKED {
+        if index == 0u {
+            discard;
+        }
+        return vec4<f32>(0.0, 0.0, 0.0, params.darkness * params.color.a);
+    }
+
+    var color = vec4<f32>(0.0);
+    if index != 0u {
+        let remapped = textureLoad(remap, vec2<i32>(i32(index), i32(params.remap_row)), 0).r;
+        color = textureLoad(palette, vec2<i32>(i32(remapped), 0), 0);
+    }
+
+    var edge = false;
+    if params.effect == EFFECT_OUTLINE && index == 0u {
+        edge = index_at(texel + vec2<i32>(1, 0)) != 0u || index_at(texel - vec2<i32>(1, 0)) != 0u
+            || index_at(texel + vec2<i32>(0, 1)) != 0u || index_at(texel - vec2<i32>(0, 1)) != 0u;
+    }
+
+    let pos = in.uv - params.frame.xy;
+    let result = apply_effect(color, params.effect, pos, params.frame.zw, edge, params.effect_color)
+        * params.color;
+    if result.a <= 0.0 {
+        discard;
+    }
+    return result;
+}
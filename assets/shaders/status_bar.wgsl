@@ -0,0 +1,70 @@
+// Renders a BW-style status bar: up to 3 rows of segments (e.g. shields, health, and energy), each
+// with some number of them filled in.
+
+#import bevy_sprite::{
+    mesh2d_functions as mesh_functions,
+    mesh2d_vertex_output::VertexOutput,
+}
+
+struct StatusBarParams {
+    // The size of the whole bar, in pixels
+    size: vec2<f32>,
+    // The width of a single SD pixel, in pixels
+    scale: f32,
+    segments: u32,
+    rows: u32,
+    filled: vec4<u32>,
+    colors: array<vec4<f32>, 3>,
+}
+
+@group(2) @binding(0) var<uniform> params: StatusBarParams;
+
+const BORDER_COLOR: vec4<f32> = vec4<f32>(0.0, 0.0, 0.0, 1.0);
+const EMPTY_COLOR: vec4<f32> = vec4<f32>(0.2, 0.2, 0.2, 1.0);
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+
+    // The mesh is a unit quad, which gets scaled to the size of the bar. The bar is anchored at its
+    // top center so that it can be placed directly underneath a construct.
+    let position = (vertex.position.xy - vec2<f32>(0.0, 0.5)) * params.size;
+    out.uv = vertex.uv;
+
+    let world_from_local = mesh_functions::get_world_from_local(vertex.instance_index);
+    out.world_position = mesh_functions::mesh2d_position_local_to_world(
+        world_from_local,
+        vec4<f32>(position, 0.0, 1.0)
+    );
+    out.position = mesh_functions::mesh2d_position_world_to_clip(out.world_position);
+    return out;
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Position within the bar in SD pixels, with (0, 0) being the top left
+    let pos = in.uv * params.size / params.scale;
+    let size = params.size / params.scale;
+    let segment_size = vec2<f32>(
+        (size.x - 1.0) / f32(params.segments),
+        (size.y - 1.0) / f32(params.rows),
+    );
+
+    let cell = vec2<u32>(floor(pos / segment_size));
+    let within = pos - vec2<f32>(cell) * segment_size;
+    if within.x < 1.0 || within.y < 1.0 || cell.x >= params.segments || cell.y >= params.rows {
+        return BORDER_COLOR;
+    }
+
+    if cell.x < params.filled[cell.y] {
+        return params.colors[cell.y];
+    } else {
+        return EMPTY_COLOR;
+    }
+}
@@ -703,7 +703,7 @@ fn write_units(data: UnitData) -> anyhow::Result<()> {
         let what_sounds = if data.what_sound_start[i] > 0 {
             PreservedOption(Some(BwSoundRange::new(
                 data.what_sound_start[i],
-                // end is inclusive for only this sound range for some reason ???
+                // end is inclusive in the data, but our ranges are exclusive
                 data.what_sound_end[i] + 1,
             )))
         } else {
@@ -738,7 +738,7 @@ fn write_units(data: UnitData) -> anyhow::Result<()> {
                 let piss_sounds = if data.piss_sound_start[i] > 0 {
                     PreservedOption(Some(BwSoundRange::new(
                         data.piss_sound_start[i],
-                        data.piss_sound_end[i],
+                        data.piss_sound_end[i] + 1,
                     )))
                 } else {
                     PreservedOption(None)
@@ -746,7 +746,7 @@ fn write_units(data: UnitData) -> anyhow::Result<()> {
                 let yes_sounds = if data.yes_sound_start[i] > 0 {
                     PreservedOption(Some(BwSoundRange::new(
                         data.yes_sound_start[i],
-                        data.yes_sound_end[i],
+                        data.yes_sound_end[i] + 1,
                     )))
                 } else {
                     PreservedOption(None)
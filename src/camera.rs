@@ -2,14 +2,18 @@ use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
 use bevy::prelude::*;
 use bevy::window::{CursorGrabMode, PrimaryWindow, WindowFocused};
 
-use crate::gameplay::InGameMenuState;
+use crate::gameplay::alerts::LastAlert;
+use crate::gameplay::players::{ControlledPlayer, PlayerNumber};
+use crate::gameplay::selection::SelectedEntities;
+use crate::gameplay::{InGameMenuState, StartLocations};
+use crate::input::InputAction;
 use crate::maps::game_map::{GameMap, GameMapSize};
+use crate::maps::position::{position_to_translation, Position};
 use crate::settings::GameSettings;
 use crate::states::AppState;
 
 /// How far from the edge of the screen the mouse needs to be to start scrolling, in pixels.
 const EDGE_SCROLL_PX: f32 = 4.0;
-const MOUSE_PAN_SPEED: f32 = 3000.0;
 /// How fast the camera zooms in/out from scrolling.
 const MOUSE_ZOOM_SPEED: f32 = 0.5;
 
@@ -18,12 +22,25 @@ pub struct CameraControlPlugin;
 impl Plugin for CameraControlPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<CameraPanLocked>()
+            .init_resource::<SavedCameraLocations>()
+            .add_event::<CenterCameraEvent>()
             .add_systems(OnEnter(AppState::InGame), setup)
             .add_systems(
                 Update,
-                (handle_window_focus, camera_control).run_if(
-                    in_state(AppState::InGame).and_then(in_state(InGameMenuState::Disabled)),
-                ),
+                (
+                    handle_window_focus,
+                    camera_control,
+                    (
+                        center_on_start_location
+                            .run_if(resource_exists_and_changed::<StartLocations>),
+                        camera_hotkeys,
+                        center_camera,
+                    )
+                        .chain(),
+                )
+                    .run_if(
+                        in_state(AppState::InGame).and_then(in_state(InGameMenuState::Disabled)),
+                    ),
             );
     }
 }
@@ -33,9 +50,21 @@ impl Plugin for CameraControlPlugin {
 #[derive(Resource, Default, Debug)]
 pub struct CameraPanLocked(pub bool);
 
+/// The number of camera locations that can be saved (and recalled with hotkeys).
+pub const NUM_CAMERA_LOCATIONS: usize = 3;
+
+/// Camera positions that have been saved by the local player during the current game.
+#[derive(Resource, Default, Debug)]
+pub struct SavedCameraLocations(pub [Option<Vec2>; NUM_CAMERA_LOCATIONS]);
+
+/// Event that centers the local camera on a position on the map (e.g. from clicking the minimap).
+#[derive(Event, Debug, Copy, Clone)]
+pub struct CenterCameraEvent(pub Position);
+
 fn setup(
     mut window: Query<&mut Window, With<PrimaryWindow>>,
     mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<Camera>>,
+    mut saved_locations: ResMut<SavedCameraLocations>,
 ) {
     let mut window = window.get_single_mut().unwrap();
     info!("Confining mouse and centering it within the window...");
@@ -44,11 +73,77 @@ fn setup(
     window.set_cursor_position(Some(cursor_pos));
     window.cursor.grab_mode = CursorGrabMode::Confined;
 
-    // Reset the camera to the default position/zoom
-    // TODO(tec27): Center on base location if there is one
+    // Reset the camera to the default position/zoom. It will be moved to the local player's start
+    // location (if they have one) once the game has been initialized.
     let (mut transform, mut projection) = camera_query.single_mut();
-    transform.translation = Vec3::splat(0.0);
+    // NOTE(tec27): The Z position is left alone, it needs to stay in front of everything we draw
+    transform.translation = Vec3::new(0.0, 0.0, transform.translation.z);
     projection.scale = 1.0;
+    *saved_locations = default();
+}
+
+fn center_on_start_location(
+    start_locations: Res<StartLocations>,
+    controlled_player: Query<&PlayerNumber, With<ControlledPlayer>>,
+    mut center_camera_writer: EventWriter<CenterCameraEvent>,
+) {
+    let Ok(player) = controlled_player.get_single() else {
+        return;
+    };
+    if let Some(&position) = start_locations.0.get(&player.0) {
+        center_camera_writer.send(CenterCameraEvent(position));
+    }
+}
+
+/// Handles saving/recalling camera locations, centering on the selection, and jumping to the last
+/// alert.
+fn camera_hotkeys(
+    actions: Res<ButtonInput<InputAction>>,
+    mut saved_locations: ResMut<SavedCameraLocations>,
+    mut camera_query: Query<&mut Transform, With<Camera>>,
+    controlled_player: Query<&SelectedEntities, With<ControlledPlayer>>,
+    positions: Query<&Position>,
+    last_alert: Res<LastAlert>,
+    mut center_camera_writer: EventWriter<CenterCameraEvent>,
+) {
+    let Ok(mut transform) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    for (i, saved) in saved_locations.0.iter_mut().enumerate() {
+        if !actions.just_pressed(InputAction::CameraLocation(i as u8)) {
+            continue;
+        }
+        if actions.pressed(InputAction::AddModifier) {
+            *saved = Some(transform.translation.truncate());
+        } else if let Some(location) = *saved {
+            // NOTE(tec27): Saved locations were valid camera positions when they were saved, so
+            // they don't need to be clamped again
+            transform.translation = location.extend(transform.translation.z);
+        }
+    }
+
+    if actions.just_pressed(InputAction::CenterOnSelection) {
+        let selected = controlled_player
+            .get_single()
+            .map(|s| {
+                positions
+                    .iter_many(&s.0)
+                    .map(|&p| IVec2::from(p))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        if !selected.is_empty() {
+            let center = selected.iter().sum::<IVec2>() / selected.len() as i32;
+            center_camera_writer.send(CenterCameraEvent(center.into()));
+        }
+    }
+
+    if actions.just_pressed(InputAction::JumpToAlert) {
+        if let Some(position) = last_alert.0 {
+            center_camera_writer.send(CenterCameraEvent(position));
+        }
+    }
 }
 
 fn handle_window_focus(
@@ -74,6 +169,7 @@ fn handle_window_focus(
 #[derive(Default)]
 struct CameraControlState {
     last_pos: Vec2,
+    middle_dragging: bool,
 }
 
 fn camera_control(
@@ -85,13 +181,16 @@ fn camera_control(
     mut scroll_events: EventReader<MouseWheel>,
     settings: Res<GameSettings>,
     map_size: Query<&GameMapSize, With<GameMap>>,
+    actions: Res<ButtonInput<InputAction>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
 ) {
-    // TODO(tec27): implement arrow key scrolling + middle mouse panning as well
     let window = window.get_single().unwrap();
     let mouse_position = window.cursor_position().unwrap_or(state.last_pos);
+    let mouse_delta = mouse_position - state.last_pos;
     state.last_pos = mouse_position;
 
     if camera_pan_locked.0 {
+        state.middle_dragging = false;
         return;
     }
 
@@ -110,49 +209,105 @@ fn camera_control(
         projection.scale = log_scale.exp().clamp(0.25, 10.0);
     }
 
-    let width = window.width();
-    let height = window.height();
+    // Dragging with the middle mouse button moves the map along with the cursor
+    let mut pan = Vec2::ZERO;
+    let middle_dragging = mouse_buttons.pressed(MouseButton::Middle);
+    if middle_dragging && state.middle_dragging {
+        pan = Vec2::new(-mouse_delta.x, mouse_delta.y);
+    }
+    state.middle_dragging = middle_dragging;
 
-    let mut pan_x = 0.0;
-    let mut pan_y = 0.0;
+    if !middle_dragging {
+        let key_pan = Vec2::new(
+            scroll_axis(&actions, InputAction::ScrollLeft, InputAction::ScrollRight),
+            scroll_axis(&actions, InputAction::ScrollDown, InputAction::ScrollUp),
+        );
+        pan = if key_pan != Vec2::ZERO {
+            key_pan * settings.scroll_speeds.keyboard * time.delta_seconds()
+        } else {
+            edge_scroll_direction(mouse_position, window)
+                * settings.scroll_speeds.edge
+                * time.delta_seconds()
+        };
+    }
 
+    if pan != Vec2::ZERO {
+        let max_pos = max_camera_pos(
+            map_size.get_single().ok().copied().unwrap_or_default(),
+            window,
+            &settings,
+        );
+
+        let (mut transform, projection) = camera_query.single_mut();
+        let translation = transform.translation.truncate() + pan * projection.scale;
+        transform.translation = translation
+            .clamp(-max_pos, max_pos)
+            .extend(transform.translation.z);
+    }
+}
+
+/// Returns the direction the camera should scroll in based on the mouse being at the edges of the
+/// window.
+fn edge_scroll_direction(mouse_position: Vec2, window: &Window) -> Vec2 {
+    let mut direction = Vec2::ZERO;
     if mouse_position.x < EDGE_SCROLL_PX {
-        pan_x = -1.0;
-    } else if mouse_position.x > width - EDGE_SCROLL_PX {
-        pan_x = 1.0;
+        direction.x = -1.0;
+    } else if mouse_position.x > window.width() - EDGE_SCROLL_PX {
+        direction.x = 1.0;
     }
 
     if mouse_position.y < EDGE_SCROLL_PX {
-        pan_y = 1.0;
-    } else if mouse_position.y > height - EDGE_SCROLL_PX {
-        pan_y = -1.0;
+        direction.y = 1.0;
+    } else if mouse_position.y > window.height() - EDGE_SCROLL_PX {
+        direction.y = -1.0;
     }
+    direction
+}
 
-    if pan_x != 0.0 || pan_y != 0.0 {
-        let mut max_pos: Vec2 = map_size
-            .get_single()
-            .ok()
-            .copied()
-            .unwrap_or_default()
-            .into();
-        max_pos *= settings.asset_quality.tile_size() / 2.0;
-        // Let the camera go half the width/height past the edge of the map so centering is always
-        // possible. Note that we divide by 4 since this value is for the edges of a rect centered
-        // at (0, 0).
-        // TODO(tec27): We can likely make this smaller without causing real issues?
-        // TODO(tec27): Need to adjust this based on what a window pixel is with camera scale as
-        // well.
-        max_pos += Vec2::new(width / 4.0, height / 4.0);
+/// Returns -1, 0, or 1 depending on which of the scroll actions for an axis are pressed.
+fn scroll_axis(
+    actions: &ButtonInput<InputAction>,
+    negative: InputAction,
+    positive: InputAction,
+) -> f32 {
+    actions.pressed(positive) as i32 as f32 - actions.pressed(negative) as i32 as f32
+}
 
-        let (mut transform, projection) = camera_query.single_mut();
-        let scale = projection.scale;
-        transform.translation += Vec3::new(
-            pan_x * MOUSE_PAN_SPEED * time.delta_seconds() * scale,
-            pan_y * MOUSE_PAN_SPEED * time.delta_seconds() * scale,
-            0.0,
-        );
-        transform.translation = transform
-            .translation
-            .clamp(-max_pos.extend(0.0), max_pos.extend(0.0));
-    }
+/// Returns the furthest the camera can be moved from the center of the map in either direction.
+fn max_camera_pos(map_size: GameMapSize, window: &Window, settings: &GameSettings) -> Vec2 {
+    let mut max_pos: Vec2 = map_size.into();
+    max_pos *= settings.asset_quality.tile_size() / 2.0;
+    // Let the camera go half the width/height past the edge of the map so centering is always
+    // possible. Note that we divide by 4 since this value is for the edges of a rect centered
+    // at (0, 0).
+    // TODO(tec27): We can likely make this smaller without causing real issues?
+    // TODO(tec27): Need to adjust this based on what a window pixel is with camera scale as
+    // well.
+    max_pos + Vec2::new(window.width() / 4.0, window.height() / 4.0)
+}
+
+fn center_camera(
+    mut events: EventReader<CenterCameraEvent>,
+    window: Query<&Window, (With<PrimaryWindow>, Without<Camera>)>,
+    mut camera_query: Query<&mut Transform, With<Camera>>,
+    settings: Res<GameSettings>,
+    map_size: Query<&GameMapSize, With<GameMap>>,
+) {
+    let Some(CenterCameraEvent(position)) = events.read().last() else {
+        return;
+    };
+    let (Ok(window), Ok(map_size), Ok(mut transform)) = (
+        window.get_single(),
+        map_size.get_single(),
+        camera_query.get_single_mut(),
+    ) else {
+        return;
+    };
+
+    let max_pos = max_camera_pos(*map_size, window, &settings);
+    let translation =
+        position_to_translation(position, map_size, settings.asset_quality.tile_size());
+    transform.translation = translation
+        .clamp(-max_pos, max_pos)
+        .extend(transform.translation.z);
 }
@@ -0,0 +1,227 @@
+//! Checks that the files extracted from the CASC archive (see the README) are set up correctly for
+//! a given set of asset settings, without needing a window or GPU. This is used by the
+//! `check-assets` command.
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Cursor};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+
+use crate::gamedata::{
+    anim::validate_anim, anim_path, grp::load_grp, grp_path, lo::load_lo, palette::load_pcx,
+    rel::RelAsset, special_overlay_path, tbl::TblAsset, IMAGES, IMAGE_PATHS_PATH, RELATIONS_PATH,
+    SOUNDS, STRINGS_PATH,
+};
+use crate::maps::tileset::{tileset_file_paths, validate_tileset_file, TILESETS};
+use crate::settings::{AssetPack, AssetQuality, AudioQuality};
+
+/// The settings that determine which asset files are needed.
+#[derive(Debug, Copy, Clone)]
+pub struct AssetCheckSettings {
+    pub asset_quality: AssetQuality,
+    pub asset_pack: AssetPack,
+    pub audio_quality: AudioQuality,
+}
+
+/// The results of checking the asset files.
+#[derive(Debug, Default)]
+pub struct AssetReport {
+    /// The number of files that were checked.
+    pub checked: usize,
+    /// Files that don't exist.
+    pub missing: Vec<String>,
+    /// Files that exist but couldn't be read or parsed, along with the reason why.
+    pub corrupt: Vec<(String, String)>,
+}
+
+impl AssetReport {
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.corrupt.is_empty()
+    }
+}
+
+impl fmt::Display for AssetReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.missing.is_empty() {
+            writeln!(f, "Missing files ({}):", self.missing.len())?;
+            for path in &self.missing {
+                writeln!(f, "  {path}")?;
+            }
+        }
+        if !self.corrupt.is_empty() {
+            writeln!(f, "Corrupt files ({}):", self.corrupt.len())?;
+            for (path, error) in &self.corrupt {
+                writeln!(f, "  {path}: {error}")?;
+            }
+        }
+        write!(
+            f,
+            "Checked {} files: {} missing, {} corrupt",
+            self.checked,
+            self.missing.len(),
+            self.corrupt.len()
+        )
+    }
+}
+
+/// Checks all the asset files needed to play with `settings` in `assets_dir` (the equivalent of
+/// Bevy's `assets` folder).
+pub fn check_assets(assets_dir: &Path, settings: AssetCheckSettings) -> AssetReport {
+    let mut report = AssetReport::default();
+    let game_data = [IMAGE_PATHS_PATH, STRINGS_PATH, RELATIONS_PATH].map(String::from);
+    check_files(assets_dir, &game_data, &mut report);
+
+    // The rest of the required files are determined by the game data, so we need to read it
+    // ourselves (if it's there)
+    let image_paths = read_file(assets_dir, IMAGE_PATHS_PATH)
+        .ok()
+        .and_then(|bytes| TblAsset::from_bytes(&bytes).ok());
+    let relations = read_file(assets_dir, RELATIONS_PATH)
+        .ok()
+        .map(|bytes| RelAsset::from_bytes(&bytes));
+
+    let mut paths = Vec::new();
+    if settings.asset_quality == AssetQuality::Standard {
+        paths.push("casc-extracted/game/tunit.pcx".to_owned());
+        if let Some(image_paths) = &image_paths {
+            paths.extend(IMAGES.iter().filter_map(|i| grp_path(i, image_paths)));
+        }
+    } else if let Some(relations) = &relations {
+        paths.extend(
+            (0..IMAGES.len() as u16)
+                .map(|id| anim_path(id, relations, settings.asset_quality, settings.asset_pack)),
+        );
+    }
+    if let Some(image_paths) = &image_paths {
+        paths.extend(
+            IMAGES
+                .iter()
+                .filter_map(|i| special_overlay_path(i, image_paths)),
+        );
+    }
+    for tileset in TILESETS {
+        paths.extend(tileset_file_paths(
+            tileset,
+            settings.asset_quality,
+            settings.asset_pack,
+        ));
+    }
+    paths.extend(SOUNDS.iter().filter(|s| !s.file.is_empty()).map(|s| {
+        format!(
+            "casc-extracted/{}{}",
+            settings.audio_quality.asset_path(),
+            s.file
+        )
+    }));
+
+    // Multiple images can share the same files
+    paths.sort();
+    paths.dedup();
+    check_files(assets_dir, &paths, &mut report);
+
+    report
+}
+
+/// Checks that each of `paths` (relative to `assets_dir`) exists and can be parsed, adding the
+/// results to `report`.
+fn check_files(assets_dir: &Path, paths: &[String], report: &mut AssetReport) {
+    for path in paths {
+        report.checked += 1;
+        match read_file(assets_dir, path) {
+            Ok(bytes) => {
+                if let Err(e) = validate_file(path, &bytes) {
+                    report.corrupt.push((path.clone(), format!("{e:#}")));
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => report.missing.push(path.clone()),
+            Err(e) => report.corrupt.push((path.clone(), e.to_string())),
+        }
+    }
+}
+
+fn read_file(assets_dir: &Path, path: &str) -> io::Result<Vec<u8>> {
+    fs::read(asset_file_path(assets_dir, path))
+}
+
+/// Converts an asset path (which may use either separator) into a path on the filesystem.
+fn asset_file_path(assets_dir: &Path, path: &str) -> PathBuf {
+    path.split(['/', '\\'])
+        .fold(assets_dir.to_path_buf(), |result, part| result.join(part))
+}
+
+/// Parses `bytes` as the type of file specified by `path`'s extension, using the same parsing code
+/// as the asset loaders.
+fn validate_file(path: &str, bytes: &[u8]) -> anyhow::Result<()> {
+    let extension = path.rsplit('.').next().unwrap_or_default();
+    match extension.to_ascii_lowercase().as_str() {
+        "tbl" => {
+            TblAsset::from_bytes(bytes)?;
+        }
+        "rel" => {
+            if !bytes.len().is_multiple_of(8) {
+                bail!("file length is not a multiple of the entry size");
+            }
+        }
+        "anim" => validate_anim(bytes)?,
+        "grp" => {
+            load_grp(bytes)?;
+        }
+        "pcx" => {
+            load_pcx(bytes)?;
+        }
+        "wav" => {
+            if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+                bail!("not a WAV file");
+            }
+        }
+        "cv5" | "vf4" | "vr4" | "wpe" => validate_tileset_file(path, bytes)?,
+        ext if ext.starts_with("lo") => {
+            load_lo(Cursor::new(bytes)).context("invalid LO file")?;
+        }
+        ext => bail!("unknown file type: {ext}"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_missing_and_corrupt_files() {
+        let assets_dir =
+            std::env::temp_dir().join(format!("neobrood-check-{}", std::process::id()));
+        let rez_dir = assets_dir.join("casc-extracted").join("rez");
+        fs::create_dir_all(&rez_dir).unwrap();
+        // A TBL with a single empty string
+        fs::write(rez_dir.join("stat_txt.tbl"), [1, 0, 4, 0, 0]).unwrap();
+        fs::write(assets_dir.join("casc-extracted").join("images.rel"), [0; 5]).unwrap();
+
+        let mut report = AssetReport::default();
+        check_files(
+            &assets_dir,
+            &[IMAGE_PATHS_PATH, STRINGS_PATH, RELATIONS_PATH].map(String::from),
+            &mut report,
+        );
+        fs::remove_dir_all(&assets_dir).unwrap();
+
+        assert_eq!(report.checked, 3);
+        assert_eq!(report.missing, vec![IMAGE_PATHS_PATH]);
+        assert_eq!(report.corrupt.len(), 1);
+        assert_eq!(report.corrupt[0].0, RELATIONS_PATH);
+        assert!(!report.is_ok());
+    }
+
+    #[test]
+    fn windows_style_paths() {
+        assert_eq!(
+            asset_file_path(
+                Path::new("assets"),
+                "casc-extracted\\unit\\terran/marine.grp"
+            ),
+            Path::new("assets/casc-extracted/unit/terran/marine.grp")
+        );
+    }
+}
@@ -33,6 +33,8 @@ pub enum AnimError {
     OutOfBoundsReference(u16, u16),
     #[error("inline sprite references are not yet supported")]
     InlineSpriteReferencesUnsupported,
+    #[error("layer {0} is outside of the file")]
+    LayerOutOfBounds(String),
     #[error("failed to read file: {0}")]
     Io(#[from] std::io::Error),
     #[error("failed to decode image: {0}")]
@@ -70,9 +72,7 @@ impl AssetLoader for AnimAssetLoader {
 
         let mut layer_handles = HashMap::with_capacity(layers.len());
         for (name, texture) in layers.iter() {
-            let texture_cursor = Cursor::new(
-                &bytes[texture.offset as usize..(texture.offset + texture.size) as usize],
-            );
+            let texture_cursor = Cursor::new(layer_bytes(&bytes, name, texture)?);
             let dyn_image = ImageReader::new(texture_cursor)
                 .with_guessed_format()?
                 .decode()?;
@@ -203,6 +203,28 @@ const TYPE_HD: u8 = 0x02;
 /// The scale that frames are specified in. (HD frames are still specified in 4K coordinates).
 const FRAME_SCALE: u16 = 4;
 
+/// Checks that `bytes` contains a valid anim file, without decoding any of its layer textures.
+pub fn validate_anim(bytes: &[u8]) -> Result<(), AnimError> {
+    let file = load_anim(Cursor::new(bytes))?;
+    for (name, texture) in file.sprite.layers.iter() {
+        layer_bytes(bytes, name, texture)?;
+    }
+    Ok(())
+}
+
+/// Returns the portion of the anim file that contains the texture data for a layer.
+fn layer_bytes<'a>(
+    bytes: &'a [u8],
+    name: &str,
+    texture: &AnimTexture,
+) -> Result<&'a [u8], AnimError> {
+    let start = texture.offset as usize;
+    let end = start + texture.size as usize;
+    bytes
+        .get(start..end)
+        .ok_or_else(|| AnimError::LayerOutOfBounds(name.to_owned()))
+}
+
 fn load_anim<R: Read + Seek + Send>(mut r: R) -> Result<AnimFile, AnimError> {
     let magic = r.read_u32::<LittleEndian>()?;
     if magic != ANIM_MAGIC {
@@ -81,7 +81,6 @@ pub struct BuildingData {
     pub addon_size: I16Vec2,
 }
 
-#[allow(dead_code)]
 #[derive(Copy, Clone, Debug)]
 pub struct UnitData {
     pub ready_sound: Option<BwSoundId>,
@@ -145,6 +144,15 @@ impl Construct {
         self.id.into()
     }
 
+    /// Returns the unit-specific data for this [Construct], if it is a unit.
+    #[inline]
+    pub const fn unit_data(&self) -> Option<&UnitData> {
+        match &self.kind {
+            ConstructKind::Unit(data) => Some(data),
+            _ => None,
+        }
+    }
+
     /// Returns if this [Construct] is a unit.
     #[inline]
     pub const fn is_unit(&self) -> bool {
@@ -419,3 +427,37 @@ impl Default for ConstructTypeId {
         ConstructTypeId::Unknown(666)
     }
 }
+
+impl ConstructTypeId {
+    /// Returns the construct types that a construct of this type can train, in the order they
+    /// appear on its command card.
+    // NOTE(tec27): BW's button sets aren't part of the .dat files (they're hardcoded in the
+    // executable), so these are listed here. Costs/build times for each type still come from the
+    // dat data.
+    // TODO(tec27): Handle Zerg, which morphs larva instead of training from a building
+    pub fn trains(&self) -> &'static [ConstructTypeId] {
+        use ConstructTypeId::*;
+        match self {
+            TerranCommandCenter => &[TerranScv],
+            TerranBarracks => &[TerranMarine, TerranFirebat, TerranGhost, TerranMedic],
+            TerranFactory => &[TerranVulture, TerranSiegeTank, TerranGoliath],
+            TerranStarport => &[
+                TerranWraith,
+                TerranDropship,
+                TerranScienceVessel,
+                TerranBattlecruiser,
+                TerranValkyrie,
+            ],
+            ProtossNexus => &[ProtossProbe],
+            ProtossGateway => &[
+                ProtossZealot,
+                ProtossDragoon,
+                ProtossHighTemplar,
+                ProtossDarkTemplar,
+            ],
+            ProtossRoboticsFacility => &[ProtossShuttle, ProtossReaver, ProtossObserver],
+            ProtossStargate => &[ProtossScout, ProtossCarrier, ProtossArbiter, ProtossCorsair],
+            _ => &[],
+        }
+    }
+}
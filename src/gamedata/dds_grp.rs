@@ -0,0 +1,164 @@
+//! Loading for the DDS GRP files that SC:R uses for UI graphics (e.g. wireframes and command
+//! icons). These are a simple container of DDS images, one per frame.
+
+use bevy::{
+    asset::{io::Reader, Asset, AssetLoader, AsyncReadExt, Handle, LoadContext},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        renderer::RenderDevice,
+        texture::{CompressedImageFormats, ImageSampler, ImageType, TextureError},
+    },
+};
+use byteorder::{ByteOrder, LittleEndian};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum DdsGrpError {
+    #[error("dds grp header is invalid")]
+    InvalidHeader,
+    #[error("frame {0} is out of bounds")]
+    OutOfBoundsFrame(usize),
+    #[error("failed to decode frame {0}: {1}")]
+    InvalidFrame(usize, TextureError),
+    #[error("failed to read file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// The size of the header at the start of the file.
+const HEADER_SIZE: usize = 8;
+/// The size of the header before each frame's data.
+const FRAME_HEADER_SIZE: usize = 12;
+
+#[derive(Debug)]
+pub struct DdsGrpAssetLoader {
+    supported_compressed_formats: CompressedImageFormats,
+}
+
+impl FromWorld for DdsGrpAssetLoader {
+    fn from_world(world: &mut World) -> Self {
+        let supported_compressed_formats = match world.get_resource::<RenderDevice>() {
+            Some(render_device) => CompressedImageFormats::from_features(render_device.features()),
+            None => CompressedImageFormats::all(),
+        };
+
+        Self {
+            supported_compressed_formats,
+        }
+    }
+}
+
+/// A set of images loaded from a DDS GRP file.
+#[derive(Asset, TypePath, Debug)]
+pub struct DdsGrpAsset {
+    pub frames: Vec<Handle<Image>>,
+}
+
+impl AssetLoader for DdsGrpAssetLoader {
+    type Asset = DdsGrpAsset;
+    type Settings = ();
+    type Error = DdsGrpError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let mut frames = Vec::new();
+        for (i, data) in parse_dds_grp(&bytes)?.into_iter().enumerate() {
+            let image = Image::from_buffer(
+                #[cfg(debug_assertions)]
+                format!("frame{i}"),
+                data,
+                ImageType::Extension("dds"),
+                self.supported_compressed_formats,
+                true,
+                ImageSampler::Default,
+                RenderAssetUsages::RENDER_WORLD,
+            )
+            .map_err(|e| DdsGrpError::InvalidFrame(i, e))?;
+            frames.push(load_context.add_labeled_asset(format!("frame{i}"), image));
+        }
+
+        Ok(DdsGrpAsset { frames })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["dds.grp"]
+    }
+}
+
+/// Splits a DDS GRP file into the DDS data for each of its frames.
+fn parse_dds_grp(bytes: &[u8]) -> Result<Vec<&[u8]>, DdsGrpError> {
+    // A DDS GRP file consists of:
+    // - A u32 that is unused (seemingly the file size in some files)
+    // - A u16 frame count
+    // - A u16 that is unused
+    // - For each frame:
+    //   - A u32 that is unused
+    //   - u16 width and height
+    //   - A u32 size, followed by that many bytes of DDS data
+    if bytes.len() < HEADER_SIZE {
+        return Err(DdsGrpError::InvalidHeader);
+    }
+    let frame_count = LittleEndian::read_u16(&bytes[4..6]) as usize;
+
+    let mut frames = Vec::with_capacity(frame_count);
+    let mut offset = HEADER_SIZE;
+    for i in 0..frame_count {
+        let header = bytes
+            .get(offset..offset + FRAME_HEADER_SIZE)
+            .ok_or(DdsGrpError::OutOfBoundsFrame(i))?;
+        let size = LittleEndian::read_u32(&header[8..12]) as usize;
+        offset += FRAME_HEADER_SIZE;
+
+        let data = bytes
+            .get(offset..offset + size)
+            .ok_or(DdsGrpError::OutOfBoundsFrame(i))?;
+        frames.push(data);
+        offset += size;
+    }
+
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(data: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![0, 0, 0, 0, 4, 0, 4, 0];
+        bytes.extend((data.len() as u32).to_le_bytes());
+        bytes.extend(data);
+        bytes
+    }
+
+    #[test]
+    fn parse_frames() {
+        let mut bytes = vec![0, 0, 0, 0, 2, 0, 0x01, 0x10];
+        bytes.extend(frame(&[1, 2, 3]));
+        bytes.extend(frame(&[4]));
+
+        let frames = parse_dds_grp(&bytes).unwrap();
+        assert_eq!(frames, vec![&[1, 2, 3][..], &[4][..]]);
+    }
+
+    #[test]
+    fn parse_truncated() {
+        assert!(matches!(
+            parse_dds_grp(&[0, 0]),
+            Err(DdsGrpError::InvalidHeader)
+        ));
+
+        let mut bytes = vec![0, 0, 0, 0, 2, 0, 0x01, 0x10];
+        bytes.extend(frame(&[1, 2, 3]));
+        assert!(matches!(
+            parse_dds_grp(&bytes),
+            Err(DdsGrpError::OutOfBoundsFrame(1))
+        ));
+    }
+}
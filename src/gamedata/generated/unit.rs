@@ -64,11 +64,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(275u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(280u16),
-                    BwSoundId::new_unchecked(286u16),
+                    BwSoundId::new_unchecked(287u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(291u16),
-                    BwSoundId::new_unchecked(294u16),
+                    BwSoundId::new_unchecked(295u16),
                 )),
             }),
         },
@@ -130,11 +130,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(225u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(226u16),
-                    BwSoundId::new_unchecked(229u16),
+                    BwSoundId::new_unchecked(230u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(234u16),
-                    BwSoundId::new_unchecked(237u16),
+                    BwSoundId::new_unchecked(238u16),
                 )),
             }),
         },
@@ -196,11 +196,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(352u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(356u16),
-                    BwSoundId::new_unchecked(359u16),
+                    BwSoundId::new_unchecked(360u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(364u16),
-                    BwSoundId::new_unchecked(367u16),
+                    BwSoundId::new_unchecked(368u16),
                 )),
             }),
         },
@@ -262,11 +262,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(241u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(242u16),
-                    BwSoundId::new_unchecked(247u16),
+                    BwSoundId::new_unchecked(248u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(252u16),
-                    BwSoundId::new_unchecked(255u16),
+                    BwSoundId::new_unchecked(256u16),
                 )),
             }),
         },
@@ -385,11 +385,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(316u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(320u16),
-                    BwSoundId::new_unchecked(323u16),
+                    BwSoundId::new_unchecked(324u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(328u16),
-                    BwSoundId::new_unchecked(331u16),
+                    BwSoundId::new_unchecked(332u16),
                 )),
             }),
         },
@@ -451,11 +451,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(316u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(320u16),
-                    BwSoundId::new_unchecked(323u16),
+                    BwSoundId::new_unchecked(324u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(328u16),
-                    BwSoundId::new_unchecked(331u16),
+                    BwSoundId::new_unchecked(332u16),
                 )),
             }),
         },
@@ -517,11 +517,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(368u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(370u16),
-                    BwSoundId::new_unchecked(376u16),
+                    BwSoundId::new_unchecked(377u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(381u16),
-                    BwSoundId::new_unchecked(384u16),
+                    BwSoundId::new_unchecked(385u16),
                 )),
             }),
         },
@@ -583,11 +583,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(256u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(258u16),
-                    BwSoundId::new_unchecked(264u16),
+                    BwSoundId::new_unchecked(265u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(269u16),
-                    BwSoundId::new_unchecked(272u16),
+                    BwSoundId::new_unchecked(273u16),
                 )),
             }),
         },
@@ -649,11 +649,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(332u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(333u16),
-                    BwSoundId::new_unchecked(339u16),
+                    BwSoundId::new_unchecked(340u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(344u16),
-                    BwSoundId::new_unchecked(347u16),
+                    BwSoundId::new_unchecked(348u16),
                 )),
             }),
         },
@@ -715,11 +715,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(295u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(303u16),
-                    BwSoundId::new_unchecked(309u16),
+                    BwSoundId::new_unchecked(310u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(310u16),
-                    BwSoundId::new_unchecked(313u16),
+                    BwSoundId::new_unchecked(314u16),
                 )),
             }),
         },
@@ -781,11 +781,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(209u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(211u16),
-                    BwSoundId::new_unchecked(214u16),
+                    BwSoundId::new_unchecked(215u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(219u16),
-                    BwSoundId::new_unchecked(224u16),
+                    BwSoundId::new_unchecked(225u16),
                 )),
             }),
         },
@@ -847,11 +847,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(176u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(180u16),
-                    BwSoundId::new_unchecked(184u16),
+                    BwSoundId::new_unchecked(185u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(189u16),
-                    BwSoundId::new_unchecked(192u16),
+                    BwSoundId::new_unchecked(193u16),
                 )),
             }),
         },
@@ -1033,11 +1033,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(193u16),
-                    BwSoundId::new_unchecked(197u16),
+                    BwSoundId::new_unchecked(198u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(202u16),
-                    BwSoundId::new_unchecked(206u16),
+                    BwSoundId::new_unchecked(207u16),
                 )),
             }),
         },
@@ -1099,11 +1099,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(457u16),
-                    BwSoundId::new_unchecked(461u16),
+                    BwSoundId::new_unchecked(462u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(466u16),
-                    BwSoundId::new_unchecked(469u16),
+                    BwSoundId::new_unchecked(470u16),
                 )),
             }),
         },
@@ -1165,11 +1165,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(241u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(242u16),
-                    BwSoundId::new_unchecked(247u16),
+                    BwSoundId::new_unchecked(248u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(252u16),
-                    BwSoundId::new_unchecked(255u16),
+                    BwSoundId::new_unchecked(256u16),
                 )),
             }),
         },
@@ -1288,11 +1288,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(419u16),
-                    BwSoundId::new_unchecked(422u16),
+                    BwSoundId::new_unchecked(423u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(427u16),
-                    BwSoundId::new_unchecked(430u16),
+                    BwSoundId::new_unchecked(431u16),
                 )),
             }),
         },
@@ -1354,11 +1354,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(407u16),
-                    BwSoundId::new_unchecked(410u16),
+                    BwSoundId::new_unchecked(411u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(415u16),
-                    BwSoundId::new_unchecked(418u16),
+                    BwSoundId::new_unchecked(419u16),
                 )),
             }),
         },
@@ -1420,11 +1420,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(256u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(258u16),
-                    BwSoundId::new_unchecked(264u16),
+                    BwSoundId::new_unchecked(265u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(269u16),
-                    BwSoundId::new_unchecked(272u16),
+                    BwSoundId::new_unchecked(273u16),
                 )),
             }),
         },
@@ -1486,11 +1486,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(332u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(333u16),
-                    BwSoundId::new_unchecked(339u16),
+                    BwSoundId::new_unchecked(340u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(344u16),
-                    BwSoundId::new_unchecked(347u16),
+                    BwSoundId::new_unchecked(348u16),
                 )),
             }),
         },
@@ -1552,11 +1552,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(431u16),
-                    BwSoundId::new_unchecked(435u16),
+                    BwSoundId::new_unchecked(436u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(440u16),
-                    BwSoundId::new_unchecked(443u16),
+                    BwSoundId::new_unchecked(444u16),
                 )),
             }),
         },
@@ -1618,11 +1618,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(431u16),
-                    BwSoundId::new_unchecked(435u16),
+                    BwSoundId::new_unchecked(436u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(440u16),
-                    BwSoundId::new_unchecked(443u16),
+                    BwSoundId::new_unchecked(444u16),
                 )),
             }),
         },
@@ -1684,11 +1684,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(431u16),
-                    BwSoundId::new_unchecked(435u16),
+                    BwSoundId::new_unchecked(436u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(440u16),
-                    BwSoundId::new_unchecked(443u16),
+                    BwSoundId::new_unchecked(444u16),
                 )),
             }),
         },
@@ -1750,11 +1750,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(431u16),
-                    BwSoundId::new_unchecked(435u16),
+                    BwSoundId::new_unchecked(436u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(440u16),
-                    BwSoundId::new_unchecked(443u16),
+                    BwSoundId::new_unchecked(444u16),
                 )),
             }),
         },
@@ -1816,11 +1816,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(444u16),
-                    BwSoundId::new_unchecked(448u16),
+                    BwSoundId::new_unchecked(449u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(453u16),
-                    BwSoundId::new_unchecked(456u16),
+                    BwSoundId::new_unchecked(457u16),
                 )),
             }),
         },
@@ -1882,11 +1882,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(407u16),
-                    BwSoundId::new_unchecked(410u16),
+                    BwSoundId::new_unchecked(411u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(415u16),
-                    BwSoundId::new_unchecked(418u16),
+                    BwSoundId::new_unchecked(419u16),
                 )),
             }),
         },
@@ -1948,11 +1948,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(444u16),
-                    BwSoundId::new_unchecked(448u16),
+                    BwSoundId::new_unchecked(449u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(453u16),
-                    BwSoundId::new_unchecked(456u16),
+                    BwSoundId::new_unchecked(457u16),
                 )),
             }),
         },
@@ -2014,11 +2014,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(316u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(320u16),
-                    BwSoundId::new_unchecked(323u16),
+                    BwSoundId::new_unchecked(324u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(328u16),
-                    BwSoundId::new_unchecked(331u16),
+                    BwSoundId::new_unchecked(332u16),
                 )),
             }),
         },
@@ -2080,11 +2080,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(316u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(320u16),
-                    BwSoundId::new_unchecked(323u16),
+                    BwSoundId::new_unchecked(324u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(328u16),
-                    BwSoundId::new_unchecked(331u16),
+                    BwSoundId::new_unchecked(332u16),
                 )),
             }),
         },
@@ -2146,11 +2146,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(295u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(303u16),
-                    BwSoundId::new_unchecked(309u16),
+                    BwSoundId::new_unchecked(310u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(310u16),
-                    BwSoundId::new_unchecked(313u16),
+                    BwSoundId::new_unchecked(314u16),
                 )),
             }),
         },
@@ -2269,11 +2269,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(999u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(1009u16),
-                    BwSoundId::new_unchecked(1015u16),
+                    BwSoundId::new_unchecked(1016u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(1005u16),
-                    BwSoundId::new_unchecked(1008u16),
+                    BwSoundId::new_unchecked(1009u16),
                 )),
             }),
         },
@@ -2335,7 +2335,7 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(850u16),
-                    BwSoundId::new_unchecked(850u16),
+                    BwSoundId::new_unchecked(851u16),
                 )),
                 yes_sounds: None,
             }),
@@ -2398,7 +2398,7 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(827u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(831u16),
-                    BwSoundId::new_unchecked(831u16),
+                    BwSoundId::new_unchecked(832u16),
                 )),
                 yes_sounds: None,
             }),
@@ -2461,11 +2461,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(900u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(897u16),
-                    BwSoundId::new_unchecked(899u16),
+                    BwSoundId::new_unchecked(900u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(905u16),
-                    BwSoundId::new_unchecked(908u16),
+                    BwSoundId::new_unchecked(909u16),
                 )),
             }),
         },
@@ -2527,11 +2527,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(866u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(868u16),
-                    BwSoundId::new_unchecked(869u16),
+                    BwSoundId::new_unchecked(870u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(873u16),
-                    BwSoundId::new_unchecked(876u16),
+                    BwSoundId::new_unchecked(877u16),
                 )),
             }),
         },
@@ -2593,11 +2593,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(882u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(879u16),
-                    BwSoundId::new_unchecked(881u16),
+                    BwSoundId::new_unchecked(882u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(887u16),
-                    BwSoundId::new_unchecked(890u16),
+                    BwSoundId::new_unchecked(891u16),
                 )),
             }),
         },
@@ -2659,11 +2659,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(787u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(788u16),
-                    BwSoundId::new_unchecked(791u16),
+                    BwSoundId::new_unchecked(792u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(796u16),
-                    BwSoundId::new_unchecked(799u16),
+                    BwSoundId::new_unchecked(800u16),
                 )),
             }),
         },
@@ -2725,11 +2725,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(832u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(834u16),
-                    BwSoundId::new_unchecked(836u16),
+                    BwSoundId::new_unchecked(837u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(842u16),
-                    BwSoundId::new_unchecked(846u16),
+                    BwSoundId::new_unchecked(847u16),
                 )),
             }),
         },
@@ -2791,11 +2791,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(909u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(911u16),
-                    BwSoundId::new_unchecked(911u16),
+                    BwSoundId::new_unchecked(912u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(916u16),
-                    BwSoundId::new_unchecked(919u16),
+                    BwSoundId::new_unchecked(920u16),
                 )),
             }),
         },
@@ -2857,11 +2857,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(941u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(943u16),
-                    BwSoundId::new_unchecked(946u16),
+                    BwSoundId::new_unchecked(947u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(951u16),
-                    BwSoundId::new_unchecked(954u16),
+                    BwSoundId::new_unchecked(955u16),
                 )),
             }),
         },
@@ -2923,11 +2923,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(857u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(853u16),
-                    BwSoundId::new_unchecked(856u16),
+                    BwSoundId::new_unchecked(857u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(862u16),
-                    BwSoundId::new_unchecked(865u16),
+                    BwSoundId::new_unchecked(866u16),
                 )),
             }),
         },
@@ -2989,11 +2989,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(928u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(929u16),
-                    BwSoundId::new_unchecked(932u16),
+                    BwSoundId::new_unchecked(933u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(937u16),
-                    BwSoundId::new_unchecked(940u16),
+                    BwSoundId::new_unchecked(941u16),
                 )),
             }),
         },
@@ -3055,11 +3055,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(814u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(818u16),
-                    BwSoundId::new_unchecked(820u16),
+                    BwSoundId::new_unchecked(821u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(824u16),
-                    BwSoundId::new_unchecked(826u16),
+                    BwSoundId::new_unchecked(827u16),
                 )),
             }),
         },
@@ -3121,11 +3121,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(775u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(779u16),
-                    BwSoundId::new_unchecked(780u16),
+                    BwSoundId::new_unchecked(781u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(783u16),
-                    BwSoundId::new_unchecked(784u16),
+                    BwSoundId::new_unchecked(785u16),
                 )),
             }),
         },
@@ -3187,11 +3187,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(882u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(879u16),
-                    BwSoundId::new_unchecked(881u16),
+                    BwSoundId::new_unchecked(882u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(887u16),
-                    BwSoundId::new_unchecked(890u16),
+                    BwSoundId::new_unchecked(891u16),
                 )),
             }),
         },
@@ -3253,11 +3253,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(928u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(929u16),
-                    BwSoundId::new_unchecked(932u16),
+                    BwSoundId::new_unchecked(933u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(937u16),
-                    BwSoundId::new_unchecked(940u16),
+                    BwSoundId::new_unchecked(941u16),
                 )),
             }),
         },
@@ -3319,11 +3319,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(800u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(801u16),
-                    BwSoundId::new_unchecked(804u16),
+                    BwSoundId::new_unchecked(805u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(809u16),
-                    BwSoundId::new_unchecked(812u16),
+                    BwSoundId::new_unchecked(813u16),
                 )),
             }),
         },
@@ -3385,11 +3385,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(955u16),
-                    BwSoundId::new_unchecked(958u16),
+                    BwSoundId::new_unchecked(959u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(963u16),
-                    BwSoundId::new_unchecked(966u16),
+                    BwSoundId::new_unchecked(967u16),
                 )),
             }),
         },
@@ -3451,11 +3451,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(814u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(818u16),
-                    BwSoundId::new_unchecked(820u16),
+                    BwSoundId::new_unchecked(821u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(824u16),
-                    BwSoundId::new_unchecked(826u16),
+                    BwSoundId::new_unchecked(827u16),
                 )),
             }),
         },
@@ -3517,11 +3517,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(866u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(868u16),
-                    BwSoundId::new_unchecked(869u16),
+                    BwSoundId::new_unchecked(870u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(873u16),
-                    BwSoundId::new_unchecked(876u16),
+                    BwSoundId::new_unchecked(877u16),
                 )),
             }),
         },
@@ -3583,11 +3583,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(900u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(897u16),
-                    BwSoundId::new_unchecked(899u16),
+                    BwSoundId::new_unchecked(900u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(905u16),
-                    BwSoundId::new_unchecked(908u16),
+                    BwSoundId::new_unchecked(909u16),
                 )),
             }),
         },
@@ -3649,11 +3649,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(941u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(943u16),
-                    BwSoundId::new_unchecked(946u16),
+                    BwSoundId::new_unchecked(947u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(951u16),
-                    BwSoundId::new_unchecked(954u16),
+                    BwSoundId::new_unchecked(955u16),
                 )),
             }),
         },
@@ -3715,11 +3715,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(857u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(853u16),
-                    BwSoundId::new_unchecked(856u16),
+                    BwSoundId::new_unchecked(857u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(862u16),
-                    BwSoundId::new_unchecked(865u16),
+                    BwSoundId::new_unchecked(866u16),
                 )),
             }),
         },
@@ -3781,11 +3781,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(909u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(911u16),
-                    BwSoundId::new_unchecked(911u16),
+                    BwSoundId::new_unchecked(912u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(916u16),
-                    BwSoundId::new_unchecked(919u16),
+                    BwSoundId::new_unchecked(920u16),
                 )),
             }),
         },
@@ -3847,11 +3847,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(1024u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(1025u16),
-                    BwSoundId::new_unchecked(1030u16),
+                    BwSoundId::new_unchecked(1031u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(1035u16),
-                    BwSoundId::new_unchecked(1039u16),
+                    BwSoundId::new_unchecked(1040u16),
                 )),
             }),
         },
@@ -3913,7 +3913,7 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(827u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(831u16),
-                    BwSoundId::new_unchecked(831u16),
+                    BwSoundId::new_unchecked(832u16),
                 )),
                 yes_sounds: None,
             }),
@@ -3976,11 +3976,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(1041u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(1052u16),
-                    BwSoundId::new_unchecked(1058u16),
+                    BwSoundId::new_unchecked(1059u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(1048u16),
-                    BwSoundId::new_unchecked(1051u16),
+                    BwSoundId::new_unchecked(1052u16),
                 )),
             }),
         },
@@ -4042,11 +4042,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(728u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(729u16),
-                    BwSoundId::new_unchecked(732u16),
+                    BwSoundId::new_unchecked(733u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(737u16),
-                    BwSoundId::new_unchecked(740u16),
+                    BwSoundId::new_unchecked(741u16),
                 )),
             }),
         },
@@ -4108,11 +4108,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(1096u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(1098u16),
-                    BwSoundId::new_unchecked(1100u16),
+                    BwSoundId::new_unchecked(1101u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(1104u16),
-                    BwSoundId::new_unchecked(1107u16),
+                    BwSoundId::new_unchecked(1108u16),
                 )),
             }),
         },
@@ -4174,11 +4174,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(1065u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(1067u16),
-                    BwSoundId::new_unchecked(1070u16),
+                    BwSoundId::new_unchecked(1071u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(1075u16),
-                    BwSoundId::new_unchecked(1078u16),
+                    BwSoundId::new_unchecked(1079u16),
                 )),
             }),
         },
@@ -4240,11 +4240,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(597u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(602u16),
-                    BwSoundId::new_unchecked(605u16),
+                    BwSoundId::new_unchecked(606u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(610u16),
-                    BwSoundId::new_unchecked(613u16),
+                    BwSoundId::new_unchecked(614u16),
                 )),
             }),
         },
@@ -4306,11 +4306,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(666u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(667u16),
-                    BwSoundId::new_unchecked(669u16),
+                    BwSoundId::new_unchecked(670u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(674u16),
-                    BwSoundId::new_unchecked(677u16),
+                    BwSoundId::new_unchecked(678u16),
                 )),
             }),
         },
@@ -4372,11 +4372,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(492u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(494u16),
-                    BwSoundId::new_unchecked(497u16),
+                    BwSoundId::new_unchecked(498u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(506u16),
-                    BwSoundId::new_unchecked(512u16),
+                    BwSoundId::new_unchecked(513u16),
                 )),
             }),
         },
@@ -4438,11 +4438,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(622u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(623u16),
-                    BwSoundId::new_unchecked(626u16),
+                    BwSoundId::new_unchecked(627u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(631u16),
-                    BwSoundId::new_unchecked(634u16),
+                    BwSoundId::new_unchecked(635u16),
                 )),
             }),
         },
@@ -4504,11 +4504,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(567u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(569u16),
-                    BwSoundId::new_unchecked(572u16),
+                    BwSoundId::new_unchecked(573u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(577u16),
-                    BwSoundId::new_unchecked(580u16),
+                    BwSoundId::new_unchecked(581u16),
                 )),
             }),
         },
@@ -4570,11 +4570,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(513u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(515u16),
-                    BwSoundId::new_unchecked(519u16),
+                    BwSoundId::new_unchecked(520u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(524u16),
-                    BwSoundId::new_unchecked(527u16),
+                    BwSoundId::new_unchecked(528u16),
                 )),
             }),
         },
@@ -4636,11 +4636,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(534u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(535u16),
-                    BwSoundId::new_unchecked(539u16),
+                    BwSoundId::new_unchecked(540u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(544u16),
-                    BwSoundId::new_unchecked(547u16),
+                    BwSoundId::new_unchecked(548u16),
                 )),
             }),
         },
@@ -4702,11 +4702,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(549u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(554u16),
-                    BwSoundId::new_unchecked(558u16),
+                    BwSoundId::new_unchecked(559u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(563u16),
-                    BwSoundId::new_unchecked(565u16),
+                    BwSoundId::new_unchecked(566u16),
                 )),
             }),
         },
@@ -4768,11 +4768,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(582u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(583u16),
-                    BwSoundId::new_unchecked(586u16),
+                    BwSoundId::new_unchecked(587u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(591u16),
-                    BwSoundId::new_unchecked(594u16),
+                    BwSoundId::new_unchecked(595u16),
                 )),
             }),
         },
@@ -4891,11 +4891,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(728u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(729u16),
-                    BwSoundId::new_unchecked(732u16),
+                    BwSoundId::new_unchecked(733u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(737u16),
-                    BwSoundId::new_unchecked(740u16),
+                    BwSoundId::new_unchecked(741u16),
                 )),
             }),
         },
@@ -4957,11 +4957,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(742u16),
-                    BwSoundId::new_unchecked(745u16),
+                    BwSoundId::new_unchecked(746u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(750u16),
-                    BwSoundId::new_unchecked(753u16),
+                    BwSoundId::new_unchecked(754u16),
                 )),
             }),
         },
@@ -5023,11 +5023,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(567u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(569u16),
-                    BwSoundId::new_unchecked(572u16),
+                    BwSoundId::new_unchecked(573u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(577u16),
-                    BwSoundId::new_unchecked(580u16),
+                    BwSoundId::new_unchecked(581u16),
                 )),
             }),
         },
@@ -5089,11 +5089,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(691u16),
-                    BwSoundId::new_unchecked(694u16),
+                    BwSoundId::new_unchecked(695u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(699u16),
-                    BwSoundId::new_unchecked(702u16),
+                    BwSoundId::new_unchecked(703u16),
                 )),
             }),
         },
@@ -5155,11 +5155,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(679u16),
-                    BwSoundId::new_unchecked(682u16),
+                    BwSoundId::new_unchecked(683u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(687u16),
-                    BwSoundId::new_unchecked(690u16),
+                    BwSoundId::new_unchecked(691u16),
                 )),
             }),
         },
@@ -5221,11 +5221,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(715u16),
-                    BwSoundId::new_unchecked(718u16),
+                    BwSoundId::new_unchecked(719u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(723u16),
-                    BwSoundId::new_unchecked(726u16),
+                    BwSoundId::new_unchecked(727u16),
                 )),
             }),
         },
@@ -5287,11 +5287,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(534u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(535u16),
-                    BwSoundId::new_unchecked(539u16),
+                    BwSoundId::new_unchecked(540u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(544u16),
-                    BwSoundId::new_unchecked(547u16),
+                    BwSoundId::new_unchecked(548u16),
                 )),
             }),
         },
@@ -5353,11 +5353,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(637u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(639u16),
-                    BwSoundId::new_unchecked(641u16),
+                    BwSoundId::new_unchecked(642u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(646u16),
-                    BwSoundId::new_unchecked(649u16),
+                    BwSoundId::new_unchecked(650u16),
                 )),
             }),
         },
@@ -5419,11 +5419,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(703u16),
-                    BwSoundId::new_unchecked(706u16),
+                    BwSoundId::new_unchecked(707u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(711u16),
-                    BwSoundId::new_unchecked(714u16),
+                    BwSoundId::new_unchecked(715u16),
                 )),
             }),
         },
@@ -5485,11 +5485,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(637u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(639u16),
-                    BwSoundId::new_unchecked(641u16),
+                    BwSoundId::new_unchecked(642u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(646u16),
-                    BwSoundId::new_unchecked(649u16),
+                    BwSoundId::new_unchecked(650u16),
                 )),
             }),
         },
@@ -5551,11 +5551,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(650u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(653u16),
-                    BwSoundId::new_unchecked(657u16),
+                    BwSoundId::new_unchecked(658u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(660u16),
-                    BwSoundId::new_unchecked(661u16),
+                    BwSoundId::new_unchecked(662u16),
                 )),
             }),
         },
@@ -5617,11 +5617,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(535u16),
-                    BwSoundId::new_unchecked(539u16),
+                    BwSoundId::new_unchecked(540u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(544u16),
-                    BwSoundId::new_unchecked(547u16),
+                    BwSoundId::new_unchecked(548u16),
                 )),
             }),
         },
@@ -5683,11 +5683,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(549u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(554u16),
-                    BwSoundId::new_unchecked(558u16),
+                    BwSoundId::new_unchecked(559u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(563u16),
-                    BwSoundId::new_unchecked(565u16),
+                    BwSoundId::new_unchecked(566u16),
                 )),
             }),
         },
@@ -5806,11 +5806,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(1130u16),
-                    BwSoundId::new_unchecked(1135u16),
+                    BwSoundId::new_unchecked(1136u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(1140u16),
-                    BwSoundId::new_unchecked(1143u16),
+                    BwSoundId::new_unchecked(1144u16),
                 )),
             }),
         },
@@ -6346,7 +6346,7 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(827u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(831u16),
-                    BwSoundId::new_unchecked(831u16),
+                    BwSoundId::new_unchecked(832u16),
                 )),
                 yes_sounds: None,
             }),
@@ -6409,11 +6409,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(1041u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(1052u16),
-                    BwSoundId::new_unchecked(1058u16),
+                    BwSoundId::new_unchecked(1059u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(1048u16),
-                    BwSoundId::new_unchecked(1051u16),
+                    BwSoundId::new_unchecked(1052u16),
                 )),
             }),
         },
@@ -6475,11 +6475,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(980u16),
-                    BwSoundId::new_unchecked(988u16),
+                    BwSoundId::new_unchecked(989u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(993u16),
-                    BwSoundId::new_unchecked(996u16),
+                    BwSoundId::new_unchecked(997u16),
                 )),
             }),
         },
@@ -6541,11 +6541,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(226u16),
-                    BwSoundId::new_unchecked(229u16),
+                    BwSoundId::new_unchecked(230u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(234u16),
-                    BwSoundId::new_unchecked(237u16),
+                    BwSoundId::new_unchecked(238u16),
                 )),
             }),
         },
@@ -6664,11 +6664,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(444u16),
-                    BwSoundId::new_unchecked(448u16),
+                    BwSoundId::new_unchecked(449u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(453u16),
-                    BwSoundId::new_unchecked(456u16),
+                    BwSoundId::new_unchecked(457u16),
                 )),
             }),
         },
@@ -6730,11 +6730,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: Some(BwSoundId::new_unchecked(1079u16)),
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(1081u16),
-                    BwSoundId::new_unchecked(1084u16),
+                    BwSoundId::new_unchecked(1085u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(1089u16),
-                    BwSoundId::new_unchecked(1092u16),
+                    BwSoundId::new_unchecked(1093u16),
                 )),
             }),
         },
@@ -6796,11 +6796,11 @@ pub const CONSTRUCTS: [Construct; 228usize] = unsafe {
                 ready_sound: None,
                 piss_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(1112u16),
-                    BwSoundId::new_unchecked(1120u16),
+                    BwSoundId::new_unchecked(1121u16),
                 )),
                 yes_sounds: Some(BwSoundRange::new(
                     BwSoundId::new_unchecked(1125u16),
-                    BwSoundId::new_unchecked(1128u16),
+                    BwSoundId::new_unchecked(1129u16),
                 )),
             }),
         },
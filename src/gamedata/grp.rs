@@ -0,0 +1,314 @@
+// Useful links for .grp stuff:
+// http://www.staredit.net/wiki/index.php/GRP
+// https://github.com/neivv/animosity/blob/master/src/grp.rs
+
+use bevy::{
+    asset::{io::Reader, Asset, AssetLoader, AsyncReadExt, Handle, LoadContext},
+    math::{URect, UVec2, Vec2},
+    reflect::TypePath,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::Image,
+    },
+    sprite::{Anchor, TextureAtlasLayout},
+};
+use byteorder::{ByteOrder, LittleEndian};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum GrpError {
+    #[error("grp header is invalid")]
+    InvalidHeader,
+    #[error("frame {0} is out of bounds")]
+    OutOfBoundsFrame(usize),
+    #[error("failed to read file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// The maximum width of the texture that GRP frames are packed into.
+const MAX_ATLAS_WIDTH: u32 = 1024;
+
+#[derive(Debug, Default)]
+pub struct GrpAssetLoader {}
+
+impl AssetLoader for GrpAssetLoader {
+    type Asset = GrpAsset;
+    type Settings = ();
+    type Error = GrpError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+
+        let grp = load_grp(&bytes)?;
+        let (indices, rects) = pack_frames(&grp.frames);
+
+        let width = grp.width as f32;
+        let height = grp.height as f32;
+        let mut layout = TextureAtlasLayout::new_empty(UVec2::new(indices.width, indices.height));
+        let mut offsets = Vec::with_capacity(grp.frames.len());
+        for (frame, rect) in grp.frames.iter().zip(rects) {
+            // Frames are positioned relative to the top-left of the GRP's full size, whereas Bevy
+            // anchors are relative to the center of the frame's own area (with +Y being up)
+            let anchor_point =
+                Vec2::new(width / 2.0 - frame.x as f32, height / 2.0 - frame.y as f32);
+            offsets.push(Anchor::Custom(Vec2::new(
+                anchor_point.x / rect.width() as f32 - 0.5,
+                0.5 - anchor_point.y / rect.height() as f32,
+            )));
+            layout.add_texture(rect);
+        }
+
+        let layout = load_context.labeled_asset_scope("layout".to_string(), |_| layout);
+        let indices = load_context.labeled_asset_scope("indices".to_string(), |_| {
+            Image::new(
+                Extent3d {
+                    width: indices.width,
+                    height: indices.height,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                indices.data,
+                TextureFormat::R8Uint,
+                RenderAssetUsages::RENDER_WORLD,
+            )
+        });
+
+        Ok(GrpAsset {
+            size: Vec2::new(width, height),
+            layout,
+            frame_count: grp.frames.len(),
+            offsets,
+            indices,
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["grp"]
+    }
+}
+
+/// A GRP (the original game's graphics format), whose frames have been packed into a single
+/// texture of palette indices. This is the SD equivalent of an [AnimAsset](super::anim::AnimAsset).
+#[derive(Asset, Debug, TypePath)]
+pub struct GrpAsset {
+    pub size: Vec2,
+    pub layout: Handle<TextureAtlasLayout>,
+    pub frame_count: usize,
+    /// How much to offset each frame from the sprite's origin during rendering. These are expressed
+    /// as a fraction of the size of the frame.
+    pub offsets: Vec<Anchor>,
+    /// A texture of palette indices (in [TextureFormat::R8Uint]), where 0 is transparent.
+    pub indices: Handle<Image>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GrpFile {
+    pub width: u16,
+    pub height: u16,
+    pub frames: Vec<GrpFrame>,
+}
+
+/// A decoded GRP frame. Only the area of the frame that contains non-transparent pixels is stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrpFrame {
+    /// X position of this frame's area within the GRP's full size.
+    pub x: u8,
+    /// Y position of this frame's area within the GRP's full size.
+    pub y: u8,
+    pub width: u8,
+    pub height: u8,
+    /// The palette index of each pixel (row-major), where 0 is transparent.
+    pub pixels: Vec<u8>,
+}
+
+pub fn load_grp(bytes: &[u8]) -> Result<GrpFile, GrpError> {
+    if bytes.len() < 6 {
+        return Err(GrpError::InvalidHeader);
+    }
+    let frame_count = LittleEndian::read_u16(&bytes[0..]) as usize;
+    let width = LittleEndian::read_u16(&bytes[2..]);
+    let height = LittleEndian::read_u16(&bytes[4..]);
+    if bytes.len() < 6 + frame_count * 8 {
+        return Err(GrpError::InvalidHeader);
+    }
+
+    let frames = (0..frame_count)
+        .map(|i| {
+            let header = &bytes[6 + i * 8..6 + (i + 1) * 8];
+            let frame_width = header[2];
+            let frame_height = header[3];
+            let offset = LittleEndian::read_u32(&header[4..]) as usize;
+            let pixels = decode_frame(bytes, offset, frame_width, frame_height)
+                .ok_or(GrpError::OutOfBoundsFrame(i))?;
+
+            Ok(GrpFrame {
+                x: header[0],
+                y: header[1],
+                width: frame_width,
+                height: frame_height,
+                pixels,
+            })
+        })
+        .collect::<Result<Vec<_>, GrpError>>()?;
+
+    Ok(GrpFile {
+        width,
+        height,
+        frames,
+    })
+}
+
+/// Decodes the RLE-encoded lines of a frame that starts at `offset`. Each line starts at an offset
+/// (relative to the frame) specified in a table at the start of the frame, and consists of a series
+/// of commands:
+///
+/// - `0x80 | n`: skip `n` transparent pixels
+/// - `0x40 | n`: repeat the next byte `n` times
+/// - `n`: copy the next `n` bytes
+fn decode_frame(bytes: &[u8], offset: usize, width: u8, height: u8) -> Option<Vec<u8>> {
+    let width = width as usize;
+    let mut pixels = vec![0; width * height as usize];
+
+    for (y, line) in pixels.chunks_exact_mut(width.max(1)).enumerate() {
+        let line_offset = bytes.get(offset + y * 2..offset + y * 2 + 2)?;
+        let mut pos = offset + LittleEndian::read_u16(line_offset) as usize;
+        let mut x = 0;
+        while x < width {
+            let command = *bytes.get(pos)?;
+            pos += 1;
+            if command & 0x3f == 0 && command & 0x80 == 0 || command == 0x80 {
+                // A command that doesn't advance would loop forever, so treat it as invalid
+                return None;
+            }
+            if command & 0x80 != 0 {
+                x += (command & 0x7f) as usize;
+            } else if command & 0x40 != 0 {
+                let count = ((command & 0x3f) as usize).min(width - x);
+                let color = *bytes.get(pos)?;
+                pos += 1;
+                line[x..x + count].fill(color);
+                x += count;
+            } else {
+                let count = (command as usize).min(width - x);
+                line[x..x + count].copy_from_slice(bytes.get(pos..pos + count)?);
+                pos += command as usize;
+                x += count;
+            }
+        }
+    }
+
+    Some(pixels)
+}
+
+/// A texture of palette indices that frames have been packed into.
+struct PackedFrames {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+/// Packs frames into a single texture (in rows, left to right), returning the texture and the area
+/// each frame was placed at.
+fn pack_frames(frames: &[GrpFrame]) -> (PackedFrames, Vec<URect>) {
+    let mut rects = Vec::with_capacity(frames.len());
+    let (mut x, mut y, mut row_height, mut width) = (0, 0, 0, 0);
+    for frame in frames {
+        // NOTE(tec27): Empty frames still get a (transparent) pixel so that they have a valid area
+        let frame_width = (frame.width as u32).max(1);
+        let frame_height = (frame.height as u32).max(1);
+        if x + frame_width > MAX_ATLAS_WIDTH {
+            x = 0;
+            y += row_height;
+            row_height = 0;
+        }
+        rects.push(URect::new(x, y, x + frame_width, y + frame_height));
+        x += frame_width;
+        width = width.max(x);
+        row_height = row_height.max(frame_height);
+    }
+    let width = width.max(1);
+    let height = (y + row_height).max(1);
+
+    let mut data = vec![0; (width * height) as usize];
+    for (frame, rect) in frames.iter().zip(rects.iter()) {
+        for (row, line) in frame
+            .pixels
+            .chunks_exact(frame.width.max(1) as usize)
+            .enumerate()
+        {
+            let start = ((rect.min.y + row as u32) * width + rect.min.x) as usize;
+            data[start..start + line.len()].copy_from_slice(line);
+        }
+    }
+
+    (
+        PackedFrames {
+            width,
+            height,
+            data,
+        },
+        rects,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_rle_frames() {
+        let mut bytes = vec![
+            // Header: 1 frame, 8x4
+            1, 0, 8, 0, 4, 0, //
+            // Frame: at (2, 1), 4x2, data at offset 14
+            2, 1, 4, 2, 14, 0, 0, 0,
+        ];
+        // Line offsets (relative to the start of the frame data)
+        bytes.extend_from_slice(&[4, 0, 9, 0]);
+        // Line 0: skip 1, repeat 5 twice, copy 1 byte
+        bytes.extend_from_slice(&[0x81, 0x42, 5, 0x01, 9]);
+        // Line 1: copy 3 bytes, skip 1
+        bytes.extend_from_slice(&[0x03, 1, 2, 3, 0x81]);
+
+        let grp = load_grp(&bytes).unwrap();
+        assert_eq!(grp.width, 8);
+        assert_eq!(grp.height, 4);
+        assert_eq!(
+            grp.frames,
+            vec![GrpFrame {
+                x: 2,
+                y: 1,
+                width: 4,
+                height: 2,
+                pixels: vec![0, 5, 5, 9, 1, 2, 3, 0],
+            }]
+        );
+    }
+
+    #[test]
+    fn pack() {
+        let frame = |width: u8, height: u8| GrpFrame {
+            x: 0,
+            y: 0,
+            width,
+            height,
+            pixels: vec![1; width as usize * height as usize],
+        };
+        let mut frames = vec![frame(200, 2); 5];
+        frames.extend([frame(200, 3), frame(0, 0)]);
+        let (packed, rects) = pack_frames(&frames);
+        assert_eq!(rects[4], URect::new(800, 0, 1000, 2));
+        assert_eq!(rects[5], URect::new(0, 2, 200, 5));
+        assert_eq!(rects[6], URect::new(200, 2, 201, 3));
+        assert_eq!((packed.width, packed.height), (1000, 5));
+        assert_eq!(packed.data[4 * 1000], 1);
+        assert_eq!(packed.data[2 * 1000 + 200], 0);
+    }
+}
@@ -68,7 +68,7 @@ impl AssetLoader for LoAssetLoader {
     }
 }
 
-fn load_lo<R: Read + Seek + Send>(mut r: R) -> anyhow::Result<LoAsset> {
+pub fn load_lo<R: Read + Seek + Send>(mut r: R) -> anyhow::Result<LoAsset> {
     let frame_count = r.read_u32::<LittleEndian>()? as usize;
     let offset_count = r.read_u32::<LittleEndian>()? as usize;
 
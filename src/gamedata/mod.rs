@@ -1,25 +1,36 @@
-use bevy::{asset::LoadState, prelude::*, sprite::Anchor, transform::TransformSystem};
+use bevy::{
+    asset::{AssetLoadFailedEvent, LoadState},
+    prelude::*,
+    sprite::Anchor,
+    transform::TransformSystem,
+};
 
 use crate::{
     gameplay::constructs::ConstructImage,
-    settings::{AssetPack, GameSettings},
+    settings::{AssetPack, AssetQuality, GameSettings},
     states::AppState,
 };
 
 use self::{
     anim::{AnimAsset, AnimAssetLoader},
+    dds_grp::{DdsGrpAsset, DdsGrpAssetLoader},
+    grp::{GrpAsset, GrpAssetLoader},
     lo::{LoAsset, LoAssetLoader},
+    palette::{PaletteAsset, PaletteAssetLoader, PcxAsset, PcxAssetLoader},
     rel::{RelAsset, RelAssetLoader},
     tbl::{TblAsset, TblAssetLoader},
 };
 
 pub mod anim;
 mod construct;
+pub mod dds_grp;
 mod flingy;
 mod generated;
+pub mod grp;
 mod image;
 mod iscript;
 pub mod lo;
+pub mod palette;
 pub mod rel;
 mod sound;
 mod sprite;
@@ -45,8 +56,16 @@ impl Plugin for GameDataPlugin {
     fn build(&self, app: &mut App) {
         app.init_asset::<AnimAsset>()
             .init_asset_loader::<AnimAssetLoader>()
+            .init_asset::<DdsGrpAsset>()
+            .init_asset_loader::<DdsGrpAssetLoader>()
+            .init_asset::<GrpAsset>()
+            .init_asset_loader::<GrpAssetLoader>()
             .init_asset::<LoAsset>()
             .init_asset_loader::<LoAssetLoader>()
+            .init_asset::<PaletteAsset>()
+            .init_asset_loader::<PaletteAssetLoader>()
+            .init_asset::<PcxAsset>()
+            .init_asset_loader::<PcxAssetLoader>()
             .init_asset::<RelAsset>()
             .init_asset_loader::<RelAssetLoader>()
             .init_asset::<TblAsset>()
@@ -55,11 +74,17 @@ impl Plugin for GameDataPlugin {
             .register_type::<AnimOffsets>()
             .register_type::<AnimFrameCount>()
             .register_type::<SpecialOverlay>()
+            .register_type::<TeamColorMask>()
+            .register_type::<AnimLightingLayers>()
             .add_systems(OnEnter(AppState::PreGame), load_game_data)
             .add_systems(
                 Update,
                 check_game_data_load.run_if(in_state(AppState::PreGame)),
             )
+            .add_systems(
+                Update,
+                reload_game_data.run_if(resource_exists::<BwGameDataHandles>),
+            )
             // TODO(tec27): Maybe make a separate schedule for this. This one is public and in the
             // correct spot (and this is a very similar usecase) but it's not mentioned much in the
             // docs so it feels a bit iffy that it will exist forever? Unsure
@@ -84,62 +109,112 @@ impl Plugin for GameDataPlugin {
                 };
 
                 let loading_anim = world.get::<LoadingAnim>(entity).unwrap();
-                let relation = game_data
-                    .relations
-                    .entries
-                    .get(loading_anim.anim_id as usize)
-                    .copied()
-                    .unwrap_or_default();
-                let id = if relation.is_image_reference() && relation.ref_image.is_some() {
-                    relation.ref_image.unwrap() as u16
-                } else {
-                    loading_anim.anim_id
-                };
+                let anim_id = loading_anim.anim_id;
+                let asset_server = world.get_resource::<AssetServer>().unwrap();
+                let image_def = IMAGES.get(anim_id as usize);
+                if image_def.is_none() {
+                    warn!("No image definition found for anim_id {anim_id}");
+                }
 
-                let asset_pack = if id == START_LOCATION_ID {
-                    AssetPack::Standard
+                let mut grp_handle: Option<Handle<GrpAsset>> = None;
+                let mut anim_handle: Option<Handle<AnimAsset>> = None;
+                if settings.asset_quality == AssetQuality::Standard {
+                    // SD graphics use the original GRPs rather than anims
+                    grp_handle = image_def
+                        .and_then(|def| grp_path(def, &game_data.image_paths))
+                        .map(|path| asset_server.load(path));
+                    if grp_handle.is_none() {
+                        warn!("No GRP found for anim_id {anim_id}");
+                    }
                 } else {
-                    settings.asset_pack
-                };
-
-                let asset_server = world.get_resource::<AssetServer>().unwrap();
-                let handle: Handle<AnimAsset> = asset_server.load(format!(
-                    "casc-extracted/{}anim/{}main_{:03}.anim",
-                    settings.asset_quality.asset_path(),
-                    asset_pack.asset_path(),
-                    id
-                ));
-
-                let Some(image_def) = IMAGES.get(loading_anim.anim_id as usize) else {
-                    warn!(
-                        "No image definition found for anim_id {}",
-                        loading_anim.anim_id
-                    );
-                    world.commands().entity(entity).insert(handle);
-                    return;
-                };
-                // TODO(tec27): implement other overlay types
-                if let Some(special_overlay) = image_def
-                    .special_overlay
-                    .and_then(|o| game_data.image_paths.get(o.get() as usize))
-                {
-                    let handle: Handle<LoAsset> =
-                        asset_server.load(format!("casc-extracted\\unit\\{}", special_overlay));
-                    world
-                        .commands()
-                        .entity(entity)
-                        .insert(SpecialOverlay(handle));
+                    anim_handle = Some(asset_server.load(anim_path(
+                        anim_id,
+                        &game_data.relations,
+                        settings.asset_quality,
+                        settings.asset_pack,
+                    )));
                 }
 
-                world.commands().entity(entity).insert(handle);
+                let special_overlay = image_def
+                    .and_then(|def| special_overlay_path(def, &game_data.image_paths))
+                    .map(|path| {
+                        let handle: Handle<LoAsset> = asset_server.load(path);
+                        SpecialOverlay(handle)
+                    });
+
+                let mut commands = world.commands();
+                let mut entity = commands.entity(entity);
+                if let Some(handle) = grp_handle {
+                    entity.insert(handle);
+                }
+                if let Some(handle) = anim_handle {
+                    entity.insert(handle);
+                }
+                if let Some(special_overlay) = special_overlay {
+                    entity.insert(special_overlay);
+                }
             });
     }
 }
 
+/// Path of the TBL file that contains the paths of image files (GRPs, overlays, etc.).
+pub const IMAGE_PATHS_PATH: &str = "casc-extracted/arr/images.tbl";
+/// Path of the TBL file that contains the game's strings.
+pub const STRINGS_PATH: &str = "casc-extracted/rez/stat_txt.tbl";
+/// Path of the file that specifies which images reuse the anims of other images.
+pub const RELATIONS_PATH: &str = "casc-extracted/images.rel";
+
+/// Returns the path of the anim file used to render `anim_id` in HD asset qualities.
+pub fn anim_path(
+    anim_id: u16,
+    relations: &RelAsset,
+    quality: AssetQuality,
+    pack: AssetPack,
+) -> String {
+    let relation = relations
+        .entries
+        .get(anim_id as usize)
+        .copied()
+        .unwrap_or_default();
+    let id = match relation.ref_image {
+        Some(ref_image) if relation.is_image_reference() => ref_image as u16,
+        _ => anim_id,
+    };
+
+    let pack = if id == START_LOCATION_ID {
+        AssetPack::Standard
+    } else {
+        pack
+    };
+
+    format!(
+        "casc-extracted/{}anim/{}main_{:03}.anim",
+        quality.asset_path(),
+        pack.asset_path(),
+        id
+    )
+}
+
+/// Returns the path of the GRP file used to render an image in SD, if it has one.
+pub fn grp_path(image: &BwImage, image_paths: &TblAsset) -> Option<String> {
+    image_paths
+        .get(image.grp as usize)
+        .map(|path| format!("casc-extracted\\unit\\{}", path))
+}
+
+/// Returns the path of the special overlay (LO) file for an image, if it has one.
+// TODO(tec27): implement other overlay types
+pub fn special_overlay_path(image: &BwImage, image_paths: &TblAsset) -> Option<String> {
+    image
+        .special_overlay
+        .and_then(|o| image_paths.get(o.get() as usize))
+        .map(|path| format!("casc-extracted\\unit\\{}", path))
+}
+
 /// Resource that tracks which handles we have for currently loading BW game data files. When they
 /// have been completely loaded, we will extract their underlying data into a [BwGameData] resource
 /// and discard this resource.
-#[derive(Resource, Default, Debug, Reflect)]
+#[derive(Resource, Default, Debug, Clone, Reflect)]
 #[reflect(Resource)]
 pub struct LoadingBwGameDataHandles {
     pub image_paths: Handle<TblAsset>,
@@ -147,6 +222,11 @@ pub struct LoadingBwGameDataHandles {
     pub relations: Handle<RelAsset>,
 }
 
+/// Handles to the BW game data files that have been loaded, which are kept so that changes to them
+/// can be hot reloaded.
+#[derive(Resource, Debug)]
+struct BwGameDataHandles(LoadingBwGameDataHandles);
+
 #[derive(Resource, Debug)]
 pub struct BwGameData {
     pub image_paths: TblAsset,
@@ -194,10 +274,10 @@ fn load_game_data(
         return;
     }
 
-    let image_paths = asset_server.load("casc-extracted/arr/images.tbl");
-    let strings = asset_server.load("casc-extracted/rez/stat_txt.tbl");
+    let image_paths = asset_server.load(IMAGE_PATHS_PATH);
+    let strings = asset_server.load(STRINGS_PATH);
 
-    let relations = asset_server.load("casc-extracted/images.rel");
+    let relations = asset_server.load(RELATIONS_PATH);
 
     commands.insert_resource(LoadingBwGameDataHandles {
         image_paths,
@@ -212,18 +292,34 @@ fn check_game_data_load(
     handles: Option<Res<LoadingBwGameDataHandles>>,
     tbl_assets: Res<Assets<TblAsset>>,
     rel_assets: Res<Assets<RelAsset>>,
+    mut next_state: ResMut<NextState<AppState>>,
 ) {
     let Some(handles) = handles else {
         // No game data is currently loading, so there's nothing for us to do
         return;
     };
 
-    // TODO(tec27): Handle load failures
+    let failed = [
+        asset_server.load_state(&handles.image_paths),
+        asset_server.load_state(&handles.strings),
+        asset_server.load_state(&handles.relations),
+    ]
+    .iter()
+    .any(|state| matches!(state, LoadState::Failed(_)));
+    if failed {
+        // NOTE(tec27): Dropping the handles means loading will be retried the next time we enter
+        // PreGame
+        commands.remove_resource::<LoadingBwGameDataHandles>();
+        next_state.set(AppState::LoadError);
+        return;
+    }
+
     if asset_server.is_loaded_with_dependencies(&handles.image_paths)
         && asset_server.is_loaded_with_dependencies(&handles.strings)
         && asset_server.is_loaded_with_dependencies(&handles.relations)
     {
         commands.remove_resource::<LoadingBwGameDataHandles>();
+        commands.insert_resource(BwGameDataHandles(handles.clone()));
 
         commands.insert_resource(BwGameData {
             image_paths: tbl_assets.get(&handles.image_paths).unwrap().clone(),
@@ -235,6 +331,71 @@ fn check_game_data_load(
     }
 }
 
+/// Updates the [BwGameData] when any of its files (`images.tbl`, `stat_txt.tbl`, and `images.rel`)
+/// are changed on disk. Changes are only detected if asset watching is enabled (e.g. through the
+/// `hot_reload` feature). If the new version of a file fails to load, the previously loaded data is
+/// kept.
+// TODO(tec27): The `.dat` files and `iscript.bin` are compiled in by `gen_rules`, so they can't be
+// hot reloaded yet. If they're ever loaded at runtime, running `IscriptController`s will need to be
+// restarted on the new scripts here as well.
+fn reload_game_data(
+    handles: Res<BwGameDataHandles>,
+    mut game_data: ResMut<BwGameData>,
+    mut tbl_events: EventReader<AssetEvent<TblAsset>>,
+    mut rel_events: EventReader<AssetEvent<RelAsset>>,
+    mut tbl_failures: EventReader<AssetLoadFailedEvent<TblAsset>>,
+    mut rel_failures: EventReader<AssetLoadFailedEvent<RelAsset>>,
+    tbl_assets: Res<Assets<TblAsset>>,
+    rel_assets: Res<Assets<RelAsset>>,
+) {
+    let handles = &handles.0;
+    for event in tbl_events.read() {
+        let &AssetEvent::Modified { id } = event else {
+            continue;
+        };
+        let Some(tbl) = tbl_assets.get(id) else {
+            continue;
+        };
+        if id == handles.image_paths.id() {
+            game_data.image_paths = tbl.clone();
+            info!("Reloaded image paths");
+        } else if id == handles.strings.id() {
+            game_data.strings = tbl.clone();
+            info!("Reloaded strings");
+        }
+    }
+    for event in rel_events.read() {
+        let &AssetEvent::Modified { id } = event else {
+            continue;
+        };
+        if id == handles.relations.id() {
+            if let Some(relations) = rel_assets.get(id) {
+                game_data.relations = relations.clone();
+                info!("Reloaded image relations");
+            }
+        }
+    }
+
+    let game_data_ids = [
+        handles.image_paths.id().untyped(),
+        handles.strings.id().untyped(),
+        handles.relations.id().untyped(),
+    ];
+    let failures = tbl_failures
+        .read()
+        .map(|f| (f.id.untyped(), &f.path, &f.error))
+        .chain(
+            rel_failures
+                .read()
+                .map(|f| (f.id.untyped(), &f.path, &f.error)),
+        );
+    for (id, path, error) in failures {
+        if game_data_ids.contains(&id) {
+            error!("Failed to reload game data from {path}, keeping the previous data: {error}");
+        }
+    }
+}
+
 #[derive(Component, Debug, Default, Reflect)]
 pub struct AnimOffsets {
     pub offsets: Vec<Anchor>,
@@ -249,6 +410,39 @@ impl Default for AnimFrameCount {
     }
 }
 
+/// Component containing the `teamcolor` layer of an anim, a mask specifying which parts of the
+/// image should be tinted with the owning player's color.
+#[derive(Component, Debug, Clone, Reflect)]
+pub struct TeamColorMask(pub Handle<Image>);
+
+/// Component containing the layers of an anim that are used for HD lighting. Any of these may be
+/// missing, depending on the anim.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+pub struct AnimLightingLayers {
+    /// Colors that are added to the image regardless of lighting (e.g. engines).
+    pub emissive: Option<Handle<Image>>,
+    pub normal: Option<Handle<Image>>,
+    pub specular: Option<Handle<Image>>,
+    /// Ambient occlusion (in the red channel) and depth (in the green channel).
+    pub ao_depth: Option<Handle<Image>>,
+}
+
+impl AnimLightingLayers {
+    fn for_asset(asset: &AnimAsset) -> Option<Self> {
+        let layers = Self {
+            emissive: asset.layers.get("emissive").cloned(),
+            normal: asset.layers.get("normal").cloned(),
+            specular: asset.layers.get("specular").cloned(),
+            ao_depth: asset.layers.get("ao_depth").cloned(),
+        };
+        let has_any = layers.emissive.is_some()
+            || layers.normal.is_some()
+            || layers.specular.is_some()
+            || layers.ao_depth.is_some();
+        has_any.then_some(layers)
+    }
+}
+
 #[derive(Component, Debug, Clone, Reflect)]
 pub struct SpecialOverlay(pub Handle<LoAsset>);
 
@@ -285,26 +479,119 @@ impl PreloadedAnimBundle {
             frame_count: AnimFrameCount(asset.frame_count),
         }
     }
+
+    /// Creates a bundle for an image that failed to load, which will be rendered as a solid
+    /// square (with the sprite's `custom_size` and `color`).
+    pub fn placeholder(index: usize) -> Self {
+        Self {
+            texture: Handle::default(),
+            atlas: TextureAtlas {
+                layout: Handle::default(),
+                index,
+            },
+            ..default()
+        }
+    }
 }
 
+/// Bundle for an SD image whose GRP has already been loaded. The paletted texture is rendered by
+/// [PalettedSpritePlugin](crate::render::paletted::PalettedSpritePlugin), so unlike
+/// [PreloadedAnimBundle] this doesn't include a texture for the sprite.
+#[derive(Bundle, Default)]
+pub struct PreloadedGrpBundle {
+    pub atlas: TextureAtlas,
+    pub anim_offsets: AnimOffsets,
+    pub frame_count: AnimFrameCount,
+}
+
+impl PreloadedGrpBundle {
+    pub fn for_asset(asset: &GrpAsset, index: usize) -> Self {
+        Self {
+            atlas: TextureAtlas {
+                layout: asset.layout.clone(),
+                index,
+            },
+            anim_offsets: AnimOffsets {
+                offsets: asset.offsets.clone(),
+            },
+            frame_count: AnimFrameCount(asset.frame_count),
+        }
+    }
+}
+
+/// Color of the placeholder sprite shown for images that failed to load.
+const PLACEHOLDER_COLOR: Color = Color::srgb(1.0, 0.0, 1.0);
+
 fn init_loaded_anims(
     mut commands: Commands,
-    mut query: Query<(Entity, &LoadingAnim, &Handle<AnimAsset>, &TextureAtlas)>,
+    mut query: Query<(
+        Entity,
+        &LoadingAnim,
+        &Handle<AnimAsset>,
+        &TextureAtlas,
+        &mut Sprite,
+    )>,
+    mut grp_query: Query<
+        (
+            Entity,
+            &LoadingAnim,
+            &Handle<GrpAsset>,
+            &TextureAtlas,
+            &mut Sprite,
+        ),
+        Without<Handle<AnimAsset>>,
+    >,
     anim_assets: Res<Assets<AnimAsset>>,
+    grp_assets: Res<Assets<GrpAsset>>,
     asset_server: Res<AssetServer>,
+    settings: Res<GameSettings>,
 ) {
-    for (entity, loading_anim, handle, atlas) in &mut query {
-        if let Some(anim) = anim_assets.get(handle) {
+    let placeholder_size = settings.asset_quality.tile_size();
+
+    for (entity, loading_anim, handle, atlas, mut sprite) in &mut grp_query {
+        if let Some(grp) = grp_assets.get(handle) {
+            commands
+                .entity(entity)
+                .remove::<LoadingAnim>()
+                .insert(PreloadedGrpBundle::for_asset(grp, atlas.index));
+        } else if let LoadState::Failed(_) = asset_server.load_state(handle) {
+            // NOTE(tec27): The error itself is reported by the load_error plugin
+            warn!(
+                "Using a placeholder for anim_id {} since its GRP failed to load",
+                loading_anim.anim_id
+            );
+            sprite.custom_size = Some(placeholder_size);
+            sprite.color = PLACEHOLDER_COLOR;
             commands
                 .entity(entity)
+                .remove::<(LoadingAnim, Handle<GrpAsset>)>()
+                .insert(PreloadedAnimBundle::placeholder(atlas.index));
+        }
+    }
+
+    for (entity, loading_anim, handle, atlas, mut sprite) in &mut query {
+        if let Some(anim) = anim_assets.get(handle) {
+            let mut entity = commands.entity(entity);
+            entity
                 .remove::<LoadingAnim>()
                 .insert(PreloadedAnimBundle::for_asset(anim, atlas.index));
-        } else if let LoadState::Failed(error) = asset_server.load_state(handle) {
-            // TODO(tec27): Show a dialog or something instead?
-            panic!(
-                "Failed to load anim asset for anim_id {}: {error:?}",
+            if let Some(mask) = anim.layers.get("teamcolor") {
+                entity.insert(TeamColorMask(mask.clone()));
+            }
+            if let Some(lighting) = AnimLightingLayers::for_asset(anim) {
+                entity.insert(lighting);
+            }
+        } else if let LoadState::Failed(_) = asset_server.load_state(handle) {
+            warn!(
+                "Using a placeholder for anim_id {} since it failed to load",
                 loading_anim.anim_id
             );
+            sprite.custom_size = Some(placeholder_size);
+            sprite.color = PLACEHOLDER_COLOR;
+            commands
+                .entity(entity)
+                .remove::<(LoadingAnim, Handle<AnimAsset>)>()
+                .insert(PreloadedAnimBundle::placeholder(atlas.index));
         }
     }
 }
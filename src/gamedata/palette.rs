@@ -0,0 +1,222 @@
+// Loaders for the palettes (.wpe) and paletted images (.pcx) used by SD graphics. Useful links:
+// http://www.staredit.net/wiki/index.php/Palettes
+// https://en.wikipedia.org/wiki/PCX
+
+use bevy::{
+    asset::{io::Reader, Asset, AssetLoader, AsyncReadExt, LoadContext},
+    reflect::TypePath,
+};
+use byteorder::{ByteOrder, LittleEndian};
+use thiserror::Error;
+
+/// The number of colors in a palette.
+pub const PALETTE_SIZE: usize = 256;
+
+#[derive(Error, Debug)]
+pub enum PaletteError {
+    #[error("palette is too small ({0} bytes)")]
+    TooSmall(usize),
+    #[error("pcx header is invalid")]
+    InvalidPcxHeader,
+    #[error("only 8-bit, single plane pcx files are supported")]
+    UnsupportedPcx,
+    #[error("pcx is missing its palette")]
+    MissingPcxPalette,
+    #[error("failed to read file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// A palette of 256 RGB colors.
+#[derive(Asset, Debug, Clone, PartialEq, Eq, TypePath)]
+pub struct PaletteAsset {
+    pub colors: Vec<[u8; 3]>,
+}
+
+#[derive(Debug, Default)]
+pub struct PaletteAssetLoader {}
+
+impl AssetLoader for PaletteAssetLoader {
+    type Asset = PaletteAsset;
+    type Settings = ();
+    type Error = PaletteError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        load_wpe(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["wpe"]
+    }
+}
+
+/// Loads a WPE palette, which stores each color as 4 bytes (R, G, B, and an unused byte).
+pub fn load_wpe(bytes: &[u8]) -> Result<PaletteAsset, PaletteError> {
+    if bytes.len() < PALETTE_SIZE * 4 {
+        return Err(PaletteError::TooSmall(bytes.len()));
+    }
+    Ok(PaletteAsset {
+        colors: bytes
+            .chunks_exact(4)
+            .take(PALETTE_SIZE)
+            .map(|c| [c[0], c[1], c[2]])
+            .collect(),
+    })
+}
+
+/// An 8-bit paletted image. The game uses these both as images and as lookup tables (e.g. for
+/// player colors).
+#[derive(Asset, Debug, Clone, PartialEq, Eq, TypePath)]
+pub struct PcxAsset {
+    pub width: u32,
+    pub height: u32,
+    /// The palette index of each pixel (row-major).
+    pub pixels: Vec<u8>,
+    pub palette: PaletteAsset,
+}
+
+impl PcxAsset {
+    /// Returns the palette index of the pixel at (`x`, `y`), if it is within the image.
+    pub fn get(&self, x: u32, y: u32) -> Option<u8> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.pixels.get((y * self.width + x) as usize).copied()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct PcxAssetLoader {}
+
+impl AssetLoader for PcxAssetLoader {
+    type Asset = PcxAsset;
+    type Settings = ();
+    type Error = PaletteError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a Self::Settings,
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        load_pcx(&bytes)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["pcx"]
+    }
+}
+
+const PCX_HEADER_SIZE: usize = 128;
+const PCX_PALETTE_MARKER: u8 = 0x0C;
+
+pub fn load_pcx(bytes: &[u8]) -> Result<PcxAsset, PaletteError> {
+    if bytes.len() < PCX_HEADER_SIZE || bytes[0] != 0x0A {
+        return Err(PaletteError::InvalidPcxHeader);
+    }
+    let bits_per_pixel = bytes[3];
+    let planes = bytes[65];
+    if bits_per_pixel != 8 || planes != 1 {
+        return Err(PaletteError::UnsupportedPcx);
+    }
+    let x_min = LittleEndian::read_u16(&bytes[4..]) as u32;
+    let y_min = LittleEndian::read_u16(&bytes[6..]) as u32;
+    let x_max = LittleEndian::read_u16(&bytes[8..]) as u32;
+    let y_max = LittleEndian::read_u16(&bytes[10..]) as u32;
+    let bytes_per_line = LittleEndian::read_u16(&bytes[66..]) as usize;
+    if x_max < x_min || y_max < y_min {
+        return Err(PaletteError::InvalidPcxHeader);
+    }
+    let width = x_max - x_min + 1;
+    let height = y_max - y_min + 1;
+
+    // The palette is stored in the last 769 bytes, after a marker byte
+    let palette_start = bytes
+        .len()
+        .checked_sub(PALETTE_SIZE * 3 + 1)
+        .filter(|&start| start >= PCX_HEADER_SIZE && bytes[start] == PCX_PALETTE_MARKER)
+        .ok_or(PaletteError::MissingPcxPalette)?;
+    let palette = PaletteAsset {
+        colors: bytes[palette_start + 1..]
+            .chunks_exact(3)
+            .map(|c| [c[0], c[1], c[2]])
+            .collect(),
+    };
+
+    // Image data is RLE-encoded, where bytes with the top 2 bits set specify a count for the
+    // following byte
+    let mut data = bytes[PCX_HEADER_SIZE..palette_start].iter();
+    let mut lines = Vec::with_capacity(bytes_per_line * height as usize);
+    while lines.len() < bytes_per_line * height as usize {
+        let Some(&b) = data.next() else {
+            break;
+        };
+        if b & 0xC0 == 0xC0 {
+            let value = data.next().copied().unwrap_or_default();
+            lines.resize(lines.len() + (b & 0x3F) as usize, value);
+        } else {
+            lines.push(b);
+        }
+    }
+    lines.resize(bytes_per_line * height as usize, 0);
+
+    // Lines may be padded past the width of the image, so we only keep the visible pixels
+    let pixels = lines
+        .chunks_exact(bytes_per_line.max(1))
+        .flat_map(|line| line.iter().take(width as usize).copied())
+        .collect();
+
+    Ok(PcxAsset {
+        width,
+        height,
+        pixels,
+        palette,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pcx_decoding() {
+        let mut bytes = vec![0u8; PCX_HEADER_SIZE];
+        bytes[0] = 0x0A;
+        bytes[3] = 8;
+        // 3x2 image, with 4 bytes per line
+        bytes[8] = 2;
+        bytes[10] = 1;
+        bytes[65] = 1;
+        bytes[66] = 4;
+        // Line 0: 7, 7, 7, (padding); Line 1: 0xC1 (escaped), 2, 3, (padding)
+        bytes.extend_from_slice(&[0xC3, 7, 0, 0xC1, 0xC1, 2, 3, 0]);
+        bytes.push(PCX_PALETTE_MARKER);
+        bytes.extend((0..PALETTE_SIZE).flat_map(|i| [i as u8, 0, 255]));
+
+        let pcx = load_pcx(&bytes).unwrap();
+        assert_eq!((pcx.width, pcx.height), (3, 2));
+        assert_eq!(pcx.pixels, vec![7, 7, 7, 0xC1, 2, 3]);
+        assert_eq!(pcx.get(1, 1), Some(2));
+        assert_eq!(pcx.get(3, 0), None);
+        assert_eq!(pcx.palette.colors[8], [8, 0, 255]);
+    }
+
+    #[test]
+    fn wpe_decoding() {
+        let bytes = (0..PALETTE_SIZE)
+            .flat_map(|i| [i as u8, 1, 2, 0])
+            .collect::<Vec<_>>();
+        let palette = load_wpe(&bytes).unwrap();
+        assert_eq!(palette.colors.len(), PALETTE_SIZE);
+        assert_eq!(palette.colors[5], [5, 1, 2]);
+        assert!(load_wpe(&bytes[..100]).is_err());
+    }
+}
@@ -20,24 +20,7 @@ impl AssetLoader for RelAssetLoader {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
 
-        let entries = bytes
-            .chunks_exact(8)
-            .map(|chunk| {
-                let rel_type = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
-                let ref_image = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
-
-                RelEntry {
-                    rel_type,
-                    ref_image: if ref_image == 0xFFFFFFFF {
-                        None
-                    } else {
-                        Some(ref_image)
-                    },
-                }
-            })
-            .collect();
-
-        Ok(RelAsset { entries })
+        Ok(RelAsset::from_bytes(&bytes))
     }
 }
 
@@ -54,7 +37,30 @@ impl RelEntry {
     }
 }
 
-#[derive(Asset, Debug, TypePath, Clone)]
+#[derive(Asset, Debug, Default, TypePath, Clone)]
 pub struct RelAsset {
     pub entries: Vec<RelEntry>,
 }
+
+impl RelAsset {
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let entries = bytes
+            .chunks_exact(8)
+            .map(|chunk| {
+                let rel_type = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+                let ref_image = u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]);
+
+                RelEntry {
+                    rel_type,
+                    ref_image: if ref_image == 0xFFFFFFFF {
+                        None
+                    } else {
+                        Some(ref_image)
+                    },
+                }
+            })
+            .collect();
+
+        Self { entries }
+    }
+}
@@ -60,6 +60,11 @@ impl BwSoundRange {
     pub const fn contains(&self, id: BwSoundId) -> bool {
         id.get() >= self.start.get() && id.get() < self.end.get()
     }
+
+    /// Returns an iterator over the sounds in this range, in order.
+    pub fn iter(&self) -> impl Iterator<Item = BwSoundId> {
+        (self.start.get()..self.end.get()).filter_map(BwSoundId::new)
+    }
 }
 
 impl From<BwSoundRange> for Range<BwSoundId> {
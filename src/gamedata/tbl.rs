@@ -10,7 +10,7 @@ use bevy::{
 #[derive(Debug, Default)]
 pub struct TblAssetLoader {}
 
-#[derive(Asset, Clone, Debug, Reflect)]
+#[derive(Asset, Clone, Debug, Default, Reflect)]
 pub struct TblAsset {
     entries: Vec<String>,
 }
@@ -30,6 +30,14 @@ impl TblAsset {
     }
 }
 
+impl TblAsset {
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        Ok(Self {
+            entries: load_tbl(bytes)?,
+        })
+    }
+}
+
 impl Index<usize> for TblAsset {
     type Output = str;
 
@@ -52,8 +60,7 @@ impl AssetLoader for TblAssetLoader {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
 
-        let entries = load_tbl(&bytes)?;
-        Ok(TblAsset { entries })
+        TblAsset::from_bytes(&bytes)
     }
 
     fn extensions(&self) -> &[&str] {
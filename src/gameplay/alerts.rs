@@ -0,0 +1,72 @@
+//! Alerts about things happening to the local player's constructs (e.g. being attacked), so that
+//! the player can quickly jump to wherever the action is.
+
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::{maps::position::Position, math::FixedPoint, states::AppState};
+
+use super::{
+    constructs::OwnedConstruct,
+    health::Health,
+    players::{ControlledPlayer, PlayerNumber},
+};
+
+pub fn plugin(app: &mut App) {
+    app.add_event::<AlertEvent>()
+        .init_resource::<LastAlert>()
+        .add_systems(OnEnter(AppState::InGame), reset_last_alert)
+        .add_systems(
+            Update,
+            (detect_attacks, record_alerts)
+                .chain()
+                .run_if(in_state(AppState::InGame)),
+        );
+}
+
+/// Event fired when something happens that the local player should be alerted to.
+// TODO(tec27): Play the "base/forces under attack" sounds and ping the minimap for these
+#[derive(Event, Debug, Copy, Clone)]
+pub struct AlertEvent {
+    pub position: Position,
+}
+
+/// The position of the most recent [AlertEvent], if there has been one.
+#[derive(Resource, Debug, Default)]
+pub struct LastAlert(pub Option<Position>);
+
+fn reset_last_alert(mut last_alert: ResMut<LastAlert>) {
+    last_alert.0 = None;
+}
+
+/// Fires an [AlertEvent] whenever one of the local player's constructs loses health.
+fn detect_attacks(
+    mut last_health: Local<HashMap<Entity, FixedPoint>>,
+    controlled_player: Query<&PlayerNumber, With<ControlledPlayer>>,
+    constructs: Query<(Entity, &Health, &OwnedConstruct, &Position), Changed<Health>>,
+    mut removed: RemovedComponents<Health>,
+    mut writer: EventWriter<AlertEvent>,
+) {
+    for entity in removed.read() {
+        last_health.remove(&entity);
+    }
+    let Ok(player) = controlled_player.get_single() else {
+        return;
+    };
+
+    for (entity, health, owner, &position) in constructs.iter() {
+        if owner.0 != player.0 {
+            continue;
+        }
+        let previous = last_health.insert(entity, health.current);
+        if previous.is_some_and(|p| health.current < p) {
+            writer.send(AlertEvent { position });
+        }
+    }
+}
+
+fn record_alerts(mut events: EventReader<AlertEvent>, mut last_alert: ResMut<LastAlert>) {
+    if let Some(event) = events.read().last() {
+        last_alert.0 = Some(event.position);
+    }
+}
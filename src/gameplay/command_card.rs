@@ -0,0 +1,590 @@
+//! The command card: the grid of actions the local player can give to their selected constructs,
+//! which can be used by clicking the buttons in the console or by pressing their hotkeys. Using a
+//! button issues a [GameCommand], or for orders that need a target (e.g. Attack), waits for the
+//! player to click a target on the map or minimap first.
+//!
+//! Building structures and researching upgrades aren't supported yet: there is no way to place a
+//! building, and the tech data isn't loaded.
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    gamedata::{ConstructFlags, ConstructTypeId},
+    input::{HotkeyLayout, InputAction, Keybindings},
+    maps::position::Position,
+    net::{GameCommand, IssueCommandEvent, TargetOrder},
+    settings::GameSettings,
+    states::AppState,
+};
+
+use super::{
+    constructs::{ConstructId, OwnedConstruct},
+    players::{ControlledPlayer, PlayerNumber},
+    resources::{PlayerResources, PlayerSupply},
+    selection::SelectedEntities,
+    speech::{check_can_make, AdvisorErrorEvent},
+    InGameMenuState,
+};
+
+/// The number of buttons on the command card (it is a 3x3 grid).
+pub const COMMAND_CARD_SIZE: usize = 9;
+
+pub fn plugin(app: &mut App) {
+    app.add_event::<TargetInputEvent>()
+        .init_resource::<CurrentCommandCard>()
+        .init_resource::<CommandTargeting>()
+        .add_systems(
+            Update,
+            (update_command_card, command_card_hotkeys, apply_targeting)
+                .chain()
+                .run_if(in_state(AppState::InGame).and_then(in_state(InGameMenuState::Disabled))),
+        );
+}
+
+/// An action that can be given to constructs from the command card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect)]
+#[serde(rename_all = "camelCase")]
+pub enum CommandAction {
+    Move,
+    Stop,
+    Attack,
+    Patrol,
+    HoldPosition,
+    Gather,
+    ReturnCargo,
+    BuildBasic,
+    BuildAdvanced,
+    Burrow,
+    SetRallyPoint,
+}
+
+impl CommandAction {
+    /// Returns the name of this action, as shown in the UI.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CommandAction::Move => "Move",
+            CommandAction::Stop => "Stop",
+            CommandAction::Attack => "Attack",
+            CommandAction::Patrol => "Patrol",
+            CommandAction::HoldPosition => "Hold Position",
+            CommandAction::Gather => "Gather",
+            CommandAction::ReturnCargo => "Return Cargo",
+            CommandAction::BuildBasic => "Build Structure",
+            CommandAction::BuildAdvanced => "Build Advanced Structure",
+            CommandAction::Burrow => "Burrow",
+            CommandAction::SetRallyPoint => "Set Rally Point",
+        }
+    }
+
+    /// Returns the key that triggers this action by default when using the classic hotkey layout.
+    pub fn default_hotkey(&self) -> KeyCode {
+        match self {
+            CommandAction::Move => KeyCode::KeyM,
+            CommandAction::Stop => KeyCode::KeyS,
+            CommandAction::Attack => KeyCode::KeyA,
+            CommandAction::Patrol => KeyCode::KeyP,
+            CommandAction::HoldPosition => KeyCode::KeyH,
+            CommandAction::Gather => KeyCode::KeyG,
+            CommandAction::ReturnCargo => KeyCode::KeyC,
+            CommandAction::BuildBasic => KeyCode::KeyB,
+            CommandAction::BuildAdvanced => KeyCode::KeyV,
+            CommandAction::Burrow => KeyCode::KeyU,
+            CommandAction::SetRallyPoint => KeyCode::KeyR,
+        }
+    }
+
+    /// Returns the order this action gives once the player picks a target for it, if it needs one.
+    pub fn target_order(&self) -> Option<TargetOrder> {
+        match self {
+            CommandAction::Move => Some(TargetOrder::Move),
+            CommandAction::Attack => Some(TargetOrder::Attack),
+            CommandAction::Patrol => Some(TargetOrder::Patrol),
+            CommandAction::Gather => Some(TargetOrder::Gather),
+            CommandAction::SetRallyPoint => Some(TargetOrder::SetRallyPoint),
+            _ => None,
+        }
+    }
+
+    /// Returns the command this action issues as soon as it is used, if it doesn't need a target.
+    pub fn immediate_command(&self, queued: bool) -> Option<GameCommand> {
+        match self {
+            CommandAction::Stop => Some(GameCommand::Stop { queued }),
+            CommandAction::HoldPosition => Some(GameCommand::HoldPosition { queued }),
+            CommandAction::ReturnCargo => Some(GameCommand::ReturnCargo { queued }),
+            CommandAction::Burrow => Some(GameCommand::Burrow),
+            // TODO(tec27): Open the list of structures to build once buildings can be placed
+            CommandAction::BuildBasic | CommandAction::BuildAdvanced => None,
+            CommandAction::Move
+            | CommandAction::Attack
+            | CommandAction::Patrol
+            | CommandAction::Gather
+            | CommandAction::SetRallyPoint => None,
+        }
+    }
+}
+
+/// What a command card button does when it is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonAction {
+    /// Gives the selected constructs the specified order.
+    Command(CommandAction),
+    /// Trains a construct of the specified type at the selected construct.
+    Train(ConstructTypeId),
+}
+
+impl ButtonAction {
+    /// Returns the key that triggers this button by default when using the classic hotkey layout.
+    pub fn default_hotkey(&self) -> Option<KeyCode> {
+        match self {
+            ButtonAction::Command(action) => Some(action.default_hotkey()),
+            ButtonAction::Train(construct_type) => train_hotkey(*construct_type),
+        }
+    }
+}
+
+/// Returns the key for training `construct_type` in the classic hotkey layout. These match BW's
+/// hotkeys, and can't be rebound (the grid layout can be used instead).
+fn train_hotkey(construct_type: ConstructTypeId) -> Option<KeyCode> {
+    use ConstructTypeId::*;
+    let key = match construct_type {
+        TerranScv | ProtossShuttle | ProtossScout => KeyCode::KeyS,
+        TerranMarine => KeyCode::KeyM,
+        TerranFirebat => KeyCode::KeyF,
+        TerranGhost | TerranGoliath => KeyCode::KeyG,
+        TerranMedic | ProtossCarrier => KeyCode::KeyC,
+        TerranVulture | TerranScienceVessel | ProtossReaver => KeyCode::KeyV,
+        TerranSiegeTank | ProtossHighTemplar => KeyCode::KeyT,
+        TerranWraith => KeyCode::KeyW,
+        TerranDropship | ProtossDragoon => KeyCode::KeyD,
+        TerranBattlecruiser => KeyCode::KeyB,
+        TerranValkyrie => KeyCode::KeyY,
+        ProtossProbe => KeyCode::KeyP,
+        ProtossZealot => KeyCode::KeyZ,
+        ProtossDarkTemplar => KeyCode::KeyK,
+        ProtossObserver | ProtossCorsair => KeyCode::KeyO,
+        ProtossArbiter => KeyCode::KeyA,
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// A button on the command card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandCardButton {
+    pub action: ButtonAction,
+    /// The key that triggers this button, if it has one.
+    pub hotkey: Option<KeyCode>,
+}
+
+impl From<ButtonAction> for CommandCardButton {
+    fn from(action: ButtonAction) -> Self {
+        Self {
+            action,
+            hotkey: action.default_hotkey(),
+        }
+    }
+}
+
+impl From<CommandAction> for CommandCardButton {
+    fn from(action: CommandAction) -> Self {
+        ButtonAction::Command(action).into()
+    }
+}
+
+/// The buttons on a command card, ordered left-to-right and then top-to-bottom.
+pub type CommandCardLayout = [Option<CommandCardButton>; COMMAND_CARD_SIZE];
+
+/// Returns the command card shown when a construct of the specified type is selected.
+pub fn command_card_for(construct_type: ConstructTypeId) -> CommandCardLayout {
+    // TODO(tec27): Add the rest of the buttons (research, spells, etc.)
+    let flags = construct_type.flags();
+    let trains = construct_type.trains();
+    let mut card = CommandCardLayout::default();
+    for (button, &trained) in card.iter_mut().zip(trains) {
+        *button = Some(ButtonAction::Train(trained).into());
+    }
+    let mut set = |index: usize, action: CommandAction| {
+        card[index] = Some(action.into());
+    };
+
+    if flags.contains(ConstructFlags::CAN_MOVE) && !construct_type.is_building() {
+        set(0, CommandAction::Move);
+        set(1, CommandAction::Stop);
+        if construct_type.has_weapon() {
+            set(2, CommandAction::Attack);
+        }
+
+        if flags.contains(ConstructFlags::WORKER) {
+            set(4, CommandAction::Gather);
+            set(5, CommandAction::ReturnCargo);
+            set(6, CommandAction::BuildBasic);
+            set(7, CommandAction::BuildAdvanced);
+        } else {
+            set(3, CommandAction::Patrol);
+            set(4, CommandAction::HoldPosition);
+        }
+
+        if flags.contains(ConstructFlags::CAN_BURROW) {
+            set(8, CommandAction::Burrow);
+        }
+    } else if flags.contains(ConstructFlags::RESOURCE_DEPOT) || !trains.is_empty() {
+        set(7, CommandAction::SetRallyPoint);
+    }
+
+    card
+}
+
+/// Sets the hotkey of each button in `card` to the key it is bound to in `keybindings`.
+pub fn apply_keybindings(card: &mut CommandCardLayout, keybindings: &Keybindings) {
+    for (slot, button) in card.iter_mut().enumerate() {
+        if let Some(button) = button {
+            button.hotkey = match button.action {
+                ButtonAction::Command(action) => keybindings.command_card_key(slot, action),
+                ButtonAction::Train(construct_type) => match keybindings.layout {
+                    HotkeyLayout::Classic => train_hotkey(construct_type),
+                    HotkeyLayout::Grid => {
+                        keybindings.key_for(InputAction::CommandCardSlot(slot as u8))
+                    }
+                },
+            };
+        }
+    }
+}
+
+/// The command card for the local player's current selection.
+#[derive(Resource, Debug, Default, PartialEq, Eq)]
+pub struct CurrentCommandCard(pub CommandCardLayout);
+
+/// The order the local player has picked from the command card that is waiting for them to click a
+/// target, if any.
+#[derive(Resource, Debug, Default, PartialEq, Eq)]
+pub struct CommandTargeting(pub Option<TargetOrder>);
+
+/// Event fired when the local player clicks a target for the order in [CommandTargeting], either on
+/// the map or the minimap.
+#[derive(Event, Debug, Copy, Clone)]
+pub struct TargetInputEvent {
+    pub position: Position,
+    /// The construct that was clicked on, if any.
+    pub target: Option<Entity>,
+}
+
+/// [SystemParam] for using the buttons on the local player's command card.
+#[derive(SystemParam)]
+pub struct CommandCardInput<'w, 's> {
+    targeting: ResMut<'w, CommandTargeting>,
+    command_writer: EventWriter<'w, IssueCommandEvent>,
+    error_writer: EventWriter<'w, AdvisorErrorEvent>,
+    controlled_player:
+        Query<'w, 's, (&'static PlayerResources, &'static PlayerSupply), With<ControlledPlayer>>,
+}
+
+impl CommandCardInput<'_, '_> {
+    /// Uses `action`, issuing its command right away or waiting for a target to be picked. If
+    /// `queued` is true, the command is queued after the constructs' current orders.
+    pub fn press(&mut self, action: ButtonAction, queued: bool) {
+        match action {
+            ButtonAction::Command(action) => {
+                if let Some(order) = action.target_order() {
+                    self.targeting.0 = Some(order);
+                } else if let Some(command) = action.immediate_command(queued) {
+                    self.targeting.0 = None;
+                    self.command_writer.send(IssueCommandEvent(command));
+                }
+            }
+            ButtonAction::Train(construct_type) => {
+                // The simulation checks this as well, but checking it here lets the advisor tell
+                // the player why nothing happened
+                if let Ok((resources, supply)) = self.controlled_player.get_single() {
+                    if let Err(error) = check_can_make(construct_type, resources, supply) {
+                        self.error_writer.send(AdvisorErrorEvent(error));
+                        return;
+                    }
+                }
+                self.command_writer
+                    .send(IssueCommandEvent(GameCommand::Train(construct_type)));
+            }
+        }
+    }
+}
+
+fn update_command_card(
+    mut current: ResMut<CurrentCommandCard>,
+    mut targeting: ResMut<CommandTargeting>,
+    controlled_player: Query<(&SelectedEntities, &PlayerNumber), With<ControlledPlayer>>,
+    constructs: Query<(&ConstructTypeId, Option<&OwnedConstruct>)>,
+    settings: Res<GameSettings>,
+) {
+    // Only constructs owned by the local player can be given commands
+    let mut card = controlled_player
+        .get_single()
+        .ok()
+        .and_then(|(selected, player)| {
+            let &first = selected.0.first()?;
+            let (construct_type, owner) = constructs.get(first).ok()?;
+            (owner.map(|o| o.0) == Some(player.0)).then(|| command_card_for(*construct_type))
+        })
+        .unwrap_or_default();
+    apply_keybindings(&mut card, &settings.keybindings);
+
+    if current.0 != card {
+        current.0 = card;
+        // The order being targeted may not be available for the new selection
+        targeting.0 = None;
+    }
+}
+
+fn command_card_hotkeys(
+    actions: Res<ButtonInput<InputAction>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<GameSettings>,
+    current: Res<CurrentCommandCard>,
+    mut input: CommandCardInput,
+) {
+    let queued = actions.pressed(InputAction::AddModifier);
+    for (slot, &button) in current.0.iter().enumerate() {
+        let Some(button) = button else {
+            continue;
+        };
+        // Only the actions for the current hotkey layout will ever be pressed
+        let pressed = match button.action {
+            ButtonAction::Command(action) => actions.just_pressed(InputAction::CommandCard(action)),
+            ButtonAction::Train(_) => {
+                settings.keybindings.layout == HotkeyLayout::Classic
+                    && button.hotkey.is_some_and(|k| keys.just_pressed(k))
+            }
+        };
+        if pressed || actions.just_pressed(InputAction::CommandCardSlot(slot as u8)) {
+            input.press(button.action, queued);
+        }
+    }
+}
+
+/// Issues the order being targeted once the local player has clicked a target for it.
+fn apply_targeting(
+    mut target_events: EventReader<TargetInputEvent>,
+    mut targeting: ResMut<CommandTargeting>,
+    construct_ids: Query<&ConstructId>,
+    actions: Res<ButtonInput<InputAction>>,
+    mut command_writer: EventWriter<IssueCommandEvent>,
+) {
+    for event in target_events.read() {
+        let Some(order) = targeting.0.take() else {
+            continue;
+        };
+        command_writer.send(IssueCommandEvent(GameCommand::TargetedOrder {
+            order,
+            position: event.position,
+            target: event
+                .target
+                .and_then(|e| construct_ids.get(e).ok().copied()),
+            queued: actions.pressed(InputAction::AddModifier),
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use crate::gameplay::speech::AdvisorError;
+
+    use super::*;
+
+    fn actions(card: CommandCardLayout) -> Vec<Option<CommandAction>> {
+        card.iter()
+            .map(|b| match b.map(|b| b.action) {
+                Some(ButtonAction::Command(action)) => Some(action),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn unit_cards() {
+        use CommandAction::*;
+
+        assert_eq!(
+            actions(command_card_for(ConstructTypeId::TerranMarine)),
+            vec![
+                Some(Move),
+                Some(Stop),
+                Some(Attack),
+                Some(Patrol),
+                Some(HoldPosition),
+                None,
+                None,
+                None,
+                None
+            ]
+        );
+        assert_eq!(
+            actions(command_card_for(ConstructTypeId::ZergDrone)),
+            vec![
+                Some(Move),
+                Some(Stop),
+                Some(Attack),
+                None,
+                Some(Gather),
+                Some(ReturnCargo),
+                Some(BuildBasic),
+                Some(BuildAdvanced),
+                Some(Burrow)
+            ]
+        );
+    }
+
+    #[test]
+    fn other_cards() {
+        let rally = command_card_for(ConstructTypeId::TerranCommandCenter);
+        assert_eq!(
+            rally[7].map(|b| b.action),
+            Some(ButtonAction::Command(CommandAction::SetRallyPoint))
+        );
+        assert_eq!(rally[7].and_then(|b| b.hotkey), Some(KeyCode::KeyR));
+
+        assert!(command_card_for(ConstructTypeId::ResourceMineralField1)
+            .iter()
+            .all(|b| b.is_none()));
+    }
+
+    #[test]
+    fn train_buttons() {
+        let gateway = command_card_for(ConstructTypeId::ProtossGateway);
+        let trains = gateway
+            .iter()
+            .filter_map(|b| match b.map(|b| (b.action, b.hotkey)) {
+                Some((ButtonAction::Train(ty), hotkey)) => Some((ty, hotkey)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            trains,
+            vec![
+                (ConstructTypeId::ProtossZealot, Some(KeyCode::KeyZ)),
+                (ConstructTypeId::ProtossDragoon, Some(KeyCode::KeyD)),
+                (ConstructTypeId::ProtossHighTemplar, Some(KeyCode::KeyT)),
+                (ConstructTypeId::ProtossDarkTemplar, Some(KeyCode::KeyK)),
+            ]
+        );
+        assert_eq!(
+            gateway[7].map(|b| b.action),
+            Some(ButtonAction::Command(CommandAction::SetRallyPoint))
+        );
+
+        let mut barracks = command_card_for(ConstructTypeId::TerranBarracks);
+        let keybindings = Keybindings {
+            layout: HotkeyLayout::Grid,
+            ..default()
+        };
+        apply_keybindings(&mut barracks, &keybindings);
+        assert_eq!(barracks[1].and_then(|b| b.hotkey), Some(KeyCode::KeyW));
+    }
+
+    #[test]
+    fn action_commands() {
+        assert_eq!(
+            CommandAction::Attack.target_order(),
+            Some(TargetOrder::Attack)
+        );
+        assert_eq!(CommandAction::Attack.immediate_command(false), None);
+        assert_eq!(
+            CommandAction::Stop.immediate_command(true),
+            Some(GameCommand::Stop { queued: true })
+        );
+        assert_eq!(CommandAction::BuildBasic.target_order(), None);
+        assert_eq!(CommandAction::BuildBasic.immediate_command(false), None);
+    }
+
+    #[test]
+    fn train_advisor_errors() {
+        let mut app = App::new();
+        app.add_event::<IssueCommandEvent>()
+            .add_event::<AdvisorErrorEvent>()
+            .init_resource::<CommandTargeting>();
+        let player = app
+            .world_mut()
+            .spawn((
+                ControlledPlayer,
+                PlayerResources {
+                    minerals: 40,
+                    gas: 0,
+                },
+                PlayerSupply::default(),
+            ))
+            .id();
+        let press = |app: &mut App, construct_type| {
+            app.world_mut()
+                .run_system_once(move |mut input: CommandCardInput| {
+                    input.press(ButtonAction::Train(construct_type), false)
+                });
+        };
+
+        press(&mut app, ConstructTypeId::TerranMarine);
+        let errors = app.world().resource::<Events<AdvisorErrorEvent>>();
+        assert_eq!(
+            errors
+                .get_reader()
+                .read(errors)
+                .copied()
+                .collect::<Vec<_>>(),
+            vec![AdvisorErrorEvent(AdvisorError::NotEnoughMinerals)]
+        );
+        assert!(app
+            .world()
+            .resource::<Events<IssueCommandEvent>>()
+            .is_empty());
+
+        app.world_mut()
+            .get_mut::<PlayerSupply>(player)
+            .unwrap()
+            .races
+            .iter_mut()
+            .for_each(|s| s.provided = 20);
+        app.world_mut()
+            .get_mut::<PlayerResources>(player)
+            .unwrap()
+            .minerals = 50;
+        press(&mut app, ConstructTypeId::TerranMarine);
+        let commands = app.world().resource::<Events<IssueCommandEvent>>();
+        assert_eq!(
+            commands
+                .get_reader()
+                .read(commands)
+                .map(|c| c.0.clone())
+                .collect::<Vec<_>>(),
+            vec![GameCommand::Train(ConstructTypeId::TerranMarine)]
+        );
+    }
+
+    #[test]
+    fn grid_hotkeys() {
+        let mut card = command_card_for(ConstructTypeId::TerranScv);
+        let mut keybindings = Keybindings {
+            layout: HotkeyLayout::Grid,
+            ..default()
+        };
+        keybindings.bind(InputAction::CommandCardSlot(0), Some(KeyCode::KeyY));
+        apply_keybindings(&mut card, &keybindings);
+
+        let hotkeys = card
+            .iter()
+            .map(|b| b.and_then(|b| b.hotkey))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            hotkeys,
+            vec![
+                Some(KeyCode::KeyY),
+                Some(KeyCode::KeyW),
+                Some(KeyCode::KeyE),
+                None,
+                Some(KeyCode::KeyS),
+                Some(KeyCode::KeyD),
+                Some(KeyCode::KeyZ),
+                Some(KeyCode::KeyX),
+                None
+            ]
+        );
+    }
+}
@@ -0,0 +1,518 @@
+//! The console at the bottom of the screen, which shows information about the current selection
+//! (stats for a single construct, or wireframes for multiple), the selected construct's portrait,
+//! and the command card.
+
+use bevy::prelude::*;
+
+use crate::{
+    fonts::FONT_BODY,
+    gamedata::{dds_grp::DdsGrpAsset, BwGameData, ConstructTypeId},
+    input::{key_name, InputAction},
+    math::FixedPoint,
+    net::{GameCommand, IssueCommandEvent},
+    settings::GameSettings,
+    states::{AppState, InGameOnly},
+};
+
+use super::{
+    command_card::{ButtonAction, CommandCardInput, CurrentCommandCard, COMMAND_CARD_SIZE},
+    constructs::ConstructId,
+    energy::Energy,
+    health::Health,
+    players::ControlledPlayer,
+    selection::{BlocksMapInput, SelectedEntities},
+    shield::Shield,
+    status::Kills,
+    status_bars::health_color,
+    InGameMenuState,
+};
+
+/// The space taken up by the minimap on the left side of the console, in logical pixels.
+const MINIMAP_AREA_WIDTH: f32 = 216.0;
+const CONSOLE_HEIGHT: f32 = 200.0;
+const PORTRAIT_SIZE: f32 = 120.0;
+const COMMAND_BUTTON_SIZE: f32 = 60.0;
+const WIREFRAME_SIZE: f32 = 64.0;
+
+const CONSOLE_COLOR: Color = Color::srgba(0.05, 0.05, 0.05, 0.9);
+const PANEL_COLOR: Color = Color::srgb(0.1, 0.1, 0.1);
+const BUTTON_COLOR: Color = Color::srgb(0.15, 0.15, 0.15);
+const BUTTON_HOVERED_COLOR: Color = Color::srgb(0.25, 0.25, 0.25);
+const TEXT_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+const HOTKEY_COLOR: Color = Color::srgb(0.9, 0.8, 0.2);
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::InGame), setup)
+            .add_systems(
+                Update,
+                (
+                    rebuild_selection_panel,
+                    (
+                        update_selection_info,
+                        update_wireframes,
+                        update_command_card,
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(
+                Update,
+                (command_card_input, wireframe_input, update_button_colors).run_if(
+                    in_state(AppState::InGame).and_then(in_state(InGameMenuState::Disabled)),
+                ),
+            );
+    }
+}
+
+#[derive(Resource, Debug)]
+struct ConsoleAssets {
+    font: Handle<Font>,
+    wireframes: Handle<DdsGrpAsset>,
+}
+
+/// Marker component for the node that contains info about the current selection.
+#[derive(Component)]
+struct SelectionPanel;
+
+/// Marker component for the text that shows the stats of a single selected construct.
+#[derive(Component)]
+struct SelectionInfo(Entity);
+
+/// A cell in the wireframe grid, showing one of the selected constructs.
+#[derive(Component)]
+struct WireframeCell(Entity);
+
+/// The portrait area, which shows the first selected construct.
+// NOTE(tec27): BW shows an animated portrait here, but those are videos (SMK for SD, WebM for HD)
+// that we can't play yet, so this shows the construct's wireframe instead
+#[derive(Component, Default)]
+struct Portrait(Option<Entity>);
+
+/// A button on the command card, at the specified index.
+#[derive(Component)]
+struct CommandCardSlot(usize);
+
+/// Marker component for the text inside of a [CommandCardSlot].
+#[derive(Component)]
+struct CommandCardSlotText;
+
+/// Marker component for console nodes that should be highlighted when hovered.
+#[derive(Component)]
+struct ConsoleButton;
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, settings: Res<GameSettings>) {
+    let font = asset_server.load(FONT_BODY);
+    // TODO(tec27): SD wireframes are stored as a regular GRP (unit/wirefram/wirefram.grp), which
+    // we'd need to render with the paletted pipeline
+    let wireframes = asset_server.load(format!(
+        "casc-extracted/{}unit/wirefram/wirefram.dds.grp",
+        settings.asset_quality.asset_path()
+    ));
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(MINIMAP_AREA_WIDTH),
+                    right: Val::Px(8.0),
+                    bottom: Val::Px(8.0),
+                    height: Val::Px(CONSOLE_HEIGHT),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    column_gap: Val::Px(8.0),
+                    ..default()
+                },
+                background_color: CONSOLE_COLOR.into(),
+                ..default()
+            },
+            Interaction::default(),
+            BlocksMapInput,
+            InGameOnly,
+        ))
+        .with_children(|builder| {
+            builder.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_grow: 1.0,
+                        flex_wrap: FlexWrap::Wrap,
+                        align_content: AlignContent::FlexStart,
+                        ..default()
+                    },
+                    background_color: PANEL_COLOR.into(),
+                    ..default()
+                },
+                SelectionPanel,
+            ));
+
+            builder.spawn((
+                ImageBundle {
+                    style: Style {
+                        width: Val::Px(PORTRAIT_SIZE),
+                        height: Val::Px(PORTRAIT_SIZE),
+                        align_self: AlignSelf::Center,
+                        border: UiRect::all(Val::Px(1.0)),
+                        ..default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                BorderColor(BUTTON_HOVERED_COLOR),
+                Portrait::default(),
+            ));
+
+            builder
+                .spawn(NodeBundle {
+                    style: Style {
+                        display: Display::Grid,
+                        grid_template_columns: RepeatedGridTrack::px(3, COMMAND_BUTTON_SIZE),
+                        grid_template_rows: RepeatedGridTrack::px(3, COMMAND_BUTTON_SIZE),
+                        row_gap: Val::Px(2.0),
+                        column_gap: Val::Px(2.0),
+                        align_self: AlignSelf::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|builder| {
+                    for i in 0..COMMAND_CARD_SIZE {
+                        builder
+                            .spawn((
+                                NodeBundle {
+                                    style: Style {
+                                        justify_content: JustifyContent::Center,
+                                        align_items: AlignItems::Center,
+                                        padding: UiRect::all(Val::Px(2.0)),
+                                        ..default()
+                                    },
+                                    background_color: BUTTON_COLOR.into(),
+                                    visibility: Visibility::Hidden,
+                                    ..default()
+                                },
+                                Interaction::default(),
+                                CommandCardSlot(i),
+                                ConsoleButton,
+                            ))
+                            .with_children(|builder| {
+                                builder.spawn((
+                                    TextBundle::default().with_text_justify(JustifyText::Center),
+                                    CommandCardSlotText,
+                                ));
+                            });
+                    }
+                });
+        });
+
+    commands.insert_resource(ConsoleAssets { font, wireframes });
+}
+
+/// Returns the name of a construct type, as it's shown in the UI.
+fn construct_name(game_data: &BwGameData, construct_type: ConstructTypeId) -> String {
+    // NOTE(tec27): The first entries in stat_txt.tbl are the construct names, in ID order. Some of
+    // them have extra info (e.g. their editor category) after a null character
+    game_data
+        .strings
+        .get(u16::from(construct_type) as usize)
+        .and_then(|s| s.split('\0').next())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| format!("{construct_type:?}"))
+}
+
+/// Formats a stat the way it's shown in the console (e.g. "35/40"). Partial points are rounded up,
+/// so a construct that is still alive never shows 0.
+fn format_stat(current: FixedPoint, max: FixedPoint) -> String {
+    format!(
+        "{}/{}",
+        current.to_num::<f32>().ceil() as i32,
+        max.to_num::<f32>().ceil() as i32
+    )
+}
+
+/// Rebuilds the contents of the selection panel whenever the local player's selection changes.
+fn rebuild_selection_panel(
+    mut commands: Commands,
+    controlled_player: Query<Ref<SelectedEntities>, With<ControlledPlayer>>,
+    panel: Query<Entity, With<SelectionPanel>>,
+    mut portrait: Query<(&mut Portrait, &mut Visibility)>,
+    constructs: Query<&ConstructTypeId>,
+    assets: Option<Res<ConsoleAssets>>,
+) {
+    let (Ok(selected), Ok(panel), Some(assets)) =
+        (controlled_player.get_single(), panel.get_single(), assets)
+    else {
+        return;
+    };
+    if !selected.is_changed() {
+        return;
+    }
+
+    let selected: Vec<(Entity, ConstructTypeId)> = selected
+        .0
+        .iter()
+        .filter_map(|&e| constructs.get(e).ok().map(|&ty| (e, ty)))
+        .collect();
+
+    if let Ok((mut portrait, mut visibility)) = portrait.get_single_mut() {
+        portrait.0 = selected.first().map(|&(entity, _)| entity);
+        *visibility = if portrait.0.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    commands.entity(panel).despawn_descendants();
+    match selected[..] {
+        [] => {}
+        [(entity, _)] => {
+            commands.entity(panel).with_children(|builder| {
+                builder.spawn((
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font: assets.font.clone(),
+                            font_size: 18.0,
+                            color: TEXT_COLOR,
+                        },
+                    )
+                    .with_style(Style {
+                        margin: UiRect::all(Val::Px(8.0)),
+                        ..default()
+                    }),
+                    SelectionInfo(entity),
+                ));
+            });
+        }
+        _ => {
+            commands.entity(panel).with_children(|builder| {
+                for &(entity, _) in selected.iter() {
+                    builder.spawn((
+                        ImageBundle {
+                            style: Style {
+                                width: Val::Px(WIREFRAME_SIZE),
+                                height: Val::Px(WIREFRAME_SIZE),
+                                margin: UiRect::all(Val::Px(2.0)),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        Interaction::default(),
+                        WireframeCell(entity),
+                    ));
+                }
+            });
+        }
+    }
+}
+
+/// Updates the stats shown for a single selected construct.
+fn update_selection_info(
+    mut info: Query<(&SelectionInfo, &mut Text)>,
+    constructs: Query<(
+        &ConstructTypeId,
+        &Health,
+        Option<&Shield>,
+        Option<&Energy>,
+        Option<&Kills>,
+    )>,
+    game_data: Option<Res<BwGameData>>,
+) {
+    let Some(game_data) = game_data else {
+        return;
+    };
+
+    for (info, mut text) in info.iter_mut() {
+        let Ok((construct_type, health, shield, energy, kills)) = constructs.get(info.0) else {
+            continue;
+        };
+
+        let mut lines = vec![
+            construct_name(&game_data, *construct_type),
+            format_stat(health.current, health.max),
+        ];
+        if let Some(shield) = shield {
+            lines.push(format!(
+                "Shields: {}",
+                format_stat(shield.current, shield.max)
+            ));
+        }
+        if let Some(energy) = energy {
+            lines.push(format!(
+                "Energy: {}",
+                format_stat(energy.current, energy.max)
+            ));
+        }
+        if let Some(kills) = kills {
+            lines.push(format!("Kills: {}", kills.0));
+        }
+
+        let value = lines.join("\n");
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+/// Updates the wireframe images (including the one in the portrait) to match the type and health of
+/// the construct they represent.
+fn update_wireframes(
+    mut cells: Query<(&WireframeCell, &mut UiImage), Without<Portrait>>,
+    mut portrait: Query<(&Portrait, &mut UiImage), Without<WireframeCell>>,
+    constructs: Query<(&ConstructTypeId, &Health)>,
+    assets: Option<Res<ConsoleAssets>>,
+    grp_assets: Res<Assets<DdsGrpAsset>>,
+) {
+    let Some(wireframes) = assets.and_then(|a| grp_assets.get(&a.wireframes)) else {
+        return;
+    };
+
+    let images = cells.iter_mut().map(|(cell, image)| (cell.0, image)).chain(
+        portrait
+            .iter_mut()
+            .filter_map(|(portrait, image)| portrait.0.map(|e| (e, image))),
+    );
+    for (entity, mut image) in images {
+        let Ok((construct_type, health)) = constructs.get(entity) else {
+            continue;
+        };
+        if let Some(frame) = wireframes.frames.get(u16::from(*construct_type) as usize) {
+            if image.texture != *frame {
+                image.texture = frame.clone();
+            }
+        }
+        let color = health_color(health.current, health.max);
+        if image.color != color {
+            image.color = color;
+        }
+    }
+}
+
+/// Updates the command card buttons to match the current selection's command card.
+fn update_command_card(
+    mut slots: Query<(&CommandCardSlot, &Children, &mut Visibility)>,
+    mut texts: Query<&mut Text, With<CommandCardSlotText>>,
+    current: Res<CurrentCommandCard>,
+    assets: Option<Res<ConsoleAssets>>,
+    game_data: Option<Res<BwGameData>>,
+) {
+    let (Some(assets), Some(game_data)) = (assets, game_data) else {
+        return;
+    };
+    if !current.is_changed() {
+        return;
+    }
+
+    for (slot, children, mut visibility) in slots.iter_mut() {
+        let button = current.0[slot.0];
+        *visibility = if button.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+
+        let Some(mut text) = children.first().and_then(|&c| texts.get_mut(c).ok()) else {
+            continue;
+        };
+        let Some(button) = button else {
+            text.sections.clear();
+            continue;
+        };
+        let style = TextStyle {
+            font: assets.font.clone(),
+            font_size: 12.0,
+            color: TEXT_COLOR,
+        };
+        let hotkey = button.hotkey.map(key_name).unwrap_or_default();
+        let name = match button.action {
+            ButtonAction::Command(action) => action.name().to_string(),
+            ButtonAction::Train(construct_type) => {
+                format!("Train {}", construct_name(&game_data, construct_type))
+            }
+        };
+        text.sections = vec![
+            TextSection::new(
+                hotkey + "\n",
+                TextStyle {
+                    color: HOTKEY_COLOR,
+                    ..style.clone()
+                },
+            ),
+            TextSection::new(name, style),
+        ];
+    }
+}
+
+fn command_card_input(
+    slots: Query<(&Interaction, &CommandCardSlot, &Visibility), Changed<Interaction>>,
+    current: Res<CurrentCommandCard>,
+    actions: Res<ButtonInput<InputAction>>,
+    mut input: CommandCardInput,
+) {
+    let queued = actions.pressed(InputAction::AddModifier);
+    for (interaction, slot, visibility) in slots.iter() {
+        if *interaction != Interaction::Pressed || *visibility == Visibility::Hidden {
+            continue;
+        }
+        if let Some(button) = current.0[slot.0] {
+            input.press(button.action, queued);
+        }
+    }
+}
+
+/// Selects only the construct whose wireframe was clicked, or removes it from the selection if
+/// shift is held.
+fn wireframe_input(
+    cells: Query<(&Interaction, &WireframeCell), Changed<Interaction>>,
+    construct_ids: Query<&ConstructId>,
+    actions: Res<ButtonInput<InputAction>>,
+    mut command_writer: EventWriter<IssueCommandEvent>,
+) {
+    let shift = actions.pressed(InputAction::AddModifier);
+    for (interaction, cell) in cells.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Ok(&id) = construct_ids.get(cell.0) else {
+            continue;
+        };
+        command_writer.send(IssueCommandEvent(if shift {
+            GameCommand::SelectRemove(vec![id])
+        } else {
+            GameCommand::Select(vec![id])
+        }));
+    }
+}
+
+fn update_button_colors(
+    mut buttons: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<ConsoleButton>),
+    >,
+) {
+    for (interaction, mut bg) in buttons.iter_mut() {
+        bg.0 = match *interaction {
+            Interaction::Pressed | Interaction::Hovered => BUTTON_HOVERED_COLOR,
+            Interaction::None => BUTTON_COLOR,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stat_formatting() {
+        assert_eq!(
+            format_stat(FixedPoint::from_num(40), FixedPoint::from_num(40)),
+            "40/40"
+        );
+        assert_eq!(
+            format_stat(FixedPoint::from_num(0.25), FixedPoint::from_num(40)),
+            "1/40"
+        );
+    }
+}
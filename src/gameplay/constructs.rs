@@ -4,15 +4,19 @@ use std::ops::Index;
 
 use crate::{
     gamedata::{
-        lo::LoAsset, AnimFrameCount, BwImage, BwSoundRange, BwSprite, Construct, ConstructFlags,
-        ConstructTypeId, Flingy, LoadingAnimBundle, RenderStyle, SpecialOverlay, CONSTRUCTS,
-        IMAGES, SPRITES,
+        lo::LoAsset, AnimFrameCount, BwImage, BwSoundId, BwSoundRange, BwSprite, Construct,
+        ConstructFlags, ConstructTypeId, Flingy, LoadingAnimBundle, RenderStyle, SpecialOverlay,
+        CONSTRUCTS, IMAGES, SPRITES,
     },
     maps::position::Position,
     math::{bounds::IBounds, FixedPoint},
     races::Race,
-    render::ysort::YSort,
+    render::{
+        draw_order::{DrawOrder, IMAGE_Z_STEP},
+        effects::SHADOW_Z_OFFSET,
+    },
     settings::GameSettings,
+    states::AppState,
 };
 
 use super::{
@@ -25,10 +29,18 @@ use super::{
 pub fn plugin(app: &mut App) {
     app.register_type::<ConstructTypeId>()
         .register_type::<ConstructSprite>()
+        .register_type::<ConstructId>()
         .register_type::<OwnedConstruct>()
         .register_type::<ConstructImageOrder>()
         .register_type::<LocationOffsetKind>()
         .register_type::<UseLocationOffset>()
+        .init_resource::<NextConstructId>()
+        .init_resource::<NextSpriteSerial>()
+        .add_systems(
+            OnEnter(AppState::InGame),
+            (reset_construct_ids, reset_sprite_serials),
+        )
+        .add_systems(Update, update_construct_elevations)
         .add_systems(
             Update,
             (
@@ -39,6 +51,18 @@ pub fn plugin(app: &mut App) {
                 .chain()
                 .after(apply_facing_to_images),
         );
+    app.world_mut()
+        .register_component_hooks::<ConstructSprite>()
+        .on_add(|mut world, entity, _component_id| {
+            let Some(mut next) = world.get_resource_mut::<NextSpriteSerial>() else {
+                return;
+            };
+            let serial = next.0;
+            next.0 = next.0.wrapping_add(1);
+            if let Some(mut sprite) = world.get_mut::<ConstructSprite>(entity) {
+                sprite.serial = serial;
+            }
+        });
 }
 
 impl Race {
@@ -70,6 +94,9 @@ impl Index<ConstructTypeId> for [Construct; 228] {
     }
 }
 
+/// The weapon ID that indicates a construct has no weapon.
+const NO_WEAPON: u8 = 130;
+
 impl ConstructTypeId {
     /// Returns the [Construct] definition that matches this type ID.
     #[inline]
@@ -127,6 +154,48 @@ impl ConstructTypeId {
         self.def().what_sounds
     }
 
+    /// Returns the sounds played when this type of unit is given a command.
+    #[inline]
+    pub fn yes_sounds(&self) -> Option<BwSoundRange> {
+        self.def().unit_data().and_then(|u| u.yes_sounds)
+    }
+
+    /// Returns the sounds played (in order) when this type of unit is clicked repeatedly.
+    #[inline]
+    pub fn piss_sounds(&self) -> Option<BwSoundRange> {
+        self.def().unit_data().and_then(|u| u.piss_sounds)
+    }
+
+    /// Returns the sound played when this type of unit finishes training.
+    #[inline]
+    pub fn ready_sound(&self) -> Option<BwSoundId> {
+        self.def().unit_data().and_then(|u| u.ready_sound)
+    }
+
+    /// Returns if this type of Construct has a weapon of its own (note that some constructs attack
+    /// through their subunits or other constructs instead).
+    #[inline]
+    pub fn has_weapon(&self) -> bool {
+        let def = self.def();
+        def.ground_weapon != NO_WEAPON || def.air_weapon != NO_WEAPON
+    }
+
+    /// Returns the race this type of Construct belongs to (e.g. for the purposes of supply), if
+    /// any.
+    pub fn race(&self) -> Option<Race> {
+        // NOTE(tec27): BW determines this from the StarEdit group flags
+        let groups = self.def().star_edit_group_flags;
+        if groups & 0x01 != 0 {
+            Some(Race::Zerg)
+        } else if groups & 0x02 != 0 {
+            Some(Race::Terran)
+        } else if groups & 0x04 != 0 {
+            Some(Race::Protoss)
+        } else {
+            None
+        }
+    }
+
     /// Returns whether this construct type is a "neutral unit". These will still be spawned even in
     /// non-UMS games when placed with a map editor.
     pub fn is_neutral(&self) -> bool {
@@ -149,31 +218,74 @@ impl ConstructTypeId {
     }
 }
 
+/// Component that identifies a construct in a way that is the same for every player in a game
+/// (unlike [Entity] IDs), so that constructs can be referred to in
+/// [GameCommand](crate::net::GameCommand)s.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Reflect)]
+pub struct ConstructId(pub u32);
+
+/// Resource that tracks the next [ConstructId] to be assigned. IDs are assigned in creation order,
+/// which is deterministic, so they'll match between players.
+#[derive(Resource, Debug, Default)]
+pub struct NextConstructId(u32);
+
+impl NextConstructId {
+    pub fn assign(&mut self) -> ConstructId {
+        let id = ConstructId(self.0);
+        self.0 += 1;
+        id
+    }
+}
+
+fn reset_construct_ids(mut next_id: ResMut<NextConstructId>) {
+    *next_id = NextConstructId::default();
+}
+
+/// Counter used to assign [ConstructSprite] serials in creation order.
+#[derive(Resource, Debug, Default)]
+struct NextSpriteSerial(u32);
+
+fn reset_sprite_serials(mut next_serial: ResMut<NextSpriteSerial>) {
+    *next_serial = NextSpriteSerial::default();
+}
+
 /// Component that specifies a [Construct]'s owner (via a player number).
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
 pub struct OwnedConstruct(pub u8);
 
-#[derive(Bundle)]
+/// Returns the owner of the construct that an image or sprite entity belongs to (if it has one).
+/// Images are children of a [ConstructSprite], which is a child of the construct.
+pub fn owner_of(
+    entity: Entity,
+    q_parents: &Query<&Parent>,
+    q_owners: &Query<&OwnedConstruct>,
+) -> Option<u8> {
+    q_parents
+        .iter_ancestors(entity)
+        .find_map(|e| q_owners.get(e).ok())
+        .map(|owner| owner.0)
+}
+
+#[derive(Bundle, Default)]
 pub struct ConstructBundle {
     pub spatial: SpatialBundle,
     pub construct_type: ConstructTypeId,
     pub position: Position,
-    pub ysort: YSort,
+    pub draw_order: DrawOrder,
     pub health: Health,
     pub under_construction: UnderConstruction,
     pub facing_direction: FacingDirection,
 }
 
-impl Default for ConstructBundle {
-    fn default() -> Self {
-        Self {
-            spatial: Default::default(),
-            position: Default::default(),
-            construct_type: Default::default(),
-            ysort: YSort(2.0),
-            health: Default::default(),
-            under_construction: Default::default(),
-            facing_direction: Default::default(),
+/// System that keeps the [DrawOrder] elevation of constructs in sync with their type (e.g. for
+/// buildings that lift off).
+fn update_construct_elevations(
+    mut query: Query<(&ConstructTypeId, &mut DrawOrder), Changed<ConstructTypeId>>,
+) {
+    for (construct_type, mut draw_order) in query.iter_mut() {
+        let elevation = construct_type.def().elevation_level;
+        if draw_order.elevation != elevation {
+            draw_order.elevation = elevation;
         }
     }
 }
@@ -212,6 +324,9 @@ pub struct ConstructSprite {
     /// always be in `images` as well.
     main_image: Option<Entity>,
     pub images: SmallVec<[Entity; 4]>,
+    /// Number assigned to sprites in the order they were created, which is the same for every
+    /// player in a game.
+    serial: u32,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -249,6 +364,13 @@ impl ConstructSprite {
         self.main_image
     }
 
+    /// Returns a number that orders this sprite relative to other sprites by when they were
+    /// created. Unlike [DrawOrder] serials, this is assigned as part of the simulation and can be
+    /// relied on to match between players.
+    pub fn serial(&self) -> u32 {
+        self.serial
+    }
+
     /// Add an image to this sprite using the specified ordering. If this is the first image to be
     /// added it will become the main image for the sprite.
     pub fn add_image(&mut self, image: Entity, order: ImageOrder) {
@@ -389,8 +511,6 @@ impl ConstructImageBundle {
     }
 }
 
-const SHADOW_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.7);
-
 /// System that applies a sprite's image ordering to the individual images.
 pub fn update_construct_image_order(
     q_sprites: Query<&ConstructSprite, Changed<ConstructSprite>>,
@@ -508,20 +628,16 @@ pub fn update_construct_image_frames(
             }
         }
         sprite.flip_x = image.flip_x;
+        // NOTE(tec27): Render styles are drawn by the image materials (see `render::effects`), the
+        // only thing we need to deal with here is making sure shadows are drawn under everything
+        let z_offset = if image.render_style == Some(RenderStyle::Shadow) {
+            SHADOW_Z_OFFSET
+        } else {
+            0.0
+        };
         transform.translation = (Vec2::new(image.offset.x as f32, -image.offset.y as f32)
             * tile_scale)
-            .extend(-(image_order.0 as f32 / 1000.0));
-
-        if image.render_style == Some(RenderStyle::Shadow) {
-            // TODO(tec27): This isn't totally correct and doesn't deal with the "shadow stacking"
-            // feature at all. I think shadows should always be 50% grey as well? Probably room for
-            // creativity here.
-            sprite.color = SHADOW_COLOR;
-        } else if sprite.color == SHADOW_COLOR {
-            sprite.color = Color::WHITE;
-        }
-        // TODO(tec27): Deal with other render styles, and probably avoid clobbering the sprite
-        // color better?
+            .extend(z_offset - image_order.0 as f32 * IMAGE_Z_STEP);
 
         if image.temp_hidden || image.waiting_for_assets && *visibility != Visibility::Hidden {
             *visibility = Visibility::Hidden;
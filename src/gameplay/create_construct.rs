@@ -9,31 +9,35 @@ use crate::{
     },
     math::FixedPoint,
     random::LcgRand,
-    states::{AppState, InGameOnly},
+    states::InGameOnly,
 };
 
 use super::{
     build_time::UnderConstruction,
     constructs::{
         ConstructBundle, ConstructImage, ConstructImageBundle, ConstructSprite,
-        ConstructSpriteBundle, ImageOrder, OwnedConstruct,
+        ConstructSpriteBundle, ImageOrder, NextConstructId, OwnedConstruct,
     },
+    energy::Energy,
     facing_direction::FacingDirection,
     health::Health,
     iscripts::{IscriptController, IscriptExecContext},
     shield::Shield,
-    status::CanTurn,
+    status::{CanTurn, Kills},
+    training::TrainedBy,
+    SimulationSet,
 };
 
 pub fn plugin(app: &mut App) {
     app.add_event::<CreateConstructEvent>()
         .add_event::<FinishConstructEvent>()
         .add_event::<PlaceConstructEvent>()
+        .add_event::<ConstructReadyEvent>()
         .add_systems(
             FixedUpdate,
             (create_constructs, finish_constructs, place_constructs)
                 .chain()
-                .run_if(in_state(AppState::InGame)),
+                .in_set(SimulationSet),
         );
 }
 
@@ -66,6 +70,8 @@ pub struct CreateConstructEvent {
     // TODO(tec27): Use this in construct creation
     pub energy_percent: Option<FixedPoint>,
     pub resource_amount: Option<u32>,
+    /// The construct that is training this one, if any (see [super::training]).
+    pub trained_by: Option<Entity>,
 }
 
 /// Event that signifies a Construct has finished construction. If it is a unit, it will have its
@@ -76,6 +82,13 @@ pub struct FinishConstructEvent {
     pub entity: Entity,
 }
 
+/// Event fired when a Construct finishes its build time (e.g. a unit finishes training). This is
+/// not sent for Constructs that were created immediately, like those placed at the start of a game.
+#[derive(Event, Debug, Copy, Clone)]
+pub struct ConstructReadyEvent {
+    pub entity: Entity,
+}
+
 /// Event that signifies a Construct should be placed on the map at its current position. The
 /// standard placement algorithm will be followed (attempting to find an empty area around the
 /// desired position that has terrain that can accomodate the construct).
@@ -91,6 +104,7 @@ pub fn create_constructs(
         EventWriter<FinishConstructEvent>,
         Commands,
         ResMut<LcgRand>,
+        ResMut<NextConstructId>,
     )>,
     init_iscript_params: &mut SystemState<(
         Query<
@@ -109,7 +123,7 @@ pub fn create_constructs(
         Query<&GameMapTileset>,
     )>,
 ) {
-    let (mut events, mut writer, mut commands, mut rng) = params.get_mut(world);
+    let (mut events, mut writer, mut commands, mut rng, mut next_id) = params.get_mut(world);
     let mut constructed = vec![];
     for e in events.read() {
         // NOTE(tec27): Blizzard's version does this as well, seemingly since very early on, I guess
@@ -136,6 +150,7 @@ pub fn create_constructs(
                 under_construction: UnderConstruction::for_type(e.construct_type),
                 ..default()
             },
+            next_id.assign(),
             InGameOnly,
         ));
         if e.construct_type.flags().contains(ConstructFlags::CAN_TURN) {
@@ -144,9 +159,18 @@ pub fn create_constructs(
         if let Some(shield) = Shield::initial(e.construct_type) {
             entity.insert(shield);
         }
+        if let Some(energy) = Energy::initial(e.construct_type) {
+            entity.insert(energy);
+        }
+        if e.construct_type.has_weapon() {
+            entity.insert(Kills::default());
+        }
         if let Some(owner) = e.owner {
             entity.insert(OwnedConstruct(owner));
         }
+        if let Some(producer) = e.trained_by {
+            entity.insert(TrainedBy(producer));
+        }
         if e.construct_type.is_resource() {
             if let Some(amount) = e.resource_amount {
                 entity.insert(match e.construct_type {
@@ -234,6 +258,7 @@ pub fn finish_constructs(
     params: &mut SystemState<(
         EventReader<FinishConstructEvent>,
         EventWriter<PlaceConstructEvent>,
+        EventWriter<ConstructReadyEvent>,
         Query<(
             Entity,
             &ConstructTypeId,
@@ -247,7 +272,7 @@ pub fn finish_constructs(
         ResMut<LcgRand>,
     )>,
 ) {
-    let (mut events, mut writer, mut constructs_query, mut commands, mut rng) =
+    let (mut events, mut writer, mut ready_writer, mut constructs_query, mut commands, mut rng) =
         params.get_mut(world);
     for e in events.read() {
         let Ok((entity, ty, uc, mut health, mut shield, can_turn, mut facing)) =
@@ -266,6 +291,8 @@ pub fn finish_constructs(
             if let Some(ref mut shield) = shield {
                 shield.current = shield.max;
             }
+        } else {
+            ready_writer.send(ConstructReadyEvent { entity });
         }
         if ty.is_building() {
             // TODO(tec27): Remove construction graphic
@@ -581,37 +608,16 @@ fn search_for_empty_position(
 
 #[cfg(test)]
 mod tests {
-    use crate::maps::game_map::GameMapBundle;
+    use crate::{headless::create_test_game, maps::MapAsset};
 
     use super::*;
 
-    fn setup_app() -> App {
-        let mut app = App::new();
-        app.add_event::<CreateConstructEvent>()
-            .add_event::<FinishConstructEvent>()
-            .add_event::<PlaceConstructEvent>()
-            .add_systems(
-                Update,
-                (create_constructs, finish_constructs, place_constructs).chain(),
-            )
-            .insert_resource(LcgRand::new(42));
-
-        app
-    }
-
     #[test]
     fn multiple_units_at_once() {
         // Check that placing multiple units in the same turns takes into account the placement of
         // those same-turn units
 
-        let mut app = setup_app();
-        app.world_mut().spawn(GameMapBundle {
-            size: GameMapSize {
-                width: 128,
-                height: 128,
-            },
-            ..default()
-        });
+        let mut app = create_test_game(MapAsset::fixture(128, 128));
         let hq_position = IVec2::new(3808, 2384);
         app.world_mut().spawn(ConstructBundle {
             construct_type: ConstructTypeId::TerranCommandCenter,
@@ -666,14 +672,7 @@ mod tests {
 
     #[test]
     fn bottleneck_right_side_scv_placement() {
-        let mut app = setup_app();
-        app.world_mut().spawn(GameMapBundle {
-            size: GameMapSize {
-                width: 128,
-                height: 128,
-            },
-            ..default()
-        });
+        let mut app = create_test_game(MapAsset::fixture(128, 128));
         let hq_position = IVec2::new(3808, 2384);
         app.world_mut().spawn(ConstructBundle {
             construct_type: ConstructTypeId::TerranCommandCenter,
@@ -827,15 +826,7 @@ mod tests {
         // This replicates a "hacked" version of the game where the initial Command Center is
         // instead a Barracks and we only spawn marines around it
 
-        let mut app = setup_app();
-
-        app.world_mut().spawn(GameMapBundle {
-            size: GameMapSize {
-                width: 128,
-                height: 128,
-            },
-            ..default()
-        });
+        let mut app = create_test_game(MapAsset::fixture(128, 128));
         let hq_position = IVec2::new(288, 2416);
         app.world_mut().spawn(ConstructBundle {
             construct_type: ConstructTypeId::TerranBarracks,
@@ -995,15 +986,7 @@ mod tests {
         // This replicates a "hacked" version of the game where the initial Command Center is
         // instead a Barracks and we only spawn ghosts around it
 
-        let mut app = setup_app();
-
-        app.world_mut().spawn(GameMapBundle {
-            size: GameMapSize {
-                width: 128,
-                height: 128,
-            },
-            ..default()
-        });
+        let mut app = create_test_game(MapAsset::fixture(128, 128));
         let hq_position = IVec2::new(288, 2416);
         app.world_mut().spawn(ConstructBundle {
             construct_type: ConstructTypeId::TerranBarracks,
@@ -1200,15 +1183,7 @@ mod tests {
         // This replicates a "hacked" version of the game where the initial Command Center is
         // instead a Factory and we only spawn tanks around it
 
-        let mut app = setup_app();
-
-        app.world_mut().spawn(GameMapBundle {
-            size: GameMapSize {
-                width: 128,
-                height: 128,
-            },
-            ..default()
-        });
+        let mut app = create_test_game(MapAsset::fixture(128, 128));
         let hq_position = IVec2::new(288, 2416);
         app.world_mut().spawn(ConstructBundle {
             construct_type: ConstructTypeId::TerranFactory,
@@ -1331,15 +1306,7 @@ mod tests {
         // This replicates a "hacked" version of the game where the initial Command Center is
         // instead a Factory and we only spawn tanks around it
 
-        let mut app = setup_app();
-
-        app.world_mut().spawn(GameMapBundle {
-            size: GameMapSize {
-                width: 64,
-                height: 64,
-            },
-            ..default()
-        });
+        let mut app = create_test_game(MapAsset::fixture(64, 64));
         let hq_position = IVec2::new(1984, 1936);
         app.world_mut().spawn(ConstructBundle {
             construct_type: ConstructTypeId::TerranFactory,
@@ -1438,15 +1405,7 @@ mod tests {
         // This replicates a "hacked" version of the game where the initial Command Center is
         // instead a Factory and we only spawn tanks around it
 
-        let mut app = setup_app();
-
-        app.world_mut().spawn(GameMapBundle {
-            size: GameMapSize {
-                width: 64,
-                height: 64,
-            },
-            ..default()
-        });
+        let mut app = create_test_game(MapAsset::fixture(64, 64));
         let hq_position = IVec2::new(64, 48);
         app.world_mut().spawn(ConstructBundle {
             construct_type: ConstructTypeId::TerranFactory,
@@ -1537,15 +1496,7 @@ mod tests {
         // This replicates assets/stacked-units.scm, which has 4 dragoons stacked on top of each
         // other that should get spread out when the game starts.
 
-        let mut app = setup_app();
-
-        app.world_mut().spawn(GameMapBundle {
-            size: GameMapSize {
-                width: 64,
-                height: 64,
-            },
-            ..default()
-        });
+        let mut app = create_test_game(MapAsset::fixture(64, 64));
 
         app.update();
 
@@ -1606,15 +1557,7 @@ mod tests {
         // This replicates assets/stacked-buildings.scm, which has 6 psi disrupters stacked on top
         // of each other that should not get spread out
 
-        let mut app = setup_app();
-
-        app.world_mut().spawn(GameMapBundle {
-            size: GameMapSize {
-                width: 64,
-                height: 64,
-            },
-            ..default()
-        });
+        let mut app = create_test_game(MapAsset::fixture(64, 64));
 
         app.update();
 
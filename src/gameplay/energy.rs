@@ -0,0 +1,30 @@
+use bevy::prelude::*;
+
+use crate::{
+    gamedata::{ConstructFlags, ConstructTypeId},
+    math::FixedPoint,
+};
+
+/// The maximum energy a construct can have without upgrades.
+const BASE_MAX_ENERGY: FixedPoint = FixedPoint::const_from_int(200);
+/// The energy a construct starts with once it has been created.
+const INITIAL_ENERGY: FixedPoint = FixedPoint::const_from_int(50);
+
+#[derive(Component, Debug, Copy, Clone, Default)]
+pub struct Energy {
+    pub max: FixedPoint,
+    pub current: FixedPoint,
+}
+
+impl Energy {
+    /// Creates the initial energy component for a given construct type.
+    pub fn initial(c: ConstructTypeId) -> Option<Self> {
+        // TODO(tec27): Account for upgrades that increase max energy
+        c.flags()
+            .contains(ConstructFlags::HAS_ENERGY)
+            .then_some(Self {
+                max: BASE_MAX_ENERGY,
+                current: INITIAL_ENERGY,
+            })
+    }
+}
@@ -0,0 +1,47 @@
+//! Execution of the [GameCommand]s in each turn. Commands are executed one at a time in the order
+//! they were issued, so that e.g. a command that changes a player's selection affects the commands
+//! issued after it (but not the ones before it) in the same turn.
+
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
+
+use crate::net::{CurrentTurn, GameCommand};
+
+use super::{create_construct::create_constructs, SimulationSet};
+
+/// Schedule that is run once for each command in the current turn. Systems that handle commands
+/// should be added to this and read the command from [ExecutingCommand].
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExecGameCommand;
+
+/// Resource containing the command being executed by the [ExecGameCommand] schedule, along with
+/// the player that issued it.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct ExecutingCommand {
+    pub player: u8,
+    pub command: GameCommand,
+}
+
+/// System set for the system that executes the commands of the current turn. Systems that need to
+/// see the results of those commands should be ordered after this.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExecCommandsSet;
+
+pub fn plugin(app: &mut App) {
+    app.init_schedule(ExecGameCommand).add_systems(
+        FixedUpdate,
+        exec_turn_commands
+            .in_set(ExecCommandsSet)
+            .in_set(SimulationSet)
+            .before(create_constructs),
+    );
+}
+
+/// Runs the [ExecGameCommand] schedule for each command in the current turn, in order.
+pub fn exec_turn_commands(world: &mut World) {
+    let commands = world.resource::<CurrentTurn>().commands.clone();
+    for (player, command) in commands {
+        world.insert_resource(ExecutingCommand { player, command });
+        world.run_schedule(ExecGameCommand);
+    }
+    world.remove_resource::<ExecutingCommand>();
+}
@@ -2,6 +2,13 @@ use bevy::prelude::*;
 
 use crate::{gamedata::ConstructTypeId, math::FixedPoint};
 
+use super::{status::Kills, SimulationSet};
+
+pub fn plugin(app: &mut App) {
+    app.add_event::<ConstructKilledEvent>()
+        .add_systems(FixedUpdate, credit_kills.in_set(SimulationSet));
+}
+
 #[derive(Component, Debug, Copy, Clone, Default)]
 pub struct Health {
     pub max: FixedPoint,
@@ -17,3 +24,24 @@ impl Health {
         }
     }
 }
+
+/// Event that signifies a construct was killed (as opposed to being removed, e.g. by a trigger).
+// TODO(tec27): Send this when weapon damage kills a construct once constructs can attack
+#[derive(Event, Debug, Copy, Clone)]
+pub struct ConstructKilledEvent {
+    pub construct_type: ConstructTypeId,
+    pub owner: Option<u8>,
+    /// The construct that did the killing, if any.
+    pub killer: Option<Entity>,
+    /// The owner of the killer, which gets credit for the kill even if the killer has since died.
+    pub killer_owner: Option<u8>,
+}
+
+/// Adds kills to the [Kills] of the constructs that killed something.
+fn credit_kills(mut events: EventReader<ConstructKilledEvent>, mut killers: Query<&mut Kills>) {
+    for event in events.read() {
+        if let Some(mut kills) = event.killer.and_then(|e| killers.get_mut(e).ok()) {
+            kills.0 = kills.0.saturating_add(1);
+        }
+    }
+}
@@ -3,10 +3,11 @@ use bevy::{app::AppExit, prelude::*};
 use crate::{
     ecs::{despawn_all, log_transitions},
     fonts::FONT_BODY,
+    input::InputAction,
     states::AppState,
 };
 
-use super::gizmos::ConstructGizmos;
+use super::{gizmos::ConstructGizmos, options_menu::RebindingAction};
 
 pub struct InGameMenuPlugin;
 
@@ -49,6 +50,8 @@ pub enum InGameMenuState {
     #[default]
     Disabled,
     General,
+    Options,
+    Hotkeys,
 }
 
 fn setup(mut menu_state: ResMut<NextState<InGameMenuState>>) {
@@ -62,9 +65,11 @@ fn cleanup(mut menu_state: ResMut<NextState<InGameMenuState>>) {
 fn handle_keys(
     cur_state: Res<State<InGameMenuState>>,
     mut next_state: ResMut<NextState<InGameMenuState>>,
-    keys: Res<ButtonInput<KeyCode>>,
+    actions: Res<ButtonInput<InputAction>>,
+    rebinding: Res<RebindingAction>,
 ) {
-    if keys.just_pressed(KeyCode::F10) {
+    // Any key pressed while rebinding is meant for the action being rebound
+    if rebinding.0.is_none() && actions.just_pressed(InputAction::ToggleMenu) {
         next_state.set(match cur_state.get() {
             InGameMenuState::Disabled => InGameMenuState::General,
             _ => InGameMenuState::Disabled,
@@ -72,7 +77,7 @@ fn handle_keys(
     }
 }
 
-const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+pub(super) const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
 const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
 const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
 
@@ -93,6 +98,7 @@ fn update_button_colors(
 
 #[derive(Component, Debug)]
 enum GeneralMenuAction {
+    Options,
     ToggleDevTools,
     EndGame,
     Quit,
@@ -133,6 +139,22 @@ fn setup_general(mut commands: Commands, asset_server: Res<AssetServer>) {
             OnGeneralMenu,
         ))
         .with_children(|parent| {
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: button_style.clone(),
+                        background_color: BackgroundColor(NORMAL_BUTTON),
+                        ..default()
+                    },
+                    GeneralMenuAction::Options,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((TextBundle::from_section(
+                        "options",
+                        button_text_style.clone(),
+                    ),));
+                });
+
             parent
                 .spawn((
                     ButtonBundle {
@@ -183,12 +205,16 @@ fn setup_general(mut commands: Commands, asset_server: Res<AssetServer>) {
 fn general_actions(
     query: Query<(&Interaction, &GeneralMenuAction), (Changed<Interaction>, With<Button>)>,
     mut gizmo_store: ResMut<GizmoConfigStore>,
+    mut next_menu_state: ResMut<NextState<InGameMenuState>>,
     mut next_app_state: ResMut<NextState<AppState>>,
     mut app_exit_events: EventWriter<AppExit>,
 ) {
     for (interaction, action) in &query {
         if *interaction == Interaction::Pressed {
             match action {
+                GeneralMenuAction::Options => {
+                    next_menu_state.set(InGameMenuState::Options);
+                }
                 GeneralMenuAction::ToggleDevTools => {
                     let (config, _) = gizmo_store.config_mut::<ConstructGizmos>();
                     config.enabled ^= true;
@@ -12,8 +12,8 @@ use std::ops::DerefMut;
 
 use super::{
     constructs::{
-        ConstructImage, ConstructImageBundle, ConstructSprite, ImageOrder, LocationOffsetKind,
-        UseLocationOffset,
+        ConstructImage, ConstructImageBundle, ConstructImageOrder, ConstructSprite, ImageOrder,
+        LocationOffsetKind, UseLocationOffset,
     },
     facing_direction::FacingDirection,
     resources::ResourceAmount,
@@ -473,17 +473,46 @@ impl IscriptController {
 }
 
 pub fn exec_iscripts(
-    mut q_images: Query<(Entity, &mut IscriptController, &mut ConstructImage, &Parent)>,
+    mut q_images: Query<(
+        Entity,
+        &mut IscriptController,
+        &mut ConstructImage,
+        &ConstructImageOrder,
+        &Parent,
+    )>,
     mut q_sprites: Query<(Entity, &mut ConstructSprite, &Parent)>,
     mut q_constructs: Query<(&mut FacingDirection, &Position, Option<&ResourceAmount>)>,
     mut commands: Commands,
     mut rand: ResMut<LcgRand>,
     q_tileset: Query<&GameMapTileset>,
+    mut exec_order: Local<Vec<(u32, u8, Entity)>>,
 ) {
     let tileset = q_tileset.get_single().ok().map(|&t| *t);
-    // TODO(tec27): This order is almost certainly not correct. #1 we need to look at sprites in
-    // a particular order, and #2 we probably need to look at their images in a particular order?
-    for (image_entity, mut controller, mut image, parent) in q_images.iter_mut() {
+
+    // NOTE(tec27): Iscripts consume random numbers, so they need to be run in a deterministic
+    // order. We go through sprites in the order they were created, and through each sprite's images
+    // from top to bottom.
+    // TODO(tec27): BW actually does this as part of updating each unit/thingy/bullet in its own
+    // list order, this should be revisited once we track those lists
+    exec_order.clear();
+    exec_order.extend(
+        q_images
+            .iter()
+            .map(|(image_entity, _, _, image_order, parent)| {
+                let serial = q_sprites
+                    .get(parent.get())
+                    .map_or(u32::MAX, |(_, sprite, _)| sprite.serial());
+                (serial, image_order.0, image_entity)
+            }),
+    );
+    exec_order.sort_unstable();
+
+    for &(_, _, image_entity) in exec_order.iter() {
+        let Ok((image_entity, mut controller, mut image, _, parent)) =
+            q_images.get_mut(image_entity)
+        else {
+            continue;
+        };
         if let Ok((sprite_entity, mut sprite, sprite_parent)) = q_sprites.get_mut(parent.get()) {
             let mut query_result = q_constructs.get_mut(sprite_parent.get());
             let (construct_facing, sprite_position, construct_resources) = match query_result {
@@ -0,0 +1,402 @@
+//! The minimap, which shows the whole map's terrain and constructs, along with the area the camera
+//! is currently viewing. Clicking on it moves the camera (or picks the target of a command card
+//! order), and right-clicking on it targets a position (like right-clicking on the map would).
+
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+    window::PrimaryWindow,
+};
+
+use crate::{
+    camera::CenterCameraEvent,
+    gamedata::ConstructTypeId,
+    maps::{
+        game_map::{GameMap, GameMapSize, LOGIC_TILE_SIZE},
+        position::Position,
+        CurrentMap, MapAsset,
+    },
+    net::{GameCommand, IssueCommandEvent},
+    settings::GameSettings,
+    states::{AppState, InGameOnly},
+};
+
+use super::{
+    command_card::{CommandTargeting, TargetInputEvent},
+    constructs::OwnedConstruct,
+    players::{ControlledPlayer, PlayerColors},
+    selection::{BlocksMapInput, SelectedEntities},
+    InGameMenuState,
+};
+
+/// The size of the minimap on screen, in logical pixels.
+const MINIMAP_SIZE: f32 = 200.0;
+/// The largest the minimap texture will be in either dimension. Smaller maps get more pixels per
+/// tile so that constructs can still be drawn with some detail.
+const MAX_TEXTURE_SIZE: u32 = 256;
+/// How often the constructs drawn on the minimap are updated.
+const UPDATE_INTERVAL: Duration = Duration::from_millis(100);
+
+const RESOURCE_COLOR: [u8; 4] = [0, 228, 252, 255];
+const NEUTRAL_COLOR: [u8; 4] = [180, 180, 180, 255];
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::InGame), setup)
+            .add_systems(
+                Update,
+                (update_minimap_image, update_viewport_box).run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(
+                Update,
+                minimap_input.run_if(
+                    in_state(AppState::InGame).and_then(in_state(InGameMenuState::Disabled)),
+                ),
+            );
+    }
+}
+
+#[derive(Resource, Debug)]
+struct Minimap {
+    image: Handle<Image>,
+    /// The terrain colors, in the same format as `image`. Constructs get drawn over top of this.
+    terrain: Vec<u8>,
+    /// The size of `image` in pixels.
+    size: UVec2,
+    pixels_per_tile: u32,
+    update_timer: Timer,
+}
+
+/// Marker component for the node that displays the minimap image.
+#[derive(Component)]
+struct MinimapImage;
+
+/// Marker component for the box that shows the area the camera is viewing.
+#[derive(Component)]
+struct MinimapViewport;
+
+/// Returns how many pixels each map tile gets in the minimap texture.
+fn pixels_per_tile(map_size: &GameMapSize) -> u32 {
+    (MAX_TEXTURE_SIZE / map_size.width.max(map_size.height).max(1)).max(1)
+}
+
+fn setup(
+    mut commands: Commands,
+    current_map: Res<CurrentMap>,
+    maps: Res<Assets<MapAsset>>,
+    map_size: Query<&GameMapSize, With<GameMap>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let (Some(map), Ok(map_size)) = (maps.get(&current_map.handle), map_size.get_single()) else {
+        warn!("Couldn't create the minimap, no map is loaded");
+        return;
+    };
+
+    let pixels_per_tile = pixels_per_tile(map_size);
+    let size = UVec2::new(map_size.width, map_size.height) * pixels_per_tile;
+    let mut terrain = vec![0; (size.x * size.y * 4) as usize];
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let [r, g, b] = map
+                .tile_color(x / pixels_per_tile, y / pixels_per_tile)
+                .unwrap_or_default();
+            let i = ((y * size.x + x) * 4) as usize;
+            terrain[i..i + 4].copy_from_slice(&[r, g, b, 255]);
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        terrain.clone(),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = ImageSampler::nearest();
+    let image = images.add(image);
+
+    // Fit the map into the minimap area while keeping its aspect ratio
+    let scale = MINIMAP_SIZE / size.x.max(size.y) as f32;
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(8.0),
+                    bottom: Val::Px(8.0),
+                    width: Val::Px(MINIMAP_SIZE),
+                    height: Val::Px(MINIMAP_SIZE),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::BLACK.into(),
+                ..default()
+            },
+            Interaction::default(),
+            BlocksMapInput,
+            InGameOnly,
+        ))
+        .with_children(|builder| {
+            builder
+                .spawn((
+                    ImageBundle {
+                        style: Style {
+                            width: Val::Px(size.x as f32 * scale),
+                            height: Val::Px(size.y as f32 * scale),
+                            overflow: Overflow::clip(),
+                            ..default()
+                        },
+                        image: UiImage::new(image.clone()),
+                        ..default()
+                    },
+                    MinimapImage,
+                ))
+                .with_children(|builder| {
+                    builder.spawn((
+                        NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                border: UiRect::all(Val::Px(1.0)),
+                                ..default()
+                            },
+                            border_color: Color::WHITE.into(),
+                            ..default()
+                        },
+                        MinimapViewport,
+                    ));
+                });
+        });
+
+    commands.insert_resource(Minimap {
+        image,
+        terrain,
+        size,
+        pixels_per_tile,
+        update_timer: Timer::new(UPDATE_INTERVAL, TimerMode::Repeating),
+    });
+}
+
+/// Redraws the constructs on the minimap.
+fn update_minimap_image(
+    minimap: Option<ResMut<Minimap>>,
+    time: Res<Time>,
+    mut images: ResMut<Assets<Image>>,
+    constructs: Query<(
+        &ConstructTypeId,
+        &Position,
+        &Visibility,
+        Option<&OwnedConstruct>,
+    )>,
+    player_colors: PlayerColors,
+) {
+    let Some(mut minimap) = minimap else {
+        return;
+    };
+    if !minimap.update_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Some(image) = images.get_mut(&minimap.image) else {
+        return;
+    };
+
+    // TODO(tec27): Darken areas the local player can't see and hide constructs within them once
+    // fog of war is implemented
+    image.data.copy_from_slice(&minimap.terrain);
+    for (construct_type, position, visibility, owner) in constructs.iter() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        let color = if construct_type.is_resource() {
+            RESOURCE_COLOR
+        } else if let Some(color) = owner.and_then(|o| player_colors.get(o.0)) {
+            color.color().to_srgba().to_u8_array()
+        } else {
+            NEUTRAL_COLOR
+        };
+
+        let rect = construct_type.bounds().at_pos(position.into());
+        let to_pixels = |v: i32| v.max(0) as u32 * minimap.pixels_per_tile / LOGIC_TILE_SIZE as u32;
+        let min = UVec2::new(to_pixels(rect.min.x), to_pixels(rect.min.y));
+        // Always draw at least one pixel so that small units don't disappear
+        let max = UVec2::new(to_pixels(rect.max.x), to_pixels(rect.max.y))
+            .max(min + UVec2::ONE)
+            .min(minimap.size);
+        for y in min.y..max.y {
+            for x in min.x..max.x {
+                let i = ((y * minimap.size.x + x) * 4) as usize;
+                image.data[i..i + 4].copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+/// Returns the area the camera can see, as fractions of the map's size (with (0, 0) being the top
+/// left of the map).
+fn camera_view_rect(
+    camera_pos: Vec2,
+    view_size: Vec2,
+    map_size: &GameMapSize,
+    tile_size: Vec2,
+) -> Rect {
+    let map_pixels = Vec2::from(map_size) * tile_size;
+    // NOTE(tec27): The terrain is offset by half a tile, see `position_to_translation`
+    let to_fraction = |pos: Vec2| {
+        let pos = (pos + tile_size / 2.0) / map_pixels;
+        Vec2::new(pos.x + 0.5, 0.5 - pos.y)
+    };
+    Rect::from_corners(
+        to_fraction(camera_pos - view_size / 2.0),
+        to_fraction(camera_pos + view_size / 2.0),
+    )
+}
+
+fn update_viewport_box(
+    mut viewport: Query<&mut Style, With<MinimapViewport>>,
+    camera: Query<(&Transform, &OrthographicProjection), With<Camera>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    map_size: Query<&GameMapSize, With<GameMap>>,
+    settings: Res<GameSettings>,
+) {
+    let (Ok(mut style), Ok((transform, projection)), Ok(window), Ok(map_size)) = (
+        viewport.get_single_mut(),
+        camera.get_single(),
+        window.get_single(),
+        map_size.get_single(),
+    ) else {
+        return;
+    };
+
+    let view_size = Vec2::new(window.width(), window.height()) * projection.scale;
+    let rect = camera_view_rect(
+        transform.translation.truncate(),
+        view_size,
+        map_size,
+        settings.asset_quality.tile_size(),
+    );
+    style.left = Val::Percent(rect.min.x * 100.0);
+    style.top = Val::Percent(rect.min.y * 100.0);
+    style.width = Val::Percent(rect.width() * 100.0);
+    style.height = Val::Percent(rect.height() * 100.0);
+}
+
+#[derive(Default)]
+struct MinimapInputState {
+    /// Whether the left mouse button was pressed over the minimap and is still being held.
+    dragging: bool,
+}
+
+fn minimap_input(
+    mut state: Local<MinimapInputState>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    minimap_image: Query<(&Node, &GlobalTransform), With<MinimapImage>>,
+    map_size: Query<&GameMapSize, With<GameMap>>,
+    controlled_player: Query<&SelectedEntities, With<ControlledPlayer>>,
+    mut center_camera_writer: EventWriter<CenterCameraEvent>,
+    mut command_writer: EventWriter<IssueCommandEvent>,
+    targeting: Res<CommandTargeting>,
+    mut target_writer: EventWriter<TargetInputEvent>,
+) {
+    let (Ok(window), Ok((node, node_transform)), Ok(map_size)) = (
+        window.get_single(),
+        minimap_image.get_single(),
+        map_size.get_single(),
+    ) else {
+        return;
+    };
+
+    if !mouse_buttons.pressed(MouseButton::Left) {
+        state.dragging = false;
+    }
+    let Some(cursor_pos) = window.cursor_position() else {
+        return;
+    };
+
+    // Convert the cursor position to a position on the map, if it's within the minimap
+    let node_rect = Rect::from_center_size(node_transform.translation().truncate(), node.size());
+    let fraction = (cursor_pos - node_rect.min) / node_rect.size();
+    let map_pixels = Vec2::from(map_size) * LOGIC_TILE_SIZE as f32;
+    let map_pos = |fraction: Vec2| -> Position {
+        let pos = (fraction.clamp(Vec2::ZERO, Vec2::ONE) * map_pixels).round();
+        IVec2::new(pos.x as i32, pos.y as i32).into()
+    };
+    let in_minimap = node_rect.contains(cursor_pos);
+
+    if mouse_buttons.just_pressed(MouseButton::Left) && in_minimap {
+        if targeting.0.is_some() {
+            target_writer.send(TargetInputEvent {
+                position: map_pos(fraction),
+                target: None,
+            });
+        } else {
+            state.dragging = true;
+        }
+    }
+    if state.dragging {
+        // NOTE(tec27): While dragging the cursor can leave the minimap, in which case we just
+        // clamp it to the edges
+        center_camera_writer.send(CenterCameraEvent(map_pos(fraction)));
+    }
+
+    let has_selection = controlled_player
+        .get_single()
+        .is_ok_and(|selected| !selected.0.is_empty());
+    if mouse_buttons.just_pressed(MouseButton::Right) && in_minimap && has_selection {
+        // NOTE(tec27): Constructs are too small on the minimap to target them, so this is always
+        // an order to the position
+        command_writer.send(IssueCommandEvent(GameCommand::RightClick {
+            position: map_pos(fraction),
+            target: None,
+            queued: keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn texture_scaling() {
+        let size = |width, height| GameMapSize { width, height };
+        assert_eq!(pixels_per_tile(&size(64, 64)), 4);
+        assert_eq!(pixels_per_tile(&size(128, 96)), 2);
+        assert_eq!(pixels_per_tile(&size(192, 192)), 1);
+        assert_eq!(pixels_per_tile(&size(256, 256)), 1);
+    }
+
+    #[test]
+    fn view_rect() {
+        let map_size = GameMapSize {
+            width: 128,
+            height: 64,
+        };
+        let tile_size = Vec2::splat(32.0);
+        // The center of the map is half a tile down and to the left of (0, 0)
+        let center = -tile_size / 2.0;
+
+        let rect = camera_view_rect(center, Vec2::new(1024.0, 512.0), &map_size, tile_size);
+        assert_eq!(rect.min, Vec2::new(0.375, 0.375));
+        assert_eq!(rect.max, Vec2::new(0.625, 0.625));
+
+        let top_left = center + Vec2::new(-2048.0, 1024.0);
+        let rect = camera_view_rect(top_left, Vec2::new(1024.0, 512.0), &map_size, tile_size);
+        assert_eq!(rect.min, Vec2::new(-0.125, -0.125));
+        assert_eq!(rect.max, Vec2::new(0.125, 0.125));
+    }
+}
@@ -5,15 +5,16 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use crate::{
     gamedata::{BwGameData, ConstructTypeId, CONSTRUCTS, SPRITES},
     gameplay::constructs::{ConstructImageBundle, ConstructSpriteBundle},
+    lobby::{default_lobby, Lobby},
     maps::{
         game_map::GameMap,
         position::{self, Position},
         CurrentMap, MapAsset,
     },
     math::FixedPoint,
-    races::Race,
+    net::{turn_ready, LockstepSession},
     random::LockedLcgRand,
-    render::ysort::YSort,
+    render::draw_order::DrawOrder,
     states::{AppState, InGameOnly},
 };
 
@@ -22,24 +23,39 @@ use self::{
     facing_direction::apply_facing_to_images,
     gizmos::{show_construct_gizmos, ConstructGizmos},
     iscripts::exec_iscripts,
+    player_selection::ControlGroups,
     players::{ControlledPlayer, Player, PlayerEntities},
+    resources::{update_player_supply, PlayerResources, PlayerSupply},
     selection::SelectedEntities,
 };
 
+pub mod alerts;
 pub mod build_time;
+pub mod command_card;
+pub mod console;
 pub mod constructs;
 pub mod create_construct;
+pub mod energy;
 pub mod facing_direction;
+pub mod game_commands;
 pub mod gizmos;
 pub mod health;
 mod in_game_menu;
 pub mod iscripts;
+pub mod minimap;
+mod options_menu;
+pub mod player_selection;
 pub mod players;
+pub mod resource_hud;
 pub mod resources;
 pub mod selection;
 pub mod shield;
 pub mod sounds;
+pub mod speech;
 pub mod status;
+pub mod status_bars;
+pub mod training;
+pub mod triggers;
 
 pub use in_game_menu::InGameMenuState;
 
@@ -69,28 +85,105 @@ impl GameSpeed {
 }
 
 /// What type of game is being played.
-#[derive(Resource, Debug, Default)]
+#[derive(Resource, Debug, Default, Copy, Clone, PartialEq, Eq)]
 pub enum GameMode {
     /// A game with no teams and standard "kill all buildings" objectives. Alliance changes are
     /// allowed.
     #[default]
     Melee,
+    /// A game where the map specifies the players, units, and objectives (via its triggers).
+    UseMapSettings,
     /// View the map with no objectives/interaction.
     MapView,
     // TODO(tec27): Implement more game modes
 }
+
+/// The number of game frames (turns) that have been simulated in the current game.
+#[derive(Resource, Debug, Default, Copy, Clone, PartialEq, Eq, Reflect)]
+#[reflect(Resource)]
+pub struct GameFrame(pub u32);
+
+/// The start location of each player on the current map, keyed by player number.
+#[derive(Resource, Debug, Default)]
+pub struct StartLocations(pub HashMap<u8, Position>);
+
+/// System set containing all the systems that advance the game simulation. These only run on
+/// fixed updates where the commands from every player are available for the current game frame.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SimulationSet;
+
+/// Plugin containing the systems that make up the game simulation. This does not depend on a
+/// window, renderer, or local input and so can be run headless.
 pub struct GameplayPlugin;
 
 impl Plugin for GameplayPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<resources::ResourceAmount>()
-            .add_plugins(in_game_menu::InGameMenuPlugin)
-            .add_plugins(selection::DragSelectionPlugin)
+            .register_type::<PlayerResources>()
+            .register_type::<PlayerSupply>()
+            .register_type::<GameFrame>()
             .add_plugins(create_construct::plugin)
+            .add_plugins(game_commands::plugin)
             .add_plugins(constructs::plugin)
+            .add_plugins(health::plugin)
             .add_plugins(players::plugin)
-            .register_type::<ConstructGizmos>()
+            .add_plugins(player_selection::plugin)
+            .add_plugins(training::plugin)
+            .add_plugins(triggers::plugin)
             .init_resource::<GameMode>()
+            .init_resource::<GameFrame>()
+            .configure_sets(
+                FixedPreUpdate,
+                SimulationSet.run_if(in_state(AppState::InGame).and_then(turn_ready)),
+            )
+            .configure_sets(
+                FixedUpdate,
+                SimulationSet.run_if(in_state(AppState::InGame).and_then(turn_ready)),
+            )
+            .configure_sets(
+                FixedPostUpdate,
+                SimulationSet.run_if(in_state(AppState::InGame).and_then(turn_ready)),
+            )
+            .add_systems(OnEnter(AppState::PreGame), init_random)
+            .add_systems(Update, proceed_to_game.run_if(in_state(AppState::PreGame)))
+            .add_systems(
+                OnEnter(AppState::InGame),
+                (
+                    reset_game_frame,
+                    init_players,
+                    init_game,
+                    triggers::init_triggers.run_if(resource_equals(GameMode::UseMapSettings)),
+                )
+                    .chain(),
+            )
+            .add_systems(FixedUpdate, exec_iscripts.in_set(SimulationSet))
+            .add_systems(
+                FixedPostUpdate,
+                (update_player_supply, advance_game_frame).in_set(SimulationSet),
+            )
+            .add_systems(Update, apply_facing_to_images);
+    }
+}
+
+/// Plugin containing the parts of gameplay that deal with the local user, such as input handling,
+/// menus, and debug visualizations. This is not needed to run the game simulation.
+pub struct GameplayInterfacePlugin;
+
+impl Plugin for GameplayInterfacePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(in_game_menu::InGameMenuPlugin)
+            .add_plugins(alerts::plugin)
+            .add_plugins(options_menu::OptionsMenuPlugin)
+            .add_plugins(triggers::TriggerMessagesPlugin)
+            .add_plugins(selection::DragSelectionPlugin)
+            .add_plugins(status_bars::plugin)
+            .add_plugins(minimap::MinimapPlugin)
+            .add_plugins(command_card::plugin)
+            .add_plugins(console::ConsolePlugin)
+            .add_plugins(resource_hud::ResourceHudPlugin)
+            .add_plugins(sounds::plugin)
+            .add_plugins(speech::plugin)
+            .register_type::<ConstructGizmos>()
             .insert_gizmo_config(
                 ConstructGizmos::default(),
                 GizmoConfig {
@@ -98,14 +191,6 @@ impl Plugin for GameplayPlugin {
                     ..default()
                 },
             )
-            .add_systems(OnEnter(AppState::PreGame), init_random)
-            .add_systems(Update, proceed_to_game.run_if(in_state(AppState::PreGame)))
-            .add_systems(OnEnter(AppState::InGame), (init_players, init_game).chain())
-            .add_systems(
-                FixedUpdate,
-                exec_iscripts.run_if(in_state(AppState::InGame)),
-            )
-            .add_systems(Update, apply_facing_to_images)
             .add_systems(
                 PostUpdate,
                 (show_construct_gizmos)
@@ -117,14 +202,25 @@ impl Plugin for GameplayPlugin {
     }
 }
 
-fn init_random(mut lcg: ResMut<LockedLcgRand>) {
-    let seed = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("System clock set incorrectly")
-        .as_millis() as u32;
+fn init_random(mut lcg: ResMut<LockedLcgRand>, net_session: Option<Res<LockstepSession>>) {
+    let seed = match net_session {
+        Some(session) => session.seed(),
+        None => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("System clock set incorrectly")
+            .as_millis() as u32,
+    };
     lcg.i_know_what_im_doing_please_reseed(seed);
 }
 
+fn reset_game_frame(mut game_frame: ResMut<GameFrame>) {
+    game_frame.0 = 0;
+}
+
+fn advance_game_frame(mut game_frame: ResMut<GameFrame>) {
+    game_frame.0 += 1;
+}
+
 fn proceed_to_game(
     mut next_state: ResMut<NextState<AppState>>,
     game_data: Option<Res<BwGameData>>,
@@ -135,28 +231,53 @@ fn proceed_to_game(
     }
 }
 
-fn init_players(mut commands: Commands, mut player_entities: ResMut<PlayerEntities>) {
-    // TODO(tec27): Init this based on lobby structure or whatever
+fn init_players(
+    mut commands: Commands,
+    mut player_entities: ResMut<PlayerEntities>,
+    lobby: Option<Res<Lobby>>,
+    game_mode: Res<GameMode>,
+    net_session: Option<Res<LockstepSession>>,
+    current_map: Res<CurrentMap>,
+    map_assets: Res<Assets<MapAsset>>,
+    mut lcg: ResMut<LockedLcgRand>,
+) {
+    let lobby = match lobby {
+        Some(lobby) => lobby.clone(),
+        None => {
+            let map = map_assets
+                .get(&current_map.handle)
+                .expect("Current map not loaded");
+            let lobby = default_lobby(map, &game_mode, net_session.as_deref());
+            commands.insert_resource(lobby.clone());
+            lobby
+        }
+    };
+
     player_entities.clear();
-    let player_id = commands
-        .spawn((
-            Player { race: Race::Terran },
-            ControlledPlayer {},
-            SelectedEntities::default(),
-            InGameOnly,
-        ))
-        .id();
-    player_entities.set(1, player_id);
-    let enemy_id = commands
-        .spawn((
+    let races = lobby.resolve_races(lcg.i_know_what_im_doing_please_give_access());
+    for (i, slot) in lobby.slots.iter().enumerate() {
+        let Some(race) = races[i] else {
+            continue;
+        };
+
+        let mut player = commands.spawn((
             Player {
-                race: Race::Protoss,
+                race,
+                color: slot.color,
+                force: slot.force,
             },
+            PlayerResources::STARTING,
+            PlayerSupply::default(),
             SelectedEntities::default(),
+            ControlGroups::default(),
             InGameOnly,
-        ))
-        .id();
-    player_entities.set(0, enemy_id);
+            Name::new(format!("Player {}", i + 1)),
+        ));
+        if lobby.local_slot == Some(i as u8) {
+            player.insert(ControlledPlayer);
+        }
+        player_entities.set(i as u8, player.id());
+    }
 }
 
 fn init_game(
@@ -174,7 +295,7 @@ fn init_game(
 
     let is_ums = match *game_mode {
         GameMode::Melee => false,
-        GameMode::MapView => true,
+        GameMode::UseMapSettings | GameMode::MapView => true,
     };
 
     create_map_sprites(is_ums, &mut commands, map, &mut creation_events);
@@ -201,8 +322,12 @@ fn init_game(
             &mut creation_events,
             &start_locations,
         ),
+        // Everything in UMS games comes from the map's placed units and triggers
+        GameMode::UseMapSettings => {}
         GameMode::MapView => init_map_view(&mut commands, &start_locations),
     }
+
+    commands.insert_resource(StartLocations(start_locations));
 }
 
 fn create_map_sprites(
@@ -237,7 +362,9 @@ fn create_map_sprites(
                 .spawn((
                     SpatialBundle::default(),
                     Position::new(sprite.x.into(), sprite.y.into()),
-                    YSort(2.0),
+                    // TODO(tec27): Figure out what elevation BW uses for these, ground level seems
+                    // right for most of them
+                    DrawOrder::new(4),
                     Name::new(format!("PureSprite #{i}")),
                     InGameOnly,
                 ))
@@ -346,6 +473,7 @@ fn create_placed_units(
                     .energy_percent
                     .map(|energy| FixedPoint::from_bits(energy as i32)),
                 resource_amount: unit.resource_amount,
+                trained_by: None,
             });
         }
     }
@@ -407,7 +535,7 @@ fn init_map_view(commands: &mut Commands<'_, '_>, start_locations: &HashMap<u8,
             .spawn((
                 SpatialBundle::default(),
                 *position,
-                YSort(2.0),
+                DrawOrder::new(ConstructTypeId::StartLocation.def().elevation_level),
                 Name::new(format!("Start Location {player}")),
                 InGameOnly,
             ))
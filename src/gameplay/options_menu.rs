@@ -0,0 +1,721 @@
+//! The options screens of the in-game menu, which edit the [GameSettings] (including hotkeys) and
+//! save them to the settings file.
+
+use bevy::{ecs::system::EntityCommands, prelude::*};
+
+use crate::{
+    ecs::despawn_all,
+    fonts::FONT_BODY,
+    input::{key_name, HotkeyLayout, InputAction},
+    settings::{
+        AssetPack, AssetQuality, AudioQuality, GameSettings, NeobroodWindowMode, SaveSettingsEvent,
+        SettingsFile,
+    },
+};
+
+use super::in_game_menu::{InGameMenuState, OnInGameMenu, NORMAL_BUTTON};
+
+pub struct OptionsMenuPlugin;
+
+impl Plugin for OptionsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RebindingAction>()
+            .add_systems(OnEnter(InGameMenuState::Disabled), clear_pending_settings)
+            .add_systems(OnEnter(InGameMenuState::General), clear_pending_settings)
+            .add_systems(OnEnter(InGameMenuState::Options), setup_options)
+            .add_systems(
+                OnExit(InGameMenuState::Options),
+                despawn_all::<OnOptionsMenu>,
+            )
+            .add_systems(OnEnter(InGameMenuState::Hotkeys), setup_hotkeys)
+            .add_systems(
+                OnExit(InGameMenuState::Hotkeys),
+                (despawn_all::<OnHotkeysMenu>, stop_rebinding),
+            )
+            .add_systems(
+                Update,
+                (options_actions, update_option_labels)
+                    .chain()
+                    .run_if(in_state(InGameMenuState::Options)),
+            )
+            .add_systems(
+                Update,
+                (capture_rebinding, hotkeys_actions, update_hotkey_labels)
+                    .chain()
+                    .run_if(in_state(InGameMenuState::Hotkeys)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct OnOptionsMenu;
+
+#[derive(Component)]
+struct OnHotkeysMenu;
+
+/// The settings being edited in the options menu, which haven't been saved yet.
+#[derive(Resource, Debug)]
+struct PendingSettings(GameSettings);
+
+/// The action that the next key press will be bound to, if one is being rebound.
+#[derive(Resource, Debug, Default)]
+pub(super) struct RebindingAction(pub Option<InputAction>);
+
+const TEXT_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+const NOTICE_COLOR: Color = Color::srgb(0.9, 0.8, 0.3);
+const CONFLICT_COLOR: Color = Color::srgb(0.9, 0.3, 0.3);
+
+/// The resolutions that can be chosen for the window size.
+const WINDOW_SIZES: [(u32, u32); 5] = [
+    (1280, 960),
+    (1600, 1200),
+    (1920, 1080),
+    (2560, 1440),
+    (3840, 2160),
+];
+const VOLUME_STEP: f32 = 0.1;
+const SCROLL_SPEED_STEP: f32 = 500.0;
+const MIN_SCROLL_SPEED: f32 = 1000.0;
+const MAX_SCROLL_SPEED: f32 = 6000.0;
+
+/// A setting that can be changed in the options menu. Clicking on a setting cycles through its
+/// possible values.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq)]
+enum OptionsSetting {
+    AssetQuality,
+    AssetPack,
+    HdLighting,
+    WindowMode,
+    WindowSize,
+    StatusBars,
+    GlobalVolume,
+    MusicVolume,
+    SoundEffectsVolume,
+    AudioQuality,
+    EdgeScrollSpeed,
+    KeyboardScrollSpeed,
+    HotkeyLayout,
+}
+
+impl OptionsSetting {
+    const ALL: [OptionsSetting; 13] = [
+        OptionsSetting::AssetQuality,
+        OptionsSetting::AssetPack,
+        OptionsSetting::HdLighting,
+        OptionsSetting::WindowMode,
+        OptionsSetting::WindowSize,
+        OptionsSetting::StatusBars,
+        OptionsSetting::GlobalVolume,
+        OptionsSetting::MusicVolume,
+        OptionsSetting::SoundEffectsVolume,
+        OptionsSetting::AudioQuality,
+        OptionsSetting::EdgeScrollSpeed,
+        OptionsSetting::KeyboardScrollSpeed,
+        OptionsSetting::HotkeyLayout,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            OptionsSetting::AssetQuality => "graphics quality",
+            OptionsSetting::AssetPack => "graphics pack",
+            OptionsSetting::HdLighting => "hd lighting",
+            OptionsSetting::WindowMode => "window mode",
+            OptionsSetting::WindowSize => "window size",
+            OptionsSetting::StatusBars => "status bars",
+            OptionsSetting::GlobalVolume => "master volume",
+            OptionsSetting::MusicVolume => "music volume",
+            OptionsSetting::SoundEffectsVolume => "sound volume",
+            OptionsSetting::AudioQuality => "audio quality",
+            OptionsSetting::EdgeScrollSpeed => "mouse scroll speed",
+            OptionsSetting::KeyboardScrollSpeed => "key scroll speed",
+            OptionsSetting::HotkeyLayout => "hotkey layout",
+        }
+    }
+
+    fn value(&self, settings: &GameSettings) -> String {
+        let on_off = |value: bool| if value { "on" } else { "off" }.to_string();
+        let percent = |value: f32| format!("{:.0}%", value * 100.0);
+        match self {
+            OptionsSetting::AssetQuality => match settings.asset_quality {
+                AssetQuality::Standard => "standard",
+                AssetQuality::High => "high",
+                AssetQuality::ExtraHigh => "extra high",
+            }
+            .into(),
+            OptionsSetting::AssetPack => match settings.asset_pack {
+                AssetPack::Standard => "standard",
+                AssetPack::Carbot => "carbot",
+            }
+            .into(),
+            OptionsSetting::HdLighting => on_off(settings.hd_lighting),
+            OptionsSetting::WindowMode => match settings.window_mode {
+                NeobroodWindowMode::Windowed => "windowed",
+                NeobroodWindowMode::BorderlessFullscreen => "borderless",
+                NeobroodWindowMode::ExclusiveFullscreen => "fullscreen",
+            }
+            .into(),
+            OptionsSetting::WindowSize => match settings.window_size {
+                Some((width, height)) => format!("{width}x{height}"),
+                None => "default".into(),
+            },
+            OptionsSetting::StatusBars => if settings.always_show_status_bars {
+                "always"
+            } else {
+                "selected"
+            }
+            .into(),
+            OptionsSetting::GlobalVolume => percent(settings.volumes.global),
+            OptionsSetting::MusicVolume => percent(settings.volumes.music),
+            OptionsSetting::SoundEffectsVolume => percent(settings.volumes.sound_effects),
+            OptionsSetting::AudioQuality => match settings.audio_quality {
+                AudioQuality::Classic => "classic",
+                AudioQuality::Remastered => "remastered",
+            }
+            .into(),
+            OptionsSetting::EdgeScrollSpeed => format!("{:.0}", settings.scroll_speeds.edge),
+            OptionsSetting::KeyboardScrollSpeed => {
+                format!("{:.0}", settings.scroll_speeds.keyboard)
+            }
+            OptionsSetting::HotkeyLayout => match settings.keybindings.layout {
+                HotkeyLayout::Classic => "classic",
+                HotkeyLayout::Grid => "grid",
+            }
+            .into(),
+        }
+    }
+
+    /// Changes this setting in `settings` to its next possible value.
+    fn cycle(&self, settings: &mut GameSettings) {
+        match self {
+            OptionsSetting::AssetQuality => {
+                settings.asset_quality = match settings.asset_quality {
+                    AssetQuality::Standard => AssetQuality::High,
+                    AssetQuality::High => AssetQuality::ExtraHigh,
+                    AssetQuality::ExtraHigh => AssetQuality::Standard,
+                }
+            }
+            OptionsSetting::AssetPack => {
+                settings.asset_pack = match settings.asset_pack {
+                    AssetPack::Standard => AssetPack::Carbot,
+                    AssetPack::Carbot => AssetPack::Standard,
+                }
+            }
+            OptionsSetting::HdLighting => settings.hd_lighting ^= true,
+            OptionsSetting::WindowMode => {
+                settings.window_mode = match settings.window_mode {
+                    NeobroodWindowMode::Windowed => NeobroodWindowMode::BorderlessFullscreen,
+                    NeobroodWindowMode::BorderlessFullscreen => {
+                        NeobroodWindowMode::ExclusiveFullscreen
+                    }
+                    NeobroodWindowMode::ExclusiveFullscreen => NeobroodWindowMode::Windowed,
+                }
+            }
+            OptionsSetting::WindowSize => {
+                let next = match settings.window_size {
+                    Some(size) => WINDOW_SIZES
+                        .iter()
+                        .position(|&s| s == size)
+                        .map_or(0, |i| i + 1),
+                    None => 0,
+                };
+                settings.window_size = WINDOW_SIZES.get(next).copied();
+            }
+            OptionsSetting::StatusBars => settings.always_show_status_bars ^= true,
+            OptionsSetting::GlobalVolume => {
+                settings.volumes.global = cycle_step(settings.volumes.global, VOLUME_STEP, 0.0, 1.0)
+            }
+            OptionsSetting::MusicVolume => {
+                settings.volumes.music = cycle_step(settings.volumes.music, VOLUME_STEP, 0.0, 1.0)
+            }
+            OptionsSetting::SoundEffectsVolume => {
+                settings.volumes.sound_effects =
+                    cycle_step(settings.volumes.sound_effects, VOLUME_STEP, 0.0, 1.0)
+            }
+            OptionsSetting::AudioQuality => {
+                settings.audio_quality = match settings.audio_quality {
+                    AudioQuality::Classic => AudioQuality::Remastered,
+                    AudioQuality::Remastered => AudioQuality::Classic,
+                }
+            }
+            OptionsSetting::EdgeScrollSpeed => {
+                settings.scroll_speeds.edge = cycle_step(
+                    settings.scroll_speeds.edge,
+                    SCROLL_SPEED_STEP,
+                    MIN_SCROLL_SPEED,
+                    MAX_SCROLL_SPEED,
+                )
+            }
+            OptionsSetting::KeyboardScrollSpeed => {
+                settings.scroll_speeds.keyboard = cycle_step(
+                    settings.scroll_speeds.keyboard,
+                    SCROLL_SPEED_STEP,
+                    MIN_SCROLL_SPEED,
+                    MAX_SCROLL_SPEED,
+                )
+            }
+            OptionsSetting::HotkeyLayout => {
+                settings.keybindings.layout = match settings.keybindings.layout {
+                    HotkeyLayout::Classic => HotkeyLayout::Grid,
+                    HotkeyLayout::Grid => HotkeyLayout::Classic,
+                }
+            }
+        }
+    }
+
+    fn text(&self, settings: &GameSettings) -> String {
+        format!("{}: {}", self.label(), self.value(settings))
+    }
+}
+
+/// Returns the next value after `value` when moving in increments of `step`, wrapping back around
+/// to `min` once `max` has been passed.
+fn cycle_step(value: f32, step: f32, min: f32, max: f32) -> f32 {
+    let next = (value / step).round() * step + step;
+    if next > max + step / 2.0 {
+        min
+    } else {
+        next.max(min)
+    }
+}
+
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq)]
+enum OptionsMenuAction {
+    Hotkeys,
+    Save,
+    Back,
+}
+
+/// Text that shows the result of saving the settings.
+#[derive(Component)]
+struct OptionsNotice;
+
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq)]
+enum HotkeysMenuAction {
+    Reset,
+    Back,
+}
+
+/// A button that rebinds an action when clicked.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq)]
+struct HotkeyButton(InputAction);
+
+/// Text that shows the current keybinding conflicts.
+#[derive(Component)]
+struct HotkeyConflicts;
+
+fn clear_pending_settings(mut commands: Commands) {
+    commands.remove_resource::<PendingSettings>();
+}
+
+/// Spawns the root of a menu screen, with a title at the top.
+fn spawn_menu_root<'a>(
+    commands: &'a mut Commands,
+    marker: impl Bundle,
+    title: &str,
+    font: &Handle<Font>,
+) -> EntityCommands<'a> {
+    let mut root = commands.spawn((
+        NodeBundle {
+            background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            ..default()
+        },
+        OnInGameMenu,
+        marker,
+    ));
+    root.with_children(|parent| {
+        parent.spawn(TextBundle::from_section(
+            title,
+            TextStyle {
+                font: font.clone(),
+                font_size: 40.0,
+                color: TEXT_COLOR,
+            },
+        ));
+    });
+    root
+}
+
+/// Spawns a button containing a single text section.
+fn spawn_text_button(
+    parent: &mut ChildBuilder,
+    marker: impl Bundle,
+    width: f32,
+    text: impl Into<String>,
+    style: &TextStyle,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(width),
+                    padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(NORMAL_BUTTON),
+                ..default()
+            },
+            marker,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(text, style.clone()));
+        });
+}
+
+fn button_row() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            column_gap: Val::Px(8.0),
+            margin: UiRect::top(Val::Px(16.0)),
+            ..default()
+        },
+        ..default()
+    }
+}
+
+fn setup_options(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    pending: Option<Res<PendingSettings>>,
+    settings: Res<GameSettings>,
+    settings_file: Option<Res<SettingsFile>>,
+) {
+    // Start from what is saved in the settings file (which may include changes that need a restart
+    // and so haven't been applied yet), unless we're coming back from the hotkeys screen
+    let pending = match pending {
+        Some(pending) => pending.0.clone(),
+        None => {
+            let pending = settings_file
+                .map(|f| f.saved.clone())
+                .unwrap_or_else(|| settings.clone());
+            commands.insert_resource(PendingSettings(pending.clone()));
+            pending
+        }
+    };
+
+    let font = asset_server.load(FONT_BODY);
+    let text_style = TextStyle {
+        font: font.clone(),
+        font_size: 24.0,
+        color: TEXT_COLOR,
+    };
+
+    spawn_menu_root(&mut commands, OnOptionsMenu, "options", &font).with_children(|parent| {
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    display: Display::Grid,
+                    grid_template_columns: RepeatedGridTrack::px(2, 400.0),
+                    row_gap: Val::Px(8.0),
+                    column_gap: Val::Px(8.0),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|parent| {
+                for setting in OptionsSetting::ALL {
+                    spawn_text_button(parent, setting, 400.0, setting.text(&pending), &text_style);
+                }
+            });
+
+        parent.spawn((
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    color: NOTICE_COLOR,
+                    ..text_style.clone()
+                },
+            ),
+            OptionsNotice,
+        ));
+
+        parent.spawn(button_row()).with_children(|parent| {
+            for (action, text) in [
+                (OptionsMenuAction::Hotkeys, "hotkeys"),
+                (OptionsMenuAction::Save, "save"),
+                (OptionsMenuAction::Back, "back"),
+            ] {
+                spawn_text_button(parent, action, 160.0, text, &text_style);
+            }
+        });
+    });
+}
+
+fn options_actions(
+    settings_buttons: Query<(&Interaction, &OptionsSetting), Changed<Interaction>>,
+    action_buttons: Query<(&Interaction, &OptionsMenuAction), Changed<Interaction>>,
+    mut pending: ResMut<PendingSettings>,
+    mut settings: ResMut<GameSettings>,
+    settings_file: Option<Res<SettingsFile>>,
+    mut save_writer: EventWriter<SaveSettingsEvent>,
+    mut notice: Query<&mut Text, With<OptionsNotice>>,
+    mut next_menu_state: ResMut<NextState<InGameMenuState>>,
+) {
+    for (interaction, setting) in settings_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            setting.cycle(&mut pending.0);
+        }
+    }
+
+    for (interaction, action) in action_buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match action {
+            OptionsMenuAction::Hotkeys => next_menu_state.set(InGameMenuState::Hotkeys),
+            OptionsMenuAction::Back => next_menu_state.set(InGameMenuState::General),
+            OptionsMenuAction::Save => {
+                let message = if settings_file.is_none() {
+                    "settings can't be saved, changes will only last until the game is closed"
+                } else if settings.needs_restart_for(&pending.0) {
+                    "settings saved, restart the game to apply quality changes"
+                } else {
+                    "settings saved"
+                };
+                if settings_file.is_some() {
+                    save_writer.send(SaveSettingsEvent(pending.0.clone()));
+                }
+                settings.apply_live(&pending.0);
+
+                if let Ok(mut text) = notice.get_single_mut() {
+                    text.sections[0].value = message.into();
+                }
+            }
+        }
+    }
+}
+
+fn update_option_labels(
+    pending: Res<PendingSettings>,
+    buttons: Query<(&OptionsSetting, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    if !pending.is_changed() {
+        return;
+    }
+    for (setting, children) in buttons.iter() {
+        let mut iter = texts.iter_many_mut(children);
+        while let Some(mut text) = iter.fetch_next() {
+            text.sections[0].value = setting.text(&pending.0);
+        }
+    }
+}
+
+fn hotkey_text(
+    action: InputAction,
+    settings: &GameSettings,
+    rebinding: &RebindingAction,
+) -> String {
+    let key = if rebinding.0 == Some(action) {
+        "press a key (esc to cancel, delete to unbind)".into()
+    } else {
+        settings
+            .keybindings
+            .key_for(action)
+            .map(key_name)
+            .unwrap_or_else(|| "unbound".into())
+    };
+    format!("{}: {}", action.name(), key)
+}
+
+fn conflicts_text(settings: &GameSettings) -> String {
+    settings
+        .keybindings
+        .conflicts()
+        .iter()
+        .map(|c| {
+            format!(
+                "{} is bound to both {} and {}",
+                key_name(c.key),
+                c.first.name(),
+                c.second.name()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn setup_hotkeys(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    pending: Res<PendingSettings>,
+    rebinding: Res<RebindingAction>,
+) {
+    let font = asset_server.load(FONT_BODY);
+    let text_style = TextStyle {
+        font: font.clone(),
+        font_size: 16.0,
+        color: TEXT_COLOR,
+    };
+
+    spawn_menu_root(&mut commands, OnHotkeysMenu, "hotkeys", &font).with_children(|parent| {
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    display: Display::Grid,
+                    grid_template_columns: RepeatedGridTrack::px(3, 320.0),
+                    row_gap: Val::Px(4.0),
+                    column_gap: Val::Px(4.0),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|parent| {
+                for action in InputAction::all(pending.0.keybindings.layout) {
+                    spawn_text_button(
+                        parent,
+                        HotkeyButton(action),
+                        320.0,
+                        hotkey_text(action, &pending.0, &rebinding),
+                        &text_style,
+                    );
+                }
+            });
+
+        parent.spawn((
+            TextBundle::from_section(
+                conflicts_text(&pending.0),
+                TextStyle {
+                    color: CONFLICT_COLOR,
+                    ..text_style.clone()
+                },
+            ),
+            HotkeyConflicts,
+        ));
+
+        let button_style = TextStyle {
+            font_size: 24.0,
+            ..text_style.clone()
+        };
+        parent.spawn(button_row()).with_children(|parent| {
+            spawn_text_button(
+                parent,
+                HotkeysMenuAction::Reset,
+                240.0,
+                "reset to defaults",
+                &button_style,
+            );
+            spawn_text_button(
+                parent,
+                HotkeysMenuAction::Back,
+                160.0,
+                "back",
+                &button_style,
+            );
+        });
+    });
+}
+
+fn stop_rebinding(mut rebinding: ResMut<RebindingAction>) {
+    rebinding.0 = None;
+}
+
+/// Binds the action being rebound to the next key that is pressed.
+fn capture_rebinding(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut rebinding: ResMut<RebindingAction>,
+    mut pending: ResMut<PendingSettings>,
+) {
+    let Some(action) = rebinding.0 else {
+        return;
+    };
+    let Some(&key) = keys.get_just_pressed().next() else {
+        return;
+    };
+
+    match key {
+        KeyCode::Escape => {}
+        KeyCode::Delete => pending.0.keybindings.bind(action, None),
+        key => pending.0.keybindings.bind(action, Some(key)),
+    }
+    rebinding.0 = None;
+}
+
+fn hotkeys_actions(
+    hotkey_buttons: Query<(&Interaction, &HotkeyButton), Changed<Interaction>>,
+    action_buttons: Query<(&Interaction, &HotkeysMenuAction), Changed<Interaction>>,
+    mut rebinding: ResMut<RebindingAction>,
+    mut pending: ResMut<PendingSettings>,
+    mut next_menu_state: ResMut<NextState<InGameMenuState>>,
+) {
+    for (interaction, button) in hotkey_buttons.iter() {
+        if *interaction == Interaction::Pressed {
+            rebinding.0 = Some(button.0);
+        }
+    }
+
+    for (interaction, action) in action_buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match action {
+            HotkeysMenuAction::Reset => pending.0.keybindings.custom.clear(),
+            HotkeysMenuAction::Back => next_menu_state.set(InGameMenuState::Options),
+        }
+    }
+}
+
+fn update_hotkey_labels(
+    pending: Res<PendingSettings>,
+    rebinding: Res<RebindingAction>,
+    buttons: Query<(&HotkeyButton, &Children)>,
+    mut texts: Query<&mut Text, Without<HotkeyConflicts>>,
+    mut conflicts: Query<&mut Text, With<HotkeyConflicts>>,
+) {
+    if !pending.is_changed() && !rebinding.is_changed() {
+        return;
+    }
+    for (button, children) in buttons.iter() {
+        let mut iter = texts.iter_many_mut(children);
+        while let Some(mut text) = iter.fetch_next() {
+            text.sections[0].value = hotkey_text(button.0, &pending.0, &rebinding);
+        }
+    }
+    if let Ok(mut text) = conflicts.get_single_mut() {
+        text.sections[0].value = conflicts_text(&pending.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stepping() {
+        assert_eq!(cycle_step(0.5, 0.1, 0.0, 1.0), 0.6);
+        assert_eq!(cycle_step(1.0, 0.1, 0.0, 1.0), 0.0);
+        assert_eq!(cycle_step(0.95, 0.1, 0.0, 1.0), 0.0);
+        assert_eq!(cycle_step(3000.0, 500.0, 1000.0, 6000.0), 3500.0);
+        assert_eq!(cycle_step(6000.0, 500.0, 1000.0, 6000.0), 1000.0);
+        assert_eq!(cycle_step(0.0, 500.0, 1000.0, 6000.0), 1000.0);
+    }
+
+    #[test]
+    fn cycling_settings() {
+        let mut settings = GameSettings::default();
+        let setting = OptionsSetting::WindowSize;
+        assert_eq!(setting.text(&settings), "window size: default");
+        setting.cycle(&mut settings);
+        assert_eq!(settings.window_size, Some((1280, 960)));
+        for _ in 0..WINDOW_SIZES.len() {
+            setting.cycle(&mut settings);
+        }
+        assert_eq!(settings.window_size, None);
+
+        let setting = OptionsSetting::MusicVolume;
+        assert_eq!(setting.text(&settings), "music volume: 100%");
+        setting.cycle(&mut settings);
+        assert_eq!(setting.text(&settings), "music volume: 0%");
+        setting.cycle(&mut settings);
+        assert_eq!(setting.text(&settings), "music volume: 10%");
+    }
+}
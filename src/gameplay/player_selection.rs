@@ -0,0 +1,233 @@
+//! The selections and control groups of each player. These are part of the synced game state and
+//! only change through [GameCommand]s, so that every client (and replay) knows what each player had
+//! selected when they issued an order.
+
+use bevy::prelude::*;
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+use smallvec::SmallVec;
+
+use crate::{gamedata::ConstructTypeId, net::GameCommand};
+
+use super::{
+    constructs::{ConstructId, OwnedConstruct},
+    game_commands::{ExecGameCommand, ExecutingCommand},
+    players::PlayerEntities,
+    selection::SelectedEntities,
+};
+
+/// The most constructs that can be selected (or be in a control group) at once.
+pub const MAX_SELECTION: usize = 12;
+/// The number of control groups each player has (one for each number key).
+pub const NUM_CONTROL_GROUPS: usize = 10;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(ExecGameCommand, apply_selection_command);
+}
+
+/// How a control group is being changed (or used).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, TryFromPrimitive, IntoPrimitive)]
+#[repr(u8)]
+pub enum ControlGroupAction {
+    /// Replaces the group with the current selection.
+    Assign = 0,
+    /// Replaces the current selection with the group.
+    Recall = 1,
+    /// Adds the current selection to the group.
+    Add = 2,
+}
+
+/// Component that stores the control groups for a [Player](super::players::Player).
+#[derive(Component, Debug, Default)]
+pub struct ControlGroups(pub [SmallVec<[Entity; MAX_SELECTION]>; NUM_CONTROL_GROUPS]);
+
+/// The information about a construct needed to determine if it can be part of a selection.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct SelectionCandidate {
+    entity: Entity,
+    /// Whether the construct is owned by the player doing the selecting.
+    owned: bool,
+    is_building: bool,
+}
+
+/// Returns the constructs out of `candidates` that can actually be selected together, following
+/// BW's rules: multiple constructs can only be selected if they are all units owned by the
+/// selecting player (up to [MAX_SELECTION] of them). Otherwise, only a single construct can be
+/// selected.
+fn valid_selection(
+    candidates: impl IntoIterator<Item = SelectionCandidate>,
+) -> SmallVec<[Entity; MAX_SELECTION]> {
+    let mut unique = Vec::<SelectionCandidate>::new();
+    for candidate in candidates {
+        if !unique.iter().any(|c| c.entity == candidate.entity) {
+            unique.push(candidate);
+        }
+    }
+
+    let mut multi_selectable = unique
+        .iter()
+        .filter(|c| c.owned && !c.is_building)
+        .map(|c| c.entity)
+        .peekable();
+    if unique.len() > 1 && multi_selectable.peek().is_some() {
+        multi_selectable.take(MAX_SELECTION).collect()
+    } else {
+        unique.first().map(|c| c.entity).into_iter().collect()
+    }
+}
+
+/// Executes a selection or control group command.
+fn apply_selection_command(
+    executing: Res<ExecutingCommand>,
+    player_entities: Res<PlayerEntities>,
+    mut players: Query<(&mut SelectedEntities, &mut ControlGroups)>,
+    constructs: Query<(
+        Entity,
+        &ConstructId,
+        &ConstructTypeId,
+        Option<&OwnedConstruct>,
+    )>,
+) {
+    let player = executing.player;
+    let Some(Ok((mut selected, mut groups))) =
+        player_entities.get(player).map(|e| players.get_mut(e))
+    else {
+        return;
+    };
+
+    let candidate = |entity: Entity| {
+        constructs
+            .get(entity)
+            .ok()
+            .map(|(entity, _, construct_type, owner)| SelectionCandidate {
+                entity,
+                owned: owner.is_some_and(|o| o.0 == player),
+                is_building: construct_type.is_building(),
+            })
+    };
+    let resolve = |ids: &[ConstructId]| {
+        ids.iter()
+            .filter_map(|id| constructs.iter().find(|(_, c, _, _)| *c == id))
+            .map(|(entity, _, _, _)| entity)
+            .collect::<Vec<_>>()
+    };
+
+    let new_selection = match &executing.command {
+        GameCommand::Select(ids) => valid_selection(resolve(ids).into_iter().filter_map(candidate)),
+        GameCommand::SelectAdd(ids) => valid_selection(
+            selected
+                .0
+                .iter()
+                .copied()
+                .chain(resolve(ids))
+                .filter_map(candidate),
+        ),
+        GameCommand::SelectRemove(ids) => {
+            let removed = resolve(ids);
+            valid_selection(
+                selected
+                    .0
+                    .iter()
+                    .copied()
+                    .filter(|e| !removed.contains(e))
+                    .filter_map(candidate),
+            )
+        }
+        GameCommand::ControlGroup { action, group } => {
+            let Some(group) = groups.0.get_mut(*group as usize) else {
+                return;
+            };
+            // Only the player's own constructs can be put into their control groups
+            let own_selection = selected
+                .0
+                .iter()
+                .copied()
+                .filter_map(candidate)
+                .filter(|c| c.owned);
+            match action {
+                ControlGroupAction::Assign => {
+                    *group = valid_selection(own_selection);
+                    return;
+                }
+                ControlGroupAction::Add => {
+                    *group = valid_selection(
+                        group
+                            .iter()
+                            .copied()
+                            .filter_map(candidate)
+                            .chain(own_selection),
+                    );
+                    return;
+                }
+                ControlGroupAction::Recall => {
+                    valid_selection(group.iter().copied().filter_map(candidate))
+                }
+            }
+        }
+        // TODO(tec27): Give orders to the selected constructs once they can move/attack
+        GameCommand::Leave
+        | GameCommand::RightClick { .. }
+        | GameCommand::TargetedOrder { .. }
+        | GameCommand::Stop { .. }
+        | GameCommand::HoldPosition { .. }
+        | GameCommand::ReturnCargo { .. }
+        | GameCommand::Burrow => return,
+        // Handled by the training systems
+        GameCommand::Train(_) => return,
+    };
+
+    if selected.0 != new_selection {
+        selected.0 = new_selection;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(index: u32, owned: bool, is_building: bool) -> SelectionCandidate {
+        SelectionCandidate {
+            entity: Entity::from_raw(index),
+            owned,
+            is_building,
+        }
+    }
+
+    fn entities(indices: &[u32]) -> SmallVec<[Entity; MAX_SELECTION]> {
+        indices.iter().map(|&i| Entity::from_raw(i)).collect()
+    }
+
+    #[test]
+    fn selection_rules() {
+        // Own units can be selected together, up to the max
+        assert_eq!(
+            valid_selection((0..20).map(|i| candidate(i, true, false))),
+            entities(&(0..12).collect::<Vec<_>>())
+        );
+        // Own units are preferred over buildings and other players' constructs
+        assert_eq!(
+            valid_selection([
+                candidate(0, false, false),
+                candidate(1, true, true),
+                candidate(2, true, false),
+                candidate(3, true, false),
+            ]),
+            entities(&[2, 3])
+        );
+        // Only a single building can be selected
+        assert_eq!(
+            valid_selection([candidate(0, true, true), candidate(1, true, true)]),
+            entities(&[0])
+        );
+        // Or a single construct owned by someone else
+        assert_eq!(
+            valid_selection([candidate(0, false, false), candidate(1, false, false)]),
+            entities(&[0])
+        );
+        // Duplicates are ignored
+        assert_eq!(
+            valid_selection([candidate(0, true, false), candidate(0, true, false)]),
+            entities(&[0])
+        );
+        assert_eq!(valid_selection([]), entities(&[]));
+    }
+}
@@ -1,9 +1,10 @@
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
 
 use crate::races::Race;
 
 pub fn plugin(app: &mut App) {
     app.register_type::<Player>()
+        .register_type::<PlayerColor>()
         .register_type::<ControlledPlayer>()
         .register_type::<PlayerNumber>()
         .init_resource::<PlayerEntities>()
@@ -20,6 +21,90 @@ pub fn plugin(app: &mut App) {
 #[derive(Component, Debug, Default, Reflect)]
 pub struct Player {
     pub race: Race,
+    pub color: PlayerColor,
+    /// The index of the force (team) this player belongs to.
+    pub force: u8,
+}
+
+/// The colors that can be assigned to players. These match the default colors for each player
+/// slot in BW, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum PlayerColor {
+    #[default]
+    Red,
+    Blue,
+    Teal,
+    Purple,
+    Orange,
+    Brown,
+    White,
+    Yellow,
+    Green,
+    PaleYellow,
+    Tan,
+    Azure,
+}
+
+impl PlayerColor {
+    pub const ALL: [PlayerColor; 12] = [
+        PlayerColor::Red,
+        PlayerColor::Blue,
+        PlayerColor::Teal,
+        PlayerColor::Purple,
+        PlayerColor::Orange,
+        PlayerColor::Brown,
+        PlayerColor::White,
+        PlayerColor::Yellow,
+        PlayerColor::Green,
+        PlayerColor::PaleYellow,
+        PlayerColor::Tan,
+        PlayerColor::Azure,
+    ];
+
+    /// Returns the default color for the specified player number.
+    pub fn for_player(player: u8) -> Self {
+        Self::ALL[player as usize % Self::ALL.len()]
+    }
+
+    /// Returns the next color in the list, wrapping around at the end.
+    pub fn next(&self) -> Self {
+        Self::ALL[(*self as usize + 1) % Self::ALL.len()]
+    }
+
+    /// Returns the color used to represent this player in the UI (e.g. on the minimap).
+    pub fn color(&self) -> Color {
+        match self {
+            PlayerColor::Red => Color::srgb_u8(244, 4, 4),
+            PlayerColor::Blue => Color::srgb_u8(12, 72, 204),
+            PlayerColor::Teal => Color::srgb_u8(44, 180, 148),
+            PlayerColor::Purple => Color::srgb_u8(136, 64, 156),
+            PlayerColor::Orange => Color::srgb_u8(248, 140, 20),
+            PlayerColor::Brown => Color::srgb_u8(112, 48, 20),
+            PlayerColor::White => Color::srgb_u8(204, 224, 208),
+            PlayerColor::Yellow => Color::srgb_u8(252, 252, 56),
+            PlayerColor::Green => Color::srgb_u8(8, 128, 8),
+            PlayerColor::PaleYellow => Color::srgb_u8(252, 252, 124),
+            PlayerColor::Tan => Color::srgb_u8(236, 196, 176),
+            PlayerColor::Azure => Color::srgb_u8(64, 104, 212),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlayerColor::Red => "Red",
+            PlayerColor::Blue => "Blue",
+            PlayerColor::Teal => "Teal",
+            PlayerColor::Purple => "Purple",
+            PlayerColor::Orange => "Orange",
+            PlayerColor::Brown => "Brown",
+            PlayerColor::White => "White",
+            PlayerColor::Yellow => "Yellow",
+            PlayerColor::Green => "Green",
+            PlayerColor::PaleYellow => "Pale Yellow",
+            PlayerColor::Tan => "Tan",
+            PlayerColor::Azure => "Azure",
+        }
+    }
 }
 
 /// Component that is attached to the `Player` entity that is currently utilizing local control
@@ -61,6 +146,76 @@ impl PlayerEntities {
     }
 }
 
+/// [SystemParam] for looking up the color assigned to each player. This is the source of player
+/// colors for anything drawn in-game (team colors, selection circles, the minimap, etc.).
+#[derive(SystemParam)]
+pub struct PlayerColors<'w, 's> {
+    player_entities: Res<'w, PlayerEntities>,
+    players: Query<'w, 's, &'static Player>,
+}
+
+impl PlayerColors<'_, '_> {
+    /// Returns the color assigned to the specified player number, if that player exists.
+    pub fn get(&self, player: u8) -> Option<PlayerColor> {
+        self.player_entities
+            .get(player)
+            .and_then(|e| self.players.get(e).ok())
+            .map(|p| p.color)
+    }
+}
+
+/// How a player relates to the local player, used for things that display differently depending on
+/// who is looking at them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerRelation {
+    Own,
+    Ally,
+    Enemy,
+    /// Not owned by a player (or there is no local player).
+    Neutral,
+}
+
+impl PlayerRelation {
+    /// Returns how `owner` relates to `local`, where each is a (player number, force) pair.
+    pub fn between(local: Option<(u8, u8)>, owner: Option<(u8, u8)>) -> Self {
+        match (local, owner) {
+            (Some((local, _)), Some((owner, _))) if local == owner => PlayerRelation::Own,
+            // TODO(tec27): Handle alliances that change during the game
+            (Some((_, local_force)), Some((_, owner_force))) if local_force == owner_force => {
+                PlayerRelation::Ally
+            }
+            (Some(_), Some(_)) => PlayerRelation::Enemy,
+            _ => PlayerRelation::Neutral,
+        }
+    }
+}
+
+/// [SystemParam] for determining how players relate to the local player.
+#[derive(SystemParam)]
+pub struct PlayerRelations<'w, 's> {
+    player_entities: Res<'w, PlayerEntities>,
+    players: Query<'w, 's, &'static Player>,
+    local_players: Query<'w, 's, (Entity, &'static Player), With<ControlledPlayer>>,
+}
+
+impl PlayerRelations<'_, '_> {
+    /// Returns how the specified player relates to the local player.
+    pub fn relation_to_local(&self, owner: Option<u8>) -> PlayerRelation {
+        let local = self.local_players.get_single().ok().and_then(|(e, p)| {
+            self.player_entities
+                .player_num_for(e)
+                .map(|num| (num, p.force))
+        });
+        let owner = owner.and_then(|num| {
+            self.player_entities
+                .get(num)
+                .and_then(|e| self.players.get(e).ok())
+                .map(|p| (num, p.force))
+        });
+        PlayerRelation::between(local, owner)
+    }
+}
+
 /// A component that specifies the player number for a given [Player] entity.
 #[derive(Component, Debug, Copy, Clone, Default, Reflect)]
 pub struct PlayerNumber(pub u8);
@@ -90,3 +245,32 @@ fn update_player_numbers(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relations() {
+        assert_eq!(
+            PlayerRelation::between(Some((0, 0)), Some((0, 0))),
+            PlayerRelation::Own
+        );
+        assert_eq!(
+            PlayerRelation::between(Some((0, 0)), Some((1, 0))),
+            PlayerRelation::Ally
+        );
+        assert_eq!(
+            PlayerRelation::between(Some((0, 0)), Some((1, 1))),
+            PlayerRelation::Enemy
+        );
+        assert_eq!(
+            PlayerRelation::between(Some((0, 0)), None),
+            PlayerRelation::Neutral
+        );
+        assert_eq!(
+            PlayerRelation::between(None, Some((1, 1))),
+            PlayerRelation::Neutral
+        );
+    }
+}
@@ -0,0 +1,500 @@
+//! The resource counters in the top right of the screen, showing minerals, gas, and supply. For
+//! players this shows their own resources, while observers can cycle between each player's
+//! resources or a table of every player's, by clicking on the counters.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    fonts::FONT_BODY,
+    gamedata::dds_grp::DdsGrpAsset,
+    races::Race,
+    settings::{AssetQuality, GameSettings},
+    states::{AppState, InGameOnly},
+};
+
+use super::{
+    players::{ControlledPlayer, Player, PlayerNumber},
+    resources::{PlayerResources, PlayerSupply},
+    selection::BlocksMapInput,
+    InGameMenuState,
+};
+
+/// How often the displayed resource amounts step towards their actual values.
+const COUNT_UP_INTERVAL: Duration = Duration::from_millis(16);
+/// The fraction of the remaining difference that is added each step when counting up.
+const COUNT_UP_DIVISOR: u32 = 8;
+/// How long the supply counter spends in each color while flashing.
+const SUPPLY_FLASH_SECS: f32 = 0.5;
+const ICON_SIZE: f32 = 16.0;
+
+const TEXT_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+const SUPPLY_BLOCKED_COLOR: Color = Color::srgb(0.9, 0.1, 0.1);
+
+// NOTE(tec27): Frame indices within game/icons, which has the mineral icon followed by the gas
+// and supply icons for each race (in BW race ID order)
+const MINERAL_ICON_FRAME: usize = 0;
+const GAS_ICON_FRAME: usize = 1;
+const SUPPLY_ICON_FRAME: usize = 4;
+
+pub struct ResourceHudPlugin;
+
+impl Plugin for ResourceHudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ResourceHudMode>()
+            .add_systems(OnEnter(AppState::InGame), setup)
+            .add_systems(
+                Update,
+                (
+                    update_hud_mode,
+                    rebuild_rows,
+                    (count_up_resources, update_icons),
+                    update_texts,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(
+                Update,
+                cycle_hud_mode.run_if(
+                    in_state(AppState::InGame).and_then(in_state(InGameMenuState::Disabled)),
+                ),
+            );
+    }
+}
+
+/// Which players' resources are shown in the HUD.
+#[derive(Resource, Debug, Default, Copy, Clone, PartialEq, Eq)]
+enum ResourceHudMode {
+    /// Shows the resources of a single player (always the [ControlledPlayer] if there is one).
+    Player(u8),
+    /// Shows a table with the resources of every player, for observers.
+    #[default]
+    AllPlayers,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ResourceKind {
+    Minerals,
+    Gas,
+    Supply,
+}
+
+#[derive(Resource, Debug)]
+struct ResourceHudAssets {
+    font: Handle<Font>,
+    /// The resource icons, if they're available for the current asset quality. If not, the
+    /// counters are labeled with text instead.
+    icons: Option<Handle<DdsGrpAsset>>,
+    count_up_timer: Timer,
+}
+
+/// Marker component for the node that contains the resource rows.
+#[derive(Component)]
+struct ResourceHud;
+
+/// A row of resource counters for a single player.
+#[derive(Component)]
+struct ResourceRow {
+    player: u8,
+    /// The minerals and gas currently displayed, which count up towards the actual amounts.
+    displayed: PlayerResources,
+}
+
+#[derive(Component)]
+struct ResourceIcon(ResourceKind);
+
+#[derive(Component)]
+struct ResourceText(ResourceKind);
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<GameSettings>,
+    mut mode: ResMut<ResourceHudMode>,
+) {
+    *mode = ResourceHudMode::default();
+    // TODO(tec27): SD icons are stored as a regular GRP (game/icons.grp), which we'd need to
+    // render with the paletted pipeline. Until then, SD uses text labels
+    let icons = (settings.asset_quality != AssetQuality::Standard).then(|| {
+        asset_server.load(format!(
+            "casc-extracted/{}game/icons.dds.grp",
+            settings.asset_quality.asset_path()
+        ))
+    });
+    commands.insert_resource(ResourceHudAssets {
+        font: asset_server.load(FONT_BODY),
+        icons,
+        count_up_timer: Timer::new(COUNT_UP_INTERVAL, TimerMode::Repeating),
+    });
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(8.0),
+                right: Val::Px(8.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexEnd,
+                row_gap: Val::Px(2.0),
+                ..default()
+            },
+            ..default()
+        },
+        Interaction::default(),
+        BlocksMapInput,
+        ResourceHud,
+        InGameOnly,
+        Name::new("ResourceHud"),
+    ));
+}
+
+/// Returns the next value to display when counting towards `target`. Like BW, increases are shown
+/// by quickly counting up, while decreases (e.g. from spending) are shown immediately.
+fn count_towards(displayed: u32, target: u32) -> u32 {
+    if displayed >= target {
+        target
+    } else {
+        displayed + ((target - displayed) / COUNT_UP_DIVISOR).max(1)
+    }
+}
+
+/// Returns the next mode when an observer cycles through the HUD modes: each player in order,
+/// followed by all of them.
+fn next_hud_mode(mode: ResourceHudMode, players: &[u8]) -> ResourceHudMode {
+    let next_index = match mode {
+        ResourceHudMode::Player(p) => players.iter().position(|&n| n == p).map(|i| i + 1),
+        ResourceHudMode::AllPlayers => Some(0),
+    };
+    match next_index.and_then(|i| players.get(i)) {
+        Some(&p) => ResourceHudMode::Player(p),
+        None => ResourceHudMode::AllPlayers,
+    }
+}
+
+/// Returns the text label shown in place of a resource's icon when icons aren't available.
+fn resource_label(kind: ResourceKind, race: Race) -> &'static str {
+    match kind {
+        ResourceKind::Minerals => "Minerals",
+        ResourceKind::Gas => "Gas",
+        ResourceKind::Supply => match race {
+            Race::Zerg => "Control",
+            Race::Terran => "Supplies",
+            Race::Protoss => "Psi",
+        },
+    }
+}
+
+/// Formats a supply amount (which is stored in half-supply units) for display.
+fn format_supply(amount: u32) -> u32 {
+    amount.div_ceil(2)
+}
+
+fn update_hud_mode(
+    mut mode: ResMut<ResourceHudMode>,
+    controlled_player: Query<&PlayerNumber, With<ControlledPlayer>>,
+) {
+    if let Ok(player) = controlled_player.get_single() {
+        let new_mode = ResourceHudMode::Player(player.0);
+        if *mode != new_mode {
+            *mode = new_mode;
+        }
+    }
+}
+
+fn cycle_hud_mode(
+    hud: Query<&Interaction, (Changed<Interaction>, With<ResourceHud>)>,
+    mut mode: ResMut<ResourceHudMode>,
+    controlled_player: Query<(), With<ControlledPlayer>>,
+    players: Query<&PlayerNumber, With<Player>>,
+) {
+    // Players can only see their own resources
+    if !controlled_player.is_empty() {
+        return;
+    }
+    if hud.iter().any(|i| *i == Interaction::Pressed) {
+        let mut player_nums = players.iter().map(|p| p.0).collect::<Vec<_>>();
+        player_nums.sort_unstable();
+        *mode = next_hud_mode(*mode, &player_nums);
+    }
+}
+
+fn rebuild_rows(
+    mut commands: Commands,
+    mode: Res<ResourceHudMode>,
+    hud: Query<(Entity, Option<&Children>), With<ResourceHud>>,
+    players: Query<(&PlayerNumber, &Player, &PlayerResources)>,
+    assets: Res<ResourceHudAssets>,
+) {
+    let Ok((hud, children)) = hud.get_single() else {
+        return;
+    };
+    // Players get added after the HUD is created, so we also need to build the rows once they exist
+    if !mode.is_changed() && children.is_some_and(|c| !c.is_empty()) {
+        return;
+    }
+
+    let mut shown = players
+        .iter()
+        .filter(|(num, _, _)| match *mode {
+            ResourceHudMode::Player(p) => num.0 == p,
+            ResourceHudMode::AllPlayers => true,
+        })
+        .collect::<Vec<_>>();
+    if shown.is_empty() {
+        return;
+    }
+    shown.sort_by_key(|(num, _, _)| num.0);
+
+    let text_style = TextStyle {
+        font: assets.font.clone(),
+        font_size: 18.0,
+        color: TEXT_COLOR,
+    };
+    commands.entity(hud).despawn_descendants();
+    commands.entity(hud).with_children(|builder| {
+        for (num, player, &resources) in shown {
+            builder
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(4.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    ResourceRow {
+                        player: num.0,
+                        displayed: resources,
+                    },
+                ))
+                .with_children(|builder| {
+                    if *mode == ResourceHudMode::AllPlayers {
+                        builder.spawn(NodeBundle {
+                            style: Style {
+                                width: Val::Px(ICON_SIZE / 2.0),
+                                height: Val::Px(ICON_SIZE),
+                                ..default()
+                            },
+                            background_color: player.color.color().into(),
+                            ..default()
+                        });
+                    }
+                    for kind in [
+                        ResourceKind::Minerals,
+                        ResourceKind::Gas,
+                        ResourceKind::Supply,
+                    ] {
+                        if assets.icons.is_some() {
+                            builder.spawn((
+                                ImageBundle {
+                                    style: Style {
+                                        width: Val::Px(ICON_SIZE),
+                                        height: Val::Px(ICON_SIZE),
+                                        margin: UiRect::left(Val::Px(8.0)),
+                                        ..default()
+                                    },
+                                    ..default()
+                                },
+                                ResourceIcon(kind),
+                            ));
+                        } else {
+                            builder.spawn(
+                                TextBundle::from_section(
+                                    resource_label(kind, player.race),
+                                    text_style.clone(),
+                                )
+                                .with_style(Style {
+                                    margin: UiRect::left(Val::Px(8.0)),
+                                    ..default()
+                                }),
+                            );
+                        }
+                        builder.spawn((
+                            TextBundle::from_section("", text_style.clone()),
+                            ResourceText(kind),
+                        ));
+                    }
+                });
+        }
+    });
+}
+
+fn count_up_resources(
+    mut rows: Query<&mut ResourceRow>,
+    players: Query<(&PlayerNumber, &PlayerResources)>,
+    mut assets: ResMut<ResourceHudAssets>,
+    time: Res<Time>,
+) {
+    let steps = assets
+        .count_up_timer
+        .tick(time.delta())
+        .times_finished_this_tick();
+    if steps == 0 {
+        return;
+    }
+
+    for mut row in rows.iter_mut() {
+        let Some((_, resources)) = players.iter().find(|(num, _)| num.0 == row.player) else {
+            continue;
+        };
+        let mut displayed = row.displayed;
+        for _ in 0..steps {
+            displayed.minerals = count_towards(displayed.minerals, resources.minerals);
+            displayed.gas = count_towards(displayed.gas, resources.gas);
+        }
+        if row.displayed != displayed {
+            row.displayed = displayed;
+        }
+    }
+}
+
+fn update_icons(
+    rows: Query<(&ResourceRow, &Children)>,
+    mut icons: Query<(&ResourceIcon, &mut UiImage)>,
+    players: Query<(&PlayerNumber, &Player)>,
+    assets: Res<ResourceHudAssets>,
+    grp_assets: Res<Assets<DdsGrpAsset>>,
+) {
+    let Some(grp) = assets
+        .icons
+        .as_ref()
+        .and_then(|icons| grp_assets.get(icons))
+    else {
+        return;
+    };
+
+    for (row, children) in rows.iter() {
+        let Some((_, player)) = players.iter().find(|(num, _)| num.0 == row.player) else {
+            continue;
+        };
+        // These are ordered by BW's race IDs, rather than ours
+        let race_offset = match player.race {
+            Race::Zerg => 0,
+            Race::Terran => 1,
+            Race::Protoss => 2,
+        };
+
+        let mut iter = icons.iter_many_mut(children);
+        while let Some((icon, mut image)) = iter.fetch_next() {
+            let frame = match icon.0 {
+                ResourceKind::Minerals => MINERAL_ICON_FRAME,
+                ResourceKind::Gas => GAS_ICON_FRAME + race_offset,
+                ResourceKind::Supply => SUPPLY_ICON_FRAME + race_offset,
+            };
+            if let Some(texture) = grp.frames.get(frame) {
+                if image.texture != *texture {
+                    image.texture = texture.clone();
+                }
+            }
+        }
+    }
+}
+
+fn update_texts(
+    rows: Query<(&ResourceRow, &Children)>,
+    mut texts: Query<(&ResourceText, &mut Text)>,
+    players: Query<(&PlayerNumber, &Player, &PlayerSupply)>,
+    time: Res<Time>,
+) {
+    let flash_on = (time.elapsed_seconds() / (SUPPLY_FLASH_SECS * 2.0)).fract() < 0.5;
+
+    for (row, children) in rows.iter() {
+        let Some((_, player, supply)) = players.iter().find(|(num, _, _)| num.0 == row.player)
+        else {
+            continue;
+        };
+        let supply = supply.get(player.race);
+
+        let mut iter = texts.iter_many_mut(children);
+        while let Some((resource_text, mut text)) = iter.fetch_next() {
+            let (value, color) = match resource_text.0 {
+                ResourceKind::Minerals => (row.displayed.minerals.to_string(), TEXT_COLOR),
+                ResourceKind::Gas => (row.displayed.gas.to_string(), TEXT_COLOR),
+                ResourceKind::Supply => (
+                    format!(
+                        "{}/{}",
+                        format_supply(supply.used),
+                        format_supply(supply.max())
+                    ),
+                    if supply.is_blocked() && flash_on {
+                        SUPPLY_BLOCKED_COLOR
+                    } else {
+                        TEXT_COLOR
+                    },
+                ),
+            };
+
+            let section = &text.sections[0];
+            if section.value != value || section.style.color != color {
+                let section = &mut text.sections[0];
+                section.value = value;
+                section.style.color = color;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counting() {
+        assert_eq!(count_towards(50, 58), 51);
+        assert_eq!(count_towards(50, 130), 60);
+        assert_eq!(count_towards(58, 58), 58);
+        assert_eq!(count_towards(100, 25), 25);
+
+        // Large increases are mostly counted within a few steps, and reach the target shortly after
+        let mut displayed = 0;
+        for _ in 0..16 {
+            displayed = count_towards(displayed, 1000);
+        }
+        assert!(displayed > 800 && displayed < 1000);
+        for _ in 0..48 {
+            displayed = count_towards(displayed, 1000);
+        }
+        assert_eq!(displayed, 1000);
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!(
+            resource_label(ResourceKind::Minerals, Race::Zerg),
+            "Minerals"
+        );
+        assert_eq!(resource_label(ResourceKind::Supply, Race::Protoss), "Psi");
+        assert_eq!(
+            resource_label(ResourceKind::Supply, Race::Terran),
+            "Supplies"
+        );
+    }
+
+    #[test]
+    fn supply_formatting() {
+        assert_eq!(format_supply(0), 0);
+        assert_eq!(format_supply(1), 1);
+        assert_eq!(format_supply(8), 4);
+        assert_eq!(format_supply(400), 200);
+    }
+
+    #[test]
+    fn observer_cycling() {
+        let players = [0, 1, 3];
+        assert_eq!(
+            next_hud_mode(ResourceHudMode::AllPlayers, &players),
+            ResourceHudMode::Player(0)
+        );
+        assert_eq!(
+            next_hud_mode(ResourceHudMode::Player(1), &players),
+            ResourceHudMode::Player(3)
+        );
+        assert_eq!(
+            next_hud_mode(ResourceHudMode::Player(3), &players),
+            ResourceHudMode::AllPlayers
+        );
+    }
+}
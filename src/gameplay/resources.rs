@@ -1,8 +1,131 @@
 use bevy::prelude::*;
 
+use crate::{gamedata::ConstructTypeId, maps::chk::NUM_CHK_PLAYERS, races::Race};
+
+use super::{build_time::UnderConstruction, constructs::OwnedConstruct, players::PlayerNumber};
+
 /// [Component] that describes how many of a particular resource type an entity contains.
 #[derive(Component, Debug, Copy, Clone, Eq, PartialEq, Reflect)]
 pub enum ResourceAmount {
     Minerals(u32),
     Gas(u32),
 }
+
+/// [Component] that tracks the resources a [Player](super::players::Player) has available to
+/// spend.
+#[derive(Component, Debug, Copy, Clone, Default, Eq, PartialEq, Reflect)]
+pub struct PlayerResources {
+    pub minerals: u32,
+    pub gas: u32,
+}
+
+impl PlayerResources {
+    /// The resources each player starts the game with.
+    pub const STARTING: Self = Self {
+        minerals: 50,
+        gas: 0,
+    };
+}
+
+/// The most supply a player can have available for a single race (in half-supply units, see
+/// [RaceSupply]).
+pub const MAX_SUPPLY: u32 = 400;
+
+/// The supply a player is using and has available for a single race. Like in BW, these are stored
+/// in half-supply units (so e.g. a zergling uses 1 and a marine uses 2).
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Reflect)]
+pub struct RaceSupply {
+    pub used: u32,
+    pub provided: u32,
+}
+
+impl RaceSupply {
+    /// Returns the supply available to this race, which is capped at [MAX_SUPPLY].
+    pub fn max(&self) -> u32 {
+        self.provided.min(MAX_SUPPLY)
+    }
+
+    /// Returns whether no more supply is available, so nothing that requires supply can be made.
+    pub fn is_blocked(&self) -> bool {
+        self.used >= self.max()
+    }
+}
+
+/// [Component] that tracks the supply used and provided for each race a
+/// [Player](super::players::Player) has constructs of. This is tracked separately per race (e.g.
+/// if a Protoss player mind controls a Zerg unit, it counts against their Zerg supply).
+#[derive(Component, Debug, Copy, Clone, Default, Eq, PartialEq, Reflect)]
+pub struct PlayerSupply {
+    /// The supply for each race, indexed by [Race].
+    pub races: [RaceSupply; 3],
+}
+
+impl PlayerSupply {
+    pub fn get(&self, race: Race) -> &RaceSupply {
+        &self.races[race as usize]
+    }
+}
+
+/// Recalculates the supply for each player from the constructs they own. Supply is used as soon as
+/// a construct is created, but is only provided once it has finished construction.
+pub fn update_player_supply(
+    mut players: Query<(&PlayerNumber, &mut PlayerSupply)>,
+    constructs: Query<(
+        &ConstructTypeId,
+        &OwnedConstruct,
+        Option<&UnderConstruction>,
+    )>,
+) {
+    // NOTE(tec27): BW tracks these as constructs are created/destroyed, but recalculating it is
+    // simple and means we can't get out of sync
+    let mut supplies = [PlayerSupply::default(); NUM_CHK_PLAYERS];
+    for (construct_type, owner, under_construction) in constructs.iter() {
+        let (Some(race), Some(supply)) =
+            (construct_type.race(), supplies.get_mut(owner.0 as usize))
+        else {
+            continue;
+        };
+        let def = construct_type.def();
+        let race_supply = &mut supply.races[race as usize];
+        race_supply.used += def.supply_required as u32;
+        if !under_construction.is_some_and(|u| u.has_time_remaining()) {
+            race_supply.provided += def.supply_provided as u32;
+        }
+    }
+
+    for (player, mut supply) in players.iter_mut() {
+        let Some(&new_supply) = supplies.get(player.0 as usize) else {
+            continue;
+        };
+        if *supply != new_supply {
+            *supply = new_supply;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn supply_limits() {
+        let supply = RaceSupply {
+            used: 18,
+            provided: 20,
+        };
+        assert_eq!(supply.max(), 20);
+        assert!(!supply.is_blocked());
+        assert!(RaceSupply {
+            used: 20,
+            provided: 20
+        }
+        .is_blocked());
+
+        let maxed = RaceSupply {
+            used: 398,
+            provided: 480,
+        };
+        assert_eq!(maxed.max(), MAX_SUPPLY);
+        assert!(!maxed.is_blocked());
+    }
+}
@@ -1,11 +1,14 @@
-use crate::camera::CameraPanLocked;
+use std::time::Duration;
+
+use crate::camera::{CameraPanLocked, CenterCameraEvent};
 use crate::gamedata::anim::AnimAsset;
-use crate::gamedata::{ConstructTypeId, PreloadedAnimBundle};
-use crate::gameplay::sounds::PlaySoundCommandsExt;
+use crate::gamedata::{ConstructFlags, ConstructTypeId, PreloadedAnimBundle};
 use crate::gameplay::InGameMenuState;
+use crate::input::InputAction;
 use crate::maps::game_map::{GameMap, GameMapSize, LOGIC_TILE_SIZE};
 use crate::maps::position::Position;
-use crate::random::UnsyncedLcgRand;
+use crate::net::{GameCommand, IssueCommandEvent};
+use crate::render::draw_order::UNDERLAY_Z_OFFSET;
 use crate::settings::{AssetPack, GameSettings};
 use crate::states::AppState;
 use bevy::input::mouse::MouseButtonInput;
@@ -14,8 +17,12 @@ use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 use smallvec::SmallVec;
 
-use super::constructs::OwnedConstruct;
-use super::players::{ControlledPlayer, PlayerNumber};
+use super::command_card::{CommandTargeting, TargetInputEvent};
+use super::constructs::{ConstructId, OwnedConstruct};
+use super::player_selection::{
+    ControlGroupAction, ControlGroups, MAX_SELECTION, NUM_CONTROL_GROUPS,
+};
+use super::players::{ControlledPlayer, PlayerColors, PlayerNumber};
 
 pub struct DragSelectionPlugin;
 
@@ -23,15 +30,16 @@ impl Plugin for DragSelectionPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<SelectInputEvent>()
             .add_event::<ConstructsSelectedEvent>()
+            .init_resource::<HoveredConstruct>()
             .add_systems(OnEnter(AppState::PreGame), preload_selection_circles)
             .add_systems(OnEnter(AppState::InGame), drag_selection_setup)
             .add_systems(OnExit(AppState::InGame), drag_selection_cleanup)
             .add_systems(
                 Update,
                 (
-                    selection_input,
+                    (selection_input, control_group_input, select_all_army_input),
                     apply_selection,
-                    (play_selection_sounds, update_locally_selected),
+                    (update_locally_selected, update_hovered_construct),
                 )
                     .chain()
                     .run_if(
@@ -43,25 +51,45 @@ impl Plugin for DragSelectionPlugin {
 
 /// How far the mouse cursor can move with the mouse down before it is considered a drag
 const DRAG_SLOP_PX: f32 = 4.0;
+/// The most time that can pass between two clicks for them to count as a double-click.
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+/// The most time that can pass between recalling the same control group twice for the camera to be
+/// centered on it.
+const DOUBLE_TAP_TIME: Duration = Duration::from_millis(400);
 
 #[derive(Default)]
 struct DragSelectionState {
     mouse_down: bool,
     mouse_down_pos: Vec2,
+    /// The time and position of the last click selection, for detecting double-clicks.
+    last_click: Option<(Duration, Vec2)>,
 }
 
 impl DragSelectionState {
     fn is_dragging(&self, mouse_pos: Vec2) -> bool {
-        self.mouse_down && mouse_pos.distance_squared(self.mouse_down_pos) >= DRAG_SLOP_PX
+        self.mouse_down && mouse_pos.distance(self.mouse_down_pos) >= DRAG_SLOP_PX
     }
 }
 
-// TODO(tec27): Handle modifiers, e.g. shift-select should add to the existing selection
 /// Event fired when a selection input is completed, specified by logical map coordinates.
 #[derive(Event, Debug, Copy, Clone)]
 pub enum SelectInputEvent {
-    Click(Position),
-    Drag(DragSelectEvent),
+    Click(Position, SelectModifier),
+    Drag(DragSelectEvent, SelectModifier),
+}
+
+/// How a selection input should affect the current selection.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum SelectModifier {
+    /// Replace the current selection.
+    #[default]
+    None,
+    /// Add to the current selection, or remove the picked constructs from it if they were all
+    /// already selected (e.g. shift-click or shift-drag).
+    Toggle,
+    /// Select all of the player's units of the clicked type within the specified area (in logical
+    /// map coordinates), typically the area visible on screen (e.g. ctrl-click or double-click).
+    AllOfType(IRect),
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -77,6 +105,12 @@ pub struct ConstructsSelectedEvent {
     pub constructs: Vec<Entity>,
 }
 
+/// Marker component for UI nodes that should prevent mouse input from reaching the map (e.g.
+/// starting a drag selection) while the cursor is over them. These nodes also need an
+/// [Interaction] component.
+#[derive(Component, Debug, Default)]
+pub struct BlocksMapInput;
+
 /// The anim ID of the first selection circle
 const FIRST_SELECTION_CIRCLE: u16 = 561;
 const NUM_SELECTION_CIRCLES: usize = 10;
@@ -94,6 +128,8 @@ fn preload_selection_circles(
 ) {
     commands.remove_resource::<SelectionCircles>();
 
+    // TODO(tec27): Load these from GRPs when using SD graphics, so they're paletted like other
+    // SD images
     let mut circles = SelectionCircles::default();
     for i in 0..NUM_SELECTION_CIRCLES {
         let handle: Handle<AnimAsset> = asset_server.load(format!(
@@ -145,36 +181,61 @@ fn selection_input(
     camera_query: Query<(&GlobalTransform, &Camera)>,
     map: Query<&GameMapSize, With<GameMap>>,
     settings: Res<GameSettings>,
+    input_blockers: Query<&Interaction, With<BlocksMapInput>>,
+    actions: Res<ButtonInput<InputAction>>,
+    time: Res<Time>,
+    mut targeting: ResMut<CommandTargeting>,
+    mut target_writer: EventWriter<TargetInputEvent>,
+    hovered: Res<HoveredConstruct>,
 ) {
     let window = window.single();
     let mouse_pos = window.cursor_position().unwrap_or_default();
+    let over_ui = input_blockers.iter().any(|i| *i != Interaction::None);
+    let shift = actions.pressed(InputAction::AddModifier);
+    let ctrl = actions.pressed(InputAction::GroupModifier);
 
     for event in mouse_reader.read() {
+        if event.button == MouseButton::Right
+            && event.state == ButtonState::Pressed
+            && targeting.0.is_some()
+        {
+            // Right-clicking cancels picking a target for a command card order
+            targeting.0 = None;
+            continue;
+        }
         if event.button != MouseButton::Left {
             continue;
         }
 
         match event.state {
             ButtonState::Pressed => {
-                // TODO(tec27): Only handle this if the mouse is over the map (e.g. not over the
-                // UI)
+                if over_ui {
+                    continue;
+                }
+                if targeting.0.is_some() {
+                    let map_size = map.single();
+                    let (cam_transform, cam) = camera_query.single();
+                    if let Some(pos) = cam.viewport_to_world_2d(cam_transform, mouse_pos) {
+                        let pos = world_to_map(pos, map_size, settings.asset_quality.tile_size());
+                        target_writer.send(TargetInputEvent {
+                            position: pos.into(),
+                            target: hovered.0,
+                        });
+                    }
+                    continue;
+                }
                 state.mouse_down = true;
                 state.mouse_down_pos = mouse_pos;
                 camera_pan_locked.0 = true;
             }
             ButtonState::Released => {
+                if !state.mouse_down {
+                    // The press started somewhere else (e.g. over the UI)
+                    continue;
+                }
                 let map_size = map.single();
-                let half_map_size = Vec2::from(map_size) / 2.0;
                 let tile_size = settings.asset_quality.tile_size();
-                // Converts the world coordinates to logical map coordinates
-                let convert_pos = |mut pos: Vec2| -> IVec2 {
-                    pos /= tile_size;
-                    pos += half_map_size + 0.5;
-                    pos.y = map_size.height as f32 - pos.y;
-                    pos *= LOGIC_TILE_SIZE as f32;
-
-                    IVec2::new(pos.x.round() as i32, pos.y.round() as i32)
-                };
+                let convert_pos = |pos: Vec2| world_to_map(pos, map_size, tile_size);
                 let (cam_transform, cam) = camera_query.single();
 
                 if state.is_dragging(mouse_pos) {
@@ -196,8 +257,16 @@ fn selection_input(
 
                     let start = clamp_to_map(convert_pos(start)).into();
                     let end = clamp_to_map(convert_pos(end)).into();
-                    select_event_writer
-                        .send(SelectInputEvent::Drag(DragSelectEvent { start, end }));
+                    let modifier = if shift {
+                        SelectModifier::Toggle
+                    } else {
+                        SelectModifier::None
+                    };
+                    select_event_writer.send(SelectInputEvent::Drag(
+                        DragSelectEvent { start, end },
+                        modifier,
+                    ));
+                    state.last_click = None;
                 } else {
                     // TODO(tec27): Would it be better to use the mouse down position? The average?
                     let pos =
@@ -210,7 +279,32 @@ fn selection_input(
                         ),
                     );
                     if map_rect.contains(pos) {
-                        select_event_writer.send(SelectInputEvent::Click(pos.into()));
+                        let now = time.elapsed();
+                        let double_click = state.last_click.is_some_and(|(time, pos)| {
+                            now - time <= DOUBLE_CLICK_TIME
+                                && pos.distance(mouse_pos) < DRAG_SLOP_PX
+                        });
+                        state.last_click = if double_click {
+                            None
+                        } else {
+                            Some((now, mouse_pos))
+                        };
+
+                        let modifier = if ctrl || double_click {
+                            // The whole area of the map that is visible on screen
+                            let screen = [Vec2::ZERO, Vec2::new(window.width(), window.height())]
+                                .map(|p| {
+                                    cam.viewport_to_world_2d(cam_transform, p)
+                                        .map(convert_pos)
+                                        .unwrap_or_default()
+                                });
+                            SelectModifier::AllOfType(IRect::from_corners(screen[0], screen[1]))
+                        } else if shift {
+                            SelectModifier::Toggle
+                        } else {
+                            SelectModifier::None
+                        };
+                        select_event_writer.send(SelectInputEvent::Click(pos.into(), modifier));
                     }
                 }
                 state.mouse_down = false;
@@ -244,9 +338,57 @@ fn selection_input(
     }
 }
 
-/// Component that stores the currently selected entities for a [Player].
+/// Converts world coordinates to logical map coordinates.
+fn world_to_map(mut pos: Vec2, map_size: &GameMapSize, tile_size: Vec2) -> IVec2 {
+    let half_map_size = Vec2::from(map_size) / 2.0;
+    pos /= tile_size;
+    pos += half_map_size + 0.5;
+    pos.y = map_size.height as f32 - pos.y;
+    pos *= LOGIC_TILE_SIZE as f32;
+
+    IVec2::new(pos.x.round() as i32, pos.y.round() as i32)
+}
+
+/// The construct that is currently under the mouse cursor, if any.
+#[derive(Resource, Debug, Default)]
+pub struct HoveredConstruct(pub Option<Entity>);
+
+fn update_hovered_construct(
+    mut hovered: ResMut<HoveredConstruct>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&GlobalTransform, &Camera)>,
+    map: Query<&GameMapSize, With<GameMap>>,
+    constructs: Query<(Entity, &Position, &ConstructTypeId, &Visibility)>,
+    settings: Res<GameSettings>,
+) {
+    let cursor_pos = window
+        .get_single()
+        .ok()
+        .and_then(|w| w.cursor_position())
+        .zip(camera_query.get_single().ok())
+        .and_then(|(cursor, (cam_transform, cam))| cam.viewport_to_world_2d(cam_transform, cursor))
+        .zip(map.get_single().ok())
+        .map(|(pos, map_size)| world_to_map(pos, map_size, settings.asset_quality.tile_size()));
+
+    // TODO(tec27): This should pick the top-most construct in draw order
+    let new_hovered = cursor_pos.and_then(|cursor_pos| {
+        constructs
+            .iter()
+            .filter(|(_, &pos, &ty, &vis)| {
+                vis != Visibility::Hidden && ty.bounds().at_pos(pos.into()).contains(cursor_pos)
+            })
+            .min_by_key(|(_, &pos, _, _)| IVec2::from(pos).distance_squared(cursor_pos))
+            .map(|(entity, _, _, _)| entity)
+    });
+    if hovered.0 != new_hovered {
+        hovered.0 = new_hovered;
+    }
+}
+
+/// Component that stores the currently selected entities for a [Player]. This is only changed by
+/// executing selection [GameCommand]s, see [super::player_selection].
 #[derive(Component, Debug, Default)]
-pub struct SelectedEntities(pub SmallVec<[Entity; 12]>);
+pub struct SelectedEntities(pub SmallVec<[Entity; MAX_SELECTION]>);
 
 // TODO(tec27): We might need to rework this for team games/obs/replays to be able to show selection
 // circles for other players. Might make sense to have this contain a bitfield?
@@ -255,62 +397,87 @@ pub struct SelectedEntities(pub SmallVec<[Entity; 12]>);
 #[derive(Component)]
 pub struct LocallySelected;
 
+type SelectableConstructs<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Position,
+        &'static ConstructTypeId,
+        &'static Visibility,
+        Option<&'static OwnedConstruct>,
+    ),
+>;
+
+/// Turns the local player's selection inputs into selection commands. The selection itself will be
+/// changed once the commands are executed.
 fn apply_selection(
     mut select_events: EventReader<SelectInputEvent>,
-    mut controlled_player: Query<(&mut SelectedEntities, &PlayerNumber), With<ControlledPlayer>>,
-    constructs: Query<(
-        Entity,
-        &Position,
-        &ConstructTypeId,
-        &Visibility,
-        Option<&OwnedConstruct>,
-    )>,
+    controlled_player: Query<(&SelectedEntities, &PlayerNumber), With<ControlledPlayer>>,
+    constructs: SelectableConstructs,
+    construct_ids: Query<&ConstructId>,
     mut constructs_selected_writer: EventWriter<ConstructsSelectedEvent>,
+    mut command_writer: EventWriter<IssueCommandEvent>,
 ) {
-    let Ok((mut selected_entities, &controlled_player)) = controlled_player.get_single_mut() else {
+    let Ok((selected_entities, &controlled_player)) = controlled_player.get_single() else {
         // No locally-controlled player so drag selection can't be done
         // TODO(tec27): Figure out how observers should work with this
         return;
     };
 
     for event in select_events.read() {
-        let updated = match event {
-            SelectInputEvent::Click(pos) => handle_click_selection(
-                pos.into(),
-                controlled_player,
-                &constructs,
-                &mut selected_entities,
-            ),
-            SelectInputEvent::Drag(event) => handle_drag_selection(
-                event.start.into(),
-                IRect::from_corners(event.start.into(), event.end.into()),
-                controlled_player,
-                &constructs,
-                &mut selected_entities,
+        let (picked, modifier) = match *event {
+            SelectInputEvent::Click(pos, modifier) => {
+                let picked = pick_click_selection(pos.into(), controlled_player, &constructs);
+                let picked = match (picked, modifier) {
+                    (Some(entity), SelectModifier::AllOfType(area)) => {
+                        all_of_type(entity, area, controlled_player, &constructs)
+                    }
+                    (picked, _) => picked.into_iter().collect(),
+                };
+                (picked, modifier)
+            }
+            SelectInputEvent::Drag(event, modifier) => (
+                pick_drag_selection(
+                    event.start.into(),
+                    IRect::from_corners(event.start.into(), event.end.into()),
+                    controlled_player,
+                    &constructs,
+                ),
+                modifier,
             ),
         };
-
-        if updated && !selected_entities.0.is_empty() {
-            let selected = selected_entities.0.iter().copied().collect();
-            constructs_selected_writer.send(ConstructsSelectedEvent {
-                constructs: selected,
-            });
+        if picked.is_empty() {
+            continue;
         }
+
+        let ids = picked
+            .iter()
+            .filter_map(|&e| construct_ids.get(e).ok().copied())
+            .collect::<Vec<_>>();
+        let command = match modifier {
+            // Like BW, toggling only removes constructs if everything picked was already
+            // selected, otherwise the picked constructs are added
+            SelectModifier::Toggle if picked.iter().all(|e| selected_entities.0.contains(e)) => {
+                command_writer.send(IssueCommandEvent(GameCommand::SelectRemove(ids)));
+                continue;
+            }
+            SelectModifier::Toggle => GameCommand::SelectAdd(ids),
+            SelectModifier::None | SelectModifier::AllOfType(_) => GameCommand::Select(ids),
+        };
+        command_writer.send(IssueCommandEvent(command));
+        constructs_selected_writer.send(ConstructsSelectedEvent {
+            constructs: picked.into_vec(),
+        });
     }
 }
 
-fn handle_click_selection(
+/// Returns the construct that should be selected by a click at `click_pos`, if any.
+fn pick_click_selection(
     click_pos: IVec2,
     controlled_player: PlayerNumber,
-    constructs: &Query<(
-        Entity,
-        &Position,
-        &ConstructTypeId,
-        &Visibility,
-        Option<&OwnedConstruct>,
-    )>,
-    selected_entities: &mut Mut<SelectedEntities>,
-) -> bool {
+    constructs: &SelectableConstructs,
+) -> Option<Entity> {
     // All constructs that are visible and contain the click
     let contained_constructs = constructs
         .iter()
@@ -351,39 +518,24 @@ fn handle_click_selection(
         contained_constructs
     };
 
-    if !selectable.is_empty() {
-        // Sort by Euclidean distnace (squared) from the click
-        selectable.sort_by_cached_key(|(_, pos, _, _, _)| {
-            (pos.x - click_pos.x).pow(2) + (pos.y - click_pos.y).pow(2)
-        });
-
-        // TODO(tec27): For click selection it probably makes more sense to prefer things at the
-        // higher "layer" (e.g. prefer flying units over ground units?). Potentially we should just
-        // project a ray from the camera through the click and select the first thing it hits?
+    // Sort by Euclidean distnace (squared) from the click
+    selectable.sort_by_cached_key(|(_, pos, _, _, _)| {
+        (pos.x - click_pos.x).pow(2) + (pos.y - click_pos.y).pow(2)
+    });
 
-        selected_entities.0.clear();
-        selected_entities
-            .0
-            .extend(selectable.first().map(|(entity, _, _, _, _)| *entity));
-        true
-    } else {
-        false
-    }
+    // TODO(tec27): For click selection it probably makes more sense to prefer things at the
+    // higher "layer" (e.g. prefer flying units over ground units?). Potentially we should just
+    // project a ray from the camera through the click and select the first thing it hits?
+    selectable.first().map(|(entity, _, _, _, _)| *entity)
 }
 
-fn handle_drag_selection(
+/// Returns the constructs that should be selected by dragging over `drag_rect`.
+fn pick_drag_selection(
     drag_start: IVec2,
     drag_rect: IRect,
     controlled_player: PlayerNumber,
-    constructs: &Query<(
-        Entity,
-        &Position,
-        &ConstructTypeId,
-        &Visibility,
-        Option<&OwnedConstruct>,
-    )>,
-    selected_entities: &mut Mut<SelectedEntities>,
-) -> bool {
+    constructs: &SelectableConstructs,
+) -> SmallVec<[Entity; MAX_SELECTION]> {
     // All constructs that are visible and within the drag
     let contained_constructs = constructs
         .iter()
@@ -394,35 +546,28 @@ fn handle_drag_selection(
                     .is_empty()
         })
         .collect::<Vec<_>>();
-    let owned = {
-        // All constructs from above that are also owned by the current player
-        let owned = contained_constructs
-            .iter()
-            .copied()
-            .filter(|(_, _, _, _, oc)| match oc {
-                Some(owner) => owner.0 == controlled_player.0,
-                None => false,
-            })
-            .collect::<Vec<_>>();
-        // All constructs from above that are also units
-        let owned_units = owned
-            .iter()
-            .copied()
-            .filter(|(_, _, &ty, _, _)| ty.is_unit())
-            .collect::<Vec<_>>();
-
-        // If there are any units, we prefer those, otherwise we fall back to buildings
-        if !owned_units.is_empty() {
-            owned_units
-        } else {
-            owned
-        }
-    };
+    // All constructs from above that are also owned by the current player
+    let owned = contained_constructs
+        .iter()
+        .copied()
+        .filter(|(_, _, _, _, oc)| match oc {
+            Some(owner) => owner.0 == controlled_player.0,
+            None => false,
+        })
+        .collect::<Vec<_>>();
+    // All constructs from above that are also units
+    let owned_units = owned
+        .iter()
+        .copied()
+        .filter(|(_, _, &ty, _, _)| ty.is_unit())
+        .collect::<Vec<_>>();
 
-    // If there are any owned constructs, we select from those (up to 12), otherwise we select
-    // the highest priority construct
-    let (mut selectable, max_count) = if !owned.is_empty() {
-        (owned, 12)
+    // If there are any owned units, we select from those (up to 12), otherwise we select the
+    // highest priority construct (only units can be selected in groups)
+    let (mut selectable, max_count) = if !owned_units.is_empty() {
+        (owned_units, MAX_SELECTION)
+    } else if !owned.is_empty() {
+        (owned, 1)
     } else {
         let owned_by_others = contained_constructs
             .iter()
@@ -436,66 +581,152 @@ fn handle_drag_selection(
         }
     };
 
-    if !selectable.is_empty() {
-        // Sort by Euclidean distnace (squared) from the start of the selection
-        selectable.sort_by_cached_key(|(_, pos, _, _, _)| {
-            (pos.x - drag_start.x).pow(2) + (pos.y - drag_start.y).pow(2)
-        });
+    // Sort by Euclidean distnace (squared) from the start of the selection
+    selectable.sort_by_cached_key(|(_, pos, _, _, _)| {
+        (pos.x - drag_start.x).pow(2) + (pos.y - drag_start.y).pow(2)
+    });
+    selectable
+        .iter()
+        .take(max_count)
+        .map(|(entity, _, _, _, _)| *entity)
+        .collect()
+}
 
-        selected_entities.0.clear();
-        selected_entities.0.extend(
-            selectable
-                .iter()
-                .take(max_count)
-                .map(|(entity, _, _, _, _)| *entity),
-        );
-        true
-    } else {
-        false
+/// Returns all of the player's units that are the same type as `entity` and within `area`, closest
+/// first. If `entity` isn't one of the player's units, only it will be returned.
+fn all_of_type(
+    entity: Entity,
+    area: IRect,
+    controlled_player: PlayerNumber,
+    constructs: &SelectableConstructs,
+) -> SmallVec<[Entity; MAX_SELECTION]> {
+    let Ok((_, &clicked_pos, &clicked_type, _, owner)) = constructs.get(entity) else {
+        return SmallVec::new();
+    };
+    if !clicked_type.is_unit() || owner.map(|o| o.0) != Some(controlled_player.0) {
+        return SmallVec::from_slice(&[entity]);
     }
+
+    let mut same_type = constructs
+        .iter()
+        .filter(|(_, &pos, &ty, &vis, oc)| {
+            ty == clicked_type
+                && vis != Visibility::Hidden
+                && oc.map(|o| o.0) == Some(controlled_player.0)
+                && area.contains(pos.into())
+        })
+        .map(|(e, &pos, _, _, _)| (e, IVec2::from(pos).distance_squared(clicked_pos.into())))
+        .collect::<Vec<_>>();
+    same_type.sort_by_key(|&(e, distance)| (e != entity, distance));
+    same_type
+        .into_iter()
+        .take(MAX_SELECTION)
+        .map(|(e, _)| e)
+        .collect()
 }
 
-fn play_selection_sounds(
-    mut commands: Commands,
-    mut selection_events: EventReader<ConstructsSelectedEvent>,
-    constructs: Query<(&ConstructTypeId, &Position, Option<&OwnedConstruct>)>,
-    mut rng: ResMut<UnsyncedLcgRand>,
-    controlled_player: Query<&PlayerNumber, With<ControlledPlayer>>,
+#[derive(Default)]
+struct ControlGroupInputState {
+    /// The last control group that was recalled, and when.
+    last_recall: Option<(u8, Duration)>,
+}
+
+/// Handles assigning (Ctrl+#), adding to (Shift+#), and recalling (#) control groups. Recalling the
+/// same group twice in quick succession centers the camera on it.
+fn control_group_input(
+    mut state: Local<ControlGroupInputState>,
+    actions: Res<ButtonInput<InputAction>>,
+    time: Res<Time>,
+    controlled_player: Query<&ControlGroups, With<ControlledPlayer>>,
+    positions: Query<&Position>,
+    mut command_writer: EventWriter<IssueCommandEvent>,
+    mut center_camera_writer: EventWriter<CenterCameraEvent>,
 ) {
-    for event in selection_events.read() {
-        // TODO(tec27): Properly pick the highest "priority" unit of the selected group, I think
-        // this ordering is correct but there might be some easier underlying logic than maintaining
-        // a list: https://tl.net/forum/brood-war/98797-unit-ranks-priority (we also don't
-        // necessarily need to match this exactly for sounds/portraits)
-        let Some((construct_type, pos, owner)) = event.constructs.iter().find_map(|&e| {
-            constructs.get(e).ok().and_then(|(c, p, o)| {
-                if c.what_sounds().is_some() {
-                    Some((c, p, o))
-                } else {
-                    None
-                }
-            })
-        }) else {
-            continue;
-        };
+    let Ok(groups) = controlled_player.get_single() else {
+        return;
+    };
+    let shift = actions.pressed(InputAction::AddModifier);
+    let ctrl = actions.pressed(InputAction::GroupModifier);
 
-        let Some(controlled) = controlled_player.get_single().ok() else {
+    for group in 0..NUM_CONTROL_GROUPS as u8 {
+        if !actions.just_pressed(InputAction::ControlGroup(group)) {
             continue;
+        }
+        let action = if ctrl {
+            ControlGroupAction::Assign
+        } else if shift {
+            ControlGroupAction::Add
+        } else {
+            ControlGroupAction::Recall
         };
-        let owner = owner.map(|o| o.0);
-        if owner.is_some() && controlled.0 != owner.unwrap() {
-            // Don't play sounds for other players' constructs
+        command_writer.send(IssueCommandEvent(GameCommand::ControlGroup {
+            action,
+            group,
+        }));
+
+        if action != ControlGroupAction::Recall {
+            state.last_recall = None;
             continue;
         }
+        let now = time.elapsed();
+        let double_tap = state
+            .last_recall
+            .is_some_and(|(last, time)| last == group && now - time <= DOUBLE_TAP_TIME);
+        state.last_recall = if double_tap { None } else { Some((group, now)) };
+
+        if double_tap {
+            let group_positions = positions
+                .iter_many(&groups.0[group as usize])
+                .map(|&p| IVec2::from(p))
+                .collect::<Vec<_>>();
+            if !group_positions.is_empty() {
+                let center = group_positions.iter().sum::<IVec2>() / group_positions.len() as i32;
+                center_camera_writer.send(CenterCameraEvent(center.into()));
+            }
+        }
+    }
+}
+
+/// Selects all of the local player's army (their units that aren't workers), up to the maximum
+/// selection size.
+fn select_all_army_input(
+    actions: Res<ButtonInput<InputAction>>,
+    controlled_player: Query<&PlayerNumber, With<ControlledPlayer>>,
+    constructs: Query<(&ConstructId, &ConstructTypeId, &OwnedConstruct)>,
+    mut command_writer: EventWriter<IssueCommandEvent>,
+) {
+    if !actions.just_pressed(InputAction::SelectAllArmy) {
+        return;
+    }
+    let Ok(player) = controlled_player.get_single() else {
+        return;
+    };
 
-        let sound = rng.next_value(construct_type.what_sounds().unwrap());
-        commands.play_sound_from(sound, *construct_type, *pos);
+    let mut army = constructs
+        .iter()
+        .filter(|(_, ty, owner)| owner.0 == player.0 && is_army(**ty))
+        .map(|(&id, _, _)| id)
+        .collect::<Vec<_>>();
+    // Prefer the oldest units so that repeated presses select the same ones
+    army.sort();
+    army.truncate(MAX_SELECTION);
+    if !army.is_empty() {
+        command_writer.send(IssueCommandEvent(GameCommand::Select(army)));
     }
 }
 
+/// Returns whether constructs of this type are considered part of a player's army.
+fn is_army(construct_type: ConstructTypeId) -> bool {
+    let flags = construct_type.flags();
+    // NOTE(tec27): Larvae can move, but can't be given any orders so they aren't very useful to
+    // select with the rest of the army
+    construct_type != ConstructTypeId::ZergLarva
+        && !construct_type.is_building()
+        && flags.contains(ConstructFlags::CAN_MOVE)
+        && !flags.contains(ConstructFlags::WORKER)
+}
+
 // TODO(tec27): Pick a better/more accurate color for this
-const COLOR_SELF: Color = Color::srgb(0.1, 1.0, 0.3);
-const COLOR_ENEMY: Color = Color::srgb(1.0, 0.1, 0.3);
 const COLOR_NEUTRAL: Color = Color::srgb(1.0, 1.0, 0.3);
 
 // NOTE(tec27): This should be kept up to date with bevy's SpriteBundle, just remove the things that
@@ -512,20 +743,20 @@ struct TexturelessSpriteBundle {
 
 fn update_locally_selected(
     mut commands: Commands,
-    controlled_player: Query<(&PlayerNumber, &SelectedEntities), With<ControlledPlayer>>,
+    controlled_player: Query<&SelectedEntities, With<ControlledPlayer>>,
     last_selected: Query<(Entity, &Parent), With<LocallySelected>>,
     constructs: Query<(&ConstructTypeId, Option<&OwnedConstruct>)>,
     settings: Res<GameSettings>,
     selection_circles: Res<SelectionCircles>,
     anim_assets: Res<Assets<AnimAsset>>,
+    player_colors: PlayerColors,
 ) {
     for (e, p) in last_selected.iter() {
         commands.entity(p.get()).remove_children(&[e]);
         commands.entity(e).despawn_recursive();
     }
 
-    for (player_num, selected) in controlled_player.iter() {
-        let player_num = player_num.0;
+    for selected in controlled_player.iter() {
         for &e in selected.0.iter() {
             let (ty, owner) = constructs.get(e).unwrap();
             let Some(circle_id) = ty.flingy().sprite().selection_circle else {
@@ -543,7 +774,7 @@ fn update_locally_selected(
                             0.0,
                             offset as f32 * -settings.asset_quality.scale(),
                             // Display under the construct
-                            -0.1,
+                            UNDERLAY_Z_OFFSET,
                         )),
                         ..default()
                     },
@@ -553,12 +784,9 @@ fn update_locally_selected(
             if let Some(circle_asset) =
                 anim_assets.get(&selection_circles.handles[circle_id as usize])
             {
-                let color = match owner {
-                    Some(o) if o.0 == player_num => COLOR_SELF,
-                    // TODO(tec27): Handle alliances
-                    Some(_) => COLOR_ENEMY,
-                    _ => COLOR_NEUTRAL,
-                };
+                let color = owner
+                    .and_then(|o| player_colors.get(o.0))
+                    .map_or(COLOR_NEUTRAL, |c| c.color());
 
                 let anim = commands
                     .spawn((
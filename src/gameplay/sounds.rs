@@ -1,17 +1,53 @@
+//! Playback of game sounds. Like the original game, only a limited number of sounds can play at
+//! once, and which sounds get to play is decided by their priority. Sounds that happen somewhere
+//! on the map are attenuated and panned based on where they are relative to the camera's view.
+
 use std::ops::Index;
+use std::time::Duration;
 
 use bevy::{
-    audio::{PlaybackMode, Volume},
+    asset::LoadState,
+    audio::{AddAudioSource, Decodable, PlaybackMode, Sample, Source, Volume},
     ecs::world::Command,
     prelude::*,
+    window::PrimaryWindow,
 };
 
 use crate::{
     gamedata::{BwSound, BwSoundFlags, BwSoundId, ConstructTypeId, SOUNDS},
-    maps::position::Position,
-    settings::GameSettings,
+    maps::{
+        game_map::{GameMap, GameMapSize},
+        position::{position_to_translation, Position},
+    },
+    settings::{GameSettings, Volumes},
+    states::InGameOnly,
 };
 
+/// The maximum number of game sounds that can play at the same time.
+pub const MAX_SOUND_CHANNELS: usize = 8;
+/// How far (in game pixels) outside of the camera's view a sound has to be to play at its minimum
+/// volume.
+const ATTENUATION_DISTANCE: f32 = 512.0;
+/// The size of a tile in game pixels, used to convert distances into world space.
+const GAME_TILE_SIZE: f32 = 32.0;
+/// The priority of sounds from the map (e.g. ones played by triggers). These are often part of a
+/// map's story, so game sounds can't take their channel.
+const MAP_SOUND_PRIORITY: u8 = u8::MAX;
+
+pub fn plugin(app: &mut App) {
+    app.add_audio_source::<PannedAudio>()
+        .register_type::<SoundChannel>()
+        .register_type::<SoundCategory>()
+        .add_systems(
+            Update,
+            (
+                start_pending_sounds,
+                end_adjusted_sounds,
+                apply_category_volumes.run_if(resource_changed::<GameSettings>),
+            ),
+        );
+}
+
 impl Index<BwSoundId> for [BwSound; 1144] {
     type Output = BwSound;
 
@@ -58,88 +94,479 @@ pub struct PlayingSound;
 pub struct UnitSpeech(pub ConstructTypeId);
 
 /// Marker component for playing audio that shouldn't be preempted by new sound playback requests.
-/// Preemption will only happen for sounds of the same type (e.g. unit speech).
 #[derive(Component, Copy, Clone, Debug, Reflect)]
 pub struct NeverPreempt;
 
+/// Component for a game sound that is occupying one of the [MAX_SOUND_CHANNELS] sound channels.
+#[derive(Component, Copy, Clone, Debug, Reflect)]
+pub struct SoundChannel {
+    /// The game sound that is playing, or `None` if it is a sound from the map.
+    pub sound: Option<BwSoundId>,
+    /// When (in real time) the sound should be stopped, if it should end before its audio does.
+    ends_at: Option<Duration>,
+}
+
+/// Which of the volume settings applies to a playing sound.
+#[derive(Component, Copy, Clone, Debug, PartialEq, Eq, Reflect)]
+pub enum SoundCategory {
+    SoundEffects,
+    Music,
+}
+
+impl SoundCategory {
+    pub fn volume(&self, volumes: &Volumes) -> f32 {
+        match self {
+            Self::SoundEffects => volumes.sound_effects,
+            Self::Music => volumes.music,
+        }
+    }
+
+    /// Returns the volume to set on an [AudioSink] for this category. Unlike the volume a sound
+    /// starts playing with, [AudioSink::set_volume] doesn't take the [GlobalVolume] into account,
+    /// so it is included here.
+    pub fn sink_volume(&self, volumes: &Volumes) -> f32 {
+        self.volume(volumes) * volumes.global
+    }
+}
+
+/// The volume of a playing sound before its [SoundCategory] volume is applied.
+#[derive(Component, Copy, Clone, Debug, Reflect)]
+pub struct SoundVolume(pub f32);
+
+/// Component for a sound whose audio is still loading, and will start playing once it has.
+#[derive(Component, Clone, Debug)]
+struct PendingSound {
+    source: Handle<AudioSource>,
+    pan: f32,
+}
+
+/// An [AudioSource] that is played panned between the left (-1.0) and right (1.0) channels.
+#[derive(Asset, TypePath, Clone)]
+pub struct PannedAudio {
+    pub source: AudioSource,
+    pub pan: f32,
+}
+
+impl Decodable for PannedAudio {
+    type DecoderItem = <AudioSource as Decodable>::DecoderItem;
+    type Decoder = PannedSource<<AudioSource as Decodable>::Decoder>;
+
+    fn decoder(&self) -> Self::Decoder {
+        PannedSource::new(self.source.decoder(), self.pan)
+    }
+}
+
+/// A [Source] that applies panning to another source. Mono sources are converted to stereo.
+pub struct PannedSource<I: Source>
+where
+    I::Item: Sample,
+{
+    input: I,
+    input_channels: u16,
+    gains: [f32; 2],
+    channel: u16,
+    /// For mono sources, the sample that still needs to be output to the right channel.
+    pending: Option<I::Item>,
+}
+
+impl<I: Source> PannedSource<I>
+where
+    I::Item: Sample,
+{
+    pub fn new(input: I, pan: f32) -> Self {
+        Self {
+            input_channels: input.channels(),
+            input,
+            gains: pan_gains(pan),
+            channel: 0,
+            pending: None,
+        }
+    }
+}
+
+impl<I: Source> Iterator for PannedSource<I>
+where
+    I::Item: Sample,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.input_channels == 1 {
+            if let Some(sample) = self.pending.take() {
+                return Some(sample.amplify(self.gains[1]));
+            }
+            let sample = self.input.next()?;
+            self.pending = Some(sample);
+            return Some(sample.amplify(self.gains[0]));
+        }
+
+        let sample = self.input.next()?;
+        let channel = self.channel as usize;
+        self.channel = (self.channel + 1) % self.input_channels;
+        Some(match self.gains.get(channel) {
+            Some(&gain) => sample.amplify(gain),
+            None => sample,
+        })
+    }
+}
+
+impl<I: Source> Source for PannedSource<I>
+where
+    I::Item: Sample,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        let len = self.input.current_frame_len()?;
+        Some(if self.input_channels == 1 {
+            len * 2
+        } else {
+            len
+        })
+    }
+
+    fn channels(&self) -> u16 {
+        self.input_channels.max(2)
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+}
+
+/// Returns the gains for the left and right channels for a pan value.
+fn pan_gains(pan: f32) -> [f32; 2] {
+    let pan = pan.clamp(-1.0, 1.0);
+    [(1.0 - pan).min(1.0), (1.0 + pan).min(1.0)]
+}
+
+/// The area of the world (in world space) that is currently visible to the local player.
 #[derive(Debug, Copy, Clone)]
+struct SoundListener {
+    view: Rect,
+    /// How many world units make up a game pixel.
+    world_scale: f32,
+}
+
+/// How loud a sound should be, and where it should be panned to.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct SoundMix {
+    volume: f32,
+    pan: f32,
+}
+
+impl SoundMix {
+    const CENTERED: Self = Self {
+        volume: 1.0,
+        pan: 0.0,
+    };
+}
+
+/// Determines how a sound at `translation` (in world space) should be mixed for `listener`. Sounds
+/// within the view play at full volume, and get quieter the further outside of it they are, down
+/// to `min_volume` (a percentage).
+fn positional_mix(translation: Vec2, listener: &SoundListener, min_volume: u8) -> SoundMix {
+    let view = listener.view;
+    let outside = (view.min - translation)
+        .max(translation - view.max)
+        .max(Vec2::ZERO);
+    let distance = outside.length() / listener.world_scale;
+    let min_volume = (min_volume as f32 / 100.0).clamp(0.0, 1.0);
+    let volume = (1.0 - distance / ATTENUATION_DISTANCE).max(min_volume);
+
+    let half_width = view.half_size().x;
+    let pan = if half_width > 0.0 {
+        ((translation.x - view.center().x) / half_width).clamp(-1.0, 1.0)
+    } else {
+        0.0
+    };
+
+    SoundMix { volume, pan }
+}
+
+/// Returns the current [SoundListener] for the local player, if there is a camera looking at a map.
+fn sound_listener(world: &mut World) -> Option<SoundListener> {
+    let window_size = world
+        .query_filtered::<&Window, With<PrimaryWindow>>()
+        .get_single(world)
+        .ok()?
+        .size();
+    let (transform, projection) = world
+        .query_filtered::<(&Transform, &OrthographicProjection), With<Camera>>()
+        .get_single(world)
+        .ok()?;
+    let tile_size = world
+        .get_resource::<GameSettings>()?
+        .asset_quality
+        .tile_size();
+
+    Some(SoundListener {
+        view: Rect::from_center_size(
+            transform.translation.truncate(),
+            window_size * projection.scale,
+        ),
+        world_scale: tile_size.x / GAME_TILE_SIZE,
+    })
+}
+
+/// Where the audio for a [PlaySoundCommand] comes from.
+#[derive(Debug, Clone)]
+enum SoundSource {
+    /// One of the game's sounds, loaded from the CASC files.
+    Game(BwSoundId),
+    /// A sound that was loaded from the map's archive.
+    Map(Handle<AudioSource>),
+}
+
+impl SoundSource {
+    fn id(&self) -> Option<BwSoundId> {
+        match self {
+            Self::Game(sound) => Some(*sound),
+            Self::Map(_) => None,
+        }
+    }
+
+    fn priority(&self) -> u8 {
+        sound_priority(self.id())
+    }
+}
+
+fn sound_priority(sound: Option<BwSoundId>) -> u8 {
+    sound.map_or(MAP_SOUND_PRIORITY, |s| s.def().priority)
+}
+
+#[derive(Debug, Clone)]
 struct PlaySoundCommand {
-    sound: BwSoundId,
+    sound: SoundSource,
     construct_type: Option<ConstructTypeId>,
-    #[allow(dead_code)]
     position: Option<Position>,
 }
 
 impl Default for PlaySoundCommand {
     fn default() -> Self {
         Self {
-            sound: BwSoundId::new(1).unwrap(),
+            sound: SoundSource::Game(BwSoundId::new(1).unwrap()),
             construct_type: Default::default(),
             position: Default::default(),
         }
     }
 }
 
-impl Command for PlaySoundCommand {
-    fn apply(self, world: &mut World) {
-        // TODO(tec27): Adjust volume + length + play based on priority/etc.
-        // TODO(tec27): Handle one-at-a-time, seems to be for sound effects that would be annoying
-        // if duplicated (e.g. repair sounds)?
+impl PlaySoundCommand {
+    fn mix(&self, world: &mut World) -> SoundMix {
+        let Some(position) = self.position else {
+            return SoundMix::CENTERED;
+        };
+        let Some(map_size) = world
+            .query_filtered::<&GameMapSize, With<GameMap>>()
+            .get_single(world)
+            .ok()
+            .copied()
+        else {
+            return SoundMix::CENTERED;
+        };
+        let Some(listener) = sound_listener(world) else {
+            return SoundMix::CENTERED;
+        };
+        let tile_size = listener.world_scale * Vec2::splat(GAME_TILE_SIZE);
+        let translation = position_to_translation(&position, &map_size, tile_size);
+        let min_volume = self.sound.id().map_or(0, |s| s.def().min_volume);
+        positional_mix(translation, &listener, min_volume)
+    }
+
+    /// Returns the channels that need to be freed up for this sound to play, or `None` if it
+    /// shouldn't be played.
+    fn make_room(&self, world: &mut World) -> Option<Vec<Entity>> {
+        let sound_id = self.sound.id();
         let mut to_despawn = Vec::new();
-        if self.sound.is_unit_speech() {
+        let mut playing = world.query::<(Entity, &SoundChannel, Option<&UnitSpeech>)>();
+        let channels = playing
+            .iter(world)
+            .map(|(e, c, speech)| (e, c.sound, speech.map(|s| s.0)))
+            .collect::<Vec<_>>();
+
+        if sound_id.is_some_and(|s| s.is_one_at_a_time())
+            && channels.iter().any(|&(_, sound, _)| sound == sound_id)
+        {
+            return None;
+        }
+
+        if sound_id.is_some_and(|s| s.is_unit_speech()) {
             let construct_type = self.construct_type.unwrap_or_default();
-            let mut playing_sounds = world.query::<(Entity, &UnitSpeech, Has<NeverPreempt>)>();
-
-            for (s, unit, has_never_preempt) in playing_sounds.iter(world) {
-                if unit.0 == construct_type {
-                    if has_never_preempt {
-                        // We won't preempt this sound to play a new one, so we're done
-                        return;
-                    } else {
-                        // We're replacing this sound, so despawn it
-                        to_despawn.push(s);
-                    }
+            for &(entity, sound, speech) in &channels {
+                if speech != Some(construct_type) {
+                    continue;
+                }
+                if sound.is_some_and(|s| s.is_never_preempt()) {
+                    // We won't preempt this sound to play a new one, so we're done
+                    return None;
                 }
+                // We're replacing this sound
+                to_despawn.push(entity);
             }
         }
 
+        if channels.len() - to_despawn.len() >= MAX_SOUND_CHANNELS {
+            // All the channels are in use, so replace the least important sound that has a lower
+            // (or equal) priority than this one
+            let replaced = channels
+                .iter()
+                .filter(|(entity, sound, _)| {
+                    !to_despawn.contains(entity)
+                        && !sound.is_some_and(|s| s.is_never_preempt())
+                        && sound_priority(*sound) <= self.sound.priority()
+                })
+                .min_by_key(|(_, sound, _)| sound_priority(*sound))?;
+            to_despawn.push(replaced.0);
+        }
+
+        Some(to_despawn)
+    }
+}
+
+impl Command for PlaySoundCommand {
+    fn apply(self, world: &mut World) {
+        if !world.contains_resource::<Assets<PannedAudio>>() {
+            // Audio isn't available (e.g. we're running headless), so there's nothing to play to
+            return;
+        }
+
+        let Some(to_despawn) = self.make_room(world) else {
+            return;
+        };
         for s in to_despawn {
             world.despawn(s);
         }
 
-        let settings = world.get_resource::<GameSettings>().unwrap();
-        let assets = world.get_resource::<AssetServer>().unwrap();
-        let mut entity = world.spawn((
-            AudioBundle {
-                source: assets.load(format!(
+        let mix = self.mix(world);
+        let source = match &self.sound {
+            SoundSource::Game(sound) => {
+                let settings = world.get_resource::<GameSettings>().unwrap();
+                let assets = world.get_resource::<AssetServer>().unwrap();
+                assets.load(format!(
                     "casc-extracted/{}{}",
                     settings.audio_quality.asset_path(),
-                    self.sound.file()
-                )),
-                settings: PlaybackSettings {
-                    volume: Volume::new(settings.volumes.sound_effects),
-                    mode: PlaybackMode::Despawn,
-                    ..default()
-                },
+                    sound.file()
+                ))
+            }
+            SoundSource::Map(source) => source.clone(),
+        };
+        let sound_id = self.sound.id();
+        let mut entity = world.spawn((
+            PendingSound {
+                source,
+                pan: mix.pan,
             },
+            SoundChannel {
+                sound: sound_id,
+                ends_at: None,
+            },
+            SoundCategory::SoundEffects,
+            SoundVolume(mix.volume),
             PlayingSound,
         ));
-        if self.sound.is_unit_speech() {
+        let Some(sound) = sound_id else {
+            // Map sounds (e.g. mission briefings) can be long, so they shouldn't keep playing after
+            // the game is over
+            entity.insert(InGameOnly);
+            return;
+        };
+        if sound.is_unit_speech() {
             if let Some(construct_type) = self.construct_type {
                 entity.insert(UnitSpeech(construct_type));
             } else {
-                warn!(
-                    "Playing unit speech sound {:?} without a construct type",
-                    self.sound
-                )
+                warn!("Playing unit speech sound {sound:?} without a construct type")
             }
         }
-        if self.sound.is_never_preempt() {
+        if sound.is_never_preempt() {
             entity.insert(NeverPreempt);
         }
     }
 }
 
+/// Starts playing sounds once their audio has loaded.
+fn start_pending_sounds(
+    mut commands: Commands,
+    mut pending: Query<(
+        Entity,
+        &PendingSound,
+        &mut SoundChannel,
+        &SoundCategory,
+        &SoundVolume,
+    )>,
+    audio_sources: Res<Assets<AudioSource>>,
+    mut panned_audio: ResMut<Assets<PannedAudio>>,
+    asset_server: Res<AssetServer>,
+    settings: Res<GameSettings>,
+    time: Res<Time<Real>>,
+) {
+    for (entity, pending, mut channel, category, volume) in &mut pending {
+        let Some(source) = audio_sources.get(&pending.source) else {
+            if let LoadState::Failed(_) = asset_server.load_state(&pending.source) {
+                // The error is reported by the load_error plugin, we just need to free the channel
+                commands.entity(entity).despawn();
+            }
+            continue;
+        };
+
+        let adjustment = Duration::from_millis(
+            channel
+                .sound
+                .map_or(0, |s| s.def().length_adjustment as u64),
+        );
+        if !adjustment.is_zero() {
+            channel.ends_at = source
+                .decoder()
+                .total_duration()
+                .filter(|&duration| duration > adjustment)
+                .map(|duration| time.elapsed() + duration - adjustment);
+        }
+
+        commands
+            .entity(entity)
+            .remove::<PendingSound>()
+            .insert(AudioSourceBundle {
+                source: panned_audio.add(PannedAudio {
+                    source: source.clone(),
+                    pan: pending.pan,
+                }),
+                settings: PlaybackSettings {
+                    volume: Volume::new(volume.0 * category.volume(&settings.volumes)),
+                    mode: PlaybackMode::Despawn,
+                    ..default()
+                },
+            });
+    }
+}
+
+/// Stops sounds that have a `length_adjustment` once their adjusted length has elapsed.
+fn end_adjusted_sounds(
+    mut commands: Commands,
+    channels: Query<(Entity, &SoundChannel)>,
+    time: Res<Time<Real>>,
+) {
+    for (entity, channel) in &channels {
+        if channel
+            .ends_at
+            .is_some_and(|ends_at| ends_at <= time.elapsed())
+        {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Updates the volume of playing sounds when the volume settings change.
+fn apply_category_volumes(
+    sinks: Query<(&AudioSink, &SoundCategory, &SoundVolume)>,
+    settings: Res<GameSettings>,
+) {
+    for (sink, category, volume) in &sinks {
+        sink.set_volume(volume.0 * category.sink_volume(&settings.volumes));
+    }
+}
+
 pub trait PlaySoundCommandsExt {
     fn play_sound(&mut self, sound_id: BwSoundId);
     fn play_sound_from(
@@ -149,12 +576,14 @@ pub trait PlaySoundCommandsExt {
         position: Position,
     );
     fn play_sound_at(&mut self, sound_id: BwSoundId, position: Position);
+    /// Plays a sound that was loaded from the map, e.g. for a trigger.
+    fn play_map_sound(&mut self, source: Handle<AudioSource>);
 }
 
 impl<'w, 's> PlaySoundCommandsExt for Commands<'w, 's> {
     fn play_sound(&mut self, sound_id: BwSoundId) {
         self.add(PlaySoundCommand {
-            sound: sound_id,
+            sound: SoundSource::Game(sound_id),
             ..default()
         });
     }
@@ -166,7 +595,7 @@ impl<'w, 's> PlaySoundCommandsExt for Commands<'w, 's> {
         position: Position,
     ) {
         self.add(PlaySoundCommand {
-            sound: sound_id,
+            sound: SoundSource::Game(sound_id),
             construct_type: Some(construct),
             position: Some(position),
         });
@@ -174,9 +603,102 @@ impl<'w, 's> PlaySoundCommandsExt for Commands<'w, 's> {
 
     fn play_sound_at(&mut self, sound_id: BwSoundId, position: Position) {
         self.add(PlaySoundCommand {
-            sound: sound_id,
+            sound: SoundSource::Game(sound_id),
             position: Some(position),
             ..default()
         });
     }
+
+    fn play_map_sound(&mut self, source: Handle<AudioSource>) {
+        self.add(PlaySoundCommand {
+            sound: SoundSource::Map(source),
+            ..default()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listener() -> SoundListener {
+        SoundListener {
+            view: Rect::new(-320.0, -240.0, 320.0, 240.0),
+            world_scale: 2.0,
+        }
+    }
+
+    #[test]
+    fn sounds_in_view() {
+        assert_eq!(
+            positional_mix(Vec2::ZERO, &listener(), 0),
+            SoundMix::CENTERED
+        );
+        assert_eq!(
+            positional_mix(Vec2::new(160.0, 100.0), &listener(), 0),
+            SoundMix {
+                volume: 1.0,
+                pan: 0.5
+            }
+        );
+    }
+
+    #[test]
+    fn sounds_outside_view() {
+        // 256 game pixels to the left of the view
+        let mix = positional_mix(Vec2::new(-832.0, 0.0), &listener(), 0);
+        assert_eq!(mix.volume, 0.5);
+        assert_eq!(mix.pan, -1.0);
+
+        let far_away = Vec2::new(0.0, 10000.0);
+        assert_eq!(positional_mix(far_away, &listener(), 0).volume, 0.0);
+        assert_eq!(positional_mix(far_away, &listener(), 25).volume, 0.25);
+    }
+
+    #[test]
+    fn sink_volumes() {
+        let volumes = Volumes {
+            global: 0.5,
+            music: 0.25,
+            sound_effects: 1.0,
+        };
+        assert_eq!(SoundCategory::Music.sink_volume(&volumes), 0.125);
+        assert_eq!(SoundCategory::SoundEffects.sink_volume(&volumes), 0.5);
+    }
+
+    #[test]
+    fn panning() {
+        assert_eq!(pan_gains(0.0), [1.0, 1.0]);
+        assert_eq!(pan_gains(-1.0), [1.0, 0.0]);
+        assert_eq!(pan_gains(0.5), [0.5, 1.0]);
+    }
+
+    #[test]
+    fn map_sounds_take_priority() {
+        let game_sound = BwSoundId::new(1).unwrap();
+        let fill_channels = |world: &mut World, sound| {
+            for _ in 0..MAX_SOUND_CHANNELS {
+                world.spawn(SoundChannel {
+                    sound,
+                    ends_at: None,
+                });
+            }
+        };
+
+        let mut world = World::new();
+        fill_channels(&mut world, Some(game_sound));
+        let map_sound = PlaySoundCommand {
+            sound: SoundSource::Map(Handle::default()),
+            ..default()
+        };
+        assert_eq!(map_sound.make_room(&mut world).map(|e| e.len()), Some(1));
+
+        let mut world = World::new();
+        fill_channels(&mut world, None);
+        let game_sound = PlaySoundCommand {
+            sound: SoundSource::Game(game_sound),
+            ..default()
+        };
+        assert_eq!(game_sound.make_room(&mut world), None);
+    }
 }
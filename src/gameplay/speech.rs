@@ -0,0 +1,403 @@
+//! Unit speech and advisor lines for the local player: "what" sounds when units are selected (and
+//! increasingly annoyed ones when the same unit is clicked over and over), "yes" sounds when they
+//! are given commands, "ready" sounds when they finish training, and the advisor telling the player
+//! why something couldn't be done.
+
+use std::cmp::Reverse;
+
+use bevy::prelude::*;
+
+use crate::{
+    gamedata::{BwSoundId, BwSoundRange, ConstructFlags, ConstructTypeId},
+    maps::position::Position,
+    net::IssueCommandEvent,
+    races::Race,
+    random::UnsyncedLcgRand,
+    states::AppState,
+};
+
+use super::{
+    constructs::OwnedConstruct,
+    create_construct::ConstructReadyEvent,
+    players::{ControlledPlayer, Player, PlayerNumber},
+    resources::{PlayerResources, PlayerSupply},
+    selection::{ConstructsSelectedEvent, SelectedEntities},
+    sounds::PlaySoundCommandsExt,
+};
+
+/// How many times in a row a unit can be clicked before it starts getting annoyed.
+const ANNOYED_AFTER_CLICKS: u32 = 4;
+
+pub fn plugin(app: &mut App) {
+    app.add_event::<AdvisorErrorEvent>()
+        .init_resource::<SpeechClicks>()
+        .add_systems(OnEnter(AppState::InGame), reset_speech_clicks)
+        .add_systems(
+            Update,
+            (
+                play_selection_sounds,
+                play_command_sounds,
+                play_ready_sounds,
+                play_advisor_errors,
+            )
+                .run_if(in_state(AppState::InGame)),
+        );
+}
+
+/// A reason the local player couldn't do something, which the advisor for their race will tell
+/// them about.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AdvisorError {
+    NotEnoughMinerals,
+    NotEnoughGas,
+    /// More supply is needed (e.g. "You must construct additional pylons").
+    SupplyBlocked,
+}
+
+impl AdvisorError {
+    /// Returns the advisor sound for this error for the specified race.
+    pub fn sound(&self, race: Race) -> BwSoundId {
+        // NOTE(tec27): These are ordered Zerg, Terran, Protoss like BW's race IDs
+        let first_id = match self {
+            AdvisorError::NotEnoughMinerals => 147,
+            AdvisorError::NotEnoughGas => 150,
+            AdvisorError::SupplyBlocked => 153,
+        };
+        let offset = match race {
+            Race::Zerg => 0,
+            Race::Terran => 1,
+            Race::Protoss => 2,
+        };
+        BwSoundId::new(first_id + offset).unwrap()
+    }
+}
+
+/// Event fired when the local player tries to do something that fails because of an
+/// [AdvisorError].
+#[derive(Event, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AdvisorErrorEvent(pub AdvisorError);
+
+/// Checks whether a player with the specified resources and supply can make a construct of type
+/// `construct_type`, returning the reason they can't if not.
+pub fn check_can_make(
+    construct_type: ConstructTypeId,
+    resources: &PlayerResources,
+    supply: &PlayerSupply,
+) -> Result<(), AdvisorError> {
+    let def = construct_type.def();
+    if resources.minerals < def.mineral_cost as u32 {
+        return Err(AdvisorError::NotEnoughMinerals);
+    }
+    if resources.gas < def.vespene_cost as u32 {
+        return Err(AdvisorError::NotEnoughGas);
+    }
+    if let Some(race) = construct_type.race() {
+        let supply = supply.get(race);
+        if def.supply_required > 0 && supply.used + def.supply_required as u32 > supply.max() {
+            return Err(AdvisorError::SupplyBlocked);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the construct that should speak for a group of constructs, along with its type. Only
+/// constructs that have the sounds returned by `sounds` are considered.
+// NOTE(tec27): BW has a ranking for this, but it doesn't need to be matched exactly for sounds:
+// https://tl.net/forum/brood-war/98797-unit-ranks-priority. We prefer army units, then workers,
+// then anything else, and pick the most "valuable" unit (by build score) among those.
+fn pick_speaker<'a>(
+    constructs: impl IntoIterator<Item = (Entity, &'a ConstructTypeId)>,
+    sounds: impl Fn(&ConstructTypeId) -> Option<BwSoundRange>,
+) -> Option<(Entity, ConstructTypeId)> {
+    constructs
+        .into_iter()
+        .filter(|(_, ty)| sounds(ty).is_some())
+        // min_by_key returns the first of equal elements, so ties go to the earliest selected
+        .min_by_key(|(_, ty)| {
+            let is_unit = ty.is_unit() && !ty.is_building();
+            let is_worker = ty.flags().contains(ConstructFlags::WORKER);
+            Reverse((is_unit && !is_worker, is_unit, ty.def().build_score))
+        })
+        .map(|(e, &ty)| (e, ty))
+}
+
+/// Tracks repeated clicks on the same construct, so it can get annoyed.
+#[derive(Resource, Debug, Default)]
+struct SpeechClicks {
+    construct: Option<Entity>,
+    clicks: u32,
+}
+
+impl SpeechClicks {
+    /// Records that `entity` was selected by itself and returns the sound it should make.
+    fn click(
+        &mut self,
+        entity: Entity,
+        construct_type: ConstructTypeId,
+        rng: &mut UnsyncedLcgRand,
+    ) -> Option<BwSoundId> {
+        if self.construct == Some(entity) {
+            self.clicks += 1;
+        } else {
+            self.construct = Some(entity);
+            self.clicks = 1;
+        }
+
+        if self.clicks > ANNOYED_AFTER_CLICKS {
+            let index = (self.clicks - ANNOYED_AFTER_CLICKS - 1) as usize;
+            if let Some(sound) = construct_type
+                .piss_sounds()
+                .and_then(|s| s.iter().nth(index))
+            {
+                return Some(sound);
+            }
+            // Either it has no annoyed sounds or it has played all of them, so it calms back down
+            self.clicks = 1;
+        }
+
+        construct_type.what_sounds().map(|s| rng.next_value(s))
+    }
+
+    fn reset(&mut self) {
+        self.construct = None;
+        self.clicks = 0;
+    }
+}
+
+fn reset_speech_clicks(mut clicks: ResMut<SpeechClicks>) {
+    clicks.reset();
+}
+
+fn play_selection_sounds(
+    mut commands: Commands,
+    mut selection_events: EventReader<ConstructsSelectedEvent>,
+    constructs: Query<(&ConstructTypeId, &Position, Option<&OwnedConstruct>)>,
+    mut clicks: ResMut<SpeechClicks>,
+    mut rng: ResMut<UnsyncedLcgRand>,
+    controlled_player: Query<&PlayerNumber, With<ControlledPlayer>>,
+) {
+    for event in selection_events.read() {
+        let Some((entity, construct_type)) = pick_speaker(
+            event
+                .constructs
+                .iter()
+                .filter_map(|&e| constructs.get(e).ok().map(|(ty, _, _)| (e, ty))),
+            |ty| ty.what_sounds(),
+        ) else {
+            clicks.reset();
+            continue;
+        };
+        let Ok((_, &pos, owner)) = constructs.get(entity) else {
+            continue;
+        };
+
+        let Some(controlled) = controlled_player.get_single().ok() else {
+            continue;
+        };
+        if owner.is_some_and(|o| o.0 != controlled.0) {
+            // Don't play sounds for other players' constructs
+            continue;
+        }
+
+        let sound = if event.constructs.len() == 1 {
+            clicks.click(entity, construct_type, &mut rng)
+        } else {
+            clicks.reset();
+            construct_type.what_sounds().map(|s| rng.next_value(s))
+        };
+        if let Some(sound) = sound {
+            commands.play_sound_from(sound, construct_type, pos);
+        }
+    }
+}
+
+fn play_command_sounds(
+    mut commands: Commands,
+    mut issued_commands: EventReader<IssueCommandEvent>,
+    controlled_player: Query<(&SelectedEntities, &PlayerNumber), With<ControlledPlayer>>,
+    constructs: Query<(&ConstructTypeId, &Position, Option<&OwnedConstruct>)>,
+    mut rng: ResMut<UnsyncedLcgRand>,
+) {
+    // Only one acknowledgement is needed no matter how many commands were given this frame
+    let orders = issued_commands
+        .read()
+        .filter(|IssueCommandEvent(c)| c.is_order())
+        .count();
+    if orders == 0 {
+        return;
+    }
+    let Ok((selected, player)) = controlled_player.get_single() else {
+        return;
+    };
+
+    // Only the local player's own constructs can be given commands
+    let Some((entity, construct_type)) = pick_speaker(
+        selected.0.iter().filter_map(|&e| {
+            constructs
+                .get(e)
+                .ok()
+                .filter(|(_, _, owner)| owner.is_some_and(|o| o.0 == player.0))
+                .map(|(ty, _, _)| (e, ty))
+        }),
+        |ty| ty.yes_sounds(),
+    ) else {
+        return;
+    };
+    let Ok((_, &pos, _)) = constructs.get(entity) else {
+        return;
+    };
+
+    let sound = rng.next_value(construct_type.yes_sounds().unwrap());
+    commands.play_sound_from(sound, construct_type, pos);
+}
+
+fn play_ready_sounds(
+    mut commands: Commands,
+    mut ready_events: EventReader<ConstructReadyEvent>,
+    constructs: Query<(&ConstructTypeId, &Position, &OwnedConstruct)>,
+    controlled_player: Query<&PlayerNumber, With<ControlledPlayer>>,
+) {
+    let controlled = controlled_player.get_single().ok();
+    for event in ready_events.read() {
+        let Ok((construct_type, &pos, owner)) = constructs.get(event.entity) else {
+            continue;
+        };
+        if controlled.map(|p| p.0) != Some(owner.0) {
+            continue;
+        }
+        if let Some(sound) = construct_type.ready_sound() {
+            commands.play_sound_from(sound, *construct_type, pos);
+        }
+    }
+}
+
+fn play_advisor_errors(
+    mut commands: Commands,
+    mut error_events: EventReader<AdvisorErrorEvent>,
+    controlled_player: Query<&Player, With<ControlledPlayer>>,
+) {
+    let Ok(player) = controlled_player.get_single() else {
+        error_events.clear();
+        return;
+    };
+    for AdvisorErrorEvent(error) in error_events.read() {
+        commands.play_sound(error.sound(player.race));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gameplay::resources::RaceSupply;
+
+    use super::*;
+
+    #[test]
+    fn speaker_priority() {
+        let marine = ConstructTypeId::TerranMarine;
+        let scv = ConstructTypeId::TerranScv;
+        let battlecruiser = ConstructTypeId::TerranBattlecruiser;
+        let e = Entity::from_raw;
+
+        let speaker = |constructs: &[(Entity, ConstructTypeId)]| {
+            pick_speaker(constructs.iter().map(|(e, ty)| (*e, ty)), |ty| {
+                ty.what_sounds()
+            })
+        };
+        assert_eq!(
+            speaker(&[(e(1), scv), (e(2), marine), (e(3), battlecruiser)]),
+            Some((e(3), battlecruiser))
+        );
+        assert_eq!(
+            speaker(&[(e(1), scv), (e(2), marine), (e(3), marine)]),
+            Some((e(2), marine))
+        );
+        assert_eq!(speaker(&[(e(1), scv)]), Some((e(1), scv)));
+        assert_eq!(speaker(&[]), None);
+    }
+
+    #[test]
+    fn annoyed_clicks() {
+        let marine = ConstructTypeId::TerranMarine;
+        let what = marine.what_sounds().unwrap();
+        let piss = marine.piss_sounds().unwrap().iter().collect::<Vec<_>>();
+        let mut rng = UnsyncedLcgRand::default();
+        let mut clicks = SpeechClicks::default();
+        let marine_entity = Entity::from_raw(1);
+
+        for _ in 0..ANNOYED_AFTER_CLICKS {
+            let sound = clicks.click(marine_entity, marine, &mut rng).unwrap();
+            assert!(what.contains(sound));
+        }
+        for &expected in &piss {
+            assert_eq!(
+                clicks.click(marine_entity, marine, &mut rng),
+                Some(expected)
+            );
+        }
+        // After all the annoyed sounds, it starts over
+        let sound = clicks.click(marine_entity, marine, &mut rng).unwrap();
+        assert!(what.contains(sound));
+
+        // Clicking a different unit resets the count
+        for _ in 0..ANNOYED_AFTER_CLICKS {
+            clicks.click(marine_entity, marine, &mut rng);
+        }
+        let sound = clicks.click(Entity::from_raw(2), marine, &mut rng).unwrap();
+        assert!(what.contains(sound));
+    }
+
+    #[test]
+    fn can_make_checks() {
+        let marine = ConstructTypeId::TerranMarine;
+        let mut supply = PlayerSupply::default();
+        supply.races[Race::Terran as usize] = RaceSupply {
+            used: 18,
+            provided: 20,
+        };
+        let resources = PlayerResources {
+            minerals: 50,
+            gas: 0,
+        };
+        assert_eq!(check_can_make(marine, &resources, &supply), Ok(()));
+        assert_eq!(
+            check_can_make(
+                marine,
+                &PlayerResources {
+                    minerals: 49,
+                    gas: 0
+                },
+                &supply
+            ),
+            Err(AdvisorError::NotEnoughMinerals)
+        );
+        assert_eq!(
+            check_can_make(ConstructTypeId::TerranGhost, &resources, &supply),
+            Err(AdvisorError::NotEnoughGas)
+        );
+
+        supply.races[Race::Terran as usize].used = 20;
+        assert_eq!(
+            check_can_make(marine, &resources, &supply),
+            Err(AdvisorError::SupplyBlocked)
+        );
+    }
+
+    #[test]
+    fn advisor_sounds() {
+        use crate::gamedata::SOUNDS;
+
+        let file = |error: AdvisorError, race| SOUNDS[error.sound(race).get() as usize].file;
+        assert_eq!(
+            file(AdvisorError::SupplyBlocked, Race::Protoss),
+            "Protoss\\Advisor\\PAdErr02.WAV"
+        );
+        assert_eq!(
+            file(AdvisorError::NotEnoughMinerals, Race::Zerg),
+            "Zerg\\Advisor\\ZAdErr00.WAV"
+        );
+        assert_eq!(
+            file(AdvisorError::NotEnoughGas, Race::Terran),
+            "Terran\\Advisor\\tAdErr01.WAV"
+        );
+    }
+}
@@ -3,3 +3,12 @@ use bevy::prelude::*;
 /// Marker component for constructs that are able to rotate.
 #[derive(Component, Debug, Copy, Clone, Default)]
 pub struct CanTurn;
+
+/// Marker component for constructs that can't be damaged (e.g. because a trigger made them
+/// invincible).
+#[derive(Component, Debug, Copy, Clone, Default)]
+pub struct Invincible;
+
+/// The number of constructs that this construct has killed.
+#[derive(Component, Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Kills(pub u32);
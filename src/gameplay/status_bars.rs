@@ -0,0 +1,237 @@
+//! Status bars (health, shields, and energy) shown underneath selected and hovered constructs.
+
+use bevy::{
+    prelude::*,
+    render::view::NoFrustumCulling,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+
+use crate::{
+    gamedata::ConstructTypeId,
+    math::FixedPoint,
+    render::{
+        draw_order::OVERLAY_Z_OFFSET,
+        status_bar::{StatusBarMaterial, StatusBarParams, MAX_STATUS_BAR_ROWS},
+    },
+    settings::GameSettings,
+    states::AppState,
+};
+
+use super::{
+    energy::Energy,
+    health::Health,
+    players::ControlledPlayer,
+    selection::{HoveredConstruct, SelectedEntities},
+    shield::Shield,
+};
+
+/// The width of each segment of a status bar (including its border), in SD pixels.
+const SEGMENT_WIDTH: i32 = 3;
+/// The height of each row of a status bar (including its border), in SD pixels.
+const ROW_HEIGHT: i32 = 4;
+/// The space between the bottom of a construct and its status bars, in SD pixels.
+const STATUS_BAR_GAP: i32 = 4;
+
+const SHIELD_COLOR: Color = Color::srgb(0.2, 0.4, 0.9);
+const ENERGY_COLOR: Color = Color::srgb(0.75, 0.3, 0.85);
+const HEALTH_HIGH_COLOR: Color = Color::srgb(0.1, 0.75, 0.1);
+const HEALTH_MEDIUM_COLOR: Color = Color::srgb(0.85, 0.8, 0.1);
+const HEALTH_LOW_COLOR: Color = Color::srgb(0.8, 0.1, 0.1);
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(Startup, setup_status_bar_assets)
+        .add_systems(
+            PostUpdate,
+            (spawn_status_bars, update_status_bars)
+                .chain()
+                .run_if(in_state(AppState::InGame)),
+        );
+}
+
+/// Marker component for the entity that draws the status bars of its parent construct.
+#[derive(Component, Debug)]
+pub struct StatusBars;
+
+#[derive(Resource, Debug)]
+struct StatusBarAssets {
+    mesh: Mesh2dHandle,
+}
+
+fn setup_status_bar_assets(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.insert_resource(StatusBarAssets {
+        mesh: meshes.add(Rectangle::new(1.0, 1.0)).into(),
+    });
+}
+
+/// Returns how many segments a status bar of the specified width (in SD pixels) is split into.
+fn segment_count(width: i32) -> u32 {
+    ((width - 1) / SEGMENT_WIDTH).max(1) as u32
+}
+
+/// Returns how many of `segments` should be filled in to represent `current` out of `max`. Any
+/// non-zero amount fills at least one segment.
+fn filled_segments(current: FixedPoint, max: FixedPoint, segments: u32) -> u32 {
+    if current <= FixedPoint::ZERO || max <= FixedPoint::ZERO {
+        return 0;
+    }
+    let filled = (current.to_num::<f32>() / max.to_num::<f32>() * segments as f32).ceil();
+    (filled as u32).clamp(1, segments)
+}
+
+/// Returns the color of a health bar based on how much health is remaining.
+pub(crate) fn health_color(current: FixedPoint, max: FixedPoint) -> Color {
+    let percent = current.to_num::<f32>() / max.to_num::<f32>().max(1.0);
+    if percent > 0.66 {
+        HEALTH_HIGH_COLOR
+    } else if percent > 0.33 {
+        HEALTH_MEDIUM_COLOR
+    } else {
+        HEALTH_LOW_COLOR
+    }
+}
+
+/// Spawns (hidden) status bars for any construct that should display them.
+fn spawn_status_bars(
+    mut commands: Commands,
+    q_constructs: Query<(Entity, &ConstructTypeId), Added<Health>>,
+    assets: Res<StatusBarAssets>,
+    mut materials: ResMut<Assets<StatusBarMaterial>>,
+    settings: Res<GameSettings>,
+) {
+    let scale = settings.asset_quality.scale();
+    for (entity, construct_type) in q_constructs.iter() {
+        let sprite = construct_type.flingy().sprite();
+        let Some(width) = sprite.health_bar else {
+            continue;
+        };
+
+        let bounds = construct_type.bounds();
+        let top = bounds
+            .bottom
+            .max(sprite.selection_circle_offset.unwrap_or(0) as i32)
+            + STATUS_BAR_GAP;
+        let material = materials.add(StatusBarMaterial {
+            params: StatusBarParams {
+                size: Vec2::new(width as f32, 0.0) * scale,
+                scale,
+                segments: segment_count(width as i32),
+                ..default()
+            },
+        });
+
+        let bars = commands
+            .spawn((
+                StatusBars,
+                MaterialMesh2dBundle {
+                    mesh: assets.mesh.clone(),
+                    material,
+                    transform: Transform::from_xyz(0.0, -top as f32 * scale, OVERLAY_Z_OFFSET),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                NoFrustumCulling,
+            ))
+            .id();
+        commands.entity(entity).add_child(bars);
+    }
+}
+
+/// Shows the status bars for selected and hovered constructs (or all constructs, if the setting
+/// is enabled) and updates them to match the construct's current stats.
+fn update_status_bars(
+    mut q_bars: Query<(&Parent, &Handle<StatusBarMaterial>, &mut Visibility), With<StatusBars>>,
+    q_constructs: Query<(&Health, Option<&Shield>, Option<&Energy>)>,
+    controlled_player: Query<&SelectedEntities, With<ControlledPlayer>>,
+    hovered: Option<Res<HoveredConstruct>>,
+    mut materials: ResMut<Assets<StatusBarMaterial>>,
+    settings: Res<GameSettings>,
+) {
+    let selected = controlled_player.get_single().ok();
+    let hovered = hovered.and_then(|h| h.0);
+
+    for (parent, material_handle, mut visibility) in q_bars.iter_mut() {
+        let construct = parent.get();
+        let shown = settings.always_show_status_bars
+            || hovered == Some(construct)
+            || selected.is_some_and(|s| s.0.contains(&construct));
+        let new_visibility = if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != new_visibility {
+            *visibility = new_visibility;
+        }
+        if !shown {
+            continue;
+        }
+
+        let Ok((health, shield, energy)) = q_constructs.get(construct) else {
+            continue;
+        };
+        let Some(material) = materials.get(material_handle) else {
+            continue;
+        };
+
+        let old_params = material.params;
+        let mut params = old_params;
+        let mut rows = 0;
+        let mut add_row = |current: FixedPoint, max: FixedPoint, color: Color| {
+            params.filled[rows] = filled_segments(current, max, params.segments);
+            params.colors[rows] = LinearRgba::from(color).to_vec4();
+            rows += 1;
+        };
+        if let Some(shield) = shield {
+            add_row(shield.current, shield.max, SHIELD_COLOR);
+        }
+        add_row(
+            health.current,
+            health.max,
+            health_color(health.current, health.max),
+        );
+        if let Some(energy) = energy {
+            add_row(energy.current, energy.max, ENERGY_COLOR);
+        }
+        debug_assert!(rows <= MAX_STATUS_BAR_ROWS);
+        params.rows = rows as u32;
+        params.size.y = (rows as i32 * ROW_HEIGHT + 1) as f32 * params.scale;
+
+        // Only touch the material if something changed, since that causes it to be re-uploaded
+        if params != old_params {
+            if let Some(material) = materials.get_mut(material_handle) {
+                material.params = params;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments() {
+        assert_eq!(segment_count(22), 7);
+        assert_eq!(segment_count(1), 1);
+
+        let max = FixedPoint::from_num(40);
+        assert_eq!(filled_segments(max, max, 7), 7);
+        assert_eq!(filled_segments(FixedPoint::from_num(20), max, 7), 4);
+        assert_eq!(filled_segments(FixedPoint::from_num(0.5), max, 7), 1);
+        assert_eq!(filled_segments(FixedPoint::ZERO, max, 7), 0);
+    }
+
+    #[test]
+    fn health_colors() {
+        let max = FixedPoint::from_num(100);
+        assert_eq!(health_color(max, max), HEALTH_HIGH_COLOR);
+        assert_eq!(
+            health_color(FixedPoint::from_num(50), max),
+            HEALTH_MEDIUM_COLOR
+        );
+        assert_eq!(
+            health_color(FixedPoint::from_num(10), max),
+            HEALTH_LOW_COLOR
+        );
+    }
+}
@@ -0,0 +1,315 @@
+//! Training constructs from other constructs (e.g. a Barracks training Marines). Each producer has
+//! a queue of up to [MAX_TRAINING_QUEUE] constructs. Queued constructs are created right away
+//! (hidden, and paid for), but only the first one in the queue counts down its build time.
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{gamedata::ConstructTypeId, maps::position::Position, net::GameCommand};
+
+use super::{
+    build_time::UnderConstruction,
+    constructs::{ConstructId, OwnedConstruct},
+    create_construct::{
+        create_constructs, finish_constructs, CreateConstructEvent, FinishConstructEvent,
+    },
+    game_commands::{ExecCommandsSet, ExecGameCommand, ExecutingCommand},
+    health::Health,
+    players::PlayerEntities,
+    resources::{PlayerResources, PlayerSupply},
+    selection::SelectedEntities,
+    shield::Shield,
+    speech::check_can_make,
+    SimulationSet,
+};
+
+/// The most constructs a single producer can have queued for training at once.
+pub const MAX_TRAINING_QUEUE: usize = 5;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<PendingTrains>()
+        .add_systems(ExecGameCommand, apply_train_command)
+        .add_systems(
+            FixedUpdate,
+            (
+                clear_pending_trains.before(ExecCommandsSet),
+                progress_training
+                    .after(create_constructs)
+                    .before(finish_constructs),
+            )
+                .in_set(SimulationSet),
+        );
+}
+
+/// Component for a construct that is queued for training at (or being trained by) the specified
+/// producer. This is removed once training completes.
+#[derive(Component, Debug, Copy, Clone, PartialEq, Eq)]
+#[component(storage = "SparseSet")]
+pub struct TrainedBy(pub Entity);
+
+/// A training request accepted this frame, whose construct hasn't been created yet.
+struct PendingTrain {
+    player: u8,
+    producer: Entity,
+    construct_type: ConstructTypeId,
+}
+
+/// The training requests accepted this frame. The constructs for these are only created after all
+/// of the turn's commands have been executed, so this is needed to account for them in later
+/// commands.
+#[derive(Resource, Default)]
+struct PendingTrains(Vec<PendingTrain>);
+
+fn clear_pending_trains(mut pending: ResMut<PendingTrains>) {
+    pending.0.clear();
+}
+
+/// Executes a train command, queueing the construct at one of the player's selected producers if
+/// they can afford it.
+fn apply_train_command(
+    executing: Res<ExecutingCommand>,
+    mut pending: ResMut<PendingTrains>,
+    player_entities: Res<PlayerEntities>,
+    mut players: Query<(&SelectedEntities, &mut PlayerResources, &PlayerSupply)>,
+    producers: Query<(
+        &ConstructTypeId,
+        &Position,
+        &OwnedConstruct,
+        Has<UnderConstruction>,
+    )>,
+    queued: Query<&TrainedBy>,
+    mut create_writer: EventWriter<CreateConstructEvent>,
+) {
+    let GameCommand::Train(construct_type) = executing.command else {
+        return;
+    };
+    let player = executing.player;
+    let Some(Ok((selected, mut resources, supply))) =
+        player_entities.get(player).map(|e| players.get_mut(e))
+    else {
+        return;
+    };
+
+    let queue_len = |producer: Entity| {
+        queued.iter().filter(|t| t.0 == producer).count()
+            + pending.0.iter().filter(|p| p.producer == producer).count()
+    };
+    // NOTE(tec27): Like BW, if multiple producers are selected the one with the shortest
+    // queue is used (ties go to the earliest selected)
+    let Some(producer) = selected
+        .0
+        .iter()
+        .copied()
+        .filter(|&e| {
+            producers
+                .get(e)
+                .is_ok_and(|(ty, _, owner, under_construction)| {
+                    owner.0 == player
+                        && !under_construction
+                        && ty.trains().contains(&construct_type)
+                })
+        })
+        .filter(|&e| queue_len(e) < MAX_TRAINING_QUEUE)
+        .min_by_key(|&e| queue_len(e))
+    else {
+        return;
+    };
+
+    // Supply is only recalculated at the end of the frame, so account for anything else
+    // queued by this player this frame
+    // NOTE(tec27): BW only takes supply once a queued construct starts training, whereas we
+    // take it as soon as the construct is queued
+    let mut supply = *supply;
+    for p in pending.0.iter().filter(|p| p.player == player) {
+        if let Some(race) = p.construct_type.race() {
+            supply.races[race as usize].used += p.construct_type.def().supply_required as u32;
+        }
+    }
+    if check_can_make(construct_type, &resources, &supply).is_err() {
+        return;
+    }
+
+    let def = construct_type.def();
+    resources.minerals -= def.mineral_cost as u32;
+    resources.gas -= def.vespene_cost as u32;
+
+    let Ok((_, &position, _, _)) = producers.get(producer) else {
+        return;
+    };
+    create_writer.send(CreateConstructEvent {
+        construct_type,
+        owner: Some(player),
+        position: Some(position),
+        trained_by: Some(producer),
+        ..default()
+    });
+    pending.0.push(PendingTrain {
+        player,
+        producer,
+        construct_type,
+    });
+}
+
+/// Counts down the build time of the first construct in each producer's queue, finishing it once
+/// it is done.
+fn progress_training(
+    mut commands: Commands,
+    mut trained: Query<(
+        Entity,
+        &ConstructId,
+        &TrainedBy,
+        &mut UnderConstruction,
+        &mut Health,
+        Option<&mut Shield>,
+    )>,
+    mut finish_writer: EventWriter<FinishConstructEvent>,
+) {
+    // The first construct in each queue is the one that was queued first (lowest ID)
+    let mut first_in_queue = HashMap::<Entity, (ConstructId, Entity)>::new();
+    for (entity, &id, trained_by, ..) in trained.iter() {
+        let first = first_in_queue.entry(trained_by.0).or_insert((id, entity));
+        if id < first.0 {
+            *first = (id, entity);
+        }
+    }
+    // Sorted so that constructs finish (and get placed) in a consistent order on every client
+    let mut training = first_in_queue.into_values().collect::<Vec<_>>();
+    training.sort();
+
+    // TODO(tec27): Cancel (and refund) training if the producer is destroyed
+    for (_, entity) in training {
+        let Ok((_, _, _, mut under_construction, mut health, shield)) = trained.get_mut(entity)
+        else {
+            continue;
+        };
+        under_construction.time_remaining = under_construction.time_remaining.saturating_sub(1);
+        if under_construction.has_time_remaining() {
+            continue;
+        }
+
+        // TODO(tec27): BW increases health (and shields) gradually while training
+        health.current = health.max;
+        if let Some(mut shield) = shield {
+            shield.current = shield.max;
+        }
+        commands.entity(entity).remove::<TrainedBy>();
+        finish_writer.send(FinishConstructEvent { entity });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        gameplay::constructs::{ConstructBundle, NextConstructId},
+        headless::create_test_game,
+        maps::MapAsset,
+        net::IssueCommandEvent,
+    };
+
+    use super::*;
+
+    /// Issues `commands` as the local player and runs the game until they've been executed.
+    fn issue_commands(app: &mut App, commands: Vec<GameCommand>) {
+        for command in commands {
+            app.world_mut().send_event(IssueCommandEvent(command));
+        }
+        // Commands are queued on the first update and executed on the next game frame
+        app.update();
+        app.update();
+    }
+
+    #[test]
+    fn train_queue() {
+        let mut app = create_test_game(MapAsset::fixture(128, 128));
+        let barracks_id = app.world_mut().resource_mut::<NextConstructId>().assign();
+        let barracks = app
+            .world_mut()
+            .spawn((
+                ConstructBundle {
+                    construct_type: ConstructTypeId::TerranBarracks,
+                    position: IVec2::new(800, 800).into(),
+                    ..default()
+                },
+                OwnedConstruct(0),
+                barracks_id,
+            ))
+            .remove::<UnderConstruction>()
+            .id();
+        // Provides enough supply for everything
+        app.world_mut()
+            .spawn((
+                ConstructBundle {
+                    construct_type: ConstructTypeId::TerranCommandCenter,
+                    position: IVec2::new(400, 400).into(),
+                    ..default()
+                },
+                OwnedConstruct(0),
+            ))
+            .remove::<UnderConstruction>();
+        let player = app.world().resource::<PlayerEntities>().get(0).unwrap();
+        *app.world_mut().get_mut::<PlayerResources>(player).unwrap() = PlayerResources {
+            minerals: 150,
+            gas: 0,
+        };
+        // Commands are executed in order, so the first train happens with nothing selected
+        issue_commands(
+            &mut app,
+            vec![
+                GameCommand::Train(ConstructTypeId::TerranMarine),
+                GameCommand::Select(vec![barracks_id]),
+                GameCommand::Train(ConstructTypeId::TerranMarine),
+            ],
+        );
+        assert_eq!(
+            app.world().get::<PlayerResources>(player),
+            Some(&PlayerResources {
+                minerals: 100,
+                gas: 0
+            })
+        );
+
+        // Only two more marines are affordable, and a Barracks can't train SCVs
+        issue_commands(
+            &mut app,
+            vec![
+                GameCommand::Train(ConstructTypeId::TerranMarine),
+                GameCommand::Train(ConstructTypeId::TerranScv),
+                GameCommand::Train(ConstructTypeId::TerranMarine),
+                GameCommand::Train(ConstructTypeId::TerranMarine),
+            ],
+        );
+        assert_eq!(
+            app.world().get::<PlayerResources>(player),
+            Some(&PlayerResources {
+                minerals: 0,
+                gas: 0
+            })
+        );
+        let mut queued = app.world_mut().query::<(&TrainedBy, &ConstructTypeId)>();
+        assert_eq!(queued.iter(app.world()).count(), 3);
+        assert!(queued
+            .iter(app.world())
+            .all(|(t, &ty)| t.0 == barracks && ty == ConstructTypeId::TerranMarine));
+
+        // Only the first marine trains at a time
+        let build_time = ConstructTypeId::TerranMarine.def().build_time;
+        for _ in 3..build_time {
+            app.update();
+        }
+        assert_eq!(queued.iter(app.world()).count(), 2);
+        let mut ready = app.world_mut().query::<(&ConstructTypeId, &Visibility)>();
+        assert_eq!(
+            ready
+                .iter(app.world())
+                .filter(
+                    |&(&ty, &vis)| ty == ConstructTypeId::TerranMarine && vis != Visibility::Hidden
+                )
+                .count(),
+            1
+        );
+
+        for _ in 0..(build_time * 2) {
+            app.update();
+        }
+        assert_eq!(queued.iter(app.world()).count(), 0);
+    }
+}
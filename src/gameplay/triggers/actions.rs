@@ -0,0 +1,529 @@
+use bevy::prelude::*;
+
+use crate::{
+    gamedata::ConstructTypeId,
+    gameplay::{
+        create_construct::{CreateConstructEvent, CreationKind},
+        resources::ResourceAmount,
+    },
+    maps::{
+        position::Position,
+        triggers::{Action, ActionType, Modifier, UnitFilter},
+    },
+    math::FixedPoint,
+    random::LcgRand,
+};
+
+use super::{
+    AllianceStatus, CenterViewEvent, GameResult, GameResultEvent, GameView, PlayMapSoundEvent,
+    TriggerState, TriggerTextEvent, NUM_TRIGGER_PLAYERS, NUM_UNIT_TYPES,
+};
+
+/// Executes `action` on behalf of `player`. Actions that affect trigger execution itself (Wait and
+/// Preserve Trigger) are handled by the caller.
+pub(super) fn execute(
+    action: &Action,
+    player: u8,
+    state: &mut TriggerState,
+    view: &mut GameView,
+    rand: &mut LcgRand,
+) {
+    match action.kind {
+        ActionType::Victory => end_game(player, GameResult::Victory, state, view),
+        ActionType::Defeat => end_game(player, GameResult::Defeat, state, view),
+        ActionType::Draw => {
+            for p in 0..NUM_TRIGGER_PLAYERS as u8 {
+                if state.is_playing(p) {
+                    end_game(p, GameResult::Draw, state, view);
+                }
+            }
+        }
+        ActionType::DisplayTextMessage => display_text(player, action.string, state, view),
+        ActionType::Transmission => {
+            // TODO(tec27): Show the unit's portrait and ping it on the minimap
+            display_text(player, action.string, state, view);
+            play_sound(player, action.wav_string, state, view);
+        }
+        ActionType::PlayWav => play_sound(player, action.wav_string, state, view),
+        ActionType::CenterView => {
+            if let Some(location) = state.location(action.location) {
+                let (x, y) = location.center();
+                view.center_views.push(CenterViewEvent {
+                    player,
+                    position: Position::new(x, y),
+                });
+            }
+        }
+        ActionType::SetMissionObjectives => state.mission_objectives = action.string,
+        ActionType::SetSwitch => {
+            let Some(switch) = state.switches.get_mut(action.number as usize) else {
+                return;
+            };
+            *switch = match action.modifier() {
+                Modifier::Set => true,
+                Modifier::Clear => false,
+                Modifier::Toggle => !*switch,
+                Modifier::Randomize => rand.next_u8() & 1 != 0,
+                _ => *switch,
+            };
+        }
+        ActionType::SetCountdownTimer => {
+            state.countdown = action.modifier().apply(state.countdown, action.time);
+        }
+        ActionType::PauseTimer => state.countdown_paused = true,
+        ActionType::UnpauseTimer => state.countdown_paused = false,
+        ActionType::SetDeaths => {
+            let unit = action.unit_type as usize;
+            if unit >= NUM_UNIT_TYPES {
+                // NOTE(tec27): Out of range unit types are used to modify arbitrary memory in BW,
+                // we don't support that
+                warn!("Ignoring Set Deaths for invalid unit {unit}");
+                return;
+            }
+            for p in state.resolve_group(action.group, player) {
+                if let Some(deaths) = state.deaths.get_mut(p as usize) {
+                    let deaths = &mut deaths[unit];
+                    *deaths = action.modifier().apply(*deaths, action.number);
+                }
+            }
+        }
+        ActionType::SetResources => {
+            for p in state.resolve_group(action.group, player) {
+                let Some(resources) = view.resources.get_mut(p as usize) else {
+                    continue;
+                };
+                let modifier = action.modifier();
+                if action.unit_type != 1 {
+                    resources.minerals = modifier.apply(resources.minerals, action.number);
+                }
+                if action.unit_type != 0 {
+                    resources.gas = modifier.apply(resources.gas, action.number);
+                }
+            }
+        }
+        ActionType::SetScore => {
+            // TODO(tec27): Support the other score types once they're tracked
+            if action.unit_type != 7 {
+                return;
+            }
+            for p in state.resolve_group(action.group, player) {
+                if let Some(score) = state.custom_scores.get_mut(p as usize) {
+                    *score = action.modifier().apply(*score, action.number);
+                }
+            }
+        }
+        ActionType::SetAllianceStatus => {
+            let status = match action.unit_type {
+                0 => AllianceStatus::Enemy,
+                1 => AllianceStatus::Allied,
+                _ => AllianceStatus::AlliedVictory,
+            };
+            for p in state.resolve_group(action.group, player) {
+                if p != player && (p as usize) < NUM_TRIGGER_PLAYERS {
+                    state.alliances[player as usize][p as usize] = status;
+                }
+            }
+        }
+        ActionType::CreateUnit | ActionType::CreateUnitWithProperties => {
+            // TODO(tec27): Apply the unit properties (from the UPRP section)
+            let UnitFilter::Construct(construct_type) = action.unit() else {
+                return;
+            };
+            if let ConstructTypeId::Unknown(_) = construct_type {
+                return;
+            }
+            let Some(location) = state.location(action.location) else {
+                return;
+            };
+            let (x, y) = location.center();
+            for p in state.resolve_group(action.group, player) {
+                // NOTE(tec27): The units created here won't be visible to other triggers until
+                // the next trigger cycle
+                view.created
+                    .extend(
+                        (0..action.unit_count().unwrap_or(1)).map(|_| CreateConstructEvent {
+                            construct_type,
+                            owner: Some(p),
+                            position: Some(Position::new(x, y)),
+                            kind: CreationKind::Immediate,
+                            ..default()
+                        }),
+                    );
+            }
+        }
+        ActionType::KillUnit
+        | ActionType::KillUnitAtLocation
+        | ActionType::RemoveUnit
+        | ActionType::RemoveUnitAtLocation => {
+            let at_location = matches!(
+                action.kind,
+                ActionType::KillUnitAtLocation | ActionType::RemoveUnitAtLocation
+            );
+            let count = if at_location {
+                action.unit_count()
+            } else {
+                None
+            };
+            let Some(units) = select_units(action, player, at_location, count, state, view) else {
+                return;
+            };
+
+            // TODO(tec27): Killed units should play their death animation instead of disappearing
+
+            // Removing in reverse order keeps the remaining indices valid
+            for i in units.into_iter().rev() {
+                let unit = view.units.remove(i);
+                // NOTE(tec27): Units killed by triggers count as deaths, but nobody gets the kill
+                state.record_death(unit.construct_type, unit.owner, None);
+                view.removed.push(unit.entity);
+            }
+        }
+        ActionType::MoveUnit => {
+            let Some(destination) = state.location(action.number) else {
+                return;
+            };
+            let (x, y) = destination.center();
+            let Some(units) = select_units(action, player, true, action.unit_count(), state, view)
+            else {
+                return;
+            };
+            for i in units {
+                let unit = &mut view.units[i];
+                unit.position = Position::new(x, y);
+                unit.changed = true;
+            }
+        }
+        ActionType::MoveLocation => {
+            let Some(units) = select_units(action, player, true, Some(1), state, view) else {
+                return;
+            };
+            let Some(&i) = units.first() else {
+                return;
+            };
+            let position = view.units[i].position;
+            let Some(location) = (action.number as usize)
+                .checked_sub(1)
+                .and_then(|l| state.locations.get_mut(l))
+            else {
+                return;
+            };
+            let half_width = (location.right as i32 - location.left as i32) / 2;
+            let half_height = (location.bottom as i32 - location.top as i32) / 2;
+            location.left = (position.x - half_width).max(0) as u32;
+            location.right = (position.x + half_width).max(0) as u32;
+            location.top = (position.y - half_height).max(0) as u32;
+            location.bottom = (position.y + half_height).max(0) as u32;
+        }
+        ActionType::GiveUnitsToPlayer => {
+            let Some(&new_owner) = state.resolve_group((action.number).into(), player).first()
+            else {
+                return;
+            };
+            let Some(units) = select_units(action, player, true, action.unit_count(), state, view)
+            else {
+                return;
+            };
+            for i in units {
+                let unit = &mut view.units[i];
+                unit.owner = Some(new_owner);
+                unit.changed = true;
+            }
+        }
+        ActionType::ModifyUnitHitPoints => {
+            let percent = action.number.min(100) as i32;
+            let Some(units) = select_units(action, player, true, action.unit_count(), state, view)
+            else {
+                return;
+            };
+            for i in units {
+                let unit = &mut view.units[i];
+                unit.health.current =
+                    (unit.health.max * percent / 100).max(FixedPoint::from_bits(1));
+                unit.changed = true;
+            }
+        }
+        ActionType::ModifyUnitShieldPoints => {
+            let percent = action.number.min(100) as i32;
+            let Some(units) = select_units(action, player, true, action.unit_count(), state, view)
+            else {
+                return;
+            };
+            for i in units {
+                let unit = &mut view.units[i];
+                if let Some(ref mut shield) = unit.shield {
+                    shield.current = shield.max * percent / 100;
+                    unit.changed = true;
+                }
+            }
+        }
+        ActionType::ModifyUnitEnergy => {
+            let percent = action.number.min(100) as i32;
+            let Some(units) = select_units(action, player, true, action.unit_count(), state, view)
+            else {
+                return;
+            };
+            for i in units {
+                let unit = &mut view.units[i];
+                if let Some(ref mut energy) = unit.energy {
+                    energy.current = energy.max * percent / 100;
+                    unit.changed = true;
+                }
+            }
+        }
+        ActionType::ModifyUnitResourceAmount => {
+            // NOTE(tec27): This affects any resource at the location, regardless of the unit type
+            // set on the action
+            let Some(location) = state.location(action.location) else {
+                return;
+            };
+            let players = state.resolve_group(action.group, player);
+            let units = view
+                .matching_units(&players, UnitFilter::AnyUnit, Some(location))
+                .filter(|&i| view.units[i].resource_amount.is_some())
+                .take(action.unit_count().unwrap_or(usize::MAX))
+                .collect::<Vec<_>>();
+            for i in units {
+                let unit = &mut view.units[i];
+                unit.resource_amount = unit.resource_amount.map(|r| match r {
+                    ResourceAmount::Minerals(_) => ResourceAmount::Minerals(action.number),
+                    ResourceAmount::Gas(_) => ResourceAmount::Gas(action.number),
+                });
+                unit.changed = true;
+            }
+        }
+        ActionType::SetInvincibility => {
+            let Some(units) = select_units(action, player, true, None, state, view) else {
+                return;
+            };
+            for i in units {
+                let unit = &mut view.units[i];
+                unit.invincible = match action.modifier() {
+                    Modifier::Set => true,
+                    Modifier::Clear => false,
+                    Modifier::Toggle => !unit.invincible,
+                    _ => unit.invincible,
+                };
+                unit.changed = true;
+            }
+        }
+        ActionType::Comment | ActionType::PreserveTrigger | ActionType::Wait => {}
+        kind => {
+            // TODO(tec27): Implement the remaining actions (AI scripts, leaderboards, unit orders,
+            // etc.) once the systems they depend on exist
+            if !state.unsupported_actions.contains(&kind) {
+                warn!("Ignoring unsupported trigger action: {kind:?}");
+                state.unsupported_actions.push(kind);
+            }
+        }
+    }
+}
+
+fn end_game(player: u8, result: GameResult, state: &mut TriggerState, view: &mut GameView) {
+    state.results[player as usize] = Some(result);
+    view.results.push(GameResultEvent { player, result });
+}
+
+fn display_text(player: u8, string: u32, state: &TriggerState, view: &mut GameView) {
+    if let Some(text) = state.string(string).filter(|s| !s.is_empty()) {
+        view.texts.push(TriggerTextEvent {
+            player,
+            text: text.clone(),
+        });
+    }
+}
+
+fn play_sound(player: u8, string: u32, state: &TriggerState, view: &mut GameView) {
+    if let Some(path) = state.string(string).filter(|s| !s.is_empty()) {
+        view.sounds.push(PlayMapSoundEvent {
+            player,
+            path: path.to_string(),
+        });
+    }
+}
+
+/// Selects the indices (in ascending order) of up to `count` units that `action` applies to, or
+/// all of them if `count` is `None`. If `at_location` is true, only units within the action's
+/// location are selected. Returns `None` if the action's location doesn't exist.
+fn select_units(
+    action: &Action,
+    player: u8,
+    at_location: bool,
+    count: Option<usize>,
+    state: &TriggerState,
+    view: &GameView,
+) -> Option<Vec<usize>> {
+    let location = if at_location {
+        Some(state.location(action.location)?)
+    } else {
+        None
+    };
+    let players = state.resolve_group(action.group, player);
+    Some(
+        view.matching_units(&players, action.unit(), location)
+            .take(count.unwrap_or(usize::MAX))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gameplay::{
+            constructs::ConstructId, health::Health, shield::Shield, triggers::UnitSnapshot,
+        },
+        maps::{
+            chk::MapLocation,
+            triggers::{ActionFlags, PlayerGroup},
+        },
+    };
+
+    fn action(kind: ActionType) -> Action {
+        Action {
+            location: 1,
+            string: 0,
+            wav_string: 0,
+            time: 0,
+            group: PlayerGroup::CurrentPlayer,
+            number: 0,
+            unit_type: ConstructTypeId::ZergZergling.into(),
+            kind,
+            modifier: 0,
+            flags: ActionFlags::empty(),
+        }
+    }
+
+    #[test]
+    fn kill_move_and_give() {
+        let mut state = super::super::tests::test_state(vec![]);
+        state.locations = vec![
+            MapLocation {
+                left: 0,
+                top: 0,
+                right: 64,
+                bottom: 64,
+                ..default()
+            },
+            MapLocation {
+                left: 256,
+                top: 256,
+                right: 320,
+                bottom: 320,
+                ..default()
+            },
+        ];
+        let mut view = GameView {
+            units: (0..4)
+                .map(|i| UnitSnapshot {
+                    entity: Entity::from_raw(i),
+                    id: ConstructId(i),
+                    construct_type: ConstructTypeId::ZergZergling,
+                    owner: Some(0),
+                    position: Position::new(i as i32 * 32, 16),
+                    health: Health::default(),
+                    shield: None,
+                    energy: None,
+                    resource_amount: None,
+                    invincible: false,
+                    completed: true,
+                    changed: false,
+                })
+                .collect(),
+            ..default()
+        };
+        let mut rand = LcgRand::new(0);
+
+        let kill = Action {
+            modifier: 1,
+            ..action(ActionType::KillUnitAtLocation)
+        };
+        execute(&kill, 0, &mut state, &mut view, &mut rand);
+        assert_eq!(view.removed, vec![Entity::from_raw(0)]);
+        assert_eq!(view.units.len(), 3);
+        let zergling = u16::from(ConstructTypeId::ZergZergling) as usize;
+        assert_eq!(state.deaths[0][zergling], 1);
+
+        let move_unit = Action {
+            number: 2,
+            ..action(ActionType::MoveUnit)
+        };
+        execute(&move_unit, 0, &mut state, &mut view, &mut rand);
+        assert_eq!(view.units[0].position, Position::new(288, 288));
+        assert!(view.units[0].changed);
+        assert_eq!(view.units[1].position, Position::new(64, 16));
+
+        let give = Action {
+            location: 2,
+            number: 1,
+            ..action(ActionType::GiveUnitsToPlayer)
+        };
+        execute(&give, 0, &mut state, &mut view, &mut rand);
+        assert_eq!(view.units[0].owner, Some(1));
+        assert_eq!(view.units[1].owner, Some(0));
+
+        let remove_all = Action {
+            group: PlayerGroup::AllPlayers,
+            ..action(ActionType::RemoveUnit)
+        };
+        execute(&remove_all, 0, &mut state, &mut view, &mut rand);
+        assert!(view.units.is_empty());
+        assert_eq!(state.deaths[0][zergling], 3);
+        assert_eq!(state.deaths[1][zergling], 1);
+        assert!(state.kills.iter().all(|k| k[zergling] == 0));
+    }
+
+    #[test]
+    fn invincibility_and_shields() {
+        let mut state = super::super::tests::test_state(vec![]);
+        state.locations = vec![MapLocation {
+            left: 0,
+            top: 0,
+            right: 64,
+            bottom: 64,
+            ..default()
+        }];
+        let mut view = GameView {
+            units: (0..2)
+                .map(|i| UnitSnapshot {
+                    entity: Entity::from_raw(i),
+                    id: ConstructId(i),
+                    construct_type: ConstructTypeId::ZergZergling,
+                    owner: Some(0),
+                    position: Position::new(16, 16),
+                    health: Health::default(),
+                    shield: (i == 0).then_some(Shield {
+                        max: FixedPoint::from_num(80),
+                        current: FixedPoint::from_num(80),
+                    }),
+                    energy: None,
+                    resource_amount: None,
+                    invincible: false,
+                    completed: true,
+                    changed: false,
+                })
+                .collect(),
+            ..default()
+        };
+        let mut rand = LcgRand::new(0);
+
+        let set = Action {
+            modifier: Modifier::Toggle.into(),
+            ..action(ActionType::SetInvincibility)
+        };
+        execute(&set, 0, &mut state, &mut view, &mut rand);
+        assert!(view.units.iter().all(|u| u.invincible));
+        execute(&set, 0, &mut state, &mut view, &mut rand);
+        assert!(view.units.iter().all(|u| !u.invincible));
+
+        let shields = Action {
+            number: 25,
+            ..action(ActionType::ModifyUnitShieldPoints)
+        };
+        execute(&shields, 0, &mut state, &mut view, &mut rand);
+        assert_eq!(
+            view.units[0].shield.map(|s| s.current),
+            Some(FixedPoint::from_num(20))
+        );
+        assert!(view.units[1].shield.is_none());
+    }
+}
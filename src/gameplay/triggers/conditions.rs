@@ -0,0 +1,301 @@
+use crate::maps::{
+    chk::MapLocation,
+    triggers::{Comparison, Condition, ConditionType, UnitFilter},
+};
+
+use super::{GameView, TriggerState, FRAMES_PER_GAME_SECOND, NUM_TRIGGER_PLAYERS};
+
+/// Returns whether `condition` is met from the perspective of `player`.
+pub(super) fn is_met(
+    condition: &Condition,
+    player: u8,
+    state: &TriggerState,
+    view: &GameView,
+) -> bool {
+    let players = || state.resolve_group(condition.group, player);
+    let location = || state.location(condition.location);
+    let compare = |value: u32| condition.comparison.compare(value, condition.amount);
+
+    match condition.kind {
+        ConditionType::None | ConditionType::Always => true,
+        ConditionType::Never | ConditionType::MissionBriefing => false,
+        ConditionType::CountdownTimer => compare(state.countdown),
+        ConditionType::ElapsedTime => compare(view.frame / FRAMES_PER_GAME_SECOND),
+        ConditionType::Switch => {
+            let set = state.switches[condition.resource as usize];
+            match condition.comparison {
+                Comparison::Set => set,
+                Comparison::Cleared => !set,
+                _ => false,
+            }
+        }
+        ConditionType::Command => compare(count_units(view, &players(), condition.unit, None)),
+        ConditionType::Bring => match location() {
+            Some(l) => compare(count_units(view, &players(), condition.unit, Some(l))),
+            None => false,
+        },
+        ConditionType::Accumulate => compare(
+            players()
+                .iter()
+                .map(|&p| resource_value(view, p, condition.resource))
+                .fold(0, u32::saturating_add),
+        ),
+        ConditionType::Deaths => compare(
+            players()
+                .iter()
+                .map(|&p| unit_table_value(&state.deaths[..], p, condition.unit))
+                .fold(0, u32::saturating_add),
+        ),
+        ConditionType::Kill => compare(
+            players()
+                .iter()
+                .map(|&p| unit_table_value(&state.kills[..], p, condition.unit))
+                .fold(0, u32::saturating_add),
+        ),
+        ConditionType::Score => compare(
+            players()
+                .iter()
+                .map(|&p| score_value(state, p, condition.resource))
+                .fold(0, u32::saturating_add),
+        ),
+        ConditionType::Opponents => compare(
+            (0..NUM_TRIGGER_PLAYERS as u8)
+                .filter(|&p| {
+                    p != player
+                        && state.is_playing(p)
+                        && !state.alliances[player as usize][p as usize].is_allied()
+                })
+                .count() as u32,
+        ),
+        ConditionType::CommandTheMost => is_extreme(state, player, Extreme::Most, |p| {
+            count_units(view, &[p], condition.unit, None)
+        }),
+        ConditionType::CommandTheLeast => is_extreme(state, player, Extreme::Least, |p| {
+            count_units(view, &[p], condition.unit, None)
+        }),
+        ConditionType::CommandTheMostAt => match location() {
+            Some(l) => is_extreme(state, player, Extreme::Most, |p| {
+                count_units(view, &[p], condition.unit, Some(l))
+            }),
+            None => false,
+        },
+        ConditionType::CommandTheLeastAt => match location() {
+            Some(l) => is_extreme(state, player, Extreme::Least, |p| {
+                count_units(view, &[p], condition.unit, Some(l))
+            }),
+            None => false,
+        },
+        ConditionType::MostKills => is_extreme(state, player, Extreme::Most, |p| {
+            unit_table_value(&state.kills[..], p, condition.unit)
+        }),
+        ConditionType::LeastKills => is_extreme(state, player, Extreme::Least, |p| {
+            unit_table_value(&state.kills[..], p, condition.unit)
+        }),
+        ConditionType::MostResources => is_extreme(state, player, Extreme::Most, |p| {
+            resource_value(view, p, condition.resource)
+        }),
+        ConditionType::LeastResources => is_extreme(state, player, Extreme::Least, |p| {
+            resource_value(view, p, condition.resource)
+        }),
+        ConditionType::HighestScore => is_extreme(state, player, Extreme::Most, |p| {
+            score_value(state, p, condition.resource)
+        }),
+        ConditionType::LowestScore => is_extreme(state, player, Extreme::Least, |p| {
+            score_value(state, p, condition.resource)
+        }),
+        ConditionType::Unknown(_) => false,
+    }
+}
+
+/// Counts the completed units owned by `players` that match `filter` (and are within `location`,
+/// if specified).
+fn count_units(
+    view: &GameView,
+    players: &[u8],
+    filter: UnitFilter,
+    location: Option<&MapLocation>,
+) -> u32 {
+    view.matching_units(players, filter, location)
+        .filter(|&i| view.units[i].completed)
+        .count() as u32
+}
+
+fn resource_value(view: &GameView, player: u8, resource_type: u8) -> u32 {
+    let Some(resources) = view.resources.get(player as usize) else {
+        return 0;
+    };
+    match resource_type {
+        0 => resources.minerals,
+        1 => resources.gas,
+        _ => resources.minerals.saturating_add(resources.gas),
+    }
+}
+
+/// Sums the values in a per-player, per-unit-type table (e.g. deaths) for the unit types that match
+/// `filter`.
+fn unit_table_value(table: &[[u32; super::NUM_UNIT_TYPES]], player: u8, filter: UnitFilter) -> u32 {
+    let Some(row) = table.get(player as usize) else {
+        return 0;
+    };
+    row.iter()
+        .enumerate()
+        .filter(|(i, _)| filter.matches((*i as u16).into()))
+        .map(|(_, &v)| v)
+        .fold(0, u32::saturating_add)
+}
+
+fn score_value(state: &TriggerState, player: u8, score_type: u8) -> u32 {
+    match score_type {
+        // Custom score
+        7 => state
+            .custom_scores
+            .get(player as usize)
+            .copied()
+            .unwrap_or_default(),
+        // TODO(tec27): Track the other score types
+        _ => 0,
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Extreme {
+    Most,
+    Least,
+}
+
+/// Returns whether `player` has strictly the most (or least) of some value compared to every other
+/// player still in the game. Having the most of something requires having at least 1 of it.
+fn is_extreme(
+    state: &TriggerState,
+    player: u8,
+    extreme: Extreme,
+    value: impl Fn(u8) -> u32,
+) -> bool {
+    let own = value(player);
+    if extreme == Extreme::Most && own == 0 {
+        return false;
+    }
+    (0..NUM_TRIGGER_PLAYERS as u8)
+        .filter(|&p| p != player && state.is_playing(p))
+        .all(|p| match extreme {
+            Extreme::Most => own > value(p),
+            Extreme::Least => own < value(p),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::{default, Entity};
+
+    use super::*;
+    use crate::{
+        gamedata::ConstructTypeId,
+        gameplay::{constructs::ConstructId, health::Health, triggers::UnitSnapshot},
+        maps::{
+            position::Position,
+            triggers::{ConditionFlags, PlayerGroup},
+        },
+    };
+
+    fn condition(kind: ConditionType, comparison: Comparison, amount: u32) -> Condition {
+        Condition {
+            location: 0,
+            group: PlayerGroup::CurrentPlayer,
+            amount,
+            unit: UnitFilter::Construct(ConstructTypeId::TerranMarine),
+            comparison,
+            kind,
+            resource: 0,
+            flags: ConditionFlags::empty(),
+        }
+    }
+
+    fn marine(index: u32, owner: u8, x: i32, completed: bool) -> UnitSnapshot {
+        UnitSnapshot {
+            entity: Entity::from_raw(index),
+            id: ConstructId(index),
+            construct_type: ConstructTypeId::TerranMarine,
+            owner: Some(owner),
+            position: Position::new(x, 16),
+            health: Health::default(),
+            shield: None,
+            energy: None,
+            resource_amount: None,
+            invincible: false,
+            completed,
+            changed: false,
+        }
+    }
+
+    #[test]
+    fn unit_counts() {
+        let mut state = super::super::tests::test_state(vec![]);
+        state.locations = vec![MapLocation {
+            left: 0,
+            top: 0,
+            right: 64,
+            bottom: 64,
+            ..default()
+        }];
+        let view = GameView {
+            units: vec![
+                marine(0, 0, 32, true),
+                marine(1, 0, 96, true),
+                marine(2, 0, 32, false),
+                marine(3, 1, 32, true),
+            ],
+            ..default()
+        };
+
+        let command = condition(ConditionType::Command, Comparison::Exactly, 2);
+        assert!(is_met(&command, 0, &state, &view));
+        let bring = Condition {
+            location: 1,
+            ..condition(ConditionType::Bring, Comparison::AtMost, 1)
+        };
+        assert!(is_met(&bring, 0, &state, &view));
+        assert!(is_met(&bring, 1, &state, &view));
+
+        let most = condition(ConditionType::CommandTheMost, Comparison::AtLeast, 0);
+        assert!(is_met(&most, 0, &state, &view));
+        assert!(!is_met(&most, 1, &state, &view));
+        let least = condition(ConditionType::CommandTheLeast, Comparison::AtLeast, 0);
+        assert!(is_met(&least, 1, &state, &view));
+    }
+
+    #[test]
+    fn timers_and_deaths() {
+        let mut state = super::super::tests::test_state(vec![]);
+        state.countdown = 5;
+        state.deaths[1][ConstructTypeId::TerranMarine.def().id as usize] = 3;
+        let view = GameView {
+            frame: FRAMES_PER_GAME_SECOND * 10,
+            ..default()
+        };
+
+        assert!(is_met(
+            &condition(ConditionType::ElapsedTime, Comparison::AtLeast, 10),
+            0,
+            &state,
+            &view
+        ));
+        assert!(is_met(
+            &condition(ConditionType::CountdownTimer, Comparison::AtMost, 5),
+            0,
+            &state,
+            &view
+        ));
+        let deaths = Condition {
+            group: PlayerGroup::Foes,
+            unit: UnitFilter::Men,
+            ..condition(ConditionType::Deaths, Comparison::Exactly, 3)
+        };
+        assert!(is_met(&deaths, 0, &state, &view));
+        assert!(is_met(
+            &condition(ConditionType::Opponents, Comparison::Exactly, 1),
+            0,
+            &state,
+            &view
+        ));
+    }
+}
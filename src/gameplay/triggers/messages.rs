@@ -0,0 +1,238 @@
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::prelude::*;
+
+use crate::{
+    camera::CameraPanLocked,
+    fonts::{FONT_BODY, FONT_BRAND},
+    gameplay::{
+        players::{ControlledPlayer, PlayerNumber},
+        sounds::PlaySoundCommandsExt,
+    },
+    maps::{
+        game_map::{GameMap, GameMapSize},
+        position::position_to_translation,
+        strings::MapString,
+        CurrentMap, MapAsset,
+    },
+    settings::GameSettings,
+    states::{AppState, InGameOnly},
+};
+
+use super::{CenterViewEvent, GameResult, GameResultEvent, PlayMapSoundEvent, TriggerTextEvent};
+
+/// How long text messages from triggers are shown for.
+const MESSAGE_DURATION: Duration = Duration::from_secs(8);
+/// The maximum number of text messages that are shown at once.
+const MAX_MESSAGES: usize = 8;
+
+/// Plugin that shows the local player the effects of triggers that are meant for them, such as
+/// text messages, sounds, view changes, and the outcome of the game.
+pub struct TriggerMessagesPlugin;
+
+impl Plugin for TriggerMessagesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::InGame), setup)
+            .add_systems(
+                Update,
+                (
+                    show_messages,
+                    play_map_sounds,
+                    show_game_result,
+                    center_view,
+                )
+                    .run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+/// The text messages currently being shown, along with the time they expire at.
+#[derive(Component)]
+struct MessageLog {
+    messages: VecDeque<(MapString, Duration)>,
+    /// The style used for messages (the color is replaced by the color of each section of text).
+    style: TextStyle,
+}
+
+#[derive(Component)]
+struct GameResultText;
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let style = TextStyle {
+        font: asset_server.load(FONT_BODY),
+        font_size: 20.0,
+        color: Color::WHITE,
+    };
+    commands.spawn((
+        TextBundle::from_sections([]).with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(16.0),
+            bottom: Val::Percent(30.0),
+            ..default()
+        }),
+        MessageLog {
+            messages: VecDeque::new(),
+            style,
+        },
+        InGameOnly,
+        Name::new("TriggerMessages"),
+    ));
+
+    commands.spawn((
+        TextBundle {
+            visibility: Visibility::Hidden,
+            ..TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load(FONT_BRAND),
+                    font_size: 64.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(35.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            })
+            .with_text_justify(JustifyText::Center)
+        },
+        GameResultText,
+        InGameOnly,
+        Name::new("GameResult"),
+    ));
+}
+
+fn show_messages(
+    mut events: EventReader<TriggerTextEvent>,
+    local_player: Query<&PlayerNumber, With<ControlledPlayer>>,
+    time: Res<Time<Real>>,
+    mut log: Query<(&mut Text, &mut MessageLog)>,
+) {
+    let Ok((mut text, mut log)) = log.get_single_mut() else {
+        return;
+    };
+    let local_player = local_player.get_single().ok().map(|p| p.0);
+    let now = time.elapsed();
+
+    let mut changed = false;
+    for event in events.read() {
+        if Some(event.player) != local_player {
+            continue;
+        }
+        // TODO(tec27): Handle text alignment
+        log.messages
+            .push_back((event.text.clone(), now + MESSAGE_DURATION));
+        if log.messages.len() > MAX_MESSAGES {
+            log.messages.pop_front();
+        }
+        changed = true;
+    }
+    while log
+        .messages
+        .front()
+        .is_some_and(|(_, expires)| *expires <= now)
+    {
+        log.messages.pop_front();
+        changed = true;
+    }
+
+    if changed {
+        let mut sections = Vec::new();
+        for (i, (message, _)) in log.messages.iter().enumerate() {
+            if i > 0 {
+                sections.push(TextSection::new("\n", log.style.clone()));
+            }
+            sections.extend(message.spans.iter().map(|span| {
+                TextSection::new(
+                    span.text.clone(),
+                    TextStyle {
+                        color: span.color.color(),
+                        ..log.style.clone()
+                    },
+                )
+            }));
+        }
+        text.sections = sections;
+    }
+}
+
+fn play_map_sounds(
+    mut commands: Commands,
+    mut events: EventReader<PlayMapSoundEvent>,
+    local_player: Query<&PlayerNumber, With<ControlledPlayer>>,
+    current_map: Res<CurrentMap>,
+    map_assets: Res<Assets<MapAsset>>,
+) {
+    let local_player = local_player.get_single().ok().map(|p| p.0);
+    let map = map_assets.get(&current_map.handle);
+    for event in events.read() {
+        if Some(event.player) != local_player {
+            continue;
+        }
+        let Some(source) = map.and_then(|m| m.sound(&event.path)) else {
+            warn!(
+                "Trigger played a sound that isn't in the map: {}",
+                event.path
+            );
+            continue;
+        };
+
+        commands.play_map_sound(source.clone());
+    }
+}
+
+fn show_game_result(
+    mut events: EventReader<GameResultEvent>,
+    local_player: Query<&PlayerNumber, With<ControlledPlayer>>,
+    mut result_text: Query<(&mut Text, &mut Visibility), With<GameResultText>>,
+) {
+    let local_player = local_player.get_single().ok().map(|p| p.0);
+    for event in events.read() {
+        if Some(event.player) != local_player {
+            continue;
+        }
+        let Ok((mut text, mut visibility)) = result_text.get_single_mut() else {
+            continue;
+        };
+        text.sections[0].value = match event.result {
+            GameResult::Victory => "Victory!",
+            GameResult::Defeat => "Defeat",
+            GameResult::Draw => "Draw",
+        }
+        .to_string();
+        *visibility = Visibility::Visible;
+    }
+}
+
+fn center_view(
+    mut events: EventReader<CenterViewEvent>,
+    local_player: Query<&PlayerNumber, With<ControlledPlayer>>,
+    map: Query<&GameMapSize, With<GameMap>>,
+    settings: Res<GameSettings>,
+    pan_locked: Res<CameraPanLocked>,
+    mut camera: Query<&mut Transform, With<Camera>>,
+) {
+    let local_player = local_player.get_single().ok().map(|p| p.0);
+    let Some(event) = events
+        .read()
+        .filter(|e| Some(e.player) == local_player)
+        .last()
+    else {
+        return;
+    };
+    let (Ok(map_size), Ok(mut transform)) = (map.get_single(), camera.get_single_mut()) else {
+        return;
+    };
+    if pan_locked.0 {
+        return;
+    }
+
+    let translation = position_to_translation(
+        &event.position,
+        map_size,
+        settings.asset_quality.tile_size(),
+    );
+    transform.translation = translation.extend(transform.translation.z);
+}
@@ -0,0 +1,947 @@
+use std::sync::Arc;
+
+use bevy::prelude::*;
+
+use crate::{
+    gamedata::{ConstructFlags, ConstructTypeId},
+    maps::{
+        chk::{Elevation, ForceFlags, MapLocation, NUM_CHK_PLAYERS, NUM_UNIT_TYPES},
+        game_map::{GameMap, GameMapTerrain},
+        position::Position,
+        strings::MapString,
+        tileset::MegaTileFlags,
+        triggers::{
+            ActionFlags, ActionType, ConditionFlags, PlayerGroup, Trigger, TriggerFlags, UnitFilter,
+        },
+        CurrentMap, MapAsset,
+    },
+    random::LcgRand,
+    states::AppState,
+};
+
+use super::{
+    build_time::UnderConstruction,
+    constructs::{ConstructId, OwnedConstruct},
+    create_construct::{create_constructs, CreateConstructEvent},
+    energy::Energy,
+    health::{ConstructKilledEvent, Health},
+    players::{Player, PlayerEntities},
+    resources::{PlayerResources, ResourceAmount},
+    shield::Shield,
+    status::Invincible,
+    GameFrame, SimulationSet,
+};
+
+mod actions;
+mod conditions;
+mod messages;
+
+pub use messages::TriggerMessagesPlugin;
+
+/// The number of players that execute triggers (the neutral players never do).
+pub const NUM_TRIGGER_PLAYERS: usize = 8;
+/// The number of game frames in a game second. Game seconds are real seconds at Normal speed.
+pub const FRAMES_PER_GAME_SECOND: u32 = 15;
+/// How often triggers are checked, in game frames (every 2 game seconds).
+const TRIGGER_INTERVAL: u32 = FRAMES_PER_GAME_SECOND * 2;
+/// How many milliseconds of a Wait action elapse each game frame.
+const WAIT_MS_PER_FRAME: u32 = 42;
+
+const NUM_SWITCHES: usize = 256;
+
+pub fn plugin(app: &mut App) {
+    app.add_event::<TriggerTextEvent>()
+        .add_event::<PlayMapSoundEvent>()
+        .add_event::<CenterViewEvent>()
+        .add_event::<GameResultEvent>()
+        .add_systems(OnExit(AppState::InGame), remove_trigger_state)
+        .add_systems(
+            FixedUpdate,
+            (record_kills, run_triggers)
+                .chain()
+                .before(create_constructs)
+                .in_set(SimulationSet)
+                .run_if(resource_exists::<TriggerState>),
+        );
+}
+
+/// Event that signifies a trigger displayed some text to a player.
+#[derive(Event, Debug, Clone)]
+pub struct TriggerTextEvent {
+    pub player: u8,
+    pub text: MapString,
+}
+
+/// Event that signifies a trigger played a sound file from the map to a player.
+#[derive(Event, Debug, Clone)]
+pub struct PlayMapSoundEvent {
+    pub player: u8,
+    /// The path of the sound file within the map archive.
+    pub path: String,
+}
+
+/// Event that signifies a trigger centered a player's view on a position.
+#[derive(Event, Debug, Copy, Clone)]
+pub struct CenterViewEvent {
+    pub player: u8,
+    pub position: Position,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GameResult {
+    Victory,
+    Defeat,
+    Draw,
+}
+
+/// Event that signifies a player's game has ended.
+#[derive(Event, Debug, Copy, Clone)]
+pub struct GameResultEvent {
+    pub player: u8,
+    pub result: GameResult,
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum AllianceStatus {
+    #[default]
+    Enemy,
+    Allied,
+    AlliedVictory,
+}
+
+impl AllianceStatus {
+    pub fn is_allied(&self) -> bool {
+        matches!(self, AllianceStatus::Allied | AllianceStatus::AlliedVictory)
+    }
+}
+
+/// A trigger in a particular player's list of triggers to execute. Each player that executes a
+/// trigger gets their own copy of its execution state.
+#[derive(Debug, Copy, Clone)]
+struct TriggerInstance {
+    /// Index of the [Trigger] this is an instance of.
+    trigger: usize,
+    /// The action to continue execution from, if the trigger is in the middle of executing.
+    next_action: usize,
+    preserved: bool,
+    done: bool,
+}
+
+/// A Wait action that is blocking a player's triggers from executing.
+#[derive(Debug, Copy, Clone)]
+struct PendingWait {
+    /// The index of the [TriggerInstance] that is waiting.
+    instance: usize,
+    frames_remaining: u32,
+}
+
+#[derive(Debug, Default)]
+struct PlayerTriggers {
+    instances: Vec<TriggerInstance>,
+    wait: Option<PendingWait>,
+}
+
+/// Resource that holds the state of the map's triggers and all the game state that only triggers
+/// use. This only exists in [GameMode::UseMapSettings](super::GameMode::UseMapSettings) games.
+#[derive(Resource, Debug)]
+pub struct TriggerState {
+    triggers: Arc<[Trigger]>,
+    players: [PlayerTriggers; NUM_TRIGGER_PLAYERS],
+    /// Whether each player slot is occupied in this game.
+    present: [bool; NUM_CHK_PLAYERS],
+    forces: [u8; NUM_CHK_PLAYERS],
+    strings: Vec<MapString>,
+    /// The map's locations, which may be moved by triggers.
+    pub locations: Vec<MapLocation>,
+    pub switches: [bool; NUM_SWITCHES],
+    /// The number of units of each type that each player has lost.
+    pub deaths: Box<[[u32; NUM_UNIT_TYPES]; NUM_CHK_PLAYERS]>,
+    /// The number of units of each type that each player has killed.
+    pub kills: Box<[[u32; NUM_UNIT_TYPES]; NUM_CHK_PLAYERS]>,
+    pub custom_scores: [u32; NUM_CHK_PLAYERS],
+    /// The value of the countdown timer, in game seconds.
+    pub countdown: u32,
+    pub countdown_paused: bool,
+    /// How each player (first index) regards every other player (second index).
+    pub alliances: [[AllianceStatus; NUM_CHK_PLAYERS]; NUM_CHK_PLAYERS],
+    pub results: [Option<GameResult>; NUM_CHK_PLAYERS],
+    /// The string ID of the current mission objectives.
+    pub mission_objectives: u32,
+    /// The unsupported action types that have been encountered, so they are only logged once.
+    unsupported_actions: Vec<ActionType>,
+}
+
+impl TriggerState {
+    /// Creates the trigger state for a game. `forces` specifies the force for each player that is
+    /// present in the game.
+    pub fn new(map: &MapAsset, forces: [Option<u8>; NUM_CHK_PLAYERS]) -> Self {
+        let present = forces.map(|f| f.is_some());
+        let forces = forces.map(|f| f.unwrap_or_default());
+
+        let alliances = std::array::from_fn(|a| {
+            std::array::from_fn(|b| {
+                if a == b {
+                    return AllianceStatus::Allied;
+                }
+                if a >= NUM_TRIGGER_PLAYERS || b >= NUM_TRIGGER_PLAYERS || forces[a] != forces[b] {
+                    return AllianceStatus::Enemy;
+                }
+                let flags = map.forces[forces[a] as usize].flags;
+                if !flags.contains(ForceFlags::ALLIED) {
+                    AllianceStatus::Enemy
+                } else if flags.contains(ForceFlags::ALLIED_VICTORY) {
+                    AllianceStatus::AlliedVictory
+                } else {
+                    AllianceStatus::Allied
+                }
+            })
+        });
+
+        Self {
+            players: build_player_triggers(&map.triggers, present, forces),
+            triggers: map.triggers.clone().into(),
+            present,
+            forces,
+            strings: map.strings.clone(),
+            locations: map.locations.clone(),
+            switches: [false; NUM_SWITCHES],
+            deaths: Box::new([[0; NUM_UNIT_TYPES]; NUM_CHK_PLAYERS]),
+            kills: Box::new([[0; NUM_UNIT_TYPES]; NUM_CHK_PLAYERS]),
+            custom_scores: [0; NUM_CHK_PLAYERS],
+            countdown: 0,
+            countdown_paused: false,
+            alliances,
+            results: [None; NUM_CHK_PLAYERS],
+            mission_objectives: 0,
+            unsupported_actions: Vec::new(),
+        }
+    }
+
+    /// Records that a construct of `construct_type` owned by `owner` died, and if it was killed by
+    /// another player's construct, gives `killer` credit for it.
+    fn record_death(
+        &mut self,
+        construct_type: ConstructTypeId,
+        owner: Option<u8>,
+        killer: Option<u8>,
+    ) {
+        let unit = u16::from(construct_type) as usize;
+        let increment = |table: &mut [[u32; NUM_UNIT_TYPES]], player: Option<u8>| {
+            if let Some(count) = player.and_then(|p| table.get_mut(p as usize)?.get_mut(unit)) {
+                *count = count.saturating_add(1);
+            }
+        };
+        increment(&mut self.deaths[..], owner);
+        increment(&mut self.kills[..], killer);
+    }
+
+    /// Returns the string with the specified ID, if there is one.
+    fn string(&self, id: u32) -> Option<&MapString> {
+        (id as usize)
+            .checked_sub(1)
+            .and_then(|i| self.strings.get(i))
+    }
+
+    /// Returns the location with the specified (1-based) index, if there is one.
+    fn location(&self, index: u32) -> Option<&MapLocation> {
+        (index as usize)
+            .checked_sub(1)
+            .and_then(|i| self.locations.get(i))
+    }
+
+    /// Returns whether `player` is still playing (e.g. is present and hasn't won or lost).
+    fn is_playing(&self, player: u8) -> bool {
+        let p = player as usize;
+        p < NUM_TRIGGER_PLAYERS && self.present[p] && self.results[p].is_none()
+    }
+
+    /// Resolves a [PlayerGroup] into the player numbers it refers to, from the perspective of
+    /// `current_player`.
+    fn resolve_group(&self, group: PlayerGroup, current_player: u8) -> Vec<u8> {
+        let current = current_player as usize;
+        let playing = (0..NUM_TRIGGER_PLAYERS as u8).filter(|&p| self.present[p as usize]);
+        match group {
+            PlayerGroup::Player(p) => vec![p],
+            PlayerGroup::CurrentPlayer => vec![current_player],
+            PlayerGroup::Foes => playing
+                .filter(|&p| {
+                    p != current_player && !self.alliances[current][p as usize].is_allied()
+                })
+                .collect(),
+            PlayerGroup::Allies => playing
+                .filter(|&p| p != current_player && self.alliances[current][p as usize].is_allied())
+                .collect(),
+            PlayerGroup::NeutralPlayers => {
+                (NUM_TRIGGER_PLAYERS as u8..NUM_CHK_PLAYERS as u8).collect()
+            }
+            PlayerGroup::AllPlayers => playing.collect(),
+            PlayerGroup::Force(f) => playing.filter(|&p| self.forces[p as usize] == f).collect(),
+            PlayerGroup::NonAlliedVictoryPlayers => playing
+                .filter(|&p| {
+                    p != current_player
+                        && self.alliances[current][p as usize] != AllianceStatus::AlliedVictory
+                })
+                .collect(),
+            PlayerGroup::NoPlayer | PlayerGroup::Unknown(_) => Vec::new(),
+        }
+    }
+}
+
+/// Builds the list of triggers that each player present in the game executes, in the order they
+/// were specified in the map.
+fn build_player_triggers(
+    triggers: &[Trigger],
+    present: [bool; NUM_CHK_PLAYERS],
+    forces: [u8; NUM_CHK_PLAYERS],
+) -> [PlayerTriggers; NUM_TRIGGER_PLAYERS] {
+    std::array::from_fn(|player| {
+        if !present[player] {
+            return PlayerTriggers::default();
+        }
+        let instances = triggers
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| executes_for(t, player as u8, forces[player]))
+            .map(|(i, t)| TriggerInstance {
+                trigger: i,
+                next_action: 0,
+                preserved: t.flags.contains(TriggerFlags::PRESERVE),
+                done: t.flags.contains(TriggerFlags::DISABLED),
+            })
+            .collect();
+        PlayerTriggers {
+            instances,
+            wait: None,
+        }
+    })
+}
+
+/// Returns whether `trigger` is executed by `player`, who is a member of `force`.
+fn executes_for(trigger: &Trigger, player: u8, force: u8) -> bool {
+    let groups = &trigger.execution_groups;
+    groups[player as usize] || groups[17] || groups[18 + force as usize]
+}
+
+/// A snapshot of a construct that triggers can inspect and modify.
+#[derive(Debug, Copy, Clone)]
+struct UnitSnapshot {
+    entity: Entity,
+    id: ConstructId,
+    construct_type: ConstructTypeId,
+    owner: Option<u8>,
+    position: Position,
+    health: Health,
+    shield: Option<Shield>,
+    energy: Option<Energy>,
+    resource_amount: Option<ResourceAmount>,
+    invincible: bool,
+    completed: bool,
+    /// Whether a trigger has modified this construct (and it needs to be written back).
+    changed: bool,
+}
+
+impl UnitSnapshot {
+    /// Returns the elevation of this unit, based on the level of the terrain beneath it. If the
+    /// terrain isn't known, the unit is treated as being on low ground.
+    fn elevation(&self, terrain: Option<&GameMapTerrain>) -> Elevation {
+        let flags = terrain
+            .and_then(|t| t.tile_at(self.position))
+            .map(|t| t.flags)
+            .unwrap_or_default();
+        let is_flyer = self
+            .construct_type
+            .def()
+            .flags
+            .contains(ConstructFlags::FLYER);
+        match (
+            flags.contains(MegaTileFlags::LEVEL_HIGH),
+            flags.contains(MegaTileFlags::LEVEL_MID),
+            is_flyer,
+        ) {
+            (true, _, false) => Elevation::HighGround,
+            (true, _, true) => Elevation::HighAir,
+            (false, true, false) => Elevation::MediumGround,
+            (false, true, true) => Elevation::MediumAir,
+            (false, false, false) => Elevation::LowGround,
+            (false, false, true) => Elevation::LowAir,
+        }
+    }
+}
+
+/// The parts of the game state that triggers can inspect and modify. Changes are made to this view
+/// while triggers run, and then written back to the ECS afterwards.
+#[derive(Debug, Default)]
+struct GameView<'a> {
+    frame: u32,
+    /// The terrain of the map, used to determine the elevation of units.
+    terrain: Option<&'a GameMapTerrain>,
+    units: Vec<UnitSnapshot>,
+    resources: [PlayerResources; NUM_CHK_PLAYERS],
+    removed: Vec<Entity>,
+    created: Vec<CreateConstructEvent>,
+    texts: Vec<TriggerTextEvent>,
+    sounds: Vec<PlayMapSoundEvent>,
+    center_views: Vec<CenterViewEvent>,
+    results: Vec<GameResultEvent>,
+}
+
+impl GameView<'_> {
+    /// Iterates over the indices of units that are owned by one of `players`, match `filter`, and
+    /// are inside `location` (if specified).
+    fn matching_units<'a>(
+        &'a self,
+        players: &'a [u8],
+        filter: UnitFilter,
+        location: Option<&'a MapLocation>,
+    ) -> impl Iterator<Item = usize> + 'a {
+        self.units.iter().enumerate().filter_map(move |(i, u)| {
+            let matches = u.owner.is_some_and(|o| players.contains(&o))
+                && filter.matches(u.construct_type)
+                && location.map_or(true, |l| {
+                    l.contains(u.position.x, u.position.y) && l.includes(u.elevation(self.terrain))
+                });
+            matches.then_some(i)
+        })
+    }
+}
+
+pub fn init_triggers(
+    mut commands: Commands,
+    current_map: Res<CurrentMap>,
+    map_assets: Res<Assets<MapAsset>>,
+    player_entities: Res<PlayerEntities>,
+    players: Query<&Player>,
+) {
+    let map = map_assets
+        .get(&current_map.handle)
+        .expect("Current map not loaded");
+    let forces = std::array::from_fn(|i| {
+        player_entities
+            .get(i as u8)
+            .and_then(|e| players.get(e).ok())
+            .map(|p| p.force)
+    });
+
+    info!("Initializing {} triggers", map.triggers.len());
+    // TODO(tec27): Run the briefing triggers before the game starts
+    commands.insert_resource(TriggerState::new(map, forces));
+}
+
+fn remove_trigger_state(mut commands: Commands) {
+    commands.remove_resource::<TriggerState>();
+}
+
+fn record_kills(mut state: ResMut<TriggerState>, mut events: EventReader<ConstructKilledEvent>) {
+    for event in events.read() {
+        // Killing your own units doesn't count as a kill
+        let killer = event.killer_owner.filter(|&k| Some(k) != event.owner);
+        state.record_death(event.construct_type, event.owner, killer);
+    }
+}
+
+fn run_triggers(
+    mut commands: Commands,
+    mut state: ResMut<TriggerState>,
+    game_frame: Res<GameFrame>,
+    mut rand: ResMut<LcgRand>,
+    player_entities: Res<PlayerEntities>,
+    mut player_resources: Query<&mut PlayerResources>,
+    terrain: Query<&GameMapTerrain, With<GameMap>>,
+    mut constructs: Query<(
+        Entity,
+        &ConstructId,
+        &ConstructTypeId,
+        &mut Position,
+        Option<&mut OwnedConstruct>,
+        &mut Health,
+        Option<&mut Shield>,
+        Option<&mut Energy>,
+        Option<&mut ResourceAmount>,
+        Has<UnderConstruction>,
+        Has<Invincible>,
+    )>,
+    mut create_events: EventWriter<CreateConstructEvent>,
+    mut text_events: EventWriter<TriggerTextEvent>,
+    mut sound_events: EventWriter<PlayMapSoundEvent>,
+    mut center_view_events: EventWriter<CenterViewEvent>,
+    mut result_events: EventWriter<GameResultEvent>,
+) {
+    let frame = game_frame.0;
+    if frame > 0 && frame % FRAMES_PER_GAME_SECOND == 0 && !state.countdown_paused {
+        state.countdown = state.countdown.saturating_sub(1);
+    }
+
+    let mut any_wait_finished = false;
+    for player in state.players.iter_mut() {
+        if let Some(ref mut wait) = player.wait {
+            wait.frames_remaining = wait.frames_remaining.saturating_sub(1);
+            any_wait_finished |= wait.frames_remaining == 0;
+        }
+    }
+    let is_trigger_cycle = frame % TRIGGER_INTERVAL == 0;
+    if !is_trigger_cycle && !any_wait_finished {
+        return;
+    }
+
+    let mut view = GameView {
+        frame,
+        terrain: terrain.get_single().ok(),
+        ..default()
+    };
+    for (
+        entity,
+        id,
+        construct_type,
+        position,
+        owner,
+        health,
+        shield,
+        energy,
+        resource_amount,
+        under_construction,
+        invincible,
+    ) in &constructs
+    {
+        view.units.push(UnitSnapshot {
+            entity,
+            id: *id,
+            construct_type: *construct_type,
+            owner: owner.map(|o| o.0),
+            position: *position,
+            health: *health,
+            shield: shield.copied(),
+            energy: energy.copied(),
+            resource_amount: resource_amount.copied(),
+            invincible,
+            completed: !under_construction,
+            changed: false,
+        });
+    }
+    // NOTE(tec27): Query order isn't something we want the simulation to depend on, so we sort by
+    // construct ID (which is the same for every player) to make sure triggers always see units in
+    // the same order
+    view.units.sort_by_key(|u| u.id);
+    for (i, resources) in view.resources.iter_mut().enumerate() {
+        if let Some(r) = player_entities
+            .get(i as u8)
+            .and_then(|e| player_resources.get(e).ok())
+        {
+            *resources = *r;
+        }
+    }
+
+    for player in 0..NUM_TRIGGER_PLAYERS as u8 {
+        let start = match state.players[player as usize].wait {
+            Some(wait) if wait.frames_remaining == 0 => {
+                state.players[player as usize].wait = None;
+                wait.instance
+            }
+            Some(_) => continue,
+            None if is_trigger_cycle => 0,
+            None => continue,
+        };
+        run_player_triggers(player, start, &mut state, &mut view, &mut rand);
+    }
+
+    for entity in view.removed.drain(..) {
+        commands.entity(entity).despawn_recursive();
+    }
+    for unit in view.units.iter().filter(|u| u.changed) {
+        let Ok((_, _, _, mut position, owner, mut health, shield, energy, resource_amount, _, _)) =
+            constructs.get_mut(unit.entity)
+        else {
+            continue;
+        };
+        *position = unit.position;
+        *health = unit.health;
+        if let (Some(mut shield), Some(new_shield)) = (shield, unit.shield) {
+            *shield = new_shield;
+        }
+        if let (Some(mut energy), Some(new_energy)) = (energy, unit.energy) {
+            *energy = new_energy;
+        }
+        if let (Some(mut amount), Some(new_amount)) = (resource_amount, unit.resource_amount) {
+            amount.set_if_neq(new_amount);
+        }
+        if unit.invincible {
+            commands.entity(unit.entity).insert(Invincible);
+        } else {
+            commands.entity(unit.entity).remove::<Invincible>();
+        }
+        match (owner, unit.owner) {
+            (Some(mut owner), Some(new_owner)) => owner.0 = new_owner,
+            (None, Some(new_owner)) => {
+                commands
+                    .entity(unit.entity)
+                    .insert(OwnedConstruct(new_owner));
+            }
+            _ => {}
+        }
+    }
+    for (i, resources) in view.resources.iter().enumerate() {
+        if let Some(mut r) = player_entities
+            .get(i as u8)
+            .and_then(|e| player_resources.get_mut(e).ok())
+        {
+            r.set_if_neq(*resources);
+        }
+    }
+
+    create_events.send_batch(view.created);
+    text_events.send_batch(view.texts);
+    sound_events.send_batch(view.sounds);
+    center_view_events.send_batch(view.center_views);
+    for result in view.results {
+        info!(
+            "Player {} game result: {:?}",
+            result.player + 1,
+            result.result
+        );
+        result_events.send(result);
+    }
+}
+
+/// Checks and executes the triggers for `player`, starting from the trigger instance at index
+/// `start`. Execution stops early if a Wait action is encountered or the player's game ends.
+fn run_player_triggers(
+    player: u8,
+    start: usize,
+    state: &mut TriggerState,
+    view: &mut GameView,
+    rand: &mut LcgRand,
+) {
+    let triggers = state.triggers.clone();
+    for i in start..state.players[player as usize].instances.len() {
+        if !state.is_playing(player) {
+            return;
+        }
+
+        let instance = state.players[player as usize].instances[i];
+        if instance.done {
+            continue;
+        }
+        let trigger = &triggers[instance.trigger];
+        // Triggers that were interrupted by a Wait already had their conditions checked
+        if instance.next_action == 0
+            && !trigger.conditions.iter().all(|c| {
+                c.flags.contains(ConditionFlags::DISABLED)
+                    || conditions::is_met(c, player, state, view)
+            })
+        {
+            continue;
+        }
+
+        let mut preserved = instance.preserved;
+        for (j, action) in trigger
+            .actions
+            .iter()
+            .enumerate()
+            .skip(instance.next_action)
+        {
+            if action.flags.contains(ActionFlags::DISABLED) {
+                continue;
+            }
+
+            match action.kind {
+                ActionType::PreserveTrigger => preserved = true,
+                ActionType::Wait => {
+                    let player_triggers = &mut state.players[player as usize];
+                    let instance = &mut player_triggers.instances[i];
+                    instance.next_action = j + 1;
+                    instance.preserved = preserved;
+                    player_triggers.wait = Some(PendingWait {
+                        instance: i,
+                        frames_remaining: action.time.div_ceil(WAIT_MS_PER_FRAME).max(1),
+                    });
+                    return;
+                }
+                _ => actions::execute(action, player, state, view, rand),
+            }
+        }
+
+        let instance = &mut state.players[player as usize].instances[i];
+        instance.next_action = 0;
+        instance.preserved = preserved;
+        instance.done = !preserved;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maps::{
+        tileset::{MegaTileInfo, MiniTileFlags},
+        triggers::{
+            Action, ActionType, Comparison, Condition, ConditionType, Modifier, UnitFilter,
+            NUM_EXECUTION_GROUPS,
+        },
+    };
+
+    fn trigger(players: &[usize], conditions: Vec<Condition>, actions: Vec<Action>) -> Trigger {
+        let mut execution_groups = [false; NUM_EXECUTION_GROUPS];
+        for &p in players {
+            execution_groups[p] = true;
+        }
+        Trigger {
+            conditions,
+            actions,
+            flags: TriggerFlags::empty(),
+            execution_groups,
+        }
+    }
+
+    fn switch_condition(switch: u8, comparison: Comparison) -> Condition {
+        Condition {
+            location: 0,
+            group: PlayerGroup::CurrentPlayer,
+            amount: 0,
+            unit: UnitFilter::AnyUnit,
+            comparison,
+            kind: ConditionType::Switch,
+            resource: switch,
+            flags: ConditionFlags::empty(),
+        }
+    }
+
+    fn action(kind: ActionType) -> Action {
+        Action {
+            location: 0,
+            string: 0,
+            wav_string: 0,
+            time: 0,
+            group: PlayerGroup::CurrentPlayer,
+            number: 0,
+            unit_type: 0,
+            kind,
+            modifier: 0,
+            flags: ActionFlags::empty(),
+        }
+    }
+
+    fn set_resources(amount: u32) -> Action {
+        Action {
+            number: amount,
+            modifier: Modifier::Add.into(),
+            ..action(ActionType::SetResources)
+        }
+    }
+
+    fn run_cycle(state: &mut TriggerState, view: &mut GameView) {
+        let mut rand = LcgRand::new(0);
+        for player in 0..NUM_TRIGGER_PLAYERS as u8 {
+            if state.players[player as usize].wait.is_none() {
+                run_player_triggers(player, 0, state, view, &mut rand);
+            }
+        }
+    }
+
+    pub(super) fn test_state(triggers: Vec<Trigger>) -> TriggerState {
+        let mut present = [false; NUM_CHK_PLAYERS];
+        present[0] = true;
+        present[1] = true;
+        let mut forces = [0; NUM_CHK_PLAYERS];
+        forces[1] = 1;
+
+        TriggerState {
+            players: build_player_triggers(&triggers, present, forces),
+            triggers: triggers.into(),
+            present,
+            forces,
+            strings: vec![MapString::decode(b"Hello")],
+            locations: Vec::new(),
+            switches: [false; NUM_SWITCHES],
+            deaths: Box::new([[0; NUM_UNIT_TYPES]; NUM_CHK_PLAYERS]),
+            kills: Box::new([[0; NUM_UNIT_TYPES]; NUM_CHK_PLAYERS]),
+            custom_scores: [0; NUM_CHK_PLAYERS],
+            countdown: 0,
+            countdown_paused: false,
+            alliances: default(),
+            results: [None; NUM_CHK_PLAYERS],
+            mission_objectives: 0,
+            unsupported_actions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn triggers_run_in_order_once() {
+        let mut state = test_state(vec![
+            // Run by all players: set switch 1, give 10 minerals
+            trigger(
+                &[17],
+                vec![],
+                vec![
+                    Action {
+                        number: 1,
+                        modifier: Modifier::Set.into(),
+                        ..action(ActionType::SetSwitch)
+                    },
+                    set_resources(10),
+                ],
+            ),
+            // Run by player 2 only, but only once switch 1 is set
+            trigger(
+                &[1],
+                vec![switch_condition(1, Comparison::Set)],
+                vec![
+                    set_resources(5),
+                    Action {
+                        string: 1,
+                        ..action(ActionType::DisplayTextMessage)
+                    },
+                ],
+            ),
+        ]);
+        let mut view = GameView::default();
+
+        run_cycle(&mut state, &mut view);
+        assert!(state.switches[1]);
+        assert_eq!(view.resources[0].minerals, 10);
+        assert_eq!(view.resources[1].minerals, 15);
+        assert_eq!(view.texts.len(), 1);
+        assert_eq!(view.texts[0].player, 1);
+
+        // Not preserved, so nothing changes on the next cycle
+        run_cycle(&mut state, &mut view);
+        assert_eq!(view.resources[0].minerals, 10);
+        assert_eq!(view.resources[1].minerals, 15);
+    }
+
+    #[test]
+    fn waits_block_and_resume() {
+        let mut state = test_state(vec![
+            trigger(
+                &[0],
+                vec![],
+                vec![
+                    set_resources(1),
+                    Action {
+                        time: 100,
+                        ..action(ActionType::Wait)
+                    },
+                    set_resources(2),
+                    action(ActionType::PreserveTrigger),
+                ],
+            ),
+            trigger(&[0], vec![], vec![set_resources(100)]),
+        ]);
+        let mut view = GameView::default();
+
+        run_cycle(&mut state, &mut view);
+        assert_eq!(view.resources[0].minerals, 1);
+        let wait = state.players[0].wait.unwrap();
+        assert_eq!(wait.frames_remaining, 3);
+
+        // Blocked by the wait
+        run_cycle(&mut state, &mut view);
+        assert_eq!(view.resources[0].minerals, 1);
+
+        state.players[0].wait = None;
+        run_player_triggers(
+            0,
+            wait.instance,
+            &mut state,
+            &mut view,
+            &mut LcgRand::new(0),
+        );
+        assert_eq!(view.resources[0].minerals, 103);
+
+        // The first trigger was preserved, so it runs again
+        run_cycle(&mut state, &mut view);
+        assert_eq!(view.resources[0].minerals, 104);
+    }
+
+    #[test]
+    fn victory_stops_execution() {
+        let mut state = test_state(vec![
+            trigger(&[0], vec![], vec![action(ActionType::Victory)]),
+            trigger(&[0], vec![], vec![set_resources(1)]),
+        ]);
+        let mut view = GameView::default();
+
+        run_cycle(&mut state, &mut view);
+        assert_eq!(state.results[0], Some(GameResult::Victory));
+        assert_eq!(view.resources[0].minerals, 0);
+        assert_eq!(view.results.len(), 1);
+    }
+
+    #[test]
+    fn deaths_and_kills() {
+        let mut state = test_state(vec![]);
+        let marine = u16::from(ConstructTypeId::TerranMarine) as usize;
+        state.record_death(ConstructTypeId::TerranMarine, Some(1), Some(0));
+        state.record_death(ConstructTypeId::TerranMarine, Some(1), None);
+        state.record_death(ConstructTypeId::TerranMarine, None, Some(2));
+        assert_eq!(state.deaths[1][marine], 2);
+        assert_eq!(state.kills[0][marine], 1);
+        assert_eq!(state.kills[2][marine], 1);
+        assert_eq!(state.deaths[0][marine], 0);
+    }
+
+    #[test]
+    fn elevation_from_terrain() {
+        let tile = |flags| MegaTileInfo {
+            flags,
+            id: 0,
+            mini_tiles: [MiniTileFlags::empty(); 16],
+        };
+        let terrain = GameMapTerrain::new(
+            vec![
+                tile(MegaTileFlags::empty()),
+                tile(MegaTileFlags::LEVEL_MID),
+                tile(MegaTileFlags::LEVEL_HIGH),
+            ],
+            3,
+        );
+        let unit = |construct_type, x| UnitSnapshot {
+            entity: Entity::from_raw(0),
+            id: ConstructId(0),
+            construct_type,
+            owner: Some(0),
+            position: Position::new(x, 16),
+            health: Health::default(),
+            shield: None,
+            energy: None,
+            resource_amount: None,
+            invincible: false,
+            completed: true,
+            changed: false,
+        };
+
+        let marine = ConstructTypeId::TerranMarine;
+        let wraith = ConstructTypeId::TerranWraith;
+        assert_eq!(
+            unit(marine, 16).elevation(Some(&terrain)),
+            Elevation::LowGround
+        );
+        assert_eq!(
+            unit(marine, 48).elevation(Some(&terrain)),
+            Elevation::MediumGround
+        );
+        assert_eq!(
+            unit(marine, 80).elevation(Some(&terrain)),
+            Elevation::HighGround
+        );
+        assert_eq!(
+            unit(wraith, 80).elevation(Some(&terrain)),
+            Elevation::HighAir
+        );
+        assert_eq!(unit(wraith, 80).elevation(None), Elevation::LowAir);
+    }
+
+    #[test]
+    fn groups() {
+        let mut state = test_state(vec![]);
+        state.present[2] = true;
+        state.forces[2] = 1;
+        state.alliances[0][2] = AllianceStatus::Allied;
+
+        assert_eq!(state.resolve_group(PlayerGroup::Foes, 0), vec![1]);
+        assert_eq!(state.resolve_group(PlayerGroup::Allies, 0), vec![2]);
+        assert_eq!(
+            state.resolve_group(PlayerGroup::AllPlayers, 0),
+            vec![0, 1, 2]
+        );
+        assert_eq!(state.resolve_group(PlayerGroup::Force(1), 0), vec![1, 2]);
+        assert_eq!(state.resolve_group(PlayerGroup::CurrentPlayer, 2), vec![2]);
+    }
+}
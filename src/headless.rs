@@ -0,0 +1,187 @@
+use std::time::Instant;
+
+use bevy::{app::AppExit, prelude::*, time::TimeUpdateStrategy};
+
+use crate::{
+    gameplay::{GameFrame, GameSpeed},
+    states::AppState,
+};
+
+/// Plugin that sets up an [App] to run the game simulation without any rendering. Assets that are
+/// normally registered by the rendering plugins are registered here instead so that game data can
+/// still be loaded, and time is advanced by exactly one game frame on every update.
+pub struct HeadlessPlugin {
+    /// The number of game frames to simulate before exiting. If `None`, the app will run until
+    /// exited some other way.
+    pub max_frames: Option<u32>,
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Image>()
+            .init_asset::<AudioSource>()
+            .init_asset::<TextureAtlasLayout>()
+            .insert_resource(TimeUpdateStrategy::ManualDuration(
+                GameSpeed::Fastest.to_turn_duration(),
+            ))
+            .insert_resource(HeadlessRun {
+                max_frames: self.max_frames,
+                started_at: None,
+            })
+            .add_systems(OnEnter(AppState::InGame), start_run)
+            .add_systems(OnEnter(AppState::LoadError), exit_on_load_error)
+            .add_systems(
+                FixedLast,
+                exit_after_max_frames.run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+/// Resource that tracks the progress of a headless run.
+#[derive(Resource, Debug)]
+pub struct HeadlessRun {
+    pub max_frames: Option<u32>,
+    started_at: Option<Instant>,
+}
+
+fn start_run(mut run: ResMut<HeadlessRun>) {
+    info!("Starting headless simulation");
+    run.started_at = Some(Instant::now());
+}
+
+fn exit_on_load_error(mut app_exit_events: EventWriter<AppExit>) {
+    error!("Couldn't load the files needed to run the game, exiting");
+    app_exit_events.send(AppExit::error());
+}
+
+fn exit_after_max_frames(
+    run: Res<HeadlessRun>,
+    game_frame: Res<GameFrame>,
+    mut app_exit_events: EventWriter<AppExit>,
+) {
+    let Some(max_frames) = run.max_frames else {
+        return;
+    };
+    if game_frame.0 < max_frames {
+        return;
+    }
+
+    let elapsed = run.started_at.map(|s| s.elapsed()).unwrap_or_default();
+    info!(
+        "Simulated {} frames in {:.2?} ({:.1} frames/sec)",
+        game_frame.0,
+        elapsed,
+        game_frame.0 as f64 / elapsed.as_secs_f64().max(f64::EPSILON),
+    );
+    app_exit_events.send(AppExit::Success);
+}
+
+/// Creates a headless [App] that has started a Use Map Settings game on `map`, for tests that need
+/// to run the game simulation. The BW game data files aren't needed (so construct graphics won't be
+/// loaded), and each update of the app simulates exactly one game frame.
+#[cfg(test)]
+pub(crate) fn create_test_game(map: crate::maps::MapAsset) -> App {
+    use crate::{
+        create_headless_app,
+        gamedata::BwGameData,
+        gameplay::GameMode,
+        maps::{CurrentMap, MapAsset},
+        settings::{AssetQuality, GameSettings},
+    };
+
+    let settings = GameSettings {
+        asset_quality: AssetQuality::Standard,
+        ..default()
+    };
+    let mut app = create_headless_app(settings, Vec::new(), None);
+    // Runs the startup systems, which would otherwise override the game mode
+    app.update();
+
+    app.insert_resource(GameMode::UseMapSettings)
+        .insert_resource(BwGameData {
+            image_paths: default(),
+            strings: default(),
+            relations: default(),
+        });
+    let map = app.world_mut().resource_mut::<Assets<MapAsset>>().add(map);
+    app.world_mut().resource_mut::<CurrentMap>().handle = map;
+    app.world_mut()
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::PreGame);
+
+    for _ in 0..10 {
+        app.update();
+        if *app.world().resource::<State<AppState>>().get() == AppState::InGame {
+            return app;
+        }
+    }
+    panic!("Test game never started");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        create_headless_app,
+        gamedata::ConstructTypeId,
+        gameplay::{
+            constructs::OwnedConstruct,
+            create_construct::{CreateConstructEvent, CreationKind},
+            players::PlayerEntities,
+        },
+        maps::{position::Position, MapAsset},
+        settings::GameSettings,
+    };
+
+    use super::*;
+
+    #[test]
+    fn runs_without_window() {
+        let mut app = create_headless_app(GameSettings::default(), Vec::new(), None);
+        for _ in 0..10 {
+            app.update();
+        }
+
+        assert_eq!(
+            *app.world().resource::<State<AppState>>().get(),
+            AppState::Menu
+        );
+        assert_eq!(
+            app.world().resource::<Time<Virtual>>().elapsed(),
+            // The first update doesn't advance time
+            GameSpeed::Fastest.to_turn_duration() * 9
+        );
+    }
+
+    #[test]
+    fn simulates_game_on_fixture_map() {
+        let mut app = create_test_game(MapAsset::fixture(64, 64));
+        // Both of the map's slots should have players
+        assert!(app.world().resource::<PlayerEntities>().get(0).is_some());
+        assert!(app.world().resource::<PlayerEntities>().get(1).is_some());
+
+        let start_frame = app.world().resource::<GameFrame>().0;
+        app.world_mut().send_event(CreateConstructEvent {
+            construct_type: ConstructTypeId::TerranCommandCenter,
+            owner: Some(0),
+            position: Some(Position::new(1024, 1024)),
+            kind: CreationKind::Immediate,
+            ..default()
+        });
+        for _ in 0..50 {
+            app.update();
+        }
+
+        assert_eq!(app.world().resource::<GameFrame>().0, start_frame + 50);
+        let mut constructs = app
+            .world_mut()
+            .query::<(&ConstructTypeId, &Position, &OwnedConstruct)>();
+        assert_eq!(
+            constructs.iter(app.world()).collect::<Vec<_>>(),
+            vec![(
+                &ConstructTypeId::TerranCommandCenter,
+                &Position::new(1024, 1024),
+                &OwnedConstruct(0)
+            )]
+        );
+    }
+}
@@ -0,0 +1,425 @@
+//! The input action layer: rather than reading keys directly, game systems check whether an
+//! [InputAction] was pressed. Which keys trigger each action is configured through the
+//! [Keybindings] in [GameSettings], so they can be rebound by the player.
+
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::camera::NUM_CAMERA_LOCATIONS;
+use crate::gameplay::command_card::{CommandAction, COMMAND_CARD_SIZE};
+use crate::gameplay::player_selection::NUM_CONTROL_GROUPS;
+use crate::settings::GameSettings;
+
+pub struct InputActionPlugin;
+
+impl Plugin for InputActionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ButtonInput<InputAction>>()
+            .add_systems(PreUpdate, update_actions.after(InputSystem));
+    }
+}
+
+/// Something the player can do by pressing a key. The current state of each action can be read
+/// from the `ButtonInput<InputAction>` resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Reflect)]
+#[serde(rename_all = "camelCase")]
+pub enum InputAction {
+    /// Opens or closes the in-game menu.
+    ToggleMenu,
+    /// Loads the next map out of the ones that were specified on the command line.
+    CycleMap,
+    /// Held to add to (or toggle) the current selection or a control group instead of replacing it.
+    AddModifier,
+    /// Held to assign control groups, or to select all constructs of the clicked type.
+    GroupModifier,
+    /// Selects all of the local player's army (units that aren't workers).
+    SelectAllArmy,
+    /// Centers the camera on the local player's selection.
+    CenterOnSelection,
+    /// Centers the camera on the location of the last alert (e.g. a construct being attacked).
+    JumpToAlert,
+    /// Moves the camera to the saved camera location with this number, or saves the current
+    /// location if [InputAction::AddModifier] is held.
+    CameraLocation(u8),
+    /// Recalls (or with modifiers, assigns/adds to) the control group with this number.
+    ControlGroup(u8),
+    /// Uses this action on the command card. Only active with [HotkeyLayout::Classic].
+    CommandCard(CommandAction),
+    /// Uses the button in this slot of the command card. Only active with [HotkeyLayout::Grid].
+    CommandCardSlot(u8),
+    ScrollUp,
+    ScrollDown,
+    ScrollLeft,
+    ScrollRight,
+}
+
+/// The command card actions, in the order they are listed for rebinding.
+const COMMAND_ACTIONS: [CommandAction; 11] = [
+    CommandAction::Move,
+    CommandAction::Stop,
+    CommandAction::Attack,
+    CommandAction::Patrol,
+    CommandAction::HoldPosition,
+    CommandAction::Gather,
+    CommandAction::ReturnCargo,
+    CommandAction::BuildBasic,
+    CommandAction::BuildAdvanced,
+    CommandAction::Burrow,
+    CommandAction::SetRallyPoint,
+];
+
+/// The default keys for each command card slot in the grid layout, matching the position of the
+/// slot on the command card.
+const GRID_KEYS: [KeyCode; COMMAND_CARD_SIZE] = [
+    KeyCode::KeyQ,
+    KeyCode::KeyW,
+    KeyCode::KeyE,
+    KeyCode::KeyA,
+    KeyCode::KeyS,
+    KeyCode::KeyD,
+    KeyCode::KeyZ,
+    KeyCode::KeyX,
+    KeyCode::KeyC,
+];
+
+impl InputAction {
+    /// Returns every action that is active when using `layout`.
+    pub fn all(layout: HotkeyLayout) -> impl Iterator<Item = InputAction> {
+        let command_card = match layout {
+            HotkeyLayout::Classic => COMMAND_ACTIONS
+                .iter()
+                .map(|&a| InputAction::CommandCard(a))
+                .collect::<Vec<_>>(),
+            HotkeyLayout::Grid => (0..COMMAND_CARD_SIZE as u8)
+                .map(InputAction::CommandCardSlot)
+                .collect(),
+        };
+
+        [
+            InputAction::ToggleMenu,
+            InputAction::CycleMap,
+            InputAction::AddModifier,
+            InputAction::GroupModifier,
+            InputAction::SelectAllArmy,
+            InputAction::CenterOnSelection,
+            InputAction::JumpToAlert,
+        ]
+        .into_iter()
+        .chain((0..NUM_CONTROL_GROUPS as u8).map(InputAction::ControlGroup))
+        .chain((0..NUM_CAMERA_LOCATIONS as u8).map(InputAction::CameraLocation))
+        .chain(command_card)
+        .chain([
+            InputAction::ScrollUp,
+            InputAction::ScrollDown,
+            InputAction::ScrollLeft,
+            InputAction::ScrollRight,
+        ])
+    }
+
+    /// Returns the name of this action, as shown in the UI.
+    pub fn name(&self) -> String {
+        match *self {
+            InputAction::ToggleMenu => "Menu".into(),
+            InputAction::CycleMap => "Next Map".into(),
+            InputAction::AddModifier => "Add Modifier".into(),
+            InputAction::GroupModifier => "Group Modifier".into(),
+            InputAction::SelectAllArmy => "Select Army".into(),
+            InputAction::CenterOnSelection => "Center on Selection".into(),
+            InputAction::JumpToAlert => "Jump to Alert".into(),
+            InputAction::CameraLocation(location) => format!("Camera Location {}", location + 1),
+            InputAction::ControlGroup(group) => format!("Control Group {group}"),
+            InputAction::CommandCard(action) => action.name().into(),
+            InputAction::CommandCardSlot(slot) => {
+                format!("Command Slot {}, {}", slot % 3 + 1, slot / 3 + 1)
+            }
+            InputAction::ScrollUp => "Scroll Up".into(),
+            InputAction::ScrollDown => "Scroll Down".into(),
+            InputAction::ScrollLeft => "Scroll Left".into(),
+            InputAction::ScrollRight => "Scroll Right".into(),
+        }
+    }
+
+    /// Returns the key this action is bound to if the player hasn't changed it.
+    pub fn default_key(&self) -> Option<KeyCode> {
+        match *self {
+            InputAction::ToggleMenu => Some(KeyCode::F10),
+            InputAction::CycleMap => Some(KeyCode::Space),
+            InputAction::AddModifier => Some(KeyCode::ShiftLeft),
+            InputAction::GroupModifier => Some(KeyCode::ControlLeft),
+            InputAction::SelectAllArmy => Some(KeyCode::Backquote),
+            InputAction::CenterOnSelection => Some(KeyCode::Backspace),
+            InputAction::JumpToAlert => Some(KeyCode::End),
+            InputAction::ControlGroup(group) => [
+                KeyCode::Digit0,
+                KeyCode::Digit1,
+                KeyCode::Digit2,
+                KeyCode::Digit3,
+                KeyCode::Digit4,
+                KeyCode::Digit5,
+                KeyCode::Digit6,
+                KeyCode::Digit7,
+                KeyCode::Digit8,
+                KeyCode::Digit9,
+            ]
+            .get(group as usize)
+            .copied(),
+            InputAction::CameraLocation(location) => [KeyCode::F2, KeyCode::F3, KeyCode::F4]
+                .get(location as usize)
+                .copied(),
+            InputAction::CommandCard(action) => Some(action.default_hotkey()),
+            InputAction::CommandCardSlot(slot) => GRID_KEYS.get(slot as usize).copied(),
+            InputAction::ScrollUp => Some(KeyCode::ArrowUp),
+            InputAction::ScrollDown => Some(KeyCode::ArrowDown),
+            InputAction::ScrollLeft => Some(KeyCode::ArrowLeft),
+            InputAction::ScrollRight => Some(KeyCode::ArrowRight),
+        }
+    }
+}
+
+/// How command card hotkeys are assigned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize, Reflect)]
+#[serde(rename_all = "camelCase")]
+pub enum HotkeyLayout {
+    /// Each action has its own key, regardless of where it is on the command card (e.g. `A` for
+    /// Attack).
+    #[default]
+    Classic,
+    /// Each slot of the command card has a key matching its position on the keyboard (e.g. `Q` for
+    /// the top left button).
+    Grid,
+}
+
+/// A key the player has chosen for an action, replacing its default key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+#[serde(rename_all = "camelCase")]
+pub struct Keybinding {
+    pub action: InputAction,
+    /// The key that triggers the action, or `None` if it has been unbound.
+    pub key: Option<KeyCode>,
+}
+
+/// Two actions that are active at the same time but bound to the same key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeybindingConflict {
+    pub key: KeyCode,
+    pub first: InputAction,
+    pub second: InputAction,
+}
+
+/// The keys bound to each [InputAction].
+// NOTE(tec27): Only the bindings that differ from the defaults are stored, so that new actions get
+// their default keys in existing settings files
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+#[serde(rename_all = "camelCase")]
+pub struct Keybindings {
+    #[serde(default)]
+    pub layout: HotkeyLayout,
+    #[serde(default)]
+    pub custom: Vec<Keybinding>,
+}
+
+impl Keybindings {
+    /// Returns the key that triggers `action`, if it is bound to one.
+    pub fn key_for(&self, action: InputAction) -> Option<KeyCode> {
+        match self.custom.iter().rev().find(|b| b.action == action) {
+            Some(binding) => binding.key,
+            None => action.default_key(),
+        }
+    }
+
+    /// Binds `action` to `key` (or unbinds it if `key` is `None`).
+    pub fn bind(&mut self, action: InputAction, key: Option<KeyCode>) {
+        self.custom.retain(|b| b.action != action);
+        if key != action.default_key() {
+            self.custom.push(Keybinding { action, key });
+        }
+    }
+
+    /// Returns each action that is active in the current layout, along with the key it is bound to.
+    pub fn active(&self) -> impl Iterator<Item = (InputAction, KeyCode)> + '_ {
+        InputAction::all(self.layout).filter_map(|a| self.key_for(a).map(|k| (a, k)))
+    }
+
+    /// Returns the key for the command card button at `slot` that uses `action`, taking the current
+    /// layout into account.
+    pub fn command_card_key(&self, slot: usize, action: CommandAction) -> Option<KeyCode> {
+        match self.layout {
+            HotkeyLayout::Classic => self.key_for(InputAction::CommandCard(action)),
+            HotkeyLayout::Grid => self.key_for(InputAction::CommandCardSlot(slot as u8)),
+        }
+    }
+
+    /// Returns every pair of active actions that are bound to the same key.
+    pub fn conflicts(&self) -> Vec<KeybindingConflict> {
+        let mut by_key = HashMap::<KeyCode, InputAction>::new();
+        let mut conflicts = Vec::new();
+        for (action, key) in self.active() {
+            let key = normalize_key(key);
+            if let Some(&first) = by_key.get(&key) {
+                conflicts.push(KeybindingConflict {
+                    key,
+                    first,
+                    second: action,
+                });
+            } else {
+                by_key.insert(key, action);
+            }
+        }
+        conflicts
+    }
+}
+
+/// Returns the name of `key`, as shown in the UI.
+pub fn key_name(key: KeyCode) -> String {
+    let name = format!("{:?}", key);
+    ["Key", "Digit"]
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+        .unwrap_or(&name)
+        .to_string()
+}
+
+/// Treats the left and right versions of modifier keys as the same key, so either can be used.
+fn normalize_key(key: KeyCode) -> KeyCode {
+    match key {
+        KeyCode::ShiftRight => KeyCode::ShiftLeft,
+        KeyCode::ControlRight => KeyCode::ControlLeft,
+        KeyCode::AltRight => KeyCode::AltLeft,
+        KeyCode::SuperRight => KeyCode::SuperLeft,
+        key => key,
+    }
+}
+
+fn update_actions(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<GameSettings>,
+    mut actions: ResMut<ButtonInput<InputAction>>,
+) {
+    actions.clear();
+
+    let pressed = keys
+        .get_pressed()
+        .map(|&k| normalize_key(k))
+        .collect::<Vec<_>>();
+    let just_pressed = keys
+        .get_just_pressed()
+        .map(|&k| normalize_key(k))
+        .collect::<Vec<_>>();
+    let active = settings.keybindings.active().collect::<Vec<_>>();
+    for &(action, key) in active.iter() {
+        let key = normalize_key(key);
+        if just_pressed.contains(&key) || pressed.contains(&key) {
+            actions.press(action);
+        } else {
+            actions.release(action);
+        }
+    }
+
+    // Actions that are no longer active (e.g. because the layout changed) shouldn't stay pressed
+    let stale = actions
+        .get_pressed()
+        .filter(|&&a| !active.iter().any(|&(b, _)| a == b))
+        .copied()
+        .collect::<Vec<_>>();
+    for action in stale {
+        actions.release(action);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_bindings() {
+        let mut bindings = Keybindings::default();
+        assert_eq!(bindings.conflicts(), vec![]);
+        assert_eq!(
+            bindings.command_card_key(2, CommandAction::Attack),
+            Some(KeyCode::KeyA)
+        );
+
+        bindings.layout = HotkeyLayout::Grid;
+        assert_eq!(bindings.conflicts(), vec![]);
+        assert_eq!(
+            bindings.command_card_key(2, CommandAction::Attack),
+            Some(KeyCode::KeyE)
+        );
+    }
+
+    #[test]
+    fn rebinding() {
+        let mut bindings = Keybindings::default();
+        bindings.bind(InputAction::ToggleMenu, Some(KeyCode::Escape));
+        bindings.bind(InputAction::CycleMap, None);
+        assert_eq!(
+            bindings.key_for(InputAction::ToggleMenu),
+            Some(KeyCode::Escape)
+        );
+        assert_eq!(bindings.key_for(InputAction::CycleMap), None);
+        assert!(bindings.active().all(|(a, _)| a != InputAction::CycleMap));
+
+        // Binding back to the default doesn't need to be stored
+        bindings.bind(InputAction::ToggleMenu, Some(KeyCode::F10));
+        bindings.bind(InputAction::CycleMap, Some(KeyCode::Space));
+        assert_eq!(bindings, Keybindings::default());
+    }
+
+    #[test]
+    fn conflicts() {
+        let mut bindings = Keybindings::default();
+        bindings.bind(InputAction::SelectAllArmy, Some(KeyCode::KeyM));
+        bindings.bind(InputAction::GroupModifier, Some(KeyCode::ShiftRight));
+        assert_eq!(
+            bindings.conflicts(),
+            vec![
+                KeybindingConflict {
+                    key: KeyCode::ShiftLeft,
+                    first: InputAction::AddModifier,
+                    second: InputAction::GroupModifier,
+                },
+                KeybindingConflict {
+                    key: KeyCode::KeyM,
+                    first: InputAction::SelectAllArmy,
+                    second: InputAction::CommandCard(CommandAction::Move),
+                },
+            ]
+        );
+
+        // Classic hotkeys aren't active in the grid layout
+        bindings.layout = HotkeyLayout::Grid;
+        assert_eq!(bindings.conflicts().len(), 1);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(key_name(KeyCode::KeyQ), "Q");
+        assert_eq!(key_name(KeyCode::Digit7), "7");
+        assert_eq!(key_name(KeyCode::F10), "F10");
+        assert_eq!(InputAction::CommandCardSlot(5).name(), "Command Slot 3, 2");
+    }
+
+    #[test]
+    fn serialization() {
+        let mut bindings = Keybindings {
+            layout: HotkeyLayout::Grid,
+            ..default()
+        };
+        bindings.bind(InputAction::ControlGroup(1), Some(KeyCode::F1));
+        bindings.bind(
+            InputAction::CommandCard(CommandAction::HoldPosition),
+            Some(KeyCode::KeyY),
+        );
+        let json = serde_json::to_string(&bindings).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Keybindings>(&json).unwrap(),
+            bindings
+        );
+        assert_eq!(
+            serde_json::from_str::<Keybindings>("{}").unwrap(),
+            Keybindings::default()
+        );
+    }
+}
@@ -5,10 +5,13 @@
 use std::path::PathBuf;
 
 use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::log::LogPlugin;
 use bevy::prelude::*;
 
 use bevy::window::{PresentMode, WindowResolution};
+use bevy_ecs_tilemap::prelude::TilemapPlugin;
 use gameplay::{GameMode, GameSpeed};
+use input::InputAction;
 use maps::{load_map, CurrentMap};
 use settings::GameSettings;
 use states::AppState;
@@ -16,13 +19,20 @@ use states::AppState;
 use crate::fonts::FONT_MONO;
 
 pub mod camera;
+pub mod check_assets;
 pub mod ecs;
 pub mod fonts;
 pub mod gamedata;
 pub mod gameplay;
+pub mod headless;
+pub mod input;
+pub mod load_error;
+pub mod lobby;
 pub mod main_menu;
 pub mod maps;
 pub mod math;
+pub mod music;
+pub mod net;
 pub mod races;
 pub mod random;
 pub mod render;
@@ -30,9 +40,8 @@ pub mod settings;
 pub mod states;
 
 pub fn create_app(settings: GameSettings, maps: Vec<PathBuf>) -> App {
-    let has_map_args = !maps.is_empty();
-
     let mut app = App::new();
+    maps::archive::register_map_asset_source(&mut app);
     // TODO(tec27): Use a smaller set of plugins, we really don't need most of this
     app.add_plugins(
         DefaultPlugins
@@ -45,8 +54,10 @@ pub fn create_app(settings: GameSettings, maps: Vec<PathBuf>) -> App {
                         settings.window_size.map(|(w, _)| w).unwrap_or(1280) as f32,
                         settings.window_size.map(|(_, h)| h).unwrap_or(960) as f32,
                     ),
-                    // TODO(tec27): Save and restore position
-                    position: WindowPosition::Centered(MonitorSelection::Primary),
+                    position: settings
+                        .window_position
+                        .map(|(x, y)| WindowPosition::At(IVec2::new(x, y)))
+                        .unwrap_or(WindowPosition::Centered(MonitorSelection::Primary)),
                     ..default()
                 }),
                 ..default()
@@ -54,34 +65,27 @@ pub fn create_app(settings: GameSettings, maps: Vec<PathBuf>) -> App {
             // Fixes issues with white "halo" effect at the transparent edges of sprites
             .set(ImagePlugin::default_nearest()),
     )
-    .register_type::<GameSettings>()
-    .insert_resource(settings)
     .insert_resource(ClearColor(Color::srgb(0.0, 0.0, 0.0)))
-    .insert_resource(LoadableMaps { maps, cur_index: 0 })
     .insert_resource(GlobalVolume::new(settings.volumes.global))
-    .insert_resource(Time::<Fixed>::from_duration(
-        GameSpeed::Fastest.to_turn_duration(),
-    ))
     .add_plugins((
         FrameTimeDiagnosticsPlugin,
+        TilemapPlugin,
         camera::CameraControlPlugin,
-        gamedata::GameDataPlugin,
-        gameplay::GameplayPlugin,
+        gameplay::GameplayInterfacePlugin,
+        input::InputActionPlugin,
+        load_error::LoadErrorScreenPlugin,
+        lobby::LobbyScreenPlugin,
         main_menu::MainMenuPlugin,
-        maps::MapsPlugin,
-        random::plugin,
+        music::MusicPlugin,
+        net::NetInterfacePlugin,
         render::RenderPlugin,
-        states::StatesPlugin,
+        settings::SettingsPlugin,
     ))
-    .add_systems(Startup, setup)
+    .add_systems(Startup, setup_ui)
     .add_systems(Update, update_fps_text)
     .add_systems(Update, map_navigator.run_if(in_state(AppState::InGame)));
 
-    if has_map_args {
-        app.insert_state(AppState::PreGame);
-    } else {
-        app.insert_state(AppState::Menu);
-    }
+    add_simulation(&mut app, settings, maps);
 
     #[cfg(feature = "framepacing")]
     app.add_plugins(bevy_framepace::FramepacePlugin);
@@ -92,6 +96,62 @@ pub fn create_app(settings: GameSettings, maps: Vec<PathBuf>) -> App {
     app
 }
 
+/// Creates an [App] that runs the game simulation without a window, GPU, audio, or UI. Each update
+/// of the app advances the game by exactly one frame, so frames are simulated as fast as possible.
+/// If `max_frames` is specified, the app will exit after that many game frames have been
+/// simulated.
+pub fn create_headless_app(
+    settings: GameSettings,
+    maps: Vec<PathBuf>,
+    max_frames: Option<u32>,
+) -> App {
+    let mut app = App::new();
+    maps::archive::register_map_asset_source(&mut app);
+    app.add_plugins((
+        MinimalPlugins,
+        LogPlugin::default(),
+        AssetPlugin::default(),
+        TransformPlugin,
+        HierarchyPlugin,
+        bevy::state::app::StatesPlugin,
+        headless::HeadlessPlugin { max_frames },
+    ));
+
+    add_simulation(&mut app, settings, maps);
+
+    app
+}
+
+/// Adds the resources, states, and plugins that are necessary to run the game simulation. These
+/// are shared between the windowed and headless configurations.
+fn add_simulation(app: &mut App, settings: GameSettings, maps: Vec<PathBuf>) {
+    let has_map_args = !maps.is_empty();
+
+    app.register_type::<GameSettings>()
+        .insert_resource(settings)
+        .insert_resource(LoadableMaps { maps, cur_index: 0 })
+        .insert_resource(Time::<Fixed>::from_duration(
+            GameSpeed::Fastest.to_turn_duration(),
+        ))
+        .add_plugins((
+            gamedata::GameDataPlugin,
+            gameplay::GameplayPlugin,
+            load_error::plugin,
+            lobby::LobbyPlugin,
+            maps::MapsPlugin,
+            net::NetPlugin,
+            random::plugin,
+            states::StatesPlugin,
+        ))
+        .add_systems(Startup, setup);
+
+    if has_map_args {
+        app.insert_state(AppState::PreGame);
+    } else {
+        app.insert_state(AppState::Menu);
+    }
+}
+
 #[derive(Resource, Clone, Debug, Default)]
 struct LoadableMaps {
     maps: Vec<PathBuf>,
@@ -116,6 +176,7 @@ fn setup(
         let map_path = loadable_maps.maps.first().cloned().unwrap();
         load_map(
             &map_path,
+            AppState::PreGame,
             &mut current_map,
             &mut next_state,
             &asset_server,
@@ -124,7 +185,9 @@ fn setup(
     } else {
         commands.insert_resource(GameMode::Melee);
     }
+}
 
+fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.spawn(Camera2dBundle::default());
 
     let font = asset_server.load(FONT_MONO);
@@ -162,16 +225,17 @@ fn update_fps_text(diagnostics: Res<DiagnosticsStore>, mut query: Query<&mut Tex
 fn map_navigator(
     mut next_state: ResMut<NextState<AppState>>,
     asset_server: Res<AssetServer>,
-    keys: Res<ButtonInput<KeyCode>>,
+    actions: Res<ButtonInput<InputAction>>,
     mut current_map: ResMut<CurrentMap>,
     mut loadable_maps: ResMut<LoadableMaps>,
     settings: Res<GameSettings>,
 ) {
-    if keys.just_pressed(KeyCode::Space) && loadable_maps.maps.len() > 1 {
+    if actions.just_pressed(InputAction::CycleMap) && loadable_maps.maps.len() > 1 {
         loadable_maps.cur_index = (loadable_maps.cur_index + 1) % loadable_maps.maps.len();
         let map_path = loadable_maps.maps[loadable_maps.cur_index].clone();
         load_map(
             &map_path,
+            AppState::PreGame,
             &mut current_map,
             &mut next_state,
             &asset_server,
@@ -0,0 +1,296 @@
+//! Reporting for game files that couldn't be loaded. Any asset that fails to load is recorded in
+//! [LoadErrors], and failures that prevent a game from starting move the app to
+//! [AppState::LoadError], which lists the missing files (and the `casc-extracted` folders they
+//! should be in) before returning to the main menu.
+
+use bevy::{asset::UntypedAssetLoadFailedEvent, prelude::*};
+
+use crate::{
+    ecs::despawn_all,
+    fonts::{FONT_BODY, FONT_BRAND},
+    states::AppState,
+};
+
+/// The folder (within `assets/`) that the extracted CASC files are expected to be in.
+const CASC_EXTRACTED_DIR: &str = "casc-extracted";
+/// The maximum number of missing files listed on the error screen.
+const MAX_LISTED_FILES: usize = 8;
+
+/// Records asset load failures. This is needed for both windowed and headless apps.
+pub fn plugin(app: &mut App) {
+    app.init_resource::<LoadErrors>()
+        .add_systems(PreUpdate, record_load_failures)
+        .add_systems(OnExit(AppState::InGame), clear_load_errors)
+        .add_systems(OnExit(AppState::LoadError), clear_load_errors);
+}
+
+/// Shows the [AppState::LoadError] screen.
+pub struct LoadErrorScreenPlugin;
+
+impl Plugin for LoadErrorScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::LoadError), setup)
+            .add_systems(
+                OnExit(AppState::LoadError),
+                despawn_all::<OnLoadErrorScreen>,
+            )
+            .add_systems(
+                Update,
+                (update_button_colors, back_to_menu).run_if(in_state(AppState::LoadError)),
+            );
+    }
+}
+
+/// An asset that failed to load.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedAsset {
+    pub path: String,
+    pub error: String,
+}
+
+/// The assets that have failed to load since the last game (or the last time the errors were
+/// shown).
+#[derive(Resource, Debug, Default)]
+pub struct LoadErrors {
+    pub failed: Vec<FailedAsset>,
+}
+
+impl LoadErrors {
+    /// Returns the `casc-extracted` folders that the failed assets should have been in, which
+    /// usually indicates which folders weren't extracted.
+    pub fn required_folders(&self) -> Vec<String> {
+        let mut folders = self
+            .failed
+            .iter()
+            .filter_map(|f| casc_folder(&f.path))
+            .collect::<Vec<_>>();
+        folders.sort();
+        folders.dedup();
+        folders
+    }
+}
+
+/// Returns the folder containing `path` if it is a path to an extracted CASC file.
+fn casc_folder(path: &str) -> Option<String> {
+    // NOTE(tec27): Some of our asset paths use Windows-style separators, so we normalize them
+    let path = path.replace('\\', "/");
+    if !path.starts_with(CASC_EXTRACTED_DIR) {
+        return None;
+    }
+    path.rsplit_once('/').map(|(folder, _)| folder.to_owned())
+}
+
+fn record_load_failures(
+    mut events: EventReader<UntypedAssetLoadFailedEvent>,
+    mut load_errors: ResMut<LoadErrors>,
+) {
+    for event in events.read() {
+        let path = event.path.to_string();
+        error!("Failed to load {path}: {}", event.error);
+        if load_errors.failed.iter().all(|f| f.path != path) {
+            load_errors.failed.push(FailedAsset {
+                path,
+                error: event.error.to_string(),
+            });
+        }
+    }
+}
+
+fn clear_load_errors(mut load_errors: ResMut<LoadErrors>) {
+    load_errors.failed.clear();
+}
+
+#[derive(Component)]
+struct OnLoadErrorScreen;
+
+#[derive(Component)]
+struct BackToMenuButton;
+
+const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, load_errors: Res<LoadErrors>) {
+    let brand = asset_server.load(FONT_BRAND);
+    let body = asset_server.load(FONT_BODY);
+
+    let text_style = TextStyle {
+        font: body.clone(),
+        font_size: 20.0,
+        color: Color::srgb(0.7, 0.7, 0.7),
+    };
+    let heading_style = TextStyle {
+        font: body.clone(),
+        font_size: 24.0,
+        color: Color::srgb(0.9, 0.9, 0.9),
+    };
+    let section_style = Style {
+        margin: UiRect::vertical(Val::Px(12.0)),
+        ..default()
+    };
+
+    let mut missing_files = load_errors
+        .failed
+        .iter()
+        .take(MAX_LISTED_FILES)
+        .map(|f| f.path.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    if load_errors.failed.len() > MAX_LISTED_FILES {
+        missing_files.push_str(&format!(
+            "\n...and {} more (see the log for details)",
+            load_errors.failed.len() - MAX_LISTED_FILES
+        ));
+    }
+    let folders = load_errors.required_folders();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnLoadErrorScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "couldn't load game files",
+                    TextStyle {
+                        font: brand,
+                        font_size: 48.0,
+                        color: Color::srgb(0.9, 0.9, 0.9),
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(24.0)),
+                    ..default()
+                }),
+            );
+
+            parent.spawn(TextBundle::from_section(
+                "these files are missing or couldn't be read:",
+                heading_style.clone(),
+            ));
+            parent.spawn(
+                TextBundle::from_section(
+                    if missing_files.is_empty() {
+                        "(no files were reported, see the log for details)".into()
+                    } else {
+                        missing_files
+                    },
+                    text_style.clone(),
+                )
+                .with_style(section_style.clone()),
+            );
+
+            if !folders.is_empty() {
+                parent.spawn(TextBundle::from_section(
+                    "make sure these folders have been extracted from the CASC archive:",
+                    heading_style.clone(),
+                ));
+                parent.spawn(
+                    TextBundle::from_section(
+                        folders
+                            .iter()
+                            .map(|f| format!("assets/{f}/"))
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                        text_style.clone(),
+                    )
+                    .with_style(section_style.clone()),
+                );
+            }
+
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(240.0),
+                            height: Val::Px(64.0),
+                            margin: UiRect::top(Val::Px(24.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: BackgroundColor(NORMAL_BUTTON),
+                        ..default()
+                    },
+                    BackToMenuButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "back to menu",
+                        TextStyle {
+                            font: body,
+                            font_size: 32.0,
+                            color: Color::srgb(0.9, 0.9, 0.9),
+                        },
+                    ));
+                });
+        });
+}
+
+fn update_button_colors(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<BackToMenuButton>),
+    >,
+) {
+    for (interaction, mut bg) in &mut interaction_query {
+        bg.0 = match *interaction {
+            Interaction::Pressed => PRESSED_BUTTON,
+            Interaction::Hovered => HOVERED_BUTTON,
+            Interaction::None => NORMAL_BUTTON,
+        }
+    }
+}
+
+fn back_to_menu(
+    query: Query<&Interaction, (Changed<Interaction>, With<BackToMenuButton>)>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if query.iter().any(|i| *i == Interaction::Pressed) {
+        next_state.set(AppState::Menu);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failed(path: &str) -> FailedAsset {
+        FailedAsset {
+            path: path.into(),
+            error: "not found".into(),
+        }
+    }
+
+    #[test]
+    fn required_folders() {
+        let load_errors = LoadErrors {
+            failed: vec![
+                failed("casc-extracted/HD2/anim/main_001.anim"),
+                failed("casc-extracted\\unit\\terran\\marine.grp"),
+                failed("casc-extracted/HD2/anim/main_002.anim"),
+                failed("casc-extracted/images.rel"),
+                failed("lt.scm"),
+            ],
+        };
+        assert_eq!(
+            load_errors.required_folders(),
+            vec![
+                "casc-extracted",
+                "casc-extracted/HD2/anim",
+                "casc-extracted/unit/terran"
+            ]
+        );
+    }
+}
@@ -0,0 +1,376 @@
+use bevy::prelude::*;
+
+use crate::{
+    gameplay::{players::PlayerColor, GameMode},
+    maps::{
+        chk::{SlotOwner, SlotRace},
+        MapAsset,
+    },
+    net::LockstepSession,
+    races::{Race, RaceSelection},
+    random::LcgRand,
+    states::AppState,
+};
+
+mod screen;
+
+pub use screen::LobbyScreenPlugin;
+
+/// The number of player slots in a lobby.
+pub const NUM_SLOTS: usize = 8;
+/// The maximum number of observers in a lobby.
+pub const MAX_OBSERVERS: usize = 4;
+/// The number of forces (teams) in a lobby.
+pub const NUM_FORCES: u8 = 4;
+
+/// Plugin that manages the [Lobby] used to set up players for a game. The screen for configuring it
+/// is provided separately by [LobbyScreenPlugin].
+pub struct LobbyPlugin;
+
+impl Plugin for LobbyPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Lobby>()
+            .add_systems(OnExit(AppState::InGame), remove_lobby);
+    }
+}
+
+/// What occupies a particular lobby slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum SlotType {
+    /// The slot can be taken by a human player, but is currently empty.
+    #[default]
+    Open,
+    /// No one can occupy the slot.
+    Closed,
+    Computer,
+    Human,
+    /// A slot that is controlled by the map and becomes allied with whoever rescues its units.
+    Rescue,
+    /// A slot that is controlled by the map.
+    Neutral,
+}
+
+impl SlotType {
+    /// Returns whether this slot will have a player in the game.
+    pub fn is_active(&self) -> bool {
+        !matches!(self, SlotType::Open | SlotType::Closed)
+    }
+
+    /// Returns whether the lobby host is allowed to change this slot's type.
+    pub fn is_changeable(&self) -> bool {
+        matches!(self, SlotType::Open | SlotType::Closed | SlotType::Computer)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Reflect)]
+pub struct LobbySlot {
+    pub slot_type: SlotType,
+    /// The name of the player in this slot (only used for humans).
+    pub name: String,
+    pub race: RaceSelection,
+    /// Whether the race for this slot is set by the map and can't be changed.
+    pub race_locked: bool,
+    pub color: PlayerColor,
+    /// The index of the force (team) this slot belongs to.
+    pub force: u8,
+}
+
+/// Resource describing the configuration of the game to be played: who is in each player slot,
+/// what race/color/team they are, and who is observing. This is used to create the players when
+/// the game starts.
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct Lobby {
+    pub slots: [LobbySlot; NUM_SLOTS],
+    pub observers: Vec<String>,
+    /// The display name of each force.
+    pub force_names: [String; NUM_FORCES as usize],
+    /// The slot the local player occupies, or `None` if they are an observer (or not present).
+    pub local_slot: Option<u8>,
+}
+
+impl Lobby {
+    /// Creates a lobby with no players in it, using the slot settings from `map` as defaults.
+    pub fn from_map(map: &MapAsset, game_mode: &GameMode) -> Self {
+        // Melee games ignore the map's race and computer settings, only its layout matters
+        let use_map_settings = matches!(game_mode, GameMode::UseMapSettings | GameMode::MapView);
+
+        let slots = std::array::from_fn(|i| {
+            let map_slot = map.slots[i];
+            let slot_type = match map_slot.owner {
+                SlotOwner::Human => SlotType::Open,
+                SlotOwner::Computer if use_map_settings => SlotType::Computer,
+                SlotOwner::Computer => SlotType::Open,
+                SlotOwner::RescuePassive if use_map_settings => SlotType::Rescue,
+                SlotOwner::Neutral if use_map_settings => SlotType::Neutral,
+                _ => SlotType::Closed,
+            };
+            let (race, race_locked) = match map_slot.race {
+                _ if !use_map_settings => (RaceSelection::Random, false),
+                SlotRace::Zerg => (RaceSelection::Zerg, true),
+                SlotRace::Terran => (RaceSelection::Terran, true),
+                SlotRace::Protoss => (RaceSelection::Protoss, true),
+                SlotRace::Random => (RaceSelection::Random, true),
+                _ => (RaceSelection::Random, false),
+            };
+
+            LobbySlot {
+                slot_type,
+                name: String::new(),
+                race,
+                race_locked,
+                color: PlayerColor::for_player(i as u8),
+                force: if use_map_settings { map_slot.force } else { 0 },
+            }
+        });
+
+        let force_names = std::array::from_fn(|i| {
+            if use_map_settings {
+                map.force_name(i)
+            } else {
+                format!("team {}", i + 1)
+            }
+        });
+
+        Self {
+            slots,
+            observers: Vec::new(),
+            force_names,
+            local_slot: None,
+        }
+    }
+
+    /// Places a human player in the first open slot, returning the slot number they were placed
+    /// in (if there was one).
+    pub fn join(&mut self, name: impl Into<String>) -> Option<u8> {
+        let slot = self
+            .slots
+            .iter()
+            .position(|s| s.slot_type == SlotType::Open)?;
+        self.take_slot(slot as u8, name);
+        Some(slot as u8)
+    }
+
+    /// Places a human player in the specified slot, if it is open.
+    pub fn take_slot(&mut self, slot: u8, name: impl Into<String>) -> bool {
+        let Some(s) = self.slots.get_mut(slot as usize) else {
+            return false;
+        };
+        if s.slot_type != SlotType::Open {
+            return false;
+        }
+        s.slot_type = SlotType::Human;
+        s.name = name.into();
+        true
+    }
+
+    /// Moves the local player into the specified open slot (or out of the observers).
+    pub fn move_local_to_slot(&mut self, slot: u8) -> bool {
+        let name = self.local_name();
+        if !self.take_slot(slot, name.clone()) {
+            return false;
+        }
+        self.remove_local();
+        self.local_slot = Some(slot);
+        true
+    }
+
+    /// Moves the local player into the observers, if there is room.
+    pub fn move_local_to_observers(&mut self) -> bool {
+        if self.local_slot.is_none() || self.observers.len() >= MAX_OBSERVERS {
+            return false;
+        }
+        let name = self.local_name();
+        self.remove_local();
+        self.observers.push(name);
+        true
+    }
+
+    fn local_name(&self) -> String {
+        match self.local_slot {
+            Some(slot) => self.slots[slot as usize].name.clone(),
+            None => self.observers.last().cloned().unwrap_or_default(),
+        }
+    }
+
+    fn remove_local(&mut self) {
+        match self.local_slot.take() {
+            Some(slot) => {
+                let s = &mut self.slots[slot as usize];
+                s.slot_type = SlotType::Open;
+                s.name.clear();
+            }
+            None => {
+                // NOTE(tec27): Observers are only local for now, so the local player is always the
+                // last one
+                self.observers.pop();
+            }
+        }
+    }
+
+    /// Changes the type of the specified slot to the next one that the host can choose (open ->
+    /// closed -> computer).
+    pub fn cycle_slot_type(&mut self, slot: u8) {
+        let Some(s) = self.slots.get_mut(slot as usize) else {
+            return;
+        };
+        s.slot_type = match s.slot_type {
+            SlotType::Open => SlotType::Closed,
+            SlotType::Closed => SlotType::Computer,
+            SlotType::Computer => SlotType::Open,
+            t => t,
+        };
+    }
+
+    /// Changes the race of the specified slot to the next one, unless it is locked by the map.
+    pub fn cycle_race(&mut self, slot: u8) {
+        if let Some(s) = self.slots.get_mut(slot as usize) {
+            if !s.race_locked {
+                s.race = s.race.next();
+            }
+        }
+    }
+
+    /// Changes the color of the specified slot to the next one that isn't used by another slot.
+    pub fn cycle_color(&mut self, slot: u8) {
+        let Some(current) = self.slots.get(slot as usize).map(|s| s.color) else {
+            return;
+        };
+        let mut color = current.next();
+        while color != current && self.slots.iter().any(|s| s.color == color) {
+            color = color.next();
+        }
+        self.slots[slot as usize].color = color;
+    }
+
+    /// Changes the force of the specified slot to the next one.
+    pub fn cycle_force(&mut self, slot: u8) {
+        if let Some(s) = self.slots.get_mut(slot as usize) {
+            s.force = (s.force + 1) % NUM_FORCES;
+        }
+    }
+
+    /// Returns whether the lobby is in a state where the game can be started.
+    pub fn can_start(&self) -> bool {
+        self.slots
+            .iter()
+            .any(|s| matches!(s.slot_type, SlotType::Human | SlotType::Computer))
+    }
+
+    /// Resolves the race for each active slot, using `rand` to pick races for any slots that
+    /// selected Random. This must be done with the synced RNG, in the same order on every client.
+    pub fn resolve_races(&self, rand: &mut LcgRand) -> [Option<Race>; NUM_SLOTS] {
+        let mut races = [None; NUM_SLOTS];
+        for (i, slot) in self.slots.iter().enumerate() {
+            if slot.slot_type.is_active() {
+                races[i] = Some(slot.race.resolve(rand));
+            }
+        }
+        races
+    }
+}
+
+/// Creates the lobby for a game that was started without going through the lobby screen (e.g. maps
+/// specified on the command line). In networked games, every player in the session is placed in
+/// the slot matching their player number, otherwise the local player takes the first open slot.
+pub fn default_lobby(
+    map: &MapAsset,
+    game_mode: &GameMode,
+    net_session: Option<&LockstepSession>,
+) -> Lobby {
+    let mut lobby = Lobby::from_map(map, game_mode);
+    match net_session {
+        Some(session) => {
+            for player in session.players() {
+                let Some(slot) = lobby.slots.get_mut(player as usize) else {
+                    warn!("Player {player} doesn't fit in a lobby slot, ignoring");
+                    continue;
+                };
+                slot.slot_type = SlotType::Human;
+                slot.name = format!("Player {}", player + 1);
+            }
+            lobby.local_slot = Some(session.local_player());
+        }
+        None => {
+            lobby.local_slot = lobby.join(LOCAL_PLAYER_NAME);
+        }
+    }
+    lobby
+}
+
+/// The name used for the local player.
+// TODO(tec27): Make this configurable
+const LOCAL_PLAYER_NAME: &str = "Player";
+
+fn remove_lobby(mut commands: Commands) {
+    commands.remove_resource::<Lobby>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_lobby() -> Lobby {
+        Lobby {
+            slots: std::array::from_fn(|i| LobbySlot {
+                color: PlayerColor::for_player(i as u8),
+                ..default()
+            }),
+            observers: Vec::new(),
+            force_names: default(),
+            local_slot: None,
+        }
+    }
+
+    #[test]
+    fn join_and_move() {
+        let mut lobby = test_lobby();
+        lobby.cycle_slot_type(0);
+        assert_eq!(lobby.slots[0].slot_type, SlotType::Closed);
+
+        let slot = lobby.join("tec27");
+        assert_eq!(slot, Some(1));
+        lobby.local_slot = slot;
+
+        assert!(lobby.move_local_to_slot(4));
+        assert_eq!(lobby.slots[1].slot_type, SlotType::Open);
+        assert_eq!(lobby.slots[4].slot_type, SlotType::Human);
+        assert_eq!(lobby.slots[4].name, "tec27");
+
+        assert!(lobby.move_local_to_observers());
+        assert_eq!(lobby.local_slot, None);
+        assert_eq!(lobby.slots[4].slot_type, SlotType::Open);
+        assert_eq!(lobby.observers, vec!["tec27".to_string()]);
+
+        assert!(lobby.move_local_to_slot(2));
+        assert_eq!(lobby.slots[2].name, "tec27");
+        assert!(lobby.observers.is_empty());
+    }
+
+    #[test]
+    fn colors_are_unique() {
+        let mut lobby = test_lobby();
+        // Red -> Blue, Teal, ... are all taken, so the first free color is Green
+        lobby.cycle_color(0);
+        assert_eq!(lobby.slots[0].color, PlayerColor::Green);
+    }
+
+    #[test]
+    fn random_races_are_deterministic() {
+        let mut lobby = test_lobby();
+        lobby.join("a");
+        lobby.join("b");
+        lobby.cycle_slot_type(2);
+        lobby.cycle_slot_type(2);
+        lobby.slots[1].race = RaceSelection::Zerg;
+        lobby.slots[3].race = RaceSelection::Terran;
+
+        let races = lobby.resolve_races(&mut LcgRand::new(42));
+        assert_eq!(races, lobby.resolve_races(&mut LcgRand::new(42)));
+        assert!(races[0].is_some());
+        assert_eq!(races[1], Some(Race::Zerg));
+        assert!(races[2].is_some());
+        // Open slots don't get a race
+        assert_eq!(races[3], None);
+    }
+}
@@ -0,0 +1,496 @@
+use bevy::prelude::*;
+
+use crate::{
+    ecs::despawn_all,
+    fonts::{FONT_BODY, FONT_BRAND},
+    gameplay::GameMode,
+    maps::{CurrentMap, MapAsset},
+    states::AppState,
+};
+
+use super::{Lobby, LobbySlot, SlotType, LOCAL_PLAYER_NAME, MAX_OBSERVERS, NUM_SLOTS};
+
+pub struct LobbyScreenPlugin;
+
+impl Plugin for LobbyScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Lobby), setup)
+            .add_systems(OnExit(AppState::Lobby), despawn_all::<OnLobbyScreen>)
+            .add_systems(
+                Update,
+                (
+                    init_lobby,
+                    actions,
+                    update_button_colors,
+                    update_slot_list.run_if(resource_exists_and_changed::<Lobby>),
+                    update_game_mode_text.run_if(resource_changed::<GameMode>),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Lobby)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct OnLobbyScreen;
+
+/// Container for the rows of player slots, which get rebuilt whenever the [Lobby] changes.
+#[derive(Component)]
+struct SlotList;
+
+#[derive(Component)]
+struct MapNameText;
+
+#[derive(Component)]
+struct GameModeText;
+
+const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
+
+const TEXT_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+const DISABLED_TEXT_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+
+#[derive(Component, Debug, Copy, Clone)]
+enum LobbyAction {
+    CycleSlotType(u8),
+    TakeSlot(u8),
+    CycleRace(u8),
+    CycleColor(u8),
+    CycleForce(u8),
+    CycleGameMode,
+    Observe,
+    Start,
+    Back,
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut game_mode: ResMut<GameMode>) {
+    // Any existing lobby is for a different map
+    commands.remove_resource::<Lobby>();
+    *game_mode = GameMode::Melee;
+
+    let brand = asset_server.load(FONT_BRAND);
+    let body = asset_server.load(FONT_BODY);
+
+    let button_style = Style {
+        width: Val::Px(200.0),
+        height: Val::Px(48.0),
+        margin: UiRect::all(Val::Px(12.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font: body.clone(),
+        font_size: 24.0,
+        color: TEXT_COLOR,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnLobbyScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "create game",
+                    TextStyle {
+                        font: brand,
+                        font_size: 64.0,
+                        color: TEXT_COLOR,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(8.0)),
+                    ..default()
+                }),
+            );
+            parent.spawn((
+                TextBundle::from_section(
+                    "loading map...",
+                    TextStyle {
+                        font: body.clone(),
+                        font_size: 24.0,
+                        color: Color::srgb(0.7, 0.7, 0.7),
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(24.0)),
+                    ..default()
+                }),
+                MapNameText,
+            ));
+
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        min_height: Val::Px(NUM_SLOTS as f32 * 40.0 + 40.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                SlotList,
+            ));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        margin: UiRect::top(Val::Px(24.0)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: BackgroundColor(NORMAL_BUTTON),
+                                ..default()
+                            },
+                            LobbyAction::CycleGameMode,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    game_mode_label(&game_mode),
+                                    button_text_style.clone(),
+                                ),
+                                GameModeText,
+                            ));
+                        });
+
+                    for (action, label) in [
+                        (LobbyAction::Back, "back"),
+                        (LobbyAction::Observe, "observe"),
+                        (LobbyAction::Start, "start game"),
+                    ] {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: button_style.clone(),
+                                    background_color: BackgroundColor(NORMAL_BUTTON),
+                                    ..default()
+                                },
+                                action,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    label,
+                                    button_text_style.clone(),
+                                ));
+                            });
+                    }
+                });
+        });
+}
+
+/// Creates the [Lobby] once the selected map has loaded.
+fn init_lobby(
+    mut commands: Commands,
+    lobby: Option<Res<Lobby>>,
+    current_map: Res<CurrentMap>,
+    map_assets: Res<Assets<MapAsset>>,
+    game_mode: Res<GameMode>,
+    mut map_name_text: Query<&mut Text, With<MapNameText>>,
+) {
+    if lobby.is_some() {
+        return;
+    }
+    let Some(map) = map_assets.get(&current_map.handle) else {
+        return;
+    };
+
+    let mut lobby = Lobby::from_map(map, &game_mode);
+    lobby.local_slot = lobby.join(LOCAL_PLAYER_NAME);
+    commands.insert_resource(lobby);
+
+    if let Ok(mut text) = map_name_text.get_single_mut() {
+        text.sections[0].value = map.name.clone();
+    }
+}
+
+fn game_mode_label(game_mode: &GameMode) -> &'static str {
+    match game_mode {
+        GameMode::Melee => "melee",
+        GameMode::UseMapSettings => "use map settings",
+        GameMode::MapView => "map view",
+    }
+}
+
+fn update_game_mode_text(game_mode: Res<GameMode>, mut text: Query<&mut Text, With<GameModeText>>) {
+    for mut text in &mut text {
+        text.sections[0].value = game_mode_label(&game_mode).to_string();
+    }
+}
+
+fn update_button_colors(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<Button>),
+    >,
+) {
+    for (interaction, mut bg) in &mut interaction_query {
+        bg.0 = match *interaction {
+            Interaction::Pressed => PRESSED_BUTTON,
+            Interaction::Hovered => HOVERED_BUTTON,
+            Interaction::None => NORMAL_BUTTON,
+        }
+    }
+}
+
+fn actions(
+    mut commands: Commands,
+    query: Query<(&Interaction, &LobbyAction), (Changed<Interaction>, With<Button>)>,
+    lobby: Option<ResMut<Lobby>>,
+    mut game_mode: ResMut<GameMode>,
+    mut current_map: ResMut<CurrentMap>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let Some(mut lobby) = lobby else {
+        for (interaction, action) in &query {
+            if *interaction == Interaction::Pressed && matches!(action, LobbyAction::Back) {
+                current_map.handle = default();
+                next_state.set(AppState::Menu);
+            }
+        }
+        return;
+    };
+
+    for (interaction, action) in &query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match *action {
+            LobbyAction::CycleSlotType(slot) => lobby.cycle_slot_type(slot),
+            LobbyAction::TakeSlot(slot) => {
+                lobby.move_local_to_slot(slot);
+            }
+            LobbyAction::CycleRace(slot) => lobby.cycle_race(slot),
+            LobbyAction::CycleColor(slot) => lobby.cycle_color(slot),
+            LobbyAction::CycleForce(slot) => lobby.cycle_force(slot),
+            LobbyAction::CycleGameMode => {
+                *game_mode = match *game_mode {
+                    GameMode::Melee => GameMode::UseMapSettings,
+                    _ => GameMode::Melee,
+                };
+                // The slot settings depend on the game mode, so the lobby needs to be recreated
+                commands.remove_resource::<Lobby>();
+            }
+            LobbyAction::Observe => {
+                lobby.move_local_to_observers();
+            }
+            LobbyAction::Start => {
+                if lobby.can_start() {
+                    next_state.set(AppState::PreGame);
+                }
+            }
+            LobbyAction::Back => {
+                commands.remove_resource::<Lobby>();
+                current_map.handle = default();
+                next_state.set(AppState::Menu);
+            }
+        }
+    }
+}
+
+fn update_slot_list(
+    mut commands: Commands,
+    lobby: Res<Lobby>,
+    slot_list: Query<Entity, With<SlotList>>,
+    asset_server: Res<AssetServer>,
+) {
+    let Ok(slot_list) = slot_list.get_single() else {
+        return;
+    };
+    let body = asset_server.load(FONT_BODY);
+
+    commands
+        .entity(slot_list)
+        .despawn_descendants()
+        .with_children(|parent| {
+            for (i, slot) in lobby.slots.iter().enumerate() {
+                let force_name = &lobby.force_names[slot.force as usize];
+                spawn_slot_row(parent, i as u8, slot, force_name, lobby.local_slot, &body);
+            }
+
+            let observers = if lobby.observers.is_empty() {
+                "none".to_string()
+            } else {
+                lobby.observers.join(", ")
+            };
+            parent.spawn(
+                TextBundle::from_section(
+                    format!(
+                        "observers ({}/{MAX_OBSERVERS}): {observers}",
+                        lobby.observers.len()
+                    ),
+                    TextStyle {
+                        font: body.clone(),
+                        font_size: 20.0,
+                        color: Color::srgb(0.7, 0.7, 0.7),
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::top(Val::Px(12.0)),
+                    ..default()
+                }),
+            );
+        });
+}
+
+fn spawn_slot_row(
+    parent: &mut ChildBuilder,
+    index: u8,
+    slot: &LobbySlot,
+    force_name: &str,
+    local_slot: Option<u8>,
+    font: &Handle<Font>,
+) {
+    let text_style = |enabled: bool| TextStyle {
+        font: font.clone(),
+        font_size: 20.0,
+        color: if enabled {
+            TEXT_COLOR
+        } else {
+            DISABLED_TEXT_COLOR
+        },
+    };
+
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                height: Val::Px(40.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(format!("{}.", index + 1), text_style(true)).with_style(
+                    Style {
+                        width: Val::Px(32.0),
+                        ..default()
+                    },
+                ),
+            );
+
+            let slot_label = match slot.slot_type {
+                SlotType::Open => "open".to_string(),
+                SlotType::Closed => "closed".to_string(),
+                SlotType::Computer => "computer".to_string(),
+                SlotType::Human if local_slot == Some(index) => format!("{} (you)", slot.name),
+                SlotType::Human => slot.name.clone(),
+                SlotType::Rescue => "rescuable".to_string(),
+                SlotType::Neutral => "neutral".to_string(),
+            };
+            let changeable = slot.slot_type.is_changeable();
+            spawn_slot_button(
+                parent,
+                changeable.then_some(LobbyAction::CycleSlotType(index)),
+                &slot_label,
+                text_style(changeable),
+                240.0,
+            );
+
+            if slot.slot_type == SlotType::Open {
+                spawn_slot_button(
+                    parent,
+                    Some(LobbyAction::TakeSlot(index)),
+                    "join",
+                    text_style(true),
+                    80.0,
+                );
+            }
+
+            if !slot.slot_type.is_active() {
+                return;
+            }
+
+            spawn_slot_button(
+                parent,
+                (!slot.race_locked).then_some(LobbyAction::CycleRace(index)),
+                slot.race.name(),
+                text_style(!slot.race_locked),
+                120.0,
+            );
+
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(120.0),
+                            height: Val::Px(32.0),
+                            margin: UiRect::horizontal(Val::Px(4.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            border: UiRect::left(Val::Px(12.0)),
+                            ..default()
+                        },
+                        background_color: BackgroundColor(NORMAL_BUTTON),
+                        border_color: BorderColor(slot.color.color()),
+                        ..default()
+                    },
+                    LobbyAction::CycleColor(index),
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        slot.color.name(),
+                        text_style(true),
+                    ));
+                });
+
+            spawn_slot_button(
+                parent,
+                Some(LobbyAction::CycleForce(index)),
+                force_name,
+                text_style(true),
+                100.0,
+            );
+        });
+}
+
+/// Spawns a button for a lobby slot. If `action` is `None`, the button will be displayed but won't
+/// do anything when clicked.
+fn spawn_slot_button(
+    parent: &mut ChildBuilder,
+    action: Option<LobbyAction>,
+    label: &str,
+    text_style: TextStyle,
+    width: f32,
+) {
+    let mut button = parent.spawn(ButtonBundle {
+        style: Style {
+            width: Val::Px(width),
+            height: Val::Px(32.0),
+            margin: UiRect::horizontal(Val::Px(4.0)),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        background_color: BackgroundColor(NORMAL_BUTTON),
+        ..default()
+    });
+    if let Some(action) = action {
+        button.insert(action);
+    }
+    button.with_children(|parent| {
+        parent.spawn(TextBundle::from_section(label, text_style));
+    });
+}
@@ -1,34 +1,34 @@
 use std::env;
-use std::fs::File;
+use std::net::SocketAddr;
 use std::path::PathBuf;
 
 use directories::UserDirs;
-use neobrood::create_app;
+use neobrood::check_assets::{check_assets, AssetCheckSettings};
+use neobrood::{create_app, create_headless_app};
 
-use neobrood::settings::GameSettings;
+use neobrood::net::{LockstepSession, NetConfig};
+use neobrood::settings::{
+    load_settings, AssetPack, AssetQuality, AudioQuality, GameSettings, SettingsError,
+    SettingsFile, SETTINGS_FILE_NAME,
+};
 
 #[cfg(feature = "mimalloc")]
 #[global_allocator]
 static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
 
 fn main() {
-    let user_dirs = UserDirs::new().expect("Couldn't get user directories!");
-    let documents_dir = user_dirs
-        .document_dir()
-        .expect("Couldn't get Documents directory!");
-    let settings_file = documents_dir
-        .join("Starcraft")
-        .join("neobrood-settings.json");
     // NOTE(tec27): We avoid using any tracing functions for logging here as that won't be
     // initialized until Bevy's LogPlugin is
-    let settings = match File::open(settings_file) {
-        Ok(mut file) => serde_json::from_reader::<_, GameSettings>(&mut file).unwrap_or_else(|e| {
+    let settings_path = settings_dir().join(SETTINGS_FILE_NAME);
+    let settings = match load_settings(&settings_path) {
+        Ok(settings) => settings,
+        Err(SettingsError::Json(e)) => {
             eprintln!(
                 "Using default settings due to error parsing settings file: {}",
                 e
             );
             GameSettings::default()
-        }),
+        }
         Err(e) => {
             eprintln!(
                 "Using default settings due to error reading settings file: {}",
@@ -38,8 +38,34 @@ fn main() {
         }
     };
 
-    let maps = env::args()
-        .skip(1)
+    if env::args().nth(1).as_deref() == Some("check-assets") {
+        run_check_assets(env::args().skip(2), &settings);
+    }
+
+    let mut headless = false;
+    let mut max_frames = None;
+    let mut net_local = None;
+    let mut net_peers = Vec::new();
+    let mut net_latency = DEFAULT_NET_LATENCY;
+    let mut net_seed = 0;
+    let mut map_args = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--headless" => headless = true,
+            "--frames" => {
+                max_frames = Some(parse_arg_value(&arg, args.next(), |v| v.parse().ok()));
+            }
+            "--net" => net_local = Some(parse_arg_value(&arg, args.next(), parse_player_addr)),
+            "--peer" => net_peers.push(parse_arg_value(&arg, args.next(), parse_player_addr)),
+            "--latency" => net_latency = parse_arg_value(&arg, args.next(), |v| v.parse().ok()),
+            "--seed" => net_seed = parse_arg_value(&arg, args.next(), |v| v.parse().ok()),
+            _ => map_args.push(arg),
+        }
+    }
+
+    let maps = map_args
+        .into_iter()
         .flat_map(|path| {
             let mut path = PathBuf::from(path);
             if !path.is_absolute() {
@@ -49,11 +75,22 @@ fn main() {
             }
 
             if path.is_dir() {
-                path.read_dir()
-                    .expect("Couldn't read specified map directory")
+                let entries = match path.read_dir() {
+                    Ok(entries) => entries,
+                    Err(e) => {
+                        eprintln!("Skipping map directory {}: {e}", path.display());
+                        return Vec::new();
+                    }
+                };
+                entries
                     .filter_map(|entry| {
-                        let entry = entry.expect("Couldn't read directory entry");
-                        let path = entry.path();
+                        let path = match entry {
+                            Ok(entry) => entry.path(),
+                            Err(e) => {
+                                eprintln!("Skipping entry in {}: {e}", path.display());
+                                return None;
+                            }
+                        };
                         let extension = path.extension().map_or("".into(), |s| {
                             s.to_ascii_lowercase().to_string_lossy().to_string()
                         });
@@ -70,6 +107,129 @@ fn main() {
         })
         .collect::<Vec<_>>();
 
-    let mut app = create_app(settings, maps);
+    let net_session = net_local.map(|(local_player, bind_addr)| {
+        let config = NetConfig {
+            local_player,
+            bind_addr,
+            peers: net_peers,
+            latency: net_latency,
+            seed: net_seed,
+        };
+        LockstepSession::new(config).unwrap_or_else(|e| {
+            eprintln!("Couldn't bind to {bind_addr}: {e}");
+            std::process::exit(1);
+        })
+    });
+
+    let mut app = if headless {
+        if maps.is_empty() {
+            eprintln!("Running headless requires at least one map to be specified");
+            std::process::exit(1);
+        }
+        create_headless_app(settings, maps, max_frames)
+    } else {
+        let settings_file = SettingsFile {
+            path: settings_path,
+            saved: settings.clone(),
+        };
+        let mut app = create_app(settings, maps);
+        app.insert_resource(settings_file);
+        app
+    };
+    if let Some(net_session) = net_session {
+        app.insert_resource(net_session);
+    }
     app.run();
 }
+
+/// Returns the directory the settings file is stored in. This is normally in the user's Documents
+/// directory (alongside the settings for the original game), but falls back to the current
+/// directory if that can't be found.
+fn settings_dir() -> PathBuf {
+    match UserDirs::new().and_then(|dirs| dirs.document_dir().map(|d| d.to_owned())) {
+        Some(documents_dir) => documents_dir.join("Starcraft"),
+        None => {
+            eprintln!(
+                "Couldn't find the Documents directory, using the current directory for settings"
+            );
+            env::current_dir().unwrap_or_default()
+        }
+    }
+}
+
+/// Checks that the asset files needed for the current (or specified) settings exist and can be
+/// parsed, printing a report and exiting with an error code if there are any problems.
+fn run_check_assets(mut args: impl Iterator<Item = String>, settings: &GameSettings) -> ! {
+    let mut assets_dir = PathBuf::from("assets");
+    let mut check_settings = AssetCheckSettings {
+        asset_quality: settings.asset_quality,
+        asset_pack: settings.asset_pack,
+        audio_quality: settings.audio_quality,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--assets" => assets_dir = parse_arg_value(&arg, args.next(), |v| Some(v.into())),
+            "--quality" => {
+                check_settings.asset_quality = parse_arg_value(&arg, args.next(), |v| match v {
+                    "sd" => Some(AssetQuality::Standard),
+                    "hd2" => Some(AssetQuality::High),
+                    "hd" => Some(AssetQuality::ExtraHigh),
+                    _ => None,
+                })
+            }
+            "--pack" => {
+                check_settings.asset_pack = parse_arg_value(&arg, args.next(), |v| match v {
+                    "standard" => Some(AssetPack::Standard),
+                    "carbot" => Some(AssetPack::Carbot),
+                    _ => None,
+                })
+            }
+            "--audio" => {
+                check_settings.audio_quality = parse_arg_value(&arg, args.next(), |v| match v {
+                    "classic" => Some(AudioQuality::Classic),
+                    "remastered" => Some(AudioQuality::Remastered),
+                    _ => None,
+                })
+            }
+            _ => {
+                eprintln!("Unknown argument for check-assets: {arg}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    println!(
+        "Checking assets in {} for {:?}",
+        assets_dir.display(),
+        check_settings
+    );
+    let report = check_assets(&assets_dir, check_settings);
+    println!("{report}");
+    std::process::exit(if report.is_ok() { 0 } else { 1 });
+}
+
+/// The default number of frames in the future that commands will be scheduled for in networked
+/// games.
+const DEFAULT_NET_LATENCY: u32 = 2;
+
+/// Parses the value for a command line argument, exiting with an error if it is missing or
+/// invalid.
+fn parse_arg_value<T>(
+    arg: &str,
+    value: Option<String>,
+    parse: impl FnOnce(&str) -> Option<T>,
+) -> T {
+    match value.as_deref().and_then(parse) {
+        Some(v) => v,
+        None => {
+            eprintln!("Missing or invalid value for {arg}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Parses a player number and address in the form `<player>@<address>`, e.g. `0@127.0.0.1:6112`.
+fn parse_player_addr(value: &str) -> Option<(u8, SocketAddr)> {
+    let (player, addr) = value.split_once('@')?;
+    Some((player.parse().ok()?, addr.parse().ok()?))
+}
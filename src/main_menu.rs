@@ -173,6 +173,7 @@ fn actions(
             match action {
                 MenuAction::LoadLostTemple => load_map(
                     &PathBuf::from("lt.scm"),
+                    AppState::Lobby,
                     &mut current_map,
                     &mut next_state,
                     &asset_server,
@@ -204,6 +205,7 @@ fn map_drag_and_drop(
         if extension == "scm" || extension == "scx" {
             load_map(
                 path_buf,
+                AppState::Lobby,
                 &mut current_map,
                 &mut next_state,
                 &asset_server,
@@ -0,0 +1,151 @@
+use std::path::{Component, Path, PathBuf};
+
+use bevy::asset::io::{
+    AssetReader, AssetReaderError, AssetSource, AssetSourceBuilder, ErasedAssetReader, PathStream,
+    Reader, VecReader,
+};
+use bevy::asset::AsyncReadExt;
+use bevy::prelude::*;
+
+/// The name of the asset source that serves files from inside map archives.
+pub const MAP_ASSET_SOURCE: &str = "map";
+
+/// Registers the [MAP_ASSET_SOURCE] asset source. This must be called before `AssetPlugin` is added.
+///
+/// Paths in this source are the path of the map file (as it would be loaded from the default
+/// source), followed by the path of a file within the map's archive, e.g.
+/// `map://maps/(2)Astral Balance.scm/staredit/wav/intro.wav`. Either slashes or backslashes can be
+/// used within the archive path.
+pub fn register_map_asset_source(app: &mut App) {
+    app.register_asset_source(
+        MAP_ASSET_SOURCE,
+        AssetSourceBuilder::default().with_reader(|| {
+            Box::new(MapArchiveReader {
+                files: AssetSource::get_default_reader("assets".to_string())(),
+            })
+        }),
+    );
+}
+
+/// Builds the path for a file within the archive of the map at `map_path`, for use with
+/// [MAP_ASSET_SOURCE].
+pub fn map_file_path(map_path: &Path, archive_path: &str) -> String {
+    format!(
+        "{MAP_ASSET_SOURCE}://{}/{}",
+        map_path.to_string_lossy().replace('\\', "/"),
+        archive_path.replace('\\', "/"),
+    )
+}
+
+/// Normalizes a path to a file within a map archive so that it can be used for lookups. MPQ paths
+/// are case-insensitive and use backslashes as separators.
+pub fn normalize_archive_path(path: &str) -> String {
+    path.replace('/', "\\").to_ascii_lowercase()
+}
+
+/// An [AssetReader] that reads files from inside of map archives.
+struct MapArchiveReader {
+    /// The reader that map files themselves are read from.
+    files: Box<dyn ErasedAssetReader>,
+}
+
+impl MapArchiveReader {
+    /// Splits a path into the path of the map file and the path of the file within its archive.
+    fn split_path(path: &Path) -> Option<(PathBuf, String)> {
+        let mut map_path = PathBuf::new();
+        let mut components = path.components();
+        for component in components.by_ref() {
+            map_path.push(component);
+            let Component::Normal(name) = component else {
+                continue;
+            };
+            let is_map = Path::new(name).extension().is_some_and(|ext| {
+                ext.eq_ignore_ascii_case("scx") || ext.eq_ignore_ascii_case("scm")
+            });
+            if is_map {
+                break;
+            }
+        }
+
+        let archive_path = components
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("\\");
+        (!archive_path.is_empty()).then_some((map_path, archive_path))
+    }
+
+    async fn read_archive_file(&self, path: &Path) -> Result<Vec<u8>, AssetReaderError> {
+        let not_found = || AssetReaderError::NotFound(path.to_owned());
+        let (map_path, archive_path) = Self::split_path(path).ok_or_else(not_found)?;
+
+        // TODO(tec27): Cache the extracted archive so that loading multiple files from the same map
+        // doesn't require reading it again each time
+        let mut reader = self.files.read(&map_path).await?;
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let (_, mpq) = broodmap::extract_chk_from_map(&bytes, None, None).map_err(|e| {
+            warn!("Failed to read map archive {}: {e}", map_path.display());
+            not_found()
+        })?;
+        mpq.read_file(&archive_path).map_err(|_| not_found())
+    }
+}
+
+impl AssetReader for MapArchiveReader {
+    async fn read<'a>(&'a self, path: &'a Path) -> Result<Box<Reader<'a>>, AssetReaderError> {
+        let bytes = self.read_archive_file(path).await?;
+        let reader: Box<Reader> = Box::new(VecReader::new(bytes));
+        Ok(reader)
+    }
+
+    async fn read_meta<'a>(&'a self, path: &'a Path) -> Result<Box<Reader<'a>>, AssetReaderError> {
+        // NOTE(tec27): Map archives never contain meta files, so this always uses the defaults
+        Err(AssetReaderError::NotFound(path.to_owned()))
+    }
+
+    async fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> Result<Box<PathStream>, AssetReaderError> {
+        Err(AssetReaderError::NotFound(path.to_owned()))
+    }
+
+    async fn is_directory<'a>(&'a self, _path: &'a Path) -> Result<bool, AssetReaderError> {
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_paths() {
+        let (map, file) =
+            MapArchiveReader::split_path(Path::new("maps/ums/Test.SCX/staredit/wav/intro.wav"))
+                .unwrap();
+        assert_eq!(map, Path::new("maps/ums/Test.SCX"));
+        assert_eq!(file, "staredit\\wav\\intro.wav");
+
+        assert_eq!(
+            MapArchiveReader::split_path(Path::new("maps/Test.scm")),
+            None
+        );
+        assert_eq!(
+            MapArchiveReader::split_path(Path::new("maps/staredit/wav/intro.wav")),
+            None
+        );
+    }
+
+    #[test]
+    fn paths() {
+        assert_eq!(
+            map_file_path(Path::new("maps/Test.scx"), "staredit\\wav\\intro.wav"),
+            "map://maps/Test.scx/staredit/wav/intro.wav"
+        );
+        assert_eq!(
+            normalize_archive_path("StarEdit/WAV\\Intro.wav"),
+            "staredit\\wav\\intro.wav"
+        );
+    }
+}
@@ -1,3 +1,6 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
 use anyhow::anyhow;
 use bevy::asset::io::Reader;
 use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
@@ -7,11 +10,19 @@ use bevy::render::renderer::RenderDevice;
 use bevy::render::texture::CompressedImageFormats;
 use bevy::utils::HashMap;
 use broodmap::chk::placed_units::PlacedUnit;
-use broodmap::chk::terrain::TerrainTileIds;
 use broodmap::chk::tileset::Tileset;
 use serde::{Deserialize, Serialize};
 
-use crate::maps::tileset::{load_mega_tile_lookup, load_tile_textures, MegaTileInfo};
+use crate::maps::archive::normalize_archive_path;
+use crate::maps::chk::{
+    parse_forces, parse_locations, parse_slots, ChkSections, MapForce, MapLocation, MapSlot,
+    NUM_CHK_PLAYERS, NUM_FORCES,
+};
+use crate::maps::game_map::GameMapTerrain;
+use crate::maps::overrides::{parse_overrides, MapOverrides};
+use crate::maps::strings::{parse_strings, MapString};
+use crate::maps::tileset::{load_mega_tile_lookup, load_tile_textures};
+use crate::maps::triggers::{parse_triggers, ActionType, Trigger};
 use crate::settings::{AssetPack, AssetQuality};
 
 /// A bevy [AssetLoader] for SCM and SCX files.
@@ -43,25 +54,79 @@ pub struct MapAssetSettings {
 pub struct MapAsset {
     /// The name of the map.
     pub name: String,
+    /// The asset path the map was loaded from.
+    pub path: PathBuf,
     /// Width of the map in tiles.
     pub width: u32,
     /// Height of the map in tiles.
     pub height: u32,
     /// The map's tileset.
     pub tileset: Tileset,
-    /// The map's terrain, as a 2D vector of tile IDs (can be converted to mega-tiles via
-    /// `mega_tile_lookup`).
-    pub terrain: TerrainTileIds,
-    /// A hashmap of tile IDs to their mega-tile info.
-    pub mega_tile_lookup: HashMap<u16, MegaTileInfo>,
+    /// The map's terrain, with the mega-tile info for each tile.
+    pub terrain: GameMapTerrain,
     /// A Vec of handles to textures for each mega-tile.
     pub tile_textures: Vec<Handle<Image>>,
     /// A map of mega-tile IDs -> an index into `tile_textures`.
     pub tile_texture_indices: HashMap<u16, usize>,
+    /// The average color of each texture in `tile_textures` (in sRGB).
+    pub tile_colors: Vec<[u8; 3]>,
     /// Units that were placed on the map during editing.
     pub placed_units: Vec<PlacedUnit>,
     /// Sprites that were placed on the map during editing.
     pub sprites: Vec<broodmap::chk::sprites::Sprite>,
+    /// The settings for each player slot (owner, race, and force).
+    pub slots: [MapSlot; NUM_CHK_PLAYERS],
+    /// The settings for each force.
+    pub forces: [MapForce; NUM_FORCES],
+    /// The locations defined by the map, used by triggers.
+    pub locations: Vec<MapLocation>,
+    /// The map's string table. Use [MapAsset::string] to look up strings by ID.
+    pub strings: Vec<MapString>,
+    /// The unit, upgrade, and tech settings specified by the map, and what each player is allowed
+    /// to build and research.
+    pub overrides: MapOverrides,
+    /// The triggers that run during the game (for Use Map Settings games).
+    pub triggers: Vec<Trigger>,
+    /// The triggers that run during the mission briefing.
+    pub briefing_triggers: Vec<Trigger>,
+    /// The sounds in the map's archive that are played by triggers, keyed by their normalized
+    /// path within the archive (see [MapAsset::sound]).
+    pub sounds: HashMap<String, Handle<AudioSource>>,
+}
+
+impl MapAsset {
+    /// Returns the string with the specified ID (IDs start at 1), if there is one.
+    pub fn string(&self, id: u32) -> Option<&MapString> {
+        let index = (id as usize).checked_sub(1)?;
+        self.strings.get(index)
+    }
+
+    /// Returns the sound at `path` within the map's archive, if it is one that the map's triggers
+    /// play.
+    pub fn sound(&self, path: &str) -> Option<&Handle<AudioSource>> {
+        self.sounds.get(&normalize_archive_path(path))
+    }
+
+    /// Returns the average color of the terrain tile at (`x`, `y`), in tiles.
+    pub fn tile_color(&self, x: u32, y: u32) -> Option<[u8; 3]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let mega_tile = self.terrain.tile(x as usize, y as usize)?;
+        let index = *self.tile_texture_indices.get(&mega_tile.id)?;
+        self.tile_colors.get(index).copied()
+    }
+
+    /// Returns the name of the force at `index`, using the default name if the map doesn't
+    /// specify one.
+    pub fn force_name(&self, index: usize) -> String {
+        self.forces
+            .get(index)
+            .and_then(|f| self.string(f.name_string as u32))
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| format!("Force {}", index + 1))
+    }
 }
 
 impl AssetLoader for MapAssetLoader {
@@ -75,11 +140,37 @@ impl AssetLoader for MapAssetLoader {
         settings: &'a Self::Settings,
         load_context: &'a mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
-        // TODO(tec27): At some point we'll need the MPQ to be able to load other assets
-        // (for UMS), but I don't want to deal with the lifetimes for now, so we just drop it
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
-        let (chk, _mpq) = broodmap::extract_chk_from_map(&bytes, None, None)?;
+        let (chk, mpq) = broodmap::extract_chk_from_map(&bytes, None, None)?;
+        // NOTE(tec27): broodmap doesn't parse every section we need, so we also parse the raw CHK
+        // ourselves for those
+        let raw_chk = mpq.read_file("staredit\\scenario.chk")?;
+        let sections = ChkSections::parse(&raw_chk);
+        let strings = parse_strings(&sections);
+        let triggers = parse_triggers(sections.get(b"TRIG").unwrap_or_default());
+        let briefing_triggers = parse_triggers(sections.get(b"MBRF").unwrap_or_default());
+        // NOTE(tec27): The MPQ borrows from the map bytes, so rather than keeping it around we load
+        // the files that triggers use up front. Anything else can be loaded through the map asset
+        // source, using `map_file_path` with the map's `path`.
+        let mut sounds = HashMap::new();
+        for path in trigger_sound_paths(&triggers, &briefing_triggers, &strings) {
+            let key = normalize_archive_path(&path);
+            if sounds.contains_key(&key) {
+                continue;
+            }
+            match mpq.read_file(&path) {
+                Ok(data) => {
+                    let source = AudioSource {
+                        bytes: Arc::from(data),
+                    };
+                    let label = format!("sound/{}", key.replace('\\', "/"));
+                    sounds.insert(key, load_context.add_labeled_asset(label, source));
+                }
+                Err(e) => warn!("Failed to load sound {path} from map: {e}"),
+            }
+        }
+
         let tileset = chk.tileset();
         let Ok(terrain) = chk.terrain() else {
             return Err(anyhow!("Could not load map's terrain"));
@@ -100,7 +191,7 @@ impl AssetLoader for MapAssetLoader {
         info!("Mega tile lookup has {} entries", mega_tile_lookup.len());
 
         info!("Loading tileset textures...");
-        let (tile_textures, tile_texture_indices) = load_tile_textures(
+        let tile_textures = load_tile_textures(
             tileset,
             &mega_tile_lookup,
             settings.quality,
@@ -109,7 +200,8 @@ impl AssetLoader for MapAssetLoader {
             self.supported_compressed_formats,
         )
         .await?;
-        info!("Loaded {} tile textures", tile_textures.len());
+        info!("Loaded {} tile textures", tile_textures.textures.len());
+        let terrain = GameMapTerrain::from_terrain_and_lookup(terrain, &mega_tile_lookup)?;
 
         Ok(MapAsset {
             name: chk
@@ -117,15 +209,24 @@ impl AssetLoader for MapAssetLoader {
                 .ok()
                 .and_then(|p| p.name.clone())
                 .unwrap_or_default(),
+            path: load_context.path().to_owned(),
             width: chk.width() as u32,
             height: chk.height() as u32,
             tileset,
-            terrain: terrain.clone(),
-            mega_tile_lookup,
-            tile_textures,
-            tile_texture_indices,
+            terrain,
+            tile_textures: tile_textures.textures,
+            tile_texture_indices: tile_textures.indices,
+            tile_colors: tile_textures.average_colors,
             placed_units: placed_units.clone(),
             sprites: sprites.clone(),
+            slots: parse_slots(&sections),
+            forces: parse_forces(&sections),
+            locations: parse_locations(&sections, chk.width() as u32, chk.height() as u32),
+            strings,
+            overrides: parse_overrides(&sections),
+            triggers,
+            briefing_triggers,
+            sounds,
         })
     }
 
@@ -133,3 +234,79 @@ impl AssetLoader for MapAssetLoader {
         &["scx", "scm"]
     }
 }
+
+#[cfg(test)]
+impl MapAsset {
+    /// Creates a map for tests that is `width` x `height` tiles of walkable ground, with nothing
+    /// placed on it. Player 1 is a human Terran and player 2 is a computer Zerg.
+    pub fn fixture(width: u32, height: u32) -> Self {
+        use crate::maps::{
+            chk::{SlotOwner, SlotRace},
+            tileset::{MegaTileFlags, MegaTileInfo, MiniTileFlags},
+        };
+
+        let ground = MegaTileInfo {
+            flags: MegaTileFlags::WALKABLE,
+            id: 0,
+            mini_tiles: [MiniTileFlags::WALKABLE; 16],
+        };
+        let mut slots = [MapSlot::default(); NUM_CHK_PLAYERS];
+        slots[0] = MapSlot {
+            owner: SlotOwner::Human,
+            race: SlotRace::Terran,
+            force: 0,
+        };
+        slots[1] = MapSlot {
+            owner: SlotOwner::Computer,
+            race: SlotRace::Zerg,
+            force: 1,
+        };
+
+        MapAsset {
+            name: "Fixture".into(),
+            path: PathBuf::from("fixture.scx"),
+            width,
+            height,
+            tileset: Tileset::Badlands,
+            terrain: GameMapTerrain::new(vec![ground; (width * height) as usize], width as usize),
+            tile_textures: Vec::new(),
+            tile_texture_indices: HashMap::new(),
+            tile_colors: Vec::new(),
+            placed_units: Vec::new(),
+            sprites: Vec::new(),
+            slots,
+            forces: Default::default(),
+            locations: Vec::new(),
+            strings: Vec::new(),
+            overrides: MapOverrides::default(),
+            triggers: Vec::new(),
+            briefing_triggers: Vec::new(),
+            sounds: HashMap::new(),
+        }
+    }
+}
+
+/// Returns the paths (within the map's archive) of every sound file played by the map's triggers.
+fn trigger_sound_paths<'a>(
+    triggers: &'a [Trigger],
+    briefing_triggers: &'a [Trigger],
+    strings: &'a [MapString],
+) -> impl Iterator<Item = String> + 'a {
+    // NOTE(tec27): Briefing triggers use their own set of action types, where 2 is "Play WAV" and
+    // 8 is "Display Transmission"
+    let game_sounds = triggers
+        .iter()
+        .flat_map(|t| t.actions.iter())
+        .filter(|a| matches!(a.kind, ActionType::PlayWav | ActionType::Transmission));
+    let briefing_sounds = briefing_triggers
+        .iter()
+        .flat_map(|t| t.actions.iter())
+        .filter(|a| matches!(u8::from(a.kind), 2 | 8));
+
+    game_sounds
+        .chain(briefing_sounds)
+        .filter_map(|a| (a.wav_string as usize).checked_sub(1))
+        .filter_map(|i| strings.get(i))
+        .map(|s| s.to_string())
+        .filter(|s| !s.is_empty())
+}
@@ -0,0 +1,365 @@
+// Parsing for CHK sections that broodmap doesn't expose. Useful links:
+// http://www.staredit.net/wiki/index.php/Scenario.chk
+
+use bevy::{reflect::Reflect, utils::HashMap};
+use bitflags::bitflags;
+use byteorder::{ByteOrder, LittleEndian};
+
+/// The number of player slots specified in a CHK, including the non-playable ones (9-12).
+pub const NUM_CHK_PLAYERS: usize = 12;
+/// The number of forces specified in a CHK.
+pub const NUM_FORCES: usize = 4;
+/// The number of unit types that per-unit map data is specified for.
+pub const NUM_UNIT_TYPES: usize = 228;
+/// The index of the "Anywhere" location, which always covers the entire map.
+pub const ANYWHERE_LOCATION: usize = 63;
+
+/// The raw sections of a CHK file, keyed by their name. If a section is present multiple times, the
+/// last one wins (matching how BW handles most sections).
+#[derive(Debug, Default)]
+pub struct ChkSections<'a> {
+    sections: HashMap<[u8; 4], &'a [u8]>,
+}
+
+impl<'a> ChkSections<'a> {
+    pub fn parse(mut bytes: &'a [u8]) -> Self {
+        let mut sections = HashMap::new();
+        while bytes.len() >= 8 {
+            let name: [u8; 4] = bytes[0..4].try_into().unwrap();
+            let size = LittleEndian::read_i32(&bytes[4..8]);
+            bytes = &bytes[8..];
+            // NOTE(tec27): Protected maps sometimes specify sizes that extend past the end of the
+            // file (or are negative), so we just take whatever data is actually there
+            let size = (size.max(0) as usize).min(bytes.len());
+            sections.insert(name, &bytes[..size]);
+            bytes = &bytes[size..];
+        }
+
+        Self { sections }
+    }
+
+    pub fn get(&self, name: &[u8; 4]) -> Option<&'a [u8]> {
+        self.sections.get(name).copied()
+    }
+
+    /// Retrieves the section with the specified name, padded with zeroes to `min_len` if it is
+    /// shorter than that (or missing).
+    fn get_padded(&self, name: &[u8; 4], min_len: usize) -> Vec<u8> {
+        let mut data = self.get(name).unwrap_or_default().to_vec();
+        if data.len() < min_len {
+            data.resize(min_len, 0);
+        }
+        data
+    }
+}
+
+/// Who controls a player slot, as specified by the map (OWNR section).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum SlotOwner {
+    #[default]
+    Inactive,
+    Computer,
+    Human,
+    RescuePassive,
+    Unused,
+    Neutral,
+}
+
+impl From<u8> for SlotOwner {
+    fn from(value: u8) -> Self {
+        match value {
+            // 1 is "computer game", which only shows up in saved games
+            1 | 5 => SlotOwner::Computer,
+            // 2 is "occupied by human player", which only shows up in saved games
+            2 | 6 => SlotOwner::Human,
+            3 => SlotOwner::RescuePassive,
+            4 => SlotOwner::Unused,
+            7 => SlotOwner::Neutral,
+            _ => SlotOwner::Inactive,
+        }
+    }
+}
+
+/// The race of a player slot, as specified by the map (SIDE section).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub enum SlotRace {
+    Zerg,
+    Terran,
+    Protoss,
+    Independent,
+    Neutral,
+    #[default]
+    UserSelectable,
+    Random,
+    Inactive,
+}
+
+impl From<u8> for SlotRace {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => SlotRace::Zerg,
+            1 => SlotRace::Terran,
+            2 => SlotRace::Protoss,
+            3 => SlotRace::Independent,
+            4 => SlotRace::Neutral,
+            6 => SlotRace::Random,
+            7 => SlotRace::Inactive,
+            _ => SlotRace::UserSelectable,
+        }
+    }
+}
+
+/// The map's settings for a particular player slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
+pub struct MapSlot {
+    pub owner: SlotOwner,
+    pub race: SlotRace,
+    /// The index of the force this player belongs to (0-3).
+    pub force: u8,
+}
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct ForceFlags: u8 {
+        const RANDOM_START_LOCATION = 0x01;
+        const ALLIED = 0x02;
+        const ALLIED_VICTORY = 0x04;
+        const SHARED_VISION = 0x08;
+    }
+}
+
+/// A force (team) specified by the map (FORC section).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MapForce {
+    /// The string ID of the force's name, or 0 if it uses the default name.
+    pub name_string: u16,
+    pub flags: ForceFlags,
+}
+
+/// Parses the player slot settings from the OWNR, SIDE, and FORC sections.
+pub fn parse_slots(sections: &ChkSections) -> [MapSlot; NUM_CHK_PLAYERS] {
+    let owners = sections.get_padded(b"OWNR", NUM_CHK_PLAYERS);
+    let races = sections.get_padded(b"SIDE", NUM_CHK_PLAYERS);
+    let forces = sections.get_padded(b"FORC", 8);
+
+    std::array::from_fn(|i| MapSlot {
+        owner: owners[i].into(),
+        race: races[i].into(),
+        // Only the first 8 players can be assigned to forces
+        force: if i < 8 {
+            forces[i].min(NUM_FORCES as u8 - 1)
+        } else {
+            0
+        },
+    })
+}
+
+/// Parses the force settings from the FORC section.
+pub fn parse_forces(sections: &ChkSections) -> [MapForce; NUM_FORCES] {
+    // NOTE(tec27): This section can be shorter than its full size, in which case the missing
+    // values are treated as 0
+    let forces = sections.get_padded(b"FORC", 20);
+    std::array::from_fn(|i| MapForce {
+        name_string: LittleEndian::read_u16(&forces[8 + i * 2..]),
+        flags: ForceFlags::from_bits_truncate(forces[16 + i]),
+    })
+}
+
+bitflags! {
+    /// The elevations that a location does *not* apply to. Units at these elevations won't be
+    /// considered to be inside the location.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct ElevationFlags: u16 {
+        const LOW_GROUND = 0x01;
+        const MEDIUM_GROUND = 0x02;
+        const HIGH_GROUND = 0x04;
+        const LOW_AIR = 0x08;
+        const MEDIUM_AIR = 0x10;
+        const HIGH_AIR = 0x20;
+    }
+}
+
+/// The elevation of a unit, as it relates to [ElevationFlags]. Air units take on the level of the
+/// terrain beneath them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Elevation {
+    LowGround,
+    MediumGround,
+    HighGround,
+    LowAir,
+    MediumAir,
+    HighAir,
+}
+
+impl Elevation {
+    fn flag(&self) -> ElevationFlags {
+        match self {
+            Elevation::LowGround => ElevationFlags::LOW_GROUND,
+            Elevation::MediumGround => ElevationFlags::MEDIUM_GROUND,
+            Elevation::HighGround => ElevationFlags::HIGH_GROUND,
+            Elevation::LowAir => ElevationFlags::LOW_AIR,
+            Elevation::MediumAir => ElevationFlags::MEDIUM_AIR,
+            Elevation::HighAir => ElevationFlags::HIGH_AIR,
+        }
+    }
+}
+
+/// A rectangular region of the map, used by triggers (MRGN section).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MapLocation {
+    /// The bounds of the location in pixels. Note that maps can specify "inverted" locations, where
+    /// the left/top are greater than the right/bottom.
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    /// The string ID of the location's name, or 0 if it is unused.
+    pub name_string: u16,
+    pub elevation_flags: ElevationFlags,
+}
+
+impl MapLocation {
+    /// Returns whether the specified point (in pixels) is inside this location.
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        let (left, right) = (self.left.min(self.right), self.left.max(self.right));
+        let (top, bottom) = (self.top.min(self.bottom), self.top.max(self.bottom));
+        x >= left as i32 && x < right as i32 && y >= top as i32 && y < bottom as i32
+    }
+
+    /// Returns whether units at the specified elevation are considered by this location.
+    pub fn includes(&self, elevation: Elevation) -> bool {
+        !self.elevation_flags.contains(elevation.flag())
+    }
+
+    /// Returns the center point of this location, in pixels.
+    pub fn center(&self) -> (i32, i32) {
+        (
+            ((self.left + self.right) / 2) as i32,
+            ((self.top + self.bottom) / 2) as i32,
+        )
+    }
+}
+
+/// Parses the locations from the MRGN section. The "Anywhere" location is always sized to cover the
+/// whole map (which is `width` x `height` tiles), regardless of what the map specifies.
+pub fn parse_locations(sections: &ChkSections, width: u32, height: u32) -> Vec<MapLocation> {
+    // NOTE(tec27): Original maps have 64 locations, Brood War maps have 255
+    let data = sections.get(b"MRGN").unwrap_or_default();
+    let count = (data.len() / 20).max(ANYWHERE_LOCATION + 1);
+    let data = sections.get_padded(b"MRGN", count * 20);
+
+    let mut locations = data
+        .chunks_exact(20)
+        .map(|l| MapLocation {
+            left: LittleEndian::read_u32(&l[0..]),
+            top: LittleEndian::read_u32(&l[4..]),
+            right: LittleEndian::read_u32(&l[8..]),
+            bottom: LittleEndian::read_u32(&l[12..]),
+            name_string: LittleEndian::read_u16(&l[16..]),
+            elevation_flags: ElevationFlags::from_bits_truncate(LittleEndian::read_u16(&l[18..])),
+        })
+        .collect::<Vec<_>>();
+    let anywhere = &mut locations[ANYWHERE_LOCATION];
+    anywhere.left = 0;
+    anywhere.top = 0;
+    anywhere.right = width * 32;
+    anywhere.bottom = height * 32;
+
+    locations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(name: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut result = name.to_vec();
+        result.extend_from_slice(&(data.len() as i32).to_le_bytes());
+        result.extend_from_slice(data);
+        result
+    }
+
+    #[test]
+    fn later_sections_override() {
+        let mut bytes = section(b"OWNR", &[6; 12]);
+        bytes.extend(section(b"VER ", &[0xcd, 0x00]));
+        bytes.extend(section(b"OWNR", &[5; 12]));
+        let sections = ChkSections::parse(&bytes);
+
+        assert_eq!(sections.get(b"OWNR"), Some(&[5u8; 12][..]));
+        assert_eq!(sections.get(b"VER "), Some(&[0xcd, 0x00][..]));
+        assert_eq!(sections.get(b"SIDE"), None);
+    }
+
+    #[test]
+    fn truncated_section() {
+        let mut bytes = section(b"SIDE", &[1; 12]);
+        bytes.truncate(bytes.len() - 4);
+        let sections = ChkSections::parse(&bytes);
+
+        assert_eq!(sections.get(b"SIDE"), Some(&[1u8; 8][..]));
+    }
+
+    #[test]
+    fn slots_and_forces() {
+        let mut bytes = section(b"OWNR", &[6, 5, 6, 0, 6, 6, 6, 6, 0, 0, 0, 7]);
+        bytes.extend(section(b"SIDE", &[5, 0, 2, 7, 5, 5, 5, 6, 7, 7, 7, 4]));
+        // Truncated FORC section, with no force properties specified
+        bytes.extend(section(
+            b"FORC",
+            &[0, 1, 0, 1, 2, 2, 3, 3, 5, 0, 0, 0, 6, 0, 0, 0],
+        ));
+        let sections = ChkSections::parse(&bytes);
+
+        let slots = parse_slots(&sections);
+        assert_eq!(
+            slots[0],
+            MapSlot {
+                owner: SlotOwner::Human,
+                race: SlotRace::UserSelectable,
+                force: 0,
+            }
+        );
+        assert_eq!(
+            slots[1],
+            MapSlot {
+                owner: SlotOwner::Computer,
+                race: SlotRace::Zerg,
+                force: 1,
+            }
+        );
+        assert_eq!(slots[3].owner, SlotOwner::Inactive);
+        assert_eq!(slots[7].race, SlotRace::Random);
+        assert_eq!(slots[7].force, 3);
+        assert_eq!(slots[11].owner, SlotOwner::Neutral);
+
+        let forces = parse_forces(&sections);
+        assert_eq!(forces[0].name_string, 5);
+        assert_eq!(forces[2].name_string, 6);
+        assert_eq!(forces[3].name_string, 0);
+        assert_eq!(forces[0].flags, ForceFlags::empty());
+    }
+
+    #[test]
+    fn locations() {
+        let mut mrgn = Vec::new();
+        for v in [64u32, 96, 128, 160] {
+            mrgn.extend_from_slice(&v.to_le_bytes());
+        }
+        mrgn.extend_from_slice(&[2, 0, 0x38, 0]);
+        let bytes = section(b"MRGN", &mrgn);
+        let sections = ChkSections::parse(&bytes);
+
+        let locations = parse_locations(&sections, 64, 128);
+        assert_eq!(locations.len(), 64);
+        assert_eq!(locations[0].name_string, 2);
+        assert_eq!(locations[0].center(), (96, 128));
+        assert!(locations[0].contains(64, 96));
+        assert!(!locations[0].contains(128, 96));
+        assert!(!locations[1].contains(0, 0));
+        assert!(locations[ANYWHERE_LOCATION].contains(2047, 4095));
+        assert!(locations[0].includes(Elevation::HighGround));
+        assert!(!locations[0].includes(Elevation::LowAir));
+        assert!(locations[1].includes(Elevation::LowAir));
+    }
+}
@@ -57,7 +57,7 @@ pub struct GameMapBundle {
     pub terrain: GameMapTerrain,
 }
 
-#[derive(Component, Default)]
+#[derive(Component, Debug, Default, Clone)]
 pub struct GameMapTerrain {
     /// The width of the map in MegaTiles.
     width: usize,
@@ -102,13 +102,21 @@ impl GameMapTerrain {
     pub fn tile_at(&self, position: Position) -> Option<&MegaTileInfo> {
         let x = position.x / LOGIC_TILE_SIZE;
         let y = position.y / LOGIC_TILE_SIZE;
+        if x < 0 || y < 0 {
+            return None;
+        }
+
+        self.tile(x as usize, y as usize)
+    }
 
+    /// Returns the mega-tile at (`x`, `y`), in tiles.
+    pub fn tile(&self, x: usize, y: usize) -> Option<&MegaTileInfo> {
         // NOTE(tec27): y check is unnecessary since the get call below will already handle that
-        if x < 0 || y < 0 || (x as usize) >= self.width {
+        if x >= self.width {
             return None;
         }
 
-        self.tiles.get((y as usize) * self.width + (x as usize))
+        self.tiles.get(y * self.width + x)
     }
 
     /// Returns the mini-tile at the given position.
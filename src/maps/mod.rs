@@ -2,10 +2,10 @@ use std::path::Path;
 
 use bevy::render::render_resource::TextureFormat;
 use bevy::utils::HashMap;
-use bevy::{prelude::*, transform::TransformSystem};
+use bevy::{asset::LoadState, prelude::*, transform::TransformSystem};
 use bevy_ecs_tilemap::prelude::*;
 
-use crate::maps::game_map::{GameMapTerrain, GameMapTileset};
+use crate::maps::game_map::GameMapTileset;
 use crate::settings::GameSettings;
 use crate::{
     gamedata::BwGameData,
@@ -17,20 +17,25 @@ use game_map::GameMap;
 use position::apply_position_to_transform;
 use position::Position;
 
+pub mod archive;
 mod asset;
+pub mod chk;
 pub mod game_map;
+pub mod overrides;
 pub mod position;
-mod tileset;
+pub mod strings;
+pub mod tileset;
+pub mod triggers;
 
 pub use asset::MapAsset;
 pub use asset::MapAssetSettings;
+pub use tileset::{tileset_dark_table_path, tileset_palette_path};
 
 pub struct MapsPlugin;
 
 impl Plugin for MapsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(TilemapPlugin)
-            .init_asset::<MapAsset>()
+        app.init_asset::<MapAsset>()
             .init_asset_loader::<MapAssetLoader>()
             .init_resource::<CurrentMap>()
             .register_type::<GameMap>()
@@ -39,6 +44,11 @@ impl Plugin for MapsPlugin {
             // TODO(tec27): Maybe this should be handled as a requirement of PreGame and we
             // guarantee that exactly one map is loaded for InGame?
             .add_systems(Update, map_init.run_if(in_state(AppState::PreGame)))
+            .add_systems(
+                Update,
+                check_map_load_failure
+                    .run_if(in_state(AppState::Lobby).or_else(in_state(AppState::PreGame))),
+            )
             .add_systems(OnExit(AppState::InGame), map_cleanup)
             .add_systems(
                 PostUpdate,
@@ -47,15 +57,18 @@ impl Plugin for MapsPlugin {
     }
 }
 
+/// Starts loading the map at `path` and transitions to `state` (which should be either the lobby or
+/// [AppState::PreGame]).
 pub fn load_map(
     path: &Path,
+    state: AppState,
     current_map: &mut ResMut<CurrentMap>,
     next_state: &mut ResMut<NextState<AppState>>,
     asset_server: &Res<AssetServer>,
     settings: &Res<GameSettings>,
 ) {
     info!("Loading map: {}", path.to_string_lossy());
-    next_state.set(AppState::PreGame);
+    next_state.set(state);
     let quality = settings.asset_quality;
     let pack = settings.asset_pack;
     current_map.handle =
@@ -70,12 +83,22 @@ pub struct CurrentMap {
     pub handle: Handle<MapAsset>,
 }
 
+fn check_map_load_failure(
+    current_map: Res<CurrentMap>,
+    asset_server: Res<AssetServer>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if let LoadState::Failed(_) = asset_server.load_state(&current_map.handle) {
+        next_state.set(AppState::LoadError);
+    }
+}
+
 fn map_init(
     mut commands: Commands,
     game_data: Option<Res<BwGameData>>,
     current_map: Res<CurrentMap>,
     map_assets: Res<Assets<MapAsset>>,
-    array_texture_loader: Res<ArrayTextureLoader>,
+    array_texture_loader: Option<Res<ArrayTextureLoader>>,
     settings: Res<GameSettings>,
     game_map_query: Query<Entity, With<GameMap>>,
 ) {
@@ -99,24 +122,23 @@ fn map_init(
                     height: map.height,
                 },
                 tileset: GameMapTileset(map.tileset),
-                // TODO(tec27): Handle errors in this conversion properly
-                terrain: GameMapTerrain::from_terrain_and_lookup(
-                    &map.terrain,
-                    &map.mega_tile_lookup,
-                )
-                .unwrap(),
+                terrain: map.terrain.clone(),
                 ..default()
             },
             Name::new(format!("GameMap - {}", map.name)),
         ))
         .id();
-    create_tilemap(
-        &mut commands,
-        map,
-        &array_texture_loader,
-        &settings,
-        map_entity,
-    );
+    // NOTE(tec27): The tilemap is only needed for rendering, so we skip it if the tilemap plugin
+    // isn't present (e.g. when running headless)
+    if let Some(array_texture_loader) = array_texture_loader {
+        create_tilemap(
+            &mut commands,
+            map,
+            &array_texture_loader,
+            &settings,
+            map_entity,
+        );
+    }
 }
 
 fn map_cleanup(mut commands: Commands, maps: Query<Entity, With<GameMap>>) {
@@ -177,8 +199,7 @@ fn create_tilemap(
 
     for x in 0..map.width {
         for y in 0..map.height {
-            let tile_id = map.terrain[y as usize][x as usize].id();
-            let mega_tile = map.mega_tile_lookup.get(&tile_id).unwrap();
+            let mega_tile = map.terrain.tile(x as usize, y as usize).unwrap();
             let texture_index = *map.tile_texture_indices.get(&mega_tile.id).unwrap();
             let (tilemap_index, tilemap_texture_index) =
                 match texture_to_tilemap.get(&texture_index) {
@@ -0,0 +1,454 @@
+// Parsing for the unit, upgrade, and tech settings that maps can override, as well as what is
+// available to each player. Maps made for the original game use the UNIS, UPGS, TECS, UPGR, and
+// PTEC sections, while Brood War maps use extended versions of these (UNIx, UPGx, TECx, PUPx, and
+// PTEx) that cover the expansion's additions. Useful links:
+// http://www.staredit.net/wiki/index.php/Scenario.chk#.22UNIS.22_-_Unit_Settings
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::maps::chk::{ChkSections, NUM_CHK_PLAYERS, NUM_UNIT_TYPES};
+
+/// The number of weapons specified in the UNIS section.
+const NUM_ORIGINAL_WEAPONS: usize = 100;
+/// The number of weapons specified in the UNIx section.
+pub const NUM_WEAPONS: usize = 130;
+/// The number of upgrades specified in the UPGS and UPGR sections.
+const NUM_ORIGINAL_UPGRADES: usize = 46;
+/// The number of upgrades specified in the UPGx and PUPx sections.
+pub const NUM_UPGRADES: usize = 61;
+/// The number of techs specified in the TECS and PTEC sections.
+const NUM_ORIGINAL_TECHS: usize = 24;
+/// The number of techs specified in the TECx and PTEx sections.
+pub const NUM_TECHS: usize = 44;
+
+/// Settings for a unit type that replace the game's defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct UnitSettings {
+    /// The unit's maximum hit points, as a fixed-point value (the lower 8 bits are the fractional
+    /// part).
+    pub hit_points: u32,
+    pub shield_points: u16,
+    pub armor: u8,
+    /// The time it takes to build the unit, in frames.
+    pub build_time: u16,
+    pub mineral_cost: u16,
+    pub gas_cost: u16,
+    /// The string ID of the unit's name, or 0 if it uses the default name.
+    pub name_string: u16,
+}
+
+/// Damage settings for a weapon. These are only used for weapons of unit types that don't use the
+/// default settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WeaponSettings {
+    pub base_damage: u16,
+    /// The damage added for each level of the weapon's upgrade.
+    pub upgrade_damage: u16,
+}
+
+/// Settings for an upgrade that replace the game's defaults. Each cost is `base + factor * level`,
+/// where `level` is the number of times the upgrade has already been researched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct UpgradeSettings {
+    pub mineral_cost_base: u16,
+    pub mineral_cost_factor: u16,
+    pub gas_cost_base: u16,
+    pub gas_cost_factor: u16,
+    /// The time it takes to research the upgrade, in frames.
+    pub time_base: u16,
+    pub time_factor: u16,
+}
+
+/// Settings for a tech that replace the game's defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TechSettings {
+    pub mineral_cost: u16,
+    pub gas_cost: u16,
+    /// The time it takes to research the tech, in frames.
+    pub time: u16,
+    pub energy_cost: u16,
+}
+
+/// The upgrade levels available to a player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct UpgradeLevels {
+    /// The maximum level the player can research.
+    pub max: u8,
+    /// The level the player starts the game with.
+    pub start: u8,
+}
+
+/// Whether a tech is available to a player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TechAvailability {
+    /// Whether the player is able to research (or use, if already researched) the tech.
+    pub available: bool,
+    /// Whether the player starts the game with the tech researched.
+    pub researched: bool,
+}
+
+/// What a single player is allowed to build and research. The map's global settings have already
+/// been applied to any values that use them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerAvailability {
+    /// Whether each unit type can be built, indexed by unit type ID.
+    pub units: Vec<bool>,
+    /// The upgrade levels for each upgrade, indexed by upgrade ID. `None` means the game's defaults
+    /// should be used (e.g. because the map doesn't specify these settings).
+    pub upgrades: Vec<Option<UpgradeLevels>>,
+    /// The availability of each tech, indexed by tech ID. `None` means the game's defaults should be
+    /// used.
+    pub techs: Vec<Option<TechAvailability>>,
+}
+
+impl Default for PlayerAvailability {
+    fn default() -> Self {
+        Self {
+            units: vec![true; NUM_UNIT_TYPES],
+            upgrades: vec![None; NUM_UPGRADES],
+            techs: vec![None; NUM_TECHS],
+        }
+    }
+}
+
+/// The unit, upgrade, and tech settings specified by the map. For all of the settings, `None`
+/// means the game's defaults should be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapOverrides {
+    /// Settings for each unit type, indexed by unit type ID.
+    pub units: Vec<Option<UnitSettings>>,
+    /// Settings for each weapon, indexed by weapon ID.
+    pub weapons: Vec<Option<WeaponSettings>>,
+    /// Settings for each upgrade, indexed by upgrade ID.
+    pub upgrades: Vec<Option<UpgradeSettings>>,
+    /// Settings for each tech, indexed by tech ID.
+    pub techs: Vec<Option<TechSettings>>,
+    /// What each player is allowed to build and research.
+    pub availability: [PlayerAvailability; NUM_CHK_PLAYERS],
+}
+
+impl Default for MapOverrides {
+    fn default() -> Self {
+        Self {
+            units: vec![None; NUM_UNIT_TYPES],
+            weapons: vec![None; NUM_WEAPONS],
+            upgrades: vec![None; NUM_UPGRADES],
+            techs: vec![None; NUM_TECHS],
+            availability: Default::default(),
+        }
+    }
+}
+
+/// Reads sequential arrays of values out of a section.
+struct SectionReader {
+    data: Vec<u8>,
+    pos: usize,
+}
+
+impl SectionReader {
+    /// Creates a reader for a section, which is padded with zeroes to `len` if it is shorter.
+    fn new(data: &[u8], len: usize) -> Self {
+        let mut data = data.to_vec();
+        if data.len() < len {
+            data.resize(len, 0);
+        }
+        Self { data, pos: 0 }
+    }
+
+    fn skip(&mut self, bytes: usize) {
+        self.pos += bytes;
+    }
+
+    fn read_u8s(&mut self, count: usize) -> Vec<u8> {
+        let result = self.data[self.pos..self.pos + count].to_vec();
+        self.pos += count;
+        result
+    }
+
+    fn read_u16s(&mut self, count: usize) -> Vec<u16> {
+        let result = (0..count)
+            .map(|i| LittleEndian::read_u16(&self.data[self.pos + i * 2..]))
+            .collect();
+        self.pos += count * 2;
+        result
+    }
+
+    fn read_u32s(&mut self, count: usize) -> Vec<u32> {
+        let result = (0..count)
+            .map(|i| LittleEndian::read_u32(&self.data[self.pos + i * 4..]))
+            .collect();
+        self.pos += count * 4;
+        result
+    }
+
+    /// Reads a per-player table of `count` bytes for each player.
+    fn read_player_u8s(&mut self, count: usize) -> Vec<Vec<u8>> {
+        (0..NUM_CHK_PLAYERS).map(|_| self.read_u8s(count)).collect()
+    }
+}
+
+/// Returns the first of the specified sections that is present, along with the number of entries
+/// it contains.
+fn first_section<'a>(
+    sections: &ChkSections<'a>,
+    choices: &[(&[u8; 4], usize)],
+) -> Option<(&'a [u8], usize)> {
+    choices
+        .iter()
+        .find_map(|&(name, count)| sections.get(name).map(|data| (data, count)))
+}
+
+/// Parses all of the unit, upgrade, and tech settings and availability from the map.
+pub fn parse_overrides(sections: &ChkSections) -> MapOverrides {
+    let mut result = MapOverrides::default();
+    parse_unit_settings(sections, &mut result);
+    parse_upgrade_settings(sections, &mut result);
+    parse_tech_settings(sections, &mut result);
+    parse_unit_availability(sections, &mut result);
+    parse_upgrade_availability(sections, &mut result);
+    parse_tech_availability(sections, &mut result);
+    result
+}
+
+/// Parses the UNIx (or UNIS) section.
+fn parse_unit_settings(sections: &ChkSections, result: &mut MapOverrides) {
+    let Some((data, num_weapons)) = first_section(
+        sections,
+        &[(b"UNIx", NUM_WEAPONS), (b"UNIS", NUM_ORIGINAL_WEAPONS)],
+    ) else {
+        return;
+    };
+    let mut reader = SectionReader::new(data, NUM_UNIT_TYPES * 16 + num_weapons * 4);
+    let use_defaults = reader.read_u8s(NUM_UNIT_TYPES);
+    let hit_points = reader.read_u32s(NUM_UNIT_TYPES);
+    let shield_points = reader.read_u16s(NUM_UNIT_TYPES);
+    let armor = reader.read_u8s(NUM_UNIT_TYPES);
+    let build_time = reader.read_u16s(NUM_UNIT_TYPES);
+    let mineral_cost = reader.read_u16s(NUM_UNIT_TYPES);
+    let gas_cost = reader.read_u16s(NUM_UNIT_TYPES);
+    let name_string = reader.read_u16s(NUM_UNIT_TYPES);
+    let base_damage = reader.read_u16s(num_weapons);
+    let upgrade_damage = reader.read_u16s(num_weapons);
+
+    for (i, unit) in result.units.iter_mut().enumerate() {
+        if use_defaults[i] == 0 {
+            *unit = Some(UnitSettings {
+                hit_points: hit_points[i],
+                shield_points: shield_points[i],
+                armor: armor[i],
+                build_time: build_time[i],
+                mineral_cost: mineral_cost[i],
+                gas_cost: gas_cost[i],
+                name_string: name_string[i],
+            });
+        }
+    }
+    for i in 0..num_weapons {
+        result.weapons[i] = Some(WeaponSettings {
+            base_damage: base_damage[i],
+            upgrade_damage: upgrade_damage[i],
+        });
+    }
+}
+
+/// Parses the UPGx (or UPGS) section.
+fn parse_upgrade_settings(sections: &ChkSections, result: &mut MapOverrides) {
+    let Some((data, count)) = first_section(
+        sections,
+        &[(b"UPGx", NUM_UPGRADES), (b"UPGS", NUM_ORIGINAL_UPGRADES)],
+    ) else {
+        return;
+    };
+    // NOTE(tec27): UPGx has a padding byte after the "use defaults" values (UPGS doesn't, since it
+    // has an even number of upgrades)
+    let padding = count % 2;
+    let mut reader = SectionReader::new(data, count * 13 + padding);
+    let use_defaults = reader.read_u8s(count);
+    reader.skip(padding);
+    let mineral_cost_base = reader.read_u16s(count);
+    let mineral_cost_factor = reader.read_u16s(count);
+    let gas_cost_base = reader.read_u16s(count);
+    let gas_cost_factor = reader.read_u16s(count);
+    let time_base = reader.read_u16s(count);
+    let time_factor = reader.read_u16s(count);
+
+    for i in (0..count).filter(|&i| use_defaults[i] == 0) {
+        result.upgrades[i] = Some(UpgradeSettings {
+            mineral_cost_base: mineral_cost_base[i],
+            mineral_cost_factor: mineral_cost_factor[i],
+            gas_cost_base: gas_cost_base[i],
+            gas_cost_factor: gas_cost_factor[i],
+            time_base: time_base[i],
+            time_factor: time_factor[i],
+        });
+    }
+}
+
+/// Parses the TECx (or TECS) section.
+fn parse_tech_settings(sections: &ChkSections, result: &mut MapOverrides) {
+    let Some((data, count)) = first_section(
+        sections,
+        &[(b"TECx", NUM_TECHS), (b"TECS", NUM_ORIGINAL_TECHS)],
+    ) else {
+        return;
+    };
+    let mut reader = SectionReader::new(data, count * 9);
+    let use_defaults = reader.read_u8s(count);
+    let mineral_cost = reader.read_u16s(count);
+    let gas_cost = reader.read_u16s(count);
+    let time = reader.read_u16s(count);
+    let energy_cost = reader.read_u16s(count);
+
+    for i in (0..count).filter(|&i| use_defaults[i] == 0) {
+        result.techs[i] = Some(TechSettings {
+            mineral_cost: mineral_cost[i],
+            gas_cost: gas_cost[i],
+            time: time[i],
+            energy_cost: energy_cost[i],
+        });
+    }
+}
+
+/// Parses the PUNI section.
+fn parse_unit_availability(sections: &ChkSections, result: &mut MapOverrides) {
+    let Some(data) = sections.get(b"PUNI") else {
+        return;
+    };
+    let mut reader = SectionReader::new(data, NUM_UNIT_TYPES * (NUM_CHK_PLAYERS * 2 + 1));
+    let player_available = reader.read_player_u8s(NUM_UNIT_TYPES);
+    let global_available = reader.read_u8s(NUM_UNIT_TYPES);
+    let use_defaults = reader.read_player_u8s(NUM_UNIT_TYPES);
+
+    for (p, availability) in result.availability.iter_mut().enumerate() {
+        for (i, unit) in availability.units.iter_mut().enumerate() {
+            *unit = if use_defaults[p][i] != 0 {
+                global_available[i] != 0
+            } else {
+                player_available[p][i] != 0
+            };
+        }
+    }
+}
+
+/// Parses the PUPx (or UPGR) section.
+fn parse_upgrade_availability(sections: &ChkSections, result: &mut MapOverrides) {
+    let Some((data, count)) = first_section(
+        sections,
+        &[(b"PUPx", NUM_UPGRADES), (b"UPGR", NUM_ORIGINAL_UPGRADES)],
+    ) else {
+        return;
+    };
+    let mut reader = SectionReader::new(data, count * (NUM_CHK_PLAYERS * 3 + 2));
+    let player_max = reader.read_player_u8s(count);
+    let player_start = reader.read_player_u8s(count);
+    let global_max = reader.read_u8s(count);
+    let global_start = reader.read_u8s(count);
+    let use_defaults = reader.read_player_u8s(count);
+
+    for (p, availability) in result.availability.iter_mut().enumerate() {
+        for i in 0..count {
+            availability.upgrades[i] = Some(if use_defaults[p][i] != 0 {
+                UpgradeLevels {
+                    max: global_max[i],
+                    start: global_start[i],
+                }
+            } else {
+                UpgradeLevels {
+                    max: player_max[p][i],
+                    start: player_start[p][i],
+                }
+            });
+        }
+    }
+}
+
+/// Parses the PTEx (or PTEC) section.
+fn parse_tech_availability(sections: &ChkSections, result: &mut MapOverrides) {
+    let Some((data, count)) = first_section(
+        sections,
+        &[(b"PTEx", NUM_TECHS), (b"PTEC", NUM_ORIGINAL_TECHS)],
+    ) else {
+        return;
+    };
+    let mut reader = SectionReader::new(data, count * (NUM_CHK_PLAYERS * 3 + 2));
+    let player_available = reader.read_player_u8s(count);
+    let player_researched = reader.read_player_u8s(count);
+    let global_available = reader.read_u8s(count);
+    let global_researched = reader.read_u8s(count);
+    let use_defaults = reader.read_player_u8s(count);
+
+    for (p, availability) in result.availability.iter_mut().enumerate() {
+        for i in 0..count {
+            availability.techs[i] = Some(if use_defaults[p][i] != 0 {
+                TechAvailability {
+                    available: global_available[i] != 0,
+                    researched: global_researched[i] != 0,
+                }
+            } else {
+                TechAvailability {
+                    available: player_available[p][i] != 0,
+                    researched: player_researched[p][i] != 0,
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(name: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut result = name.to_vec();
+        result.extend_from_slice(&(data.len() as i32).to_le_bytes());
+        result.extend_from_slice(data);
+        result
+    }
+
+    #[test]
+    fn missing_sections_use_defaults() {
+        let overrides = parse_overrides(&ChkSections::default());
+        assert_eq!(overrides, MapOverrides::default());
+        assert!(overrides.availability[0].units[0]);
+    }
+
+    #[test]
+    fn unit_settings_and_availability() {
+        let mut unis = vec![1u8; NUM_UNIT_TYPES];
+        // Marines (0) don't use the defaults
+        unis[0] = 0;
+        unis.extend_from_slice(&(45u32 << 8).to_le_bytes());
+        let mut bytes = section(b"UNIS", &unis);
+
+        let mut puni = vec![1u8; NUM_UNIT_TYPES * NUM_CHK_PLAYERS];
+        // Player 1 can't build marines, but uses the global setting for everything else
+        puni[0] = 0;
+        puni.extend(vec![1u8; NUM_UNIT_TYPES]);
+        // Globally, SCVs (7) can't be built
+        puni[NUM_UNIT_TYPES * NUM_CHK_PLAYERS + 7] = 0;
+        let mut use_defaults = vec![1u8; NUM_UNIT_TYPES * NUM_CHK_PLAYERS];
+        use_defaults[0] = 0;
+        puni.extend(use_defaults);
+        bytes.extend(section(b"PUNI", &puni));
+
+        let sections = ChkSections::parse(&bytes);
+        let overrides = parse_overrides(&sections);
+
+        assert_eq!(
+            overrides.units[0],
+            Some(UnitSettings {
+                hit_points: 45 << 8,
+                ..Default::default()
+            })
+        );
+        assert_eq!(overrides.units[1], None);
+        assert!(overrides.weapons[0].is_some());
+        assert!(overrides.weapons[NUM_ORIGINAL_WEAPONS].is_none());
+
+        assert!(!overrides.availability[0].units[0]);
+        assert!(overrides.availability[1].units[0]);
+        assert!(!overrides.availability[0].units[7]);
+        assert!(!overrides.availability[1].units[7]);
+        assert!(overrides.availability[0].upgrades[0].is_none());
+    }
+}
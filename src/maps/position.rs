@@ -96,14 +96,19 @@ pub fn apply_position_to_transform(
     // assumed to only change outside of gameplay. If that is not the case this system probably
     // needs to be reworked a bit.
 
-    let half_map_size = Vec2::from(map_size) / 2.0;
     let tile_size = settings.asset_quality.tile_size();
-    let half_tile_adjustment = Vec2::new(tile_size.x / 2.0, tile_size.y / 2.0);
-
     for (pos, mut transform) in positioned.iter_mut() {
-        let mut pos = Vec2::new(pos.x as f32, pos.y as f32) / LOGIC_TILE_SIZE_FLOAT;
-        pos.y = map_size.height as f32 - pos.y;
-        transform.translation =
-            ((pos - half_map_size) * tile_size - half_tile_adjustment).extend(0.0);
+        transform.translation = position_to_translation(pos, map_size, tile_size).extend(0.0);
     }
 }
+
+/// Converts a [Position] on a map of the specified size into a translation in world space, given
+/// the size of the tiles being rendered.
+pub fn position_to_translation(pos: &Position, map_size: &GameMapSize, tile_size: Vec2) -> Vec2 {
+    let half_map_size = Vec2::from(map_size) / 2.0;
+    let half_tile_adjustment = tile_size / 2.0;
+
+    let mut pos = Vec2::new(pos.x as f32, pos.y as f32) / LOGIC_TILE_SIZE_FLOAT;
+    pos.y = map_size.height as f32 - pos.y;
+    (pos - half_map_size) * tile_size - half_tile_adjustment
+}
@@ -0,0 +1,284 @@
+// Decoding for the map's string table (STR and STRx sections). Useful links:
+// http://www.staredit.net/wiki/index.php/Scenario.chk#.22STR.22_-_String_Data
+// http://www.staredit.net/wiki/index.php/String_Colors
+
+use std::fmt;
+
+use bevy::color::Color;
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::maps::chk::ChkSections;
+
+/// The color of a section of text, as specified by control codes in a string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextColor {
+    #[default]
+    Default,
+    Yellow,
+    White,
+    Grey,
+    Red,
+    Green,
+    PlayerRed,
+    PlayerBlue,
+    PlayerTeal,
+    PlayerPurple,
+    PlayerOrange,
+    PlayerBrown,
+    PlayerWhite,
+    PlayerYellow,
+    PlayerGreen,
+    PaleYellow,
+    Tan,
+    Azure,
+    GreyGreen,
+    BlueGrey,
+    Turquoise,
+}
+
+impl TextColor {
+    /// Returns the color for a control code, if it specifies one.
+    fn from_code(code: u8) -> Option<Self> {
+        let color = match code {
+            0x01 | 0x02 => TextColor::Default,
+            0x03 => TextColor::Yellow,
+            0x04 => TextColor::White,
+            0x05 => TextColor::Grey,
+            0x06 => TextColor::Red,
+            0x07 => TextColor::Green,
+            0x08 => TextColor::PlayerRed,
+            0x0E => TextColor::PlayerBlue,
+            0x0F => TextColor::PlayerTeal,
+            0x10 => TextColor::PlayerPurple,
+            0x11 => TextColor::PlayerOrange,
+            0x15 => TextColor::PlayerBrown,
+            0x16 => TextColor::PlayerWhite,
+            0x17 => TextColor::PlayerYellow,
+            0x18 => TextColor::PlayerGreen,
+            0x19 => TextColor::PaleYellow,
+            0x1B => TextColor::Tan,
+            0x1C => TextColor::Azure,
+            0x1D => TextColor::GreyGreen,
+            0x1E => TextColor::BlueGrey,
+            0x1F => TextColor::Turquoise,
+            _ => return None,
+        };
+        Some(color)
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            TextColor::Default => Color::srgb_u8(184, 184, 232),
+            TextColor::Yellow => Color::srgb_u8(220, 220, 60),
+            TextColor::White => Color::srgb_u8(255, 255, 255),
+            TextColor::Grey => Color::srgb_u8(132, 116, 116),
+            TextColor::Red => Color::srgb_u8(200, 24, 24),
+            TextColor::Green => Color::srgb_u8(16, 252, 24),
+            TextColor::PlayerRed => Color::srgb_u8(244, 4, 4),
+            TextColor::PlayerBlue => Color::srgb_u8(12, 72, 204),
+            TextColor::PlayerTeal => Color::srgb_u8(44, 180, 148),
+            TextColor::PlayerPurple => Color::srgb_u8(136, 64, 156),
+            TextColor::PlayerOrange => Color::srgb_u8(248, 140, 20),
+            TextColor::PlayerBrown => Color::srgb_u8(112, 48, 20),
+            TextColor::PlayerWhite => Color::srgb_u8(204, 224, 208),
+            TextColor::PlayerYellow => Color::srgb_u8(252, 252, 56),
+            TextColor::PlayerGreen => Color::srgb_u8(8, 128, 8),
+            TextColor::PaleYellow => Color::srgb_u8(252, 252, 124),
+            TextColor::Tan => Color::srgb_u8(236, 196, 176),
+            TextColor::Azure => Color::srgb_u8(64, 104, 212),
+            TextColor::GreyGreen => Color::srgb_u8(116, 164, 124),
+            TextColor::BlueGrey => Color::srgb_u8(144, 144, 184),
+            TextColor::Turquoise => Color::srgb_u8(0, 228, 252),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// A section of a string that is displayed in a single color.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TextSpan {
+    pub text: String,
+    pub color: TextColor,
+}
+
+/// A string from the map, with its formatting codes decoded.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MapString {
+    pub spans: Vec<TextSpan>,
+    pub align: TextAlign,
+}
+
+impl MapString {
+    /// Decodes a string (without its null terminator), converting BW's control codes into colors
+    /// and alignment. Strings are treated as UTF-8 if they are valid UTF-8, otherwise they are
+    /// treated as Windows-1252 (which is what most older maps use).
+    pub fn decode(bytes: &[u8]) -> Self {
+        let text = match std::str::from_utf8(bytes) {
+            Ok(s) => s.to_string(),
+            Err(_) => bytes.iter().map(|&b| decode_cp1252(b)).collect(),
+        };
+
+        let mut result = MapString::default();
+        let mut current = TextSpan::default();
+        for c in text.chars() {
+            let code = if (c as u32) < 0x20 { c as u8 } else { 0 };
+            if let Some(color) = TextColor::from_code(code) {
+                if color != current.color {
+                    let next = TextSpan {
+                        text: String::new(),
+                        color,
+                    };
+                    result.push_span(std::mem::replace(&mut current, next));
+                }
+                continue;
+            }
+
+            match code {
+                0 => current.text.push(c),
+                0x09 => current.text.push('\t'),
+                0x0A | 0x0C => current.text.push('\n'),
+                // Everything after these is hidden
+                0x0B | 0x14 => break,
+                0x12 => result.align = TextAlign::Right,
+                0x13 => result.align = TextAlign::Center,
+                // Carriage returns and any other codes aren't displayed
+                _ => {}
+            }
+        }
+        result.push_span(current);
+
+        result
+    }
+
+    fn push_span(&mut self, span: TextSpan) {
+        if !span.text.is_empty() {
+            self.spans.push(span);
+        }
+    }
+
+    /// Returns whether this string has no visible text.
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+}
+
+impl fmt::Display for MapString {
+    /// Formats the text of this string without any of its formatting.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for span in &self.spans {
+            f.write_str(&span.text)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decodes a single Windows-1252 byte.
+fn decode_cp1252(b: u8) -> char {
+    const HIGH_CHARS: [char; 32] = [
+        '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž',
+        '\u{8f}', '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9d}',
+        'ž', 'Ÿ',
+    ];
+    match b {
+        0x80..=0x9F => HIGH_CHARS[(b - 0x80) as usize],
+        b => b as char,
+    }
+}
+
+/// Parses the map's string table, preferring the STRx section (which allows for larger string
+/// tables) if it is present. String IDs are 1-based, so the string with ID `n` is at index `n - 1`
+/// in the result.
+pub fn parse_strings(sections: &ChkSections) -> Vec<MapString> {
+    if let Some(data) = sections.get(b"STRx") {
+        parse_string_table(data, 4)
+    } else if let Some(data) = sections.get(b"STR ") {
+        parse_string_table(data, 2)
+    } else {
+        Vec::new()
+    }
+}
+
+/// Parses a string table, where the count and offsets are `int_size` bytes each.
+fn parse_string_table(data: &[u8], int_size: usize) -> Vec<MapString> {
+    let read_int = |offset: usize| -> Option<usize> {
+        let bytes = data.get(offset..offset + int_size)?;
+        Some(LittleEndian::read_uint(bytes, int_size) as usize)
+    };
+
+    // NOTE(tec27): Maps can specify a count larger than what would fit in the section (usually to
+    // break map editors), so we only read as many offsets as could actually be present
+    let max_count = (data.len() / int_size).saturating_sub(1);
+    let count = read_int(0).unwrap_or_default().min(max_count);
+    (0..count)
+        .map(|i| {
+            let Some(offset) = read_int(int_size * (i + 1)) else {
+                return MapString::default();
+            };
+            let bytes = data.get(offset..).unwrap_or_default();
+            let end = memchr::memchr(0, bytes).unwrap_or(bytes.len());
+            MapString::decode(&bytes[..end])
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_colors() {
+        let s = MapString::decode(b"\x13\x04Welcome to \x08Red\x04!\r\nGo\x0bhidden");
+        assert_eq!(s.align, TextAlign::Center);
+        assert_eq!(
+            s.spans,
+            vec![
+                TextSpan {
+                    text: "Welcome to ".into(),
+                    color: TextColor::White
+                },
+                TextSpan {
+                    text: "Red".into(),
+                    color: TextColor::PlayerRed
+                },
+                TextSpan {
+                    text: "!\nGo".into(),
+                    color: TextColor::White
+                },
+            ]
+        );
+        assert_eq!(s.to_string(), "Welcome to Red!\nGo");
+    }
+
+    #[test]
+    fn decode_cp1252_fallback() {
+        let s = MapString::decode(b"Caf\xe9 \x93quoted\x94");
+        assert_eq!(s.to_string(), "Café “quoted”");
+    }
+
+    #[test]
+    fn string_tables() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(&12u32.to_le_bytes());
+        data.extend_from_slice(&16u32.to_le_bytes());
+        data.extend_from_slice(b"abc\0Home\0");
+        let strings = parse_string_table(&data, 4);
+        assert_eq!(strings.len(), 2);
+        assert_eq!(strings[0].to_string(), "abc");
+        assert_eq!(strings[1].to_string(), "Home");
+
+        let strings = parse_string_table(b"\x02\x00\x06\x00\x0a\x00abc\x00Home\x00", 2);
+        assert_eq!(strings[1].to_string(), "Home");
+
+        let strings = parse_string_table(b"\xff\xff\x06\x00\x0a\x00abc\x00Home\x00", 2);
+        assert_eq!(strings.len(), 6);
+        assert_eq!(strings[0].to_string(), "abc");
+    }
+}
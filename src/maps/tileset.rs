@@ -2,6 +2,7 @@ use anyhow::{bail, Context, Result};
 use bevy::asset::LoadContext;
 use bevy::prelude::*;
 use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::TextureFormat;
 use bevy::render::texture::{CompressedImageFormats, ImageSampler, ImageType};
 use bevy::utils::{HashMap, HashSet};
 use bitflags::bitflags;
@@ -65,6 +66,118 @@ impl TilesetFilename {
             self.0
         )
     }
+
+    fn wpe_path(&self) -> String {
+        format!("tileset/{}.wpe", self.0)
+    }
+
+    fn dark_pcx_path(&self) -> String {
+        format!("tileset/{}/dark.pcx", self.0)
+    }
+}
+
+/// All of the tilesets a map can use.
+pub const TILESETS: [Tileset; 8] = [
+    Tileset::Badlands,
+    Tileset::SpacePlatform,
+    Tileset::Installation,
+    Tileset::Ashworld,
+    Tileset::Jungle,
+    Tileset::Desert,
+    Tileset::Arctic,
+    Tileset::Twilight,
+];
+
+/// Returns the paths of all the files needed to load maps with the specified tileset.
+pub fn tileset_file_paths(tileset: Tileset, quality: AssetQuality, pack: AssetPack) -> Vec<String> {
+    let filename = TilesetFilename::from(tileset);
+    let mut paths = vec![
+        format!("casc-extracted/{}", filename.cv5_path()),
+        format!("casc-extracted/{}", filename.vf4_path()),
+        format!("casc-extracted/{}", filename.vr4_path(quality, pack)),
+    ];
+    if quality == AssetQuality::Standard {
+        paths.push(tileset_palette_path(tileset));
+        paths.push(tileset_dark_table_path(tileset));
+    }
+    paths
+}
+
+/// Checks that `data` is a valid tileset file of the type specified by `path`'s extension. This
+/// parses the file the same way that loading a map would.
+pub fn validate_tileset_file(path: &str, data: &[u8]) -> Result<()> {
+    let extension = path.rsplit('.').next().unwrap_or_default();
+    match extension.to_ascii_lowercase().as_str() {
+        "cv5" => {
+            if parse_cv5(data).is_empty() {
+                bail!("CV5 file contains no tile groups");
+            }
+        }
+        "vf4" => {
+            if data.len() < 32 || !data.len().is_multiple_of(32) {
+                bail!("VF4 file has an invalid length");
+            }
+        }
+        "vr4" => validate_vr4(data)?,
+        "wpe" => {
+            crate::gamedata::palette::load_wpe(data)?;
+        }
+        _ => bail!("Unknown tileset file type"),
+    }
+    Ok(())
+}
+
+/// Checks that all the frames in a VR4 file are within its bounds, and that the first frame can be
+/// decoded.
+fn validate_vr4(data: &[u8]) -> Result<()> {
+    if data.len() < 8 {
+        bail!("VR4 file is too short");
+    }
+    let mut data = &data[4..];
+    let frame_count = data.read_u16::<LittleEndian>()?;
+    data = &data[2..];
+    for i in 0..frame_count {
+        if data.len() < 12 {
+            bail!("VR4 frame {i} header is outside of the file");
+        }
+        data = &data[8..];
+        let size = data.read_u32::<LittleEndian>()? as usize;
+        if data.len() < size {
+            bail!("VR4 frame {i} is outside of the file");
+        }
+        if i == 0 {
+            Image::from_buffer(
+                #[cfg(debug_assertions)]
+                "megatile0".into(),
+                &data[..size],
+                ImageType::Extension("dds"),
+                CompressedImageFormats::all(),
+                true,
+                ImageSampler::Default,
+                RenderAssetUsages::default(),
+            )
+            .context("Failed to decode VR4 frame 0")?;
+        }
+        data = &data[size..];
+    }
+    Ok(())
+}
+
+/// Returns the path of the palette used for SD graphics on maps with the specified tileset.
+pub fn tileset_palette_path(tileset: Tileset) -> String {
+    format!(
+        "casc-extracted/{}",
+        TilesetFilename::from(tileset).wpe_path()
+    )
+}
+
+/// Returns the path of the darkening table used for SD graphics on maps with the specified
+/// tileset. Each row of the table maps palette indices to a darker version of the same color.
+pub fn tileset_dark_table_path(tileset: Tileset) -> String {
+    format!(
+        "casc-extracted/{}",
+        TilesetFilename::from(tileset).dark_pcx_path()
+    )
 }
 
 bitflags! {
@@ -316,10 +429,88 @@ pub async fn load_mega_tile_lookup(
     Ok(result)
 }
 
+/// The textures needed to render a map's terrain.
+pub struct TileTextures {
+    /// Handles to textures for each mega-tile.
+    pub textures: Vec<Handle<Image>>,
+    /// A map from mega-tile IDs to indices into `textures`.
+    pub indices: HashMap<u16, usize>,
+    /// The average color of each texture (in sRGB), indexed the same as `textures`. This is used
+    /// for things that draw the terrain at low resolution, like the minimap.
+    pub average_colors: Vec<[u8; 3]>,
+}
+
+/// Converts a 16-bit RGB565 color to 8 bits per channel.
+fn rgb565_to_rgb888(color: u16) -> [u8; 3] {
+    let r = (color >> 11) & 0x1f;
+    let g = (color >> 5) & 0x3f;
+    let b = color & 0x1f;
+    [
+        (r as u32 * 255 / 31) as u8,
+        (g as u32 * 255 / 63) as u8,
+        (b as u32 * 255 / 31) as u8,
+    ]
+}
+
+/// Returns the average color of some BC1-compressed image data. This only looks at the endpoint
+/// colors of each block, which is close enough for our purposes and avoids decompressing the whole
+/// image.
+fn bc1_average_color(data: &[u8]) -> [u8; 3] {
+    let mut sum = [0u32; 3];
+    let mut count = 0;
+    for block in data.chunks_exact(8) {
+        for endpoint in [&block[0..2], &block[2..4]] {
+            let color = rgb565_to_rgb888(u16::from_le_bytes([endpoint[0], endpoint[1]]));
+            for (s, c) in sum.iter_mut().zip(color) {
+                *s += c as u32;
+            }
+            count += 1;
+        }
+    }
+
+    if count == 0 {
+        [0; 3]
+    } else {
+        sum.map(|s| (s / count) as u8)
+    }
+}
+
+/// Returns the average color of the first mip level of `image`, if it's in a format we know how to
+/// read.
+fn average_color(image: &Image) -> Option<[u8; 3]> {
+    let size = image.texture_descriptor.size;
+    match image.texture_descriptor.format {
+        TextureFormat::Bc1RgbaUnorm | TextureFormat::Bc1RgbaUnormSrgb => {
+            // NOTE(tec27): The image data contains every mip level, we only want the first one
+            let first_mip_len = (size.width.div_ceil(4) * size.height.div_ceil(4) * 8) as usize;
+            Some(bc1_average_color(
+                &image.data[..first_mip_len.min(image.data.len())],
+            ))
+        }
+        format @ (TextureFormat::Rgba8Unorm
+        | TextureFormat::Rgba8UnormSrgb
+        | TextureFormat::Bgra8Unorm
+        | TextureFormat::Bgra8UnormSrgb) => {
+            let first_mip_len = (size.width * size.height * 4) as usize;
+            let pixels = image.data[..first_mip_len.min(image.data.len())].chunks_exact(4);
+            let count = pixels.len().max(1) as u32;
+            let mut sum = [0u32; 3];
+            for pixel in pixels {
+                for (s, &c) in sum.iter_mut().zip(pixel) {
+                    *s += c as u32;
+                }
+            }
+            let [a, g, b] = sum.map(|s| (s / count) as u8);
+            Some(match format {
+                TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => [b, g, a],
+                _ => [a, g, b],
+            })
+        }
+        _ => None,
+    }
+}
+
 /// Loads the needed tile textures from the VR4 file for the given tileset/mega-tiles.
-///
-/// Returns a tuple of a vector of texture handles and a map from mega-tile IDs to indices into the
-/// texture vector.
 pub async fn load_tile_textures(
     tileset: Tileset,
     mega_tile_lookup: &HashMap<u16, MegaTileInfo>,
@@ -327,7 +518,7 @@ pub async fn load_tile_textures(
     asset_pack: AssetPack,
     load_context: &mut LoadContext<'_>,
     supported_compressed_formats: CompressedImageFormats,
-) -> Result<(Vec<Handle<Image>>, HashMap<u16, usize>)> {
+) -> Result<TileTextures> {
     let filename: TilesetFilename = tileset.into();
     let path = format!(
         "casc-extracted/{}",
@@ -352,6 +543,8 @@ pub async fn load_tile_textures(
     info!("frame count: {}", frame_count);
     let mut textures = Vec::with_capacity(mega_tile_ids.len());
     let mut texture_indices = HashMap::new();
+    let mut average_colors = Vec::with_capacity(mega_tile_ids.len());
+    let mut warned_unknown_format = false;
 
     data = &data[2..];
     for i in 0..frame_count {
@@ -369,6 +562,17 @@ pub async fn load_tile_textures(
                 ImageSampler::Default,
                 RenderAssetUsages::default(),
             )?;
+            let color = average_color(&image).unwrap_or_else(|| {
+                if !warned_unknown_format {
+                    warn!(
+                        "Can't determine tile colors for {:?} textures",
+                        image.texture_descriptor.format
+                    );
+                    warned_unknown_format = true;
+                }
+                [0; 3]
+            });
+            average_colors.push(color);
             let handle = load_context.add_labeled_asset(format!("texture{}", i), image);
 
             texture_indices.insert(i, textures.len());
@@ -383,5 +587,61 @@ pub async fn load_tile_textures(
         data = &data[size as usize..];
     }
 
-    Ok((textures, texture_indices))
+    Ok(TileTextures {
+        textures,
+        indices: texture_indices,
+        average_colors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bc1_colors() {
+        assert_eq!(rgb565_to_rgb888(0xffff), [255, 255, 255]);
+        assert_eq!(rgb565_to_rgb888(0xf800), [255, 0, 0]);
+        assert_eq!(rgb565_to_rgb888(0x07e0), [0, 255, 0]);
+
+        // Two blocks: one red/black, one blue/black
+        let data = [
+            0x00, 0xf8, 0x00, 0x00, 0, 0, 0, 0, //
+            0x1f, 0x00, 0x00, 0x00, 0, 0, 0, 0,
+        ];
+        assert_eq!(bc1_average_color(&data), [63, 0, 63]);
+        assert_eq!(bc1_average_color(&[]), [0, 0, 0]);
+    }
+
+    #[test]
+    fn average_colors_by_format() {
+        let image = |format, data: Vec<u8>| {
+            let mut image = Image::default();
+            image.texture_descriptor.size.width = 4;
+            image.texture_descriptor.size.height = 4;
+            image.texture_descriptor.format = format;
+            image.data = data;
+            image
+        };
+
+        let bc1 = vec![0x00, 0xf8, 0x00, 0x00, 0, 0, 0, 0];
+        assert_eq!(
+            average_color(&image(TextureFormat::Bc1RgbaUnormSrgb, bc1.clone())),
+            Some([127, 0, 0])
+        );
+        let rgba = [[255, 0, 0, 255], [0, 0, 255, 255]].repeat(8).concat();
+        assert_eq!(
+            average_color(&image(TextureFormat::Rgba8UnormSrgb, rgba.clone())),
+            Some([127, 0, 127])
+        );
+        assert_eq!(
+            average_color(&image(
+                TextureFormat::Bgra8Unorm,
+                [[255, 0, 0, 255]; 16].concat()
+            )),
+            Some([0, 0, 255])
+        );
+        // Formats we don't know how to read aren't treated as BC1 data
+        assert_eq!(average_color(&image(TextureFormat::R8Unorm, bc1)), None);
+    }
 }
@@ -0,0 +1,486 @@
+// Parsing for the trigger data in the TRIG and MBRF sections. Useful links:
+// http://www.staredit.net/wiki/index.php/Scenario.chk#.22TRIG.22_-_Triggers
+// http://www.staredit.net/wiki/index.php/Scenario.chk#.22MBRF.22_-_Mission_Briefings
+
+use bitflags::bitflags;
+use byteorder::{ByteOrder, LittleEndian};
+use num_enum::{FromPrimitive, IntoPrimitive};
+
+use crate::gamedata::ConstructTypeId;
+
+/// The maximum number of conditions a trigger can have.
+pub const MAX_CONDITIONS: usize = 16;
+/// The maximum number of actions a trigger can have.
+pub const MAX_ACTIONS: usize = 64;
+/// The number of entries in a trigger's list of players that execute it (players 1-12, then the
+/// various player groups and forces).
+pub const NUM_EXECUTION_GROUPS: usize = 27;
+
+const CONDITION_SIZE: usize = 20;
+const ACTION_SIZE: usize = 32;
+const TRIGGER_SIZE: usize = CONDITION_SIZE * MAX_CONDITIONS + ACTION_SIZE * MAX_ACTIONS + 32;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, FromPrimitive, IntoPrimitive)]
+#[repr(u8)]
+pub enum ConditionType {
+    None = 0,
+    CountdownTimer = 1,
+    Command = 2,
+    Bring = 3,
+    Accumulate = 4,
+    Kill = 5,
+    CommandTheMost = 6,
+    CommandTheMostAt = 7,
+    MostKills = 8,
+    HighestScore = 9,
+    MostResources = 10,
+    Switch = 11,
+    ElapsedTime = 12,
+    MissionBriefing = 13,
+    Opponents = 14,
+    Deaths = 15,
+    CommandTheLeast = 16,
+    CommandTheLeastAt = 17,
+    LeastKills = 18,
+    LowestScore = 19,
+    LeastResources = 20,
+    Score = 21,
+    Always = 22,
+    Never = 23,
+    #[num_enum(catch_all)]
+    Unknown(u8),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, FromPrimitive, IntoPrimitive)]
+#[repr(u8)]
+pub enum ActionType {
+    None = 0,
+    Victory = 1,
+    Defeat = 2,
+    PreserveTrigger = 3,
+    Wait = 4,
+    PauseGame = 5,
+    UnpauseGame = 6,
+    Transmission = 7,
+    PlayWav = 8,
+    DisplayTextMessage = 9,
+    CenterView = 10,
+    CreateUnitWithProperties = 11,
+    SetMissionObjectives = 12,
+    SetSwitch = 13,
+    SetCountdownTimer = 14,
+    RunAiScript = 15,
+    RunAiScriptAtLocation = 16,
+    LeaderboardControl = 17,
+    LeaderboardControlAtLocation = 18,
+    LeaderboardResources = 19,
+    LeaderboardKills = 20,
+    LeaderboardPoints = 21,
+    KillUnit = 22,
+    KillUnitAtLocation = 23,
+    RemoveUnit = 24,
+    RemoveUnitAtLocation = 25,
+    SetResources = 26,
+    SetScore = 27,
+    MinimapPing = 28,
+    TalkingPortrait = 29,
+    MuteUnitSpeech = 30,
+    UnmuteUnitSpeech = 31,
+    LeaderboardComputerPlayers = 32,
+    LeaderboardGoalControl = 33,
+    LeaderboardGoalControlAtLocation = 34,
+    LeaderboardGoalResources = 35,
+    LeaderboardGoalKills = 36,
+    LeaderboardGoalPoints = 37,
+    MoveLocation = 38,
+    MoveUnit = 39,
+    LeaderboardGreed = 40,
+    SetNextScenario = 41,
+    SetDoodadState = 42,
+    SetInvincibility = 43,
+    CreateUnit = 44,
+    SetDeaths = 45,
+    Order = 46,
+    Comment = 47,
+    GiveUnitsToPlayer = 48,
+    ModifyUnitHitPoints = 49,
+    ModifyUnitEnergy = 50,
+    ModifyUnitShieldPoints = 51,
+    ModifyUnitResourceAmount = 52,
+    ModifyUnitHangarCount = 53,
+    PauseTimer = 54,
+    UnpauseTimer = 55,
+    Draw = 56,
+    SetAllianceStatus = 57,
+    #[num_enum(catch_all)]
+    Unknown(u8),
+}
+
+/// How a condition compares its value against the amount it specifies.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, FromPrimitive, IntoPrimitive)]
+#[repr(u8)]
+pub enum Comparison {
+    AtLeast = 0,
+    AtMost = 1,
+    Set = 2,
+    Cleared = 3,
+    Exactly = 10,
+    #[num_enum(catch_all)]
+    Unknown(u8),
+}
+
+impl Comparison {
+    /// Compares `value` against `amount`. Comparisons that don't apply to numbers never match.
+    pub fn compare(&self, value: u32, amount: u32) -> bool {
+        match self {
+            Comparison::AtLeast => value >= amount,
+            Comparison::AtMost => value <= amount,
+            Comparison::Exactly => value == amount,
+            _ => false,
+        }
+    }
+}
+
+/// How an action modifies the value it targets.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, FromPrimitive, IntoPrimitive)]
+#[repr(u8)]
+pub enum Modifier {
+    Set = 4,
+    Clear = 5,
+    Toggle = 6,
+    SetTo = 7,
+    Add = 8,
+    Subtract = 9,
+    Randomize = 11,
+    #[num_enum(catch_all)]
+    Unknown(u8),
+}
+
+impl Modifier {
+    /// Applies this modifier to a numeric `value`, returning the new value. Values saturate rather
+    /// than wrapping.
+    pub fn apply(&self, value: u32, amount: u32) -> u32 {
+        match self {
+            Modifier::SetTo => amount,
+            Modifier::Add => value.saturating_add(amount),
+            Modifier::Subtract => value.saturating_sub(amount),
+            _ => value,
+        }
+    }
+}
+
+/// A player or group of players referenced by a trigger.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PlayerGroup {
+    Player(u8),
+    NoPlayer,
+    CurrentPlayer,
+    Foes,
+    Allies,
+    NeutralPlayers,
+    AllPlayers,
+    Force(u8),
+    NonAlliedVictoryPlayers,
+    Unknown(u32),
+}
+
+impl From<u32> for PlayerGroup {
+    fn from(value: u32) -> Self {
+        match value {
+            0..=11 => PlayerGroup::Player(value as u8),
+            12 => PlayerGroup::NoPlayer,
+            13 => PlayerGroup::CurrentPlayer,
+            14 => PlayerGroup::Foes,
+            15 => PlayerGroup::Allies,
+            16 => PlayerGroup::NeutralPlayers,
+            17 => PlayerGroup::AllPlayers,
+            18..=21 => PlayerGroup::Force((value - 18) as u8),
+            26 => PlayerGroup::NonAlliedVictoryPlayers,
+            v => PlayerGroup::Unknown(v),
+        }
+    }
+}
+
+/// The unit types that a trigger condition or action applies to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UnitFilter {
+    Construct(ConstructTypeId),
+    AnyUnit,
+    Men,
+    Buildings,
+    Factories,
+}
+
+impl From<u16> for UnitFilter {
+    fn from(value: u16) -> Self {
+        match value {
+            229 => UnitFilter::AnyUnit,
+            230 => UnitFilter::Men,
+            231 => UnitFilter::Buildings,
+            232 => UnitFilter::Factories,
+            v => UnitFilter::Construct(v.into()),
+        }
+    }
+}
+
+bitflags! {
+    /// The "group" flags that StarEdit assigns to each construct type, used to decide which
+    /// constructs match the special unit types in triggers.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct StarEditGroups: u8 {
+        const ZERG = 0x01;
+        const TERRAN = 0x02;
+        const PROTOSS = 0x04;
+        const MEN = 0x08;
+        const BUILDING = 0x10;
+        const FACTORY = 0x20;
+        const INDEPENDENT = 0x40;
+        const NEUTRAL = 0x80;
+    }
+}
+
+impl UnitFilter {
+    /// Returns whether the specified construct type matches this filter.
+    pub fn matches(&self, construct_type: ConstructTypeId) -> bool {
+        if let UnitFilter::Construct(c) = self {
+            return *c == construct_type;
+        }
+        if let ConstructTypeId::Unknown(_) = construct_type {
+            return false;
+        }
+
+        let groups = StarEditGroups::from_bits_truncate(construct_type.def().star_edit_group_flags);
+        match self {
+            UnitFilter::AnyUnit => true,
+            UnitFilter::Men => groups.contains(StarEditGroups::MEN),
+            UnitFilter::Buildings => groups.contains(StarEditGroups::BUILDING),
+            UnitFilter::Factories => groups.contains(StarEditGroups::FACTORY),
+            UnitFilter::Construct(_) => unreachable!(),
+        }
+    }
+}
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct ConditionFlags: u8 {
+        const DISABLED = 0x02;
+        const ALWAYS_DISPLAY = 0x04;
+        const UNIT_PROPERTIES_USED = 0x08;
+        const UNIT_TYPE_USED = 0x10;
+    }
+}
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct ActionFlags: u8 {
+        const IGNORE_WAIT_ONCE = 0x01;
+        const DISABLED = 0x02;
+        const ALWAYS_DISPLAY_TEXT = 0x04;
+        const UNIT_PROPERTIES_USED = 0x08;
+        const UNIT_TYPE_USED = 0x10;
+    }
+}
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct TriggerFlags: u32 {
+        const IGNORE_CONDITIONS_ONCE = 0x01;
+        const IGNORE_DEFEAT_DRAW = 0x02;
+        const PRESERVE = 0x04;
+        const DISABLED = 0x08;
+        const IGNORE_MISC_ACTIONS_ONCE = 0x10;
+        const PAUSED = 0x20;
+        const IGNORE_WAIT_SKIPPING_ONCE = 0x40;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Condition {
+    /// The 1-based index of the location this condition checks, or 0 if it doesn't use one.
+    pub location: u32,
+    pub group: PlayerGroup,
+    pub amount: u32,
+    pub unit: UnitFilter,
+    pub comparison: Comparison,
+    pub kind: ConditionType,
+    /// The resource type, score type, or switch index, depending on the condition type.
+    pub resource: u8,
+    pub flags: ConditionFlags,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Action {
+    /// The 1-based index of the location this action uses, or 0 if it doesn't use one.
+    pub location: u32,
+    /// The ID of the string this action displays, or 0 if it doesn't use one.
+    pub string: u32,
+    /// The ID of the string containing the path of the WAV file this action plays.
+    pub wav_string: u32,
+    /// A duration in milliseconds (or seconds for the countdown timer).
+    pub time: u32,
+    pub group: PlayerGroup,
+    /// A second player group, an amount, a switch index, or a destination location, depending on
+    /// the action type.
+    pub number: u32,
+    /// A unit type, a resource/score type, or an alliance status, depending on the action type.
+    pub unit_type: u16,
+    pub kind: ActionType,
+    /// The number of units affected (0 means all of them), or a [Modifier], depending on the action
+    /// type.
+    pub modifier: u8,
+    pub flags: ActionFlags,
+}
+
+impl Action {
+    pub fn unit(&self) -> UnitFilter {
+        self.unit_type.into()
+    }
+
+    pub fn modifier(&self) -> Modifier {
+        self.modifier.into()
+    }
+
+    /// Returns the number of units this action should affect, or `None` if it affects all of them.
+    pub fn unit_count(&self) -> Option<usize> {
+        match self.modifier {
+            0 => None,
+            n => Some(n as usize),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trigger {
+    pub conditions: Vec<Condition>,
+    pub actions: Vec<Action>,
+    pub flags: TriggerFlags,
+    /// Which players and player groups execute this trigger, indexed by their group ID.
+    pub execution_groups: [bool; NUM_EXECUTION_GROUPS],
+}
+
+/// Parses a list of triggers from the contents of a TRIG or MBRF section. Any trailing partial
+/// trigger is ignored.
+pub fn parse_triggers(data: &[u8]) -> Vec<Trigger> {
+    data.chunks_exact(TRIGGER_SIZE).map(parse_trigger).collect()
+}
+
+fn parse_trigger(data: &[u8]) -> Trigger {
+    let (condition_data, rest) = data.split_at(CONDITION_SIZE * MAX_CONDITIONS);
+    let (action_data, rest) = rest.split_at(ACTION_SIZE * MAX_ACTIONS);
+
+    // BW stops processing conditions and actions at the first empty one
+    let conditions = condition_data
+        .chunks_exact(CONDITION_SIZE)
+        .map(|c| Condition {
+            location: LittleEndian::read_u32(&c[0..]),
+            group: LittleEndian::read_u32(&c[4..]).into(),
+            amount: LittleEndian::read_u32(&c[8..]),
+            unit: LittleEndian::read_u16(&c[12..]).into(),
+            comparison: c[14].into(),
+            kind: c[15].into(),
+            resource: c[16],
+            flags: ConditionFlags::from_bits_retain(c[17]),
+        })
+        .take_while(|c| c.kind != ConditionType::None)
+        .collect();
+    let actions = action_data
+        .chunks_exact(ACTION_SIZE)
+        .map(|a| Action {
+            location: LittleEndian::read_u32(&a[0..]),
+            string: LittleEndian::read_u32(&a[4..]),
+            wav_string: LittleEndian::read_u32(&a[8..]),
+            time: LittleEndian::read_u32(&a[12..]),
+            group: LittleEndian::read_u32(&a[16..]).into(),
+            number: LittleEndian::read_u32(&a[20..]),
+            unit_type: LittleEndian::read_u16(&a[24..]),
+            kind: a[26].into(),
+            modifier: a[27],
+            flags: ActionFlags::from_bits_retain(a[28]),
+        })
+        .take_while(|a| a.kind != ActionType::None)
+        .collect();
+
+    Trigger {
+        conditions,
+        actions,
+        flags: TriggerFlags::from_bits_retain(LittleEndian::read_u32(rest)),
+        execution_groups: std::array::from_fn(|i| rest[4 + i] != 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let mut data = vec![0; TRIGGER_SIZE * 2 + 100];
+        // Condition: Player 1 brings at least 5 Marines to location 3
+        data[0..4].copy_from_slice(&3u32.to_le_bytes());
+        data[8..12].copy_from_slice(&5u32.to_le_bytes());
+        data[14] = 0;
+        data[15] = 3;
+        // Condition: Always (this shouldn't be parsed because it follows an empty condition)
+        data[CONDITION_SIZE * 2 + 15] = 22;
+
+        // Action: Set switch 7 for Current Player
+        let action = CONDITION_SIZE * MAX_CONDITIONS;
+        data[action + 16..action + 20].copy_from_slice(&13u32.to_le_bytes());
+        data[action + 20..action + 24].copy_from_slice(&7u32.to_le_bytes());
+        data[action + 26] = 13;
+        data[action + 27] = 4;
+        // Action: Preserve trigger
+        data[action + ACTION_SIZE + 26] = 3;
+
+        let flags = action + ACTION_SIZE * MAX_ACTIONS;
+        data[flags] = 0x04;
+        // Executed by Player 2 and Force 1
+        data[flags + 4 + 1] = 1;
+        data[flags + 4 + 18] = 1;
+
+        let triggers = parse_triggers(&data);
+        assert_eq!(triggers.len(), 2);
+
+        let trigger = &triggers[0];
+        assert_eq!(
+            trigger.conditions,
+            vec![Condition {
+                location: 3,
+                group: PlayerGroup::Player(0),
+                amount: 5,
+                unit: UnitFilter::Construct(ConstructTypeId::TerranMarine),
+                comparison: Comparison::AtLeast,
+                kind: ConditionType::Bring,
+                resource: 0,
+                flags: ConditionFlags::empty(),
+            }]
+        );
+        assert_eq!(trigger.actions.len(), 2);
+        assert_eq!(trigger.actions[0].kind, ActionType::SetSwitch);
+        assert_eq!(trigger.actions[0].group, PlayerGroup::CurrentPlayer);
+        assert_eq!(trigger.actions[0].number, 7);
+        assert_eq!(trigger.actions[0].modifier(), Modifier::Set);
+        assert_eq!(trigger.actions[1].kind, ActionType::PreserveTrigger);
+        assert_eq!(trigger.flags, TriggerFlags::PRESERVE);
+        let groups = trigger
+            .execution_groups
+            .iter()
+            .enumerate()
+            .filter(|(_, &e)| e)
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        assert_eq!(groups, vec![1, 18]);
+
+        assert!(triggers[1].conditions.is_empty());
+        assert!(triggers[1].actions.is_empty());
+    }
+
+    #[test]
+    fn modifiers() {
+        assert_eq!(Modifier::SetTo.apply(5, 3), 3);
+        assert_eq!(Modifier::Add.apply(u32::MAX, 3), u32::MAX);
+        assert_eq!(Modifier::Subtract.apply(2, 3), 0);
+        assert!(Comparison::Exactly.compare(3, 3));
+        assert!(!Comparison::AtMost.compare(4, 3));
+    }
+}
@@ -0,0 +1,367 @@
+//! Background music. The menu theme plays outside of games, and in games a shuffled playlist of the
+//! local player's race's tracks plays, followed by victory/defeat music when their game ends.
+//! Switching between tracks crossfades between them.
+
+use std::time::Duration;
+
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+};
+
+use crate::{
+    gameplay::{
+        players::{ControlledPlayer, Player, PlayerNumber},
+        sounds::{SoundCategory, SoundVolume},
+        triggers::{GameResult, GameResultEvent},
+    },
+    races::Race,
+    random::UnsyncedLcgRand,
+    settings::GameSettings,
+    states::AppState,
+};
+
+/// How long it takes for one track to fade into the next.
+const CROSSFADE_DURATION: Duration = Duration::from_secs(3);
+
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Music>()
+            .register_type::<MusicTrack>()
+            .add_systems(OnEnter(AppState::InGame), reset_game_result)
+            .add_systems(
+                Update,
+                (
+                    record_game_result.run_if(in_state(AppState::InGame)),
+                    select_music,
+                    track_durations,
+                    advance_playlist,
+                    update_fades,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Which set of tracks should be playing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MusicSelection {
+    Menu,
+    Race(Race),
+    Victory(Race),
+    Defeat(Race),
+}
+
+impl MusicSelection {
+    /// The paths of the tracks for this selection, relative to the `music` folder.
+    pub fn tracks(&self) -> &'static [&'static str] {
+        match self {
+            Self::Menu => &["title.ogg"],
+            Self::Race(Race::Protoss) => &["protoss1.ogg", "protoss2.ogg", "protoss3.ogg"],
+            Self::Race(Race::Terran) => &["terran1.ogg", "terran2.ogg", "terran3.ogg"],
+            Self::Race(Race::Zerg) => &["zerg1.ogg", "zerg2.ogg", "zerg3.ogg"],
+            Self::Victory(Race::Protoss) => &["pvict.ogg"],
+            Self::Victory(Race::Terran) => &["tvict.ogg"],
+            Self::Victory(Race::Zerg) => &["zvict.ogg"],
+            Self::Defeat(Race::Protoss) => &["pdefeat.ogg"],
+            Self::Defeat(Race::Terran) => &["tdefeat.ogg"],
+            Self::Defeat(Race::Zerg) => &["zdefeat.ogg"],
+        }
+    }
+
+    /// Whether the tracks should repeat once they've all been played.
+    pub fn repeats(&self) -> bool {
+        matches!(self, Self::Menu | Self::Race(_))
+    }
+}
+
+/// The current state of the music playlist.
+#[derive(Resource, Debug, Default)]
+pub struct Music {
+    pub selection: Option<MusicSelection>,
+    playlist: Vec<&'static str>,
+    next_track: usize,
+    current: Option<Entity>,
+    /// How the local player's current game ended, if it has.
+    game_result: Option<GameResult>,
+}
+
+/// Component on a playing music track.
+#[derive(Component, Debug, Reflect)]
+pub struct MusicTrack {
+    /// The current volume of the track from fading (0.0 - 1.0).
+    fade: f32,
+    fading_out: bool,
+    /// When (in real time) the track will be close enough to its end that the next track should
+    /// start fading in. This is `None` until the track has loaded (or if its length is unknown).
+    crossfade_at: Option<Duration>,
+    duration_checked: bool,
+    started_at: Duration,
+}
+
+fn reset_game_result(mut music: ResMut<Music>) {
+    music.game_result = None;
+}
+
+fn record_game_result(
+    mut events: EventReader<GameResultEvent>,
+    local_player: Query<&PlayerNumber, With<ControlledPlayer>>,
+    mut music: ResMut<Music>,
+) {
+    let local_player = local_player.get_single().ok().map(|p| p.0);
+    for event in events.read() {
+        if Some(event.player) == local_player {
+            music.game_result = Some(event.result);
+        }
+    }
+}
+
+fn select_music(
+    mut commands: Commands,
+    state: Res<State<AppState>>,
+    local_player: Query<&Player, With<ControlledPlayer>>,
+    mut music: ResMut<Music>,
+    mut tracks: Query<&mut MusicTrack>,
+    mut rng: ResMut<UnsyncedLcgRand>,
+    asset_server: Res<AssetServer>,
+    time: Res<Time<Real>>,
+) {
+    let selection = match state.get() {
+        AppState::Menu | AppState::Lobby | AppState::LoadError => MusicSelection::Menu,
+        // Keep playing whatever was playing while the game loads
+        AppState::PreGame => return,
+        AppState::InGame => {
+            // NOTE(tec27): There may not be a controlled player (e.g. when viewing a map), in
+            // which case we just pick the default race's music
+            let race = local_player
+                .get_single()
+                .map(|p| p.race)
+                .unwrap_or_default();
+            match music.game_result {
+                Some(GameResult::Victory) => MusicSelection::Victory(race),
+                Some(GameResult::Defeat) => MusicSelection::Defeat(race),
+                Some(GameResult::Draw) | None => MusicSelection::Race(race),
+            }
+        }
+    };
+    if music.selection == Some(selection) {
+        return;
+    }
+
+    info!("Switching music to {selection:?}");
+    music.selection = Some(selection);
+    music.playlist = shuffled(selection.tracks(), &mut rng);
+    music.next_track = 0;
+    start_next_track(
+        &mut commands,
+        &mut music,
+        &mut tracks,
+        &asset_server,
+        time.elapsed(),
+    );
+}
+
+/// Fades out the current track (if there is one) and starts fading in the next one in the
+/// playlist.
+fn start_next_track(
+    commands: &mut Commands,
+    music: &mut Music,
+    tracks: &mut Query<&mut MusicTrack>,
+    asset_server: &AssetServer,
+    now: Duration,
+) {
+    if let Some(mut current) = music.current.and_then(|e| tracks.get_mut(e).ok()) {
+        current.fading_out = true;
+    }
+    music.current = None;
+
+    let Some(selection) = music.selection else {
+        return;
+    };
+    if music.next_track >= music.playlist.len() {
+        if !selection.repeats() || music.playlist.is_empty() {
+            return;
+        }
+        music.next_track = 0;
+    }
+    let track = music.playlist[music.next_track];
+    music.next_track += 1;
+
+    let entity = commands
+        .spawn((
+            AudioBundle {
+                source: asset_server.load(format!("casc-extracted/music/{track}")),
+                settings: PlaybackSettings {
+                    mode: PlaybackMode::Despawn,
+                    volume: Volume::new(0.0),
+                    ..default()
+                },
+            },
+            MusicTrack {
+                fade: 0.0,
+                fading_out: false,
+                crossfade_at: None,
+                duration_checked: false,
+                started_at: now,
+            },
+            SoundCategory::Music,
+            SoundVolume(0.0),
+            Name::new(format!("Music - {track}")),
+        ))
+        .id();
+    music.current = Some(entity);
+}
+
+/// Returns the tracks in a random order.
+fn shuffled(tracks: &[&'static str], rng: &mut UnsyncedLcgRand) -> Vec<&'static str> {
+    let mut result = tracks.to_vec();
+    for i in (1..result.len()).rev() {
+        let j = rng.in_range_usize(0, i);
+        result.swap(i, j);
+    }
+    result
+}
+
+/// Determines when tracks should start crossfading into the next one, once they've loaded.
+fn track_durations(
+    mut tracks: Query<(&mut MusicTrack, &Handle<AudioSource>)>,
+    audio_sources: Res<Assets<AudioSource>>,
+) {
+    for (mut track, handle) in &mut tracks {
+        if track.duration_checked {
+            continue;
+        }
+        let Some(source) = audio_sources.get(handle) else {
+            continue;
+        };
+        track.duration_checked = true;
+        track.crossfade_at = ogg_duration(&source.bytes)
+            .and_then(|d| d.checked_sub(CROSSFADE_DURATION))
+            .map(|d| track.started_at + d);
+    }
+}
+
+/// Starts the next track when the current one is about to end (or has ended).
+fn advance_playlist(
+    mut commands: Commands,
+    mut music: ResMut<Music>,
+    mut tracks: Query<&mut MusicTrack>,
+    asset_server: Res<AssetServer>,
+    time: Res<Time<Real>>,
+) {
+    let Some(current) = music.current else {
+        return;
+    };
+    let should_advance = match tracks.get(current) {
+        Ok(track) => track.crossfade_at.is_some_and(|at| at <= time.elapsed()),
+        // The track finished (or failed to load)
+        Err(_) => true,
+    };
+    if should_advance {
+        start_next_track(
+            &mut commands,
+            &mut music,
+            &mut tracks,
+            &asset_server,
+            time.elapsed(),
+        );
+    }
+}
+
+fn update_fades(
+    mut commands: Commands,
+    mut tracks: Query<(
+        Entity,
+        &mut MusicTrack,
+        &mut SoundVolume,
+        Option<&AudioSink>,
+    )>,
+    settings: Res<GameSettings>,
+    time: Res<Time<Real>>,
+) {
+    let step = time.delta_seconds() / CROSSFADE_DURATION.as_secs_f32();
+    for (entity, mut track, mut volume, sink) in &mut tracks {
+        track.fade = if track.fading_out {
+            (track.fade - step).max(0.0)
+        } else {
+            (track.fade + step).min(1.0)
+        };
+        if track.fading_out && track.fade <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        volume.0 = track.fade;
+        if let Some(sink) = sink {
+            sink.set_volume(volume.0 * SoundCategory::Music.sink_volume(&settings.volumes));
+        }
+    }
+}
+
+/// Returns the length of an Ogg Vorbis file, based on the sample rate in its identification header
+/// and the granule position (the number of samples) of its last page.
+// NOTE(tec27): The decoder we use for Ogg files doesn't know how long they are, so we figure it out
+// ourselves to be able to crossfade before a track ends
+fn ogg_duration(bytes: &[u8]) -> Option<Duration> {
+    const PAGE_MAGIC: &[u8] = b"OggS";
+    const VORBIS_ID_HEADER: &[u8] = b"\x01vorbis";
+
+    let id_header = memchr::memmem::find(bytes, VORBIS_ID_HEADER)?;
+    // The header is followed by the version (u32) and channel count (u8)
+    let rate_offset = id_header + VORBIS_ID_HEADER.len() + 5;
+    let sample_rate = u32::from_le_bytes(bytes.get(rate_offset..rate_offset + 4)?.try_into().ok()?);
+
+    let last_page = memchr::memmem::rfind(bytes, PAGE_MAGIC)?;
+    // The page header has the magic, a version byte, a flags byte, and then the granule position
+    let granule_offset = last_page + 6;
+    let granule = i64::from_le_bytes(
+        bytes
+            .get(granule_offset..granule_offset + 8)?
+            .try_into()
+            .ok()?,
+    );
+
+    if sample_rate == 0 || granule <= 0 {
+        return None;
+    }
+    Some(Duration::from_secs_f64(granule as f64 / sample_rate as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ogg_page(granule: i64, contents: &[u8]) -> Vec<u8> {
+        let mut page = b"OggS\x00\x02".to_vec();
+        page.extend(granule.to_le_bytes());
+        page.extend([0; 13]);
+        page.extend(contents);
+        page
+    }
+
+    #[test]
+    fn ogg_durations() {
+        let mut id_header = b"\x01vorbis".to_vec();
+        id_header.extend(0u32.to_le_bytes());
+        id_header.push(2);
+        id_header.extend(22050u32.to_le_bytes());
+
+        let mut bytes = ogg_page(0, &id_header);
+        bytes.extend(ogg_page(22050 * 90, &[0; 32]));
+        assert_eq!(ogg_duration(&bytes), Some(Duration::from_secs(90)));
+
+        assert_eq!(ogg_duration(b"RIFF"), None);
+    }
+
+    #[test]
+    fn shuffling() {
+        let mut rng = UnsyncedLcgRand::default();
+        let tracks = MusicSelection::Race(Race::Zerg).tracks();
+        let mut result = shuffled(tracks, &mut rng);
+        result.sort();
+        assert_eq!(result, tracks);
+        assert_eq!(shuffled(&[], &mut rng), Vec::<&str>::new());
+    }
+}
@@ -0,0 +1,387 @@
+use std::io::{Read, Write};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+use num_enum::{IntoPrimitive, TryFromPrimitive};
+
+use crate::{
+    gamedata::ConstructTypeId,
+    gameplay::{
+        constructs::ConstructId,
+        player_selection::{ControlGroupAction, MAX_SELECTION, NUM_CONTROL_GROUPS},
+    },
+    maps::position::Position,
+};
+
+use super::packet::PacketError;
+
+/// A command issued by a player that affects the game simulation. These are exchanged between all
+/// players in a game and executed on the same game frame by each of them, so they must contain
+/// only data that is identical across all clients (e.g. no [Entity](bevy::prelude::Entity) IDs).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameCommand {
+    /// The player has left the game.
+    Leave,
+    /// The player has replaced their selection with the specified constructs.
+    Select(Vec<ConstructId>),
+    /// The player has added the specified constructs to their selection.
+    SelectAdd(Vec<ConstructId>),
+    /// The player has removed the specified constructs from their selection.
+    SelectRemove(Vec<ConstructId>),
+    /// The player has changed or recalled one of their control groups.
+    ControlGroup {
+        action: ControlGroupAction,
+        group: u8,
+    },
+    /// The player has right-clicked a position (and possibly a construct at that position) with
+    /// their selected constructs, giving them whatever order is appropriate for the target.
+    RightClick {
+        position: Position,
+        target: Option<ConstructId>,
+        /// Whether the order should be queued after the constructs' current orders.
+        queued: bool,
+    },
+    /// The player has given their selected constructs an order that needs a target, picked after
+    /// choosing the order on the command card.
+    TargetedOrder {
+        order: TargetOrder,
+        position: Position,
+        target: Option<ConstructId>,
+        queued: bool,
+    },
+    /// The player has told their selected constructs to stop what they're doing.
+    Stop { queued: bool },
+    /// The player has told their selected constructs to hold their current position.
+    HoldPosition { queued: bool },
+    /// The player has told their selected workers to return the resources they're carrying.
+    ReturnCargo { queued: bool },
+    /// The player has told their selected constructs to burrow.
+    Burrow,
+    /// The player has started training a construct of the specified type at one of their selected
+    /// constructs.
+    Train(ConstructTypeId),
+}
+
+/// An order given with [GameCommand::TargetedOrder].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, TryFromPrimitive, IntoPrimitive)]
+#[repr(u8)]
+pub enum TargetOrder {
+    Move = 0,
+    Attack = 1,
+    Patrol = 2,
+    Gather = 3,
+    SetRallyPoint = 4,
+}
+
+// NOTE(tec27): These IDs match the equivalent commands in BW where possible
+const COMMAND_SELECT: u8 = 0x09;
+const COMMAND_SELECT_ADD: u8 = 0x0a;
+const COMMAND_SELECT_REMOVE: u8 = 0x0b;
+const COMMAND_CONTROL_GROUP: u8 = 0x13;
+const COMMAND_RIGHT_CLICK: u8 = 0x14;
+const COMMAND_TARGETED_ORDER: u8 = 0x15;
+const COMMAND_STOP: u8 = 0x1a;
+const COMMAND_RETURN_CARGO: u8 = 0x1e;
+const COMMAND_TRAIN: u8 = 0x1f;
+const COMMAND_HOLD_POSITION: u8 = 0x2b;
+const COMMAND_BURROW: u8 = 0x2c;
+const COMMAND_LEAVE: u8 = 0x57;
+
+impl GameCommand {
+    /// Returns whether this command gives orders to the player's selected constructs.
+    pub fn is_order(&self) -> bool {
+        matches!(
+            self,
+            GameCommand::RightClick { .. }
+                | GameCommand::TargetedOrder { .. }
+                | GameCommand::Stop { .. }
+                | GameCommand::HoldPosition { .. }
+                | GameCommand::ReturnCargo { .. }
+                | GameCommand::Burrow
+        )
+    }
+
+    /// Writes the binary representation of this command to `w`.
+    pub fn write<W: Write>(&self, mut w: W) -> Result<(), PacketError> {
+        match self {
+            GameCommand::Leave => w.write_u8(COMMAND_LEAVE)?,
+            GameCommand::Select(ids) => {
+                w.write_u8(COMMAND_SELECT)?;
+                write_construct_ids(w, ids)?;
+            }
+            GameCommand::SelectAdd(ids) => {
+                w.write_u8(COMMAND_SELECT_ADD)?;
+                write_construct_ids(w, ids)?;
+            }
+            GameCommand::SelectRemove(ids) => {
+                w.write_u8(COMMAND_SELECT_REMOVE)?;
+                write_construct_ids(w, ids)?;
+            }
+            GameCommand::ControlGroup { action, group } => {
+                w.write_u8(COMMAND_CONTROL_GROUP)?;
+                w.write_u8(*action as u8)?;
+                w.write_u8(*group)?;
+            }
+            GameCommand::RightClick {
+                position,
+                target,
+                queued,
+            } => {
+                w.write_u8(COMMAND_RIGHT_CLICK)?;
+                write_target(&mut w, *position, *target)?;
+                w.write_u8(*queued as u8)?;
+            }
+            GameCommand::TargetedOrder {
+                order,
+                position,
+                target,
+                queued,
+            } => {
+                w.write_u8(COMMAND_TARGETED_ORDER)?;
+                w.write_u8((*order).into())?;
+                write_target(&mut w, *position, *target)?;
+                w.write_u8(*queued as u8)?;
+            }
+            GameCommand::Stop { queued } => {
+                w.write_u8(COMMAND_STOP)?;
+                w.write_u8(*queued as u8)?;
+            }
+            GameCommand::HoldPosition { queued } => {
+                w.write_u8(COMMAND_HOLD_POSITION)?;
+                w.write_u8(*queued as u8)?;
+            }
+            GameCommand::ReturnCargo { queued } => {
+                w.write_u8(COMMAND_RETURN_CARGO)?;
+                w.write_u8(*queued as u8)?;
+            }
+            GameCommand::Burrow => w.write_u8(COMMAND_BURROW)?,
+            GameCommand::Train(construct_type) => {
+                w.write_u8(COMMAND_TRAIN)?;
+                w.write_u16::<LittleEndian>((*construct_type).into())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a command from its binary representation in `r`.
+    pub fn read<R: Read>(mut r: R) -> Result<Self, PacketError> {
+        let id = r.read_u8()?;
+        match id {
+            COMMAND_LEAVE => Ok(GameCommand::Leave),
+            COMMAND_SELECT => Ok(GameCommand::Select(read_construct_ids(r, id)?)),
+            COMMAND_SELECT_ADD => Ok(GameCommand::SelectAdd(read_construct_ids(r, id)?)),
+            COMMAND_SELECT_REMOVE => Ok(GameCommand::SelectRemove(read_construct_ids(r, id)?)),
+            COMMAND_CONTROL_GROUP => {
+                let action = ControlGroupAction::try_from(r.read_u8()?)
+                    .map_err(|_| PacketError::InvalidCommand(id))?;
+                let group = r.read_u8()?;
+                if group as usize >= NUM_CONTROL_GROUPS {
+                    return Err(PacketError::InvalidCommand(id));
+                }
+                Ok(GameCommand::ControlGroup { action, group })
+            }
+            COMMAND_RIGHT_CLICK => {
+                let (position, target) = read_target(&mut r, id)?;
+                Ok(GameCommand::RightClick {
+                    position,
+                    target,
+                    queued: read_bool(&mut r, id)?,
+                })
+            }
+            COMMAND_TARGETED_ORDER => {
+                let order = TargetOrder::try_from(r.read_u8()?)
+                    .map_err(|_| PacketError::InvalidCommand(id))?;
+                let (position, target) = read_target(&mut r, id)?;
+                Ok(GameCommand::TargetedOrder {
+                    order,
+                    position,
+                    target,
+                    queued: read_bool(&mut r, id)?,
+                })
+            }
+            COMMAND_STOP => Ok(GameCommand::Stop {
+                queued: read_bool(r, id)?,
+            }),
+            COMMAND_HOLD_POSITION => Ok(GameCommand::HoldPosition {
+                queued: read_bool(r, id)?,
+            }),
+            COMMAND_RETURN_CARGO => Ok(GameCommand::ReturnCargo {
+                queued: read_bool(r, id)?,
+            }),
+            COMMAND_BURROW => Ok(GameCommand::Burrow),
+            COMMAND_TRAIN => {
+                let construct_type = ConstructTypeId::from(r.read_u16::<LittleEndian>()?);
+                if matches!(construct_type, ConstructTypeId::Unknown(_)) {
+                    return Err(PacketError::InvalidCommand(id));
+                }
+                Ok(GameCommand::Train(construct_type))
+            }
+            _ => Err(PacketError::UnknownCommand(id)),
+        }
+    }
+}
+
+fn write_target<W: Write>(
+    mut w: W,
+    position: Position,
+    target: Option<ConstructId>,
+) -> std::io::Result<()> {
+    // NOTE(tec27): Positions are always within the map, which is at most 256 tiles across, so they
+    // fit in a u16 (as in BW)
+    w.write_u16::<LittleEndian>(position.x.clamp(0, u16::MAX as i32) as u16)?;
+    w.write_u16::<LittleEndian>(position.y.clamp(0, u16::MAX as i32) as u16)?;
+    match target {
+        Some(id) => {
+            w.write_u8(1)?;
+            w.write_u32::<LittleEndian>(id.0)
+        }
+        None => w.write_u8(0),
+    }
+}
+
+fn read_target<R: Read>(
+    mut r: R,
+    command: u8,
+) -> Result<(Position, Option<ConstructId>), PacketError> {
+    let x = r.read_u16::<LittleEndian>()?;
+    let y = r.read_u16::<LittleEndian>()?;
+    let target = match r.read_u8()? {
+        0 => None,
+        1 => Some(ConstructId(r.read_u32::<LittleEndian>()?)),
+        _ => return Err(PacketError::InvalidCommand(command)),
+    };
+    Ok((Position::new(x.into(), y.into()), target))
+}
+
+fn read_bool<R: Read>(mut r: R, command: u8) -> Result<bool, PacketError> {
+    match r.read_u8()? {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(PacketError::InvalidCommand(command)),
+    }
+}
+
+fn write_construct_ids<W: Write>(mut w: W, ids: &[ConstructId]) -> Result<(), PacketError> {
+    if ids.len() > MAX_SELECTION {
+        return Err(PacketError::TooMany {
+            field: "selected constructs",
+            count: ids.len(),
+        });
+    }
+    w.write_u8(ids.len() as u8)?;
+    for id in ids {
+        w.write_u32::<LittleEndian>(id.0)?;
+    }
+    Ok(())
+}
+
+fn read_construct_ids<R: Read>(mut r: R, command: u8) -> Result<Vec<ConstructId>, PacketError> {
+    let count = r.read_u8()? as usize;
+    if count > MAX_SELECTION {
+        return Err(PacketError::InvalidCommand(command));
+    }
+    (0..count)
+        .map(|_| Ok(ConstructId(r.read_u32::<LittleEndian>()?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use claims::{assert_matches, assert_ok};
+
+    use super::*;
+
+    fn roundtrip(command: GameCommand) -> GameCommand {
+        let mut bytes = Vec::new();
+        assert_ok!(command.write(&mut bytes));
+        assert_ok!(GameCommand::read(bytes.as_slice()))
+    }
+
+    #[test]
+    fn selection_roundtrip() {
+        let commands = [
+            GameCommand::Select(vec![ConstructId(1), ConstructId(70000)]),
+            GameCommand::SelectAdd(vec![]),
+            GameCommand::SelectRemove(vec![ConstructId(5)]),
+            GameCommand::ControlGroup {
+                action: ControlGroupAction::Add,
+                group: 9,
+            },
+        ];
+        for command in commands {
+            assert_eq!(roundtrip(command.clone()), command);
+        }
+        assert_matches!(
+            GameCommand::Select(vec![ConstructId(0); MAX_SELECTION + 1]).write(&mut Vec::new()),
+            Err(PacketError::TooMany {
+                field: "selected constructs",
+                count: 13
+            })
+        );
+    }
+
+    #[test]
+    fn order_roundtrip() {
+        let commands = [
+            GameCommand::RightClick {
+                position: Position::new(8191, 0),
+                target: None,
+                queued: false,
+            },
+            GameCommand::RightClick {
+                position: Position::new(40, 72),
+                target: Some(ConstructId(300)),
+                queued: true,
+            },
+            GameCommand::TargetedOrder {
+                order: TargetOrder::Attack,
+                position: Position::new(100, 200),
+                target: Some(ConstructId(12)),
+                queued: false,
+            },
+            GameCommand::Stop { queued: true },
+            GameCommand::HoldPosition { queued: false },
+            GameCommand::ReturnCargo { queued: false },
+            GameCommand::Burrow,
+        ];
+        for command in commands {
+            assert!(command.is_order());
+            assert_eq!(roundtrip(command.clone()), command);
+        }
+        assert_matches!(
+            GameCommand::read(&[COMMAND_RIGHT_CLICK, 0, 0, 0, 0, 2][..]),
+            Err(PacketError::InvalidCommand(COMMAND_RIGHT_CLICK))
+        );
+        assert_matches!(
+            GameCommand::read(&[COMMAND_TARGETED_ORDER, 9, 0, 0, 0, 0, 0, 0][..]),
+            Err(PacketError::InvalidCommand(COMMAND_TARGETED_ORDER))
+        );
+    }
+
+    #[test]
+    fn train_roundtrip() {
+        let command = GameCommand::Train(ConstructTypeId::ProtossZealot);
+        assert!(!command.is_order());
+        assert_eq!(roundtrip(command.clone()), command);
+        assert_matches!(
+            GameCommand::read(&[COMMAND_TRAIN, 0xff, 0x7f][..]),
+            Err(PacketError::InvalidCommand(COMMAND_TRAIN))
+        );
+    }
+
+    #[test]
+    fn invalid_selection() {
+        assert_matches!(
+            GameCommand::read(&[COMMAND_SELECT, 13][..]),
+            Err(PacketError::InvalidCommand(COMMAND_SELECT))
+        );
+        assert_matches!(
+            GameCommand::read(&[COMMAND_CONTROL_GROUP, 0, 10][..]),
+            Err(PacketError::InvalidCommand(COMMAND_CONTROL_GROUP))
+        );
+        assert_matches!(
+            GameCommand::read(&[COMMAND_CONTROL_GROUP, 7, 0][..]),
+            Err(PacketError::InvalidCommand(COMMAND_CONTROL_GROUP))
+        );
+    }
+}
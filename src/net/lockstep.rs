@@ -0,0 +1,608 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use super::{
+    command::GameCommand,
+    packet::{FrameChecksum, Packet, PacketError, Turn, MAX_PACKET_SIZE},
+};
+
+/// How long to wait for new data before resending any unacknowledged turns to a peer. This also
+/// acts as a keep-alive while the game is stalled.
+const RESEND_INTERVAL: Duration = Duration::from_millis(50);
+/// The maximum number of turns that will be included in a single packet. Packets are also limited
+/// to [MAX_PACKET_SIZE], so they may contain fewer turns than this.
+const MAX_TURNS_PER_PACKET: usize = 24;
+/// The most space the commands in a single turn can take up once encoded. Commands past this are
+/// pushed back to the next turn, which ensures that any turn fits in a packet by itself.
+const MAX_TURN_COMMANDS_SIZE: usize = MAX_PACKET_SIZE / 2;
+/// How many of the most recent local checksums are included in each packet.
+const CHECKSUMS_PER_PACKET: usize = 4;
+/// How many frames worth of checksums we keep around for comparison with late packets.
+const CHECKSUM_HISTORY_FRAMES: u32 = 24 * 30;
+
+/// A difference in game state between the local player and a peer, detected by comparing
+/// checksums for the same game frame.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Desync {
+    pub frame: u32,
+    pub player: u8,
+    pub local_checksum: u32,
+    pub remote_checksum: u32,
+}
+
+#[derive(Debug)]
+struct Peer {
+    player: u8,
+    addr: SocketAddr,
+    /// Turns received from this peer that haven't been executed yet.
+    turns: BTreeMap<u32, Vec<GameCommand>>,
+    /// The frame of the next turn we need from this peer. All turns before this have been
+    /// received.
+    received_through: u32,
+    /// The frame of the next turn this peer needs from us.
+    acked: u32,
+    checksums: BTreeMap<u32, u32>,
+    last_sent: Option<Instant>,
+    /// Whether we have new data (turns or acks) that this peer hasn't been sent yet.
+    dirty: bool,
+}
+
+/// The state of a lockstep game session. This handles turn buffering, acknowledgements/resends,
+/// and checksum comparisons, but does no actual I/O, so packets must be passed in and out of it.
+///
+/// Commands issued by the local player are scheduled to execute `latency` frames in the future,
+/// which gives them time to reach the other players. The game may only simulate a frame once the
+/// turns for that frame have been received from every player.
+#[derive(Debug)]
+pub struct Lockstep {
+    local_player: u8,
+    latency: u32,
+    /// The frame the next local turn will be scheduled for.
+    next_local_turn: u32,
+    /// Commands issued by the local player that haven't been assigned to a turn yet, along with
+    /// their encoded size.
+    pending: Vec<(GameCommand, usize)>,
+    /// Local turns that haven't been executed yet.
+    local_turns: BTreeMap<u32, Vec<GameCommand>>,
+    /// Local turns that haven't been acknowledged by every peer yet, along with their encoded size.
+    unacked: VecDeque<(Turn, usize)>,
+    peers: Vec<Peer>,
+    local_checksums: BTreeMap<u32, u32>,
+    desyncs: Vec<Desync>,
+}
+
+impl Lockstep {
+    /// Creates a new lockstep session. `peers` contains the player number and address of every
+    /// other player in the game.
+    pub fn new(
+        local_player: u8,
+        latency: u32,
+        peers: impl IntoIterator<Item = (u8, SocketAddr)>,
+    ) -> Self {
+        let mut peers = peers
+            .into_iter()
+            .map(|(player, addr)| Peer {
+                player,
+                addr,
+                turns: BTreeMap::new(),
+                // Turns before the latency are implicitly empty for everyone
+                received_through: latency,
+                acked: latency,
+                checksums: BTreeMap::new(),
+                last_sent: None,
+                dirty: true,
+            })
+            .collect::<Vec<_>>();
+        peers.sort_by_key(|p| p.player);
+
+        Self {
+            local_player,
+            latency,
+            next_local_turn: latency,
+            pending: Vec::new(),
+            local_turns: BTreeMap::new(),
+            unacked: VecDeque::new(),
+            peers,
+            local_checksums: BTreeMap::new(),
+            desyncs: Vec::new(),
+        }
+    }
+
+    pub fn local_player(&self) -> u8 {
+        self.local_player
+    }
+
+    pub fn latency(&self) -> u32 {
+        self.latency
+    }
+
+    /// Queues a command from the local player to be sent with the next scheduled turn. Commands
+    /// that can't be encoded (and so couldn't be sent to peers) are rejected.
+    pub fn queue_command(&mut self, command: GameCommand) -> Result<(), PacketError> {
+        let mut bytes = Vec::new();
+        command.write(&mut bytes)?;
+        self.pending.push((command, bytes.len()));
+        Ok(())
+    }
+
+    /// Returns whether the turns from every player are available for the specified frame.
+    pub fn is_turn_ready(&self, frame: u32) -> bool {
+        frame < self.latency || self.peers.iter().all(|p| p.received_through > frame)
+    }
+
+    /// Returns the player numbers of all the peers whose turn for `frame` hasn't been received.
+    pub fn waiting_on(&self, frame: u32) -> Vec<u8> {
+        if frame < self.latency {
+            return Vec::new();
+        }
+        self.peers
+            .iter()
+            .filter(|p| p.received_through <= frame)
+            .map(|p| p.player)
+            .collect()
+    }
+
+    /// Takes the commands for the specified frame from every player, ordered by player number,
+    /// and schedules the local player's pending commands for a future turn. This should only be
+    /// called once [Lockstep::is_turn_ready] returns true for the frame, and frames must be taken
+    /// in order.
+    pub fn take_turn(&mut self, frame: u32) -> Vec<(u8, GameCommand)> {
+        debug_assert!(self.is_turn_ready(frame));
+
+        let mut commands = Vec::new();
+        if let Some(local) = self.local_turns.remove(&frame) {
+            commands.extend(local.into_iter().map(|c| (self.local_player, c)));
+        }
+        for peer in self.peers.iter_mut() {
+            if let Some(turn) = peer.turns.remove(&frame) {
+                commands.extend(turn.into_iter().map(|c| (peer.player, c)));
+            }
+        }
+        // Peers are sorted by player number, but the local player may fall anywhere among them
+        commands.sort_by_key(|&(player, _)| player);
+
+        self.schedule_local_turn(frame + self.latency);
+
+        commands
+    }
+
+    fn schedule_local_turn(&mut self, frame: u32) {
+        // Normally this will only schedule a single turn, but handles any turns that might have
+        // been skipped as well, since peers will need to receive something for each of them
+        while self.next_local_turn <= frame {
+            let commands = if self.next_local_turn == frame {
+                self.take_pending_commands()
+            } else {
+                Vec::new()
+            };
+            self.local_turns
+                .insert(self.next_local_turn, commands.clone());
+            let turn = Turn {
+                frame: self.next_local_turn,
+                commands,
+            };
+            let size = turn
+                .encoded_len()
+                .expect("Local turns only contain commands that can be encoded");
+            self.unacked.push_back((turn, size));
+            self.next_local_turn += 1;
+        }
+
+        for peer in self.peers.iter_mut() {
+            peer.dirty = true;
+        }
+    }
+
+    /// Takes as many of the pending commands as will fit in a single turn. Any others are left for
+    /// the next turn.
+    fn take_pending_commands(&mut self) -> Vec<GameCommand> {
+        let mut size = 0;
+        let count = self
+            .pending
+            .iter()
+            .take(u8::MAX as usize)
+            .take_while(|&&(_, len)| {
+                size += len;
+                size <= MAX_TURN_COMMANDS_SIZE
+            })
+            .count();
+        self.pending
+            .drain(..count)
+            .map(|(command, _)| command)
+            .collect()
+    }
+
+    /// Records the checksum of the local game state after the specified frame was simulated. It
+    /// will be sent to all peers and compared to their checksums for the same frame.
+    pub fn record_checksum(&mut self, frame: u32, checksum: u32) {
+        self.local_checksums.insert(frame, checksum);
+        for peer in self.peers.iter_mut() {
+            if let Some(&remote_checksum) = peer.checksums.get(&frame) {
+                if remote_checksum != checksum {
+                    self.desyncs.push(Desync {
+                        frame,
+                        player: peer.player,
+                        local_checksum: checksum,
+                        remote_checksum,
+                    });
+                }
+            }
+        }
+
+        let oldest = frame.saturating_sub(CHECKSUM_HISTORY_FRAMES);
+        self.local_checksums.retain(|&f, _| f >= oldest);
+        for peer in self.peers.iter_mut() {
+            peer.checksums.retain(|&f, _| f >= oldest);
+        }
+    }
+
+    /// Returns any desyncs that have been detected since the last call.
+    pub fn take_desyncs(&mut self) -> Vec<Desync> {
+        std::mem::take(&mut self.desyncs)
+    }
+
+    /// Handles a packet received from `from`. Packets are only accepted if they come from the
+    /// address of the peer they claim to be from, returns whether the packet was accepted.
+    pub fn receive(&mut self, from: SocketAddr, packet: Packet) -> bool {
+        let Some(peer) = self
+            .peers
+            .iter_mut()
+            .find(|p| p.player == packet.player && p.addr == from)
+        else {
+            return false;
+        };
+
+        for turn in packet.turns {
+            if turn.frame >= peer.received_through {
+                peer.turns.entry(turn.frame).or_insert(turn.commands);
+            }
+        }
+        let old_received = peer.received_through;
+        while peer.turns.contains_key(&peer.received_through) {
+            peer.received_through += 1;
+        }
+        if peer.received_through != old_received {
+            // Make sure the peer finds out about this promptly so it can stop resending
+            peer.dirty = true;
+        }
+
+        peer.acked = peer.acked.max(packet.ack);
+
+        for FrameChecksum { frame, checksum } in packet.checksums {
+            if peer.checksums.insert(frame, checksum).is_some() {
+                // Already compared this one
+                continue;
+            }
+            if let Some(&local_checksum) = self.local_checksums.get(&frame) {
+                if local_checksum != checksum {
+                    self.desyncs.push(Desync {
+                        frame,
+                        player: peer.player,
+                        local_checksum,
+                        remote_checksum: checksum,
+                    });
+                }
+            }
+        }
+
+        let min_acked = self.peers.iter().map(|p| p.acked).min().unwrap_or(u32::MAX);
+        while self
+            .unacked
+            .front()
+            .is_some_and(|(t, _)| t.frame < min_acked)
+        {
+            self.unacked.pop_front();
+        }
+
+        true
+    }
+
+    /// Returns the packets that should be sent to peers at this point in time. Peers will be sent
+    /// a packet if there is new data for them, or if they haven't been sent anything recently.
+    pub fn outgoing(&mut self, now: Instant) -> Vec<(SocketAddr, Packet)> {
+        let checksums = self
+            .local_checksums
+            .iter()
+            .rev()
+            .take(CHECKSUMS_PER_PACKET)
+            .map(|(&frame, &checksum)| FrameChecksum { frame, checksum })
+            .collect::<Vec<_>>();
+        let empty_packet_size = Packet {
+            player: self.local_player,
+            ack: 0,
+            turns: Vec::new(),
+            checksums: checksums.clone(),
+        }
+        .encoded_len()
+        .expect("Packets without turns can always be encoded");
+
+        let mut packets = Vec::new();
+        for peer in self.peers.iter_mut() {
+            let needs_resend = peer
+                .last_sent
+                .map_or(true, |t| now.duration_since(t) >= RESEND_INTERVAL);
+            if !peer.dirty && !needs_resend {
+                continue;
+            }
+
+            let mut size = empty_packet_size;
+            let mut turns = Vec::new();
+            for (turn, turn_size) in self
+                .unacked
+                .iter()
+                .filter(|(t, _)| t.frame >= peer.acked)
+                .take(MAX_TURNS_PER_PACKET)
+            {
+                // Any turn fits in a packet by itself, so there's always at least one included
+                if !turns.is_empty() && size + turn_size > MAX_PACKET_SIZE {
+                    break;
+                }
+                size += turn_size;
+                turns.push(turn.clone());
+            }
+            packets.push((
+                peer.addr,
+                Packet {
+                    player: self.local_player,
+                    ack: peer.received_through,
+                    turns,
+                    checksums: checksums.clone(),
+                },
+            ));
+            peer.last_sent = Some(now);
+            peer.dirty = false;
+        }
+
+        packets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, UdpSocket};
+
+    use claims::assert_ok;
+
+    use crate::{
+        gameplay::{constructs::ConstructId, player_selection::MAX_SELECTION},
+        random::LcgRand,
+    };
+
+    use super::*;
+
+    fn addr(port: u16) -> SocketAddr {
+        SocketAddr::from((Ipv4Addr::LOCALHOST, port))
+    }
+
+    /// Delivers all the outgoing packets from `from` to `to`, dropping any for which `drop`
+    /// returns true.
+    fn deliver(
+        from: &mut Lockstep,
+        to: &mut Lockstep,
+        now: Instant,
+        mut drop: impl FnMut() -> bool,
+    ) {
+        let from_addr = to
+            .peers
+            .iter()
+            .find(|p| p.player == from.local_player)
+            .unwrap()
+            .addr;
+        for (_, packet) in from.outgoing(now) {
+            if !drop() {
+                // Run it through the binary format to make sure that works as well
+                assert!(to.receive(
+                    from_addr,
+                    assert_ok!(Packet::from_bytes(&assert_ok!(packet.to_bytes())))
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn commands_execute_after_latency() {
+        let mut a = Lockstep::new(0, 2, [(1, addr(2))]);
+        let mut b = Lockstep::new(1, 2, [(0, addr(1))]);
+        let now = Instant::now();
+
+        assert_ok!(a.queue_command(GameCommand::Leave));
+        for frame in 0..2 {
+            assert!(a.is_turn_ready(frame));
+            assert!(b.is_turn_ready(frame));
+            assert_eq!(a.take_turn(frame), vec![]);
+            assert_eq!(b.take_turn(frame), vec![]);
+        }
+
+        // Nothing has been exchanged yet, so both should be waiting on each other
+        assert!(!a.is_turn_ready(2));
+        assert_eq!(a.waiting_on(2), vec![1]);
+        assert_eq!(b.waiting_on(2), vec![0]);
+
+        deliver(&mut a, &mut b, now, || false);
+        deliver(&mut b, &mut a, now, || false);
+
+        assert!(a.is_turn_ready(2));
+        assert!(b.is_turn_ready(2));
+        assert_eq!(a.take_turn(2), vec![(0, GameCommand::Leave)]);
+        assert_eq!(b.take_turn(2), vec![(0, GameCommand::Leave)]);
+    }
+
+    #[test]
+    fn resends_lost_turns() {
+        let mut a = Lockstep::new(0, 3, [(1, addr(2))]);
+        let mut b = Lockstep::new(1, 3, [(0, addr(1))]);
+        let mut now = Instant::now();
+        let mut rand = LcgRand::new(1234);
+
+        let mut a_log = Vec::new();
+        let mut b_log = Vec::new();
+        let mut frame = 0;
+        let mut iterations = 0;
+        while frame < 200 {
+            iterations += 1;
+            assert!(iterations < 10_000, "game never progressed");
+
+            if frame % 7 == 0 {
+                assert_ok!(a.queue_command(GameCommand::Leave));
+            }
+            // Drop roughly 40% of all packets
+            deliver(&mut a, &mut b, now, || rand.in_range_u32(0, 99) < 40);
+            deliver(&mut b, &mut a, now, || rand.in_range_u32(0, 99) < 40);
+            now += Duration::from_millis(21);
+
+            if a.is_turn_ready(frame) && b.is_turn_ready(frame) {
+                a_log.push(a.take_turn(frame));
+                b_log.push(b.take_turn(frame));
+                frame += 1;
+            }
+        }
+
+        assert_eq!(a_log, b_log);
+        assert!(a_log.iter().any(|t| !t.is_empty()));
+    }
+
+    #[test]
+    fn splits_large_turns_between_packets() {
+        let mut a = Lockstep::new(0, 10, [(1, addr(2))]);
+        let mut b = Lockstep::new(1, 10, [(0, addr(1))]);
+        let mut now = Instant::now();
+
+        let select = GameCommand::Select((0..MAX_SELECTION as u32).map(ConstructId).collect());
+        for _ in 0..200 {
+            assert_ok!(a.queue_command(select.clone()));
+        }
+        // Schedule a bunch of large turns before anything gets sent, so they're all unacked
+        for frame in 0..10 {
+            a.take_turn(frame);
+            b.take_turn(frame);
+        }
+        let unacked_size = a.unacked.iter().map(|&(_, size)| size).sum::<usize>();
+        assert!(unacked_size > MAX_PACKET_SIZE * 4);
+
+        let mut received = 0;
+        let mut frame = 10;
+        let mut iterations = 0;
+        while frame < 30 {
+            iterations += 1;
+            assert!(iterations < 100, "game never progressed");
+
+            for (_, packet) in a.outgoing(now) {
+                let bytes = assert_ok!(packet.to_bytes());
+                assert!(bytes.len() <= MAX_PACKET_SIZE);
+                assert!(b.receive(addr(1), assert_ok!(Packet::from_bytes(&bytes))));
+            }
+            deliver(&mut b, &mut a, now, || false);
+            now += RESEND_INTERVAL;
+
+            if a.is_turn_ready(frame) && b.is_turn_ready(frame) {
+                let turn = b.take_turn(frame);
+                assert_eq!(a.take_turn(frame), turn);
+                assert!(turn.iter().all(|(player, c)| *player == 0 && *c == select));
+                received += turn.len();
+                frame += 1;
+            }
+        }
+        assert_eq!(received, 200);
+    }
+
+    #[test]
+    fn ignores_packets_from_unknown_addresses() {
+        let mut a = Lockstep::new(0, 2, [(1, addr(2))]);
+        let mut b = Lockstep::new(1, 2, [(0, addr(1))]);
+        let now = Instant::now();
+
+        assert_ok!(b.queue_command(GameCommand::Leave));
+        b.take_turn(0);
+        b.take_turn(1);
+        for (_, packet) in b.outgoing(now) {
+            // Claims to be from player 1, but comes from somewhere else
+            assert!(!a.receive(addr(3), packet.clone()));
+            // From the right address, but claims to be from a player that isn't in the game
+            assert!(!a.receive(
+                addr(2),
+                Packet {
+                    player: 2,
+                    ..packet
+                }
+            ));
+        }
+        assert!(!a.is_turn_ready(2));
+        assert_eq!(a.waiting_on(2), vec![1]);
+
+        deliver(&mut b, &mut a, now + RESEND_INTERVAL, || false);
+        assert!(a.is_turn_ready(2));
+    }
+
+    #[test]
+    fn detects_desyncs() {
+        let mut a = Lockstep::new(0, 2, [(1, addr(2))]);
+        let mut b = Lockstep::new(1, 2, [(0, addr(1))]);
+        let now = Instant::now();
+
+        a.record_checksum(10, 1);
+        b.record_checksum(10, 1);
+        a.record_checksum(20, 2);
+        b.record_checksum(20, 3);
+        deliver(&mut a, &mut b, now, || false);
+        deliver(&mut b, &mut a, now, || false);
+
+        let expected = Desync {
+            frame: 20,
+            player: 1,
+            local_checksum: 2,
+            remote_checksum: 3,
+        };
+        assert_eq!(a.take_desyncs(), vec![expected]);
+        assert_eq!(a.take_desyncs(), vec![]);
+        assert_eq!(b.take_desyncs().len(), 1);
+    }
+
+    #[test]
+    fn over_localhost() {
+        let socket_a = assert_ok!(UdpSocket::bind(addr(0)));
+        let socket_b = assert_ok!(UdpSocket::bind(addr(0)));
+        let addr_a = assert_ok!(socket_a.local_addr());
+        let addr_b = assert_ok!(socket_b.local_addr());
+        for socket in [&socket_a, &socket_b] {
+            assert_ok!(socket.set_read_timeout(Some(Duration::from_millis(5))));
+        }
+
+        let mut a = Lockstep::new(0, 2, [(1, addr_b)]);
+        let mut b = Lockstep::new(1, 2, [(0, addr_a)]);
+        let mut buf = [0u8; MAX_PACKET_SIZE];
+
+        let mut frame = 0;
+        let mut iterations = 0;
+        while frame < 20 {
+            iterations += 1;
+            assert!(iterations < 1_000, "game never progressed");
+
+            if frame == 5 {
+                assert_ok!(b.queue_command(GameCommand::Leave));
+            }
+            for (lockstep, socket) in [(&mut a, &socket_a), (&mut b, &socket_b)] {
+                for (to, packet) in lockstep.outgoing(Instant::now()) {
+                    assert_ok!(socket.send_to(&assert_ok!(packet.to_bytes()), to));
+                }
+            }
+            for (lockstep, socket) in [(&mut a, &socket_a), (&mut b, &socket_b)] {
+                while let Ok((len, from)) = socket.recv_from(&mut buf) {
+                    assert!(lockstep.receive(from, assert_ok!(Packet::from_bytes(&buf[..len]))));
+                }
+            }
+
+            if a.is_turn_ready(frame) && b.is_turn_ready(frame) {
+                let a_turn = a.take_turn(frame);
+                let b_turn = b.take_turn(frame);
+                assert_eq!(a_turn, b_turn);
+                if frame == 7 {
+                    assert_eq!(a_turn, vec![(1, GameCommand::Leave)]);
+                }
+                frame += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,431 @@
+use std::{
+    io::ErrorKind,
+    net::{SocketAddr, UdpSocket},
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    fonts::FONT_BODY,
+    gamedata::ConstructTypeId,
+    gameplay::{
+        constructs::OwnedConstruct,
+        health::Health,
+        players::{ControlledPlayer, PlayerNumber},
+        GameFrame, InGameMenuState, SimulationSet,
+    },
+    maps::position::Position,
+    random::LcgRand,
+    states::{AppState, InGameOnly},
+};
+
+pub use command::{GameCommand, TargetOrder};
+pub use lockstep::{Desync, Lockstep};
+pub use packet::PacketError;
+
+mod command;
+mod lockstep;
+mod packet;
+
+/// How often (in game frames) a checksum of the game state is computed and compared between
+/// players.
+const CHECKSUM_INTERVAL: u32 = 24;
+/// How long the game must be stalled before the "waiting for players" overlay is shown.
+const WAITING_OVERLAY_DELAY: Duration = Duration::from_millis(500);
+
+/// Plugin that distributes the commands of each player in BW-style lockstep. If no
+/// [LockstepSession] is present, commands from the local player are executed on the next game
+/// frame.
+pub struct NetPlugin;
+
+impl Plugin for NetPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<IssueCommandEvent>()
+            .init_resource::<LocalCommandQueue>()
+            .init_resource::<WaitingForPlayers>()
+            .add_systems(OnEnter(AppState::InGame), reset_session)
+            .add_systems(
+                PreUpdate,
+                receive_packets.run_if(
+                    in_state(AppState::InGame).and_then(resource_exists::<LockstepSession>),
+                ),
+            )
+            .add_systems(
+                Update,
+                queue_local_commands.run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(FixedFirst, begin_turn.run_if(in_state(AppState::InGame)))
+            .add_systems(FixedPostUpdate, record_checksum.in_set(SimulationSet))
+            .add_systems(FixedLast, end_turn.run_if(in_state(AppState::InGame)))
+            .add_systems(
+                PostUpdate,
+                send_packets.run_if(
+                    in_state(AppState::InGame).and_then(resource_exists::<LockstepSession>),
+                ),
+            );
+    }
+}
+
+/// Plugin that shows the local user when the game is stalled waiting on other players.
+pub struct NetInterfacePlugin;
+
+impl Plugin for NetInterfacePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::InGame), waiting_overlay_setup)
+            .add_systems(
+                Update,
+                update_waiting_overlay.run_if(in_state(AppState::InGame)),
+            );
+    }
+}
+
+/// Configuration for a networked game session.
+#[derive(Debug, Clone)]
+pub struct NetConfig {
+    /// The player number of the local player.
+    pub local_player: u8,
+    /// The local address to send and receive packets on.
+    pub bind_addr: SocketAddr,
+    /// The player number and address of every other player in the game.
+    pub peers: Vec<(u8, SocketAddr)>,
+    /// How many frames in the future commands are scheduled to execute. Higher values hide more
+    /// network latency at the cost of responsiveness.
+    pub latency: u32,
+    /// The seed used for the game's random number generator. This must match between all players.
+    pub seed: u32,
+}
+
+/// Resource that holds the state of a networked game. When present, game frames will only be
+/// simulated once the commands for that frame have been received from every player.
+#[derive(Resource, Debug)]
+pub struct LockstepSession {
+    config: NetConfig,
+    socket: UdpSocket,
+    lockstep: Lockstep,
+}
+
+impl LockstepSession {
+    pub fn new(config: NetConfig) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind(config.bind_addr)?;
+        socket.set_nonblocking(true)?;
+        let lockstep = Lockstep::new(
+            config.local_player,
+            config.latency,
+            config.peers.iter().copied(),
+        );
+
+        Ok(Self {
+            config,
+            socket,
+            lockstep,
+        })
+    }
+
+    pub fn local_player(&self) -> u8 {
+        self.lockstep.local_player()
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.config.seed
+    }
+
+    /// Returns the player numbers of everyone in the session, including the local player.
+    pub fn players(&self) -> impl Iterator<Item = u8> + '_ {
+        std::iter::once(self.config.local_player).chain(self.config.peers.iter().map(|p| p.0))
+    }
+}
+
+/// Event that signifies the local player wants to issue a [GameCommand]. The command will be
+/// executed for every player on some future game frame.
+#[derive(Event, Debug, Clone)]
+pub struct IssueCommandEvent(pub GameCommand);
+
+/// Commands issued by the local player while not in a networked game.
+#[derive(Resource, Debug, Default)]
+struct LocalCommandQueue(Vec<GameCommand>);
+
+/// Resource containing the commands that should be executed for the game frame currently being
+/// simulated, ordered by player number. This only exists while a game frame is being simulated,
+/// and its absence means that the simulation should not advance.
+#[derive(Resource, Debug, Default)]
+pub struct CurrentTurn {
+    pub frame: u32,
+    pub commands: Vec<(u8, GameCommand)>,
+}
+
+/// Resource that tracks which players (if any) the game is currently stalled on.
+#[derive(Resource, Debug, Default)]
+pub struct WaitingForPlayers {
+    pub players: Vec<u8>,
+    pub since: Option<Instant>,
+}
+
+/// Run condition that returns true if the current game frame should be simulated.
+pub fn turn_ready(turn: Option<Res<CurrentTurn>>) -> bool {
+    turn.is_some()
+}
+
+fn reset_session(
+    session: Option<ResMut<LockstepSession>>,
+    mut local_commands: ResMut<LocalCommandQueue>,
+    mut waiting: ResMut<WaitingForPlayers>,
+) {
+    local_commands.0.clear();
+    *waiting = default();
+
+    if let Some(session) = session {
+        let session = session.into_inner();
+        let config = &session.config;
+        session.lockstep = Lockstep::new(
+            config.local_player,
+            config.latency,
+            config.peers.iter().copied(),
+        );
+        info!(
+            "Starting lockstep game as player {} with {} peers, latency of {} frames",
+            config.local_player,
+            config.peers.len(),
+            config.latency
+        );
+    }
+}
+
+fn receive_packets(mut session: ResMut<LockstepSession>) {
+    let session = &mut *session;
+    let mut buf = [0u8; packet::MAX_PACKET_SIZE];
+    loop {
+        match session.socket.recv_from(&mut buf) {
+            Ok((len, from)) => match packet::Packet::from_bytes(&buf[..len]) {
+                Ok(packet) => {
+                    let player = packet.player;
+                    if !session.lockstep.receive(from, packet) {
+                        warn!("Ignoring packet from {from} claiming to be from player {player}");
+                    }
+                }
+                Err(e) => warn!("Ignoring invalid packet from {from}: {e}"),
+            },
+            Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+            // NOTE(tec27): Windows reports ICMP port unreachable messages as errors on the next
+            // recv, which happens whenever a peer isn't running yet. Resends will cover those.
+            Err(e) if e.kind() == ErrorKind::ConnectionReset => continue,
+            Err(e) => {
+                error!("Error receiving packets: {e}");
+                break;
+            }
+        }
+    }
+
+    for desync in session.lockstep.take_desyncs() {
+        error!(
+            "Desync detected with player {} on frame {}: local checksum {:#010x}, remote checksum \
+            {:#010x}",
+            desync.player, desync.frame, desync.local_checksum, desync.remote_checksum
+        );
+    }
+}
+
+fn send_packets(session: ResMut<LockstepSession>) {
+    let session = session.into_inner();
+    for (addr, packet) in session.lockstep.outgoing(Instant::now()) {
+        let bytes = match packet.to_bytes() {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                error!("Failed to encode packet for {addr}: {e}");
+                continue;
+            }
+        };
+        match session.socket.send_to(&bytes, addr) {
+            Ok(_) => {}
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(e) => warn!("Error sending packet to {addr}: {e}"),
+        }
+    }
+}
+
+fn queue_local_commands(
+    mut events: EventReader<IssueCommandEvent>,
+    session: Option<ResMut<LockstepSession>>,
+    mut local_commands: ResMut<LocalCommandQueue>,
+) {
+    match session {
+        Some(mut session) => {
+            for IssueCommandEvent(command) in events.read() {
+                if let Err(e) = session.lockstep.queue_command(command.clone()) {
+                    error!("Dropping command that can't be sent to peers ({command:?}): {e}");
+                }
+            }
+        }
+        None => local_commands
+            .0
+            .extend(events.read().map(|IssueCommandEvent(c)| c.clone())),
+    }
+}
+
+fn begin_turn(
+    mut commands: Commands,
+    session: Option<ResMut<LockstepSession>>,
+    mut local_commands: ResMut<LocalCommandQueue>,
+    game_frame: Res<GameFrame>,
+    mut waiting: ResMut<WaitingForPlayers>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    controlled_player: Query<&PlayerNumber, With<ControlledPlayer>>,
+) {
+    let frame = game_frame.0;
+    let Some(mut session) = session else {
+        let player = controlled_player.get_single().map_or(0, |p| p.0);
+        commands.insert_resource(CurrentTurn {
+            frame,
+            commands: local_commands.0.drain(..).map(|c| (player, c)).collect(),
+        });
+        return;
+    };
+
+    if session.lockstep.is_turn_ready(frame) {
+        if let Some(since) = waiting.since {
+            info!(
+                "Resuming after waiting {:.2?} for players",
+                Instant::now().duration_since(since)
+            );
+        }
+        *waiting = default();
+        commands.insert_resource(CurrentTurn {
+            frame,
+            commands: session.lockstep.take_turn(frame),
+        });
+    } else {
+        waiting.players = session.lockstep.waiting_on(frame);
+        waiting.since.get_or_insert_with(Instant::now);
+        // Don't try to catch up on the time we spent waiting once the turn arrives, the game just
+        // continues from where it stalled
+        let overstep = fixed_time.overstep();
+        fixed_time.discard_overstep(overstep);
+    }
+}
+
+fn end_turn(mut commands: Commands) {
+    commands.remove_resource::<CurrentTurn>();
+}
+
+/// Computes a checksum of the parts of the game state that must match between all players.
+/// Constructs are combined in an order-independent way, since their query order is not guaranteed
+/// to match between clients.
+fn record_checksum(
+    session: Option<ResMut<LockstepSession>>,
+    turn: Res<CurrentTurn>,
+    lcg_rand: Res<LcgRand>,
+    constructs: Query<(
+        &ConstructTypeId,
+        &Position,
+        Option<&OwnedConstruct>,
+        Option<&Health>,
+    )>,
+) {
+    let Some(mut session) = session else {
+        return;
+    };
+    if turn.frame % CHECKSUM_INTERVAL != 0 {
+        return;
+    }
+
+    let mut constructs_hash = 0u32;
+    for (construct_type, position, owner, health) in constructs.iter() {
+        let mut hasher = Fnv1a::default();
+        hasher.write_u32(u16::from(*construct_type) as u32);
+        hasher.write_u32(position.x as u32);
+        hasher.write_u32(position.y as u32);
+        hasher.write_u32(owner.map_or(u32::MAX, |o| o.0 as u32));
+        hasher.write_u32(health.map_or(0, |h| h.current.to_bits() as u32));
+        constructs_hash = constructs_hash.wrapping_add(hasher.finish());
+    }
+
+    let mut hasher = Fnv1a::default();
+    hasher.write_u32(turn.frame);
+    hasher.write_u32(lcg_rand.state());
+    hasher.write_u32(constructs.iter().len() as u32);
+    hasher.write_u32(constructs_hash);
+    session
+        .lockstep
+        .record_checksum(turn.frame, hasher.finish());
+}
+
+/// A 32-bit FNV-1a hasher. This is used instead of [std::hash::Hasher] implementations because its
+/// output must be stable across platforms and builds.
+struct Fnv1a(u32);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0x811c9dc5)
+    }
+}
+
+impl Fnv1a {
+    fn write_u32(&mut self, value: u32) {
+        for byte in value.to_le_bytes() {
+            self.0 ^= byte as u32;
+            self.0 = self.0.wrapping_mul(0x01000193);
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        self.0
+    }
+}
+
+#[derive(Component)]
+struct WaitingOverlay;
+
+fn waiting_overlay_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        TextBundle {
+            visibility: Visibility::Hidden,
+            ..TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load(FONT_BODY),
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(40.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            })
+            .with_text_justify(JustifyText::Center)
+        },
+        WaitingOverlay,
+        InGameOnly,
+        Name::new("WaitingForPlayersOverlay"),
+    ));
+}
+
+fn update_waiting_overlay(
+    waiting: Res<WaitingForPlayers>,
+    menu_state: Res<State<InGameMenuState>>,
+    mut overlay: Query<(&mut Text, &mut Visibility), With<WaitingOverlay>>,
+) {
+    let Ok((mut text, mut visibility)) = overlay.get_single_mut() else {
+        return;
+    };
+
+    let show = *menu_state.get() == InGameMenuState::Disabled
+        && waiting
+            .since
+            .is_some_and(|s| Instant::now().duration_since(s) >= WAITING_OVERLAY_DELAY);
+    if !show {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    }
+
+    let players = waiting
+        .players
+        .iter()
+        .map(|p| format!("Player {}", p + 1))
+        .collect::<Vec<_>>()
+        .join(", ");
+    text.sections[0].value = format!("Waiting for players...\n{players}");
+    visibility.set_if_neq(Visibility::Visible);
+}
@@ -0,0 +1,228 @@
+use std::io::{Cursor, Read, Write};
+
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use thiserror::Error;
+
+use super::command::GameCommand;
+
+#[derive(Error, Debug)]
+pub enum PacketError {
+    #[error("packet header is invalid")]
+    InvalidHeader,
+    #[error("unsupported protocol version: {0}")]
+    UnsupportedVersion(u8),
+    #[error("unknown command type: {0:#04x}")]
+    UnknownCommand(u8),
+    #[error("invalid data for command type: {0:#04x}")]
+    InvalidCommand(u8),
+    #[error("too many {field} to fit in a packet: {count}")]
+    TooMany { field: &'static str, count: usize },
+    #[error("failed to read packet: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+const PACKET_MAGIC: u32 = u32::from_le_bytes(*b"NBLS");
+const PROTOCOL_VERSION: u8 = 3;
+/// The largest a packet can be once encoded. Packets are received into buffers of this size, so
+/// anything larger would be truncated.
+pub const MAX_PACKET_SIZE: usize = 2048;
+
+/// The commands a single player issued for a particular game frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Turn {
+    pub frame: u32,
+    pub commands: Vec<GameCommand>,
+}
+
+impl Turn {
+    /// Returns the number of bytes this turn takes up in an encoded [Packet].
+    pub fn encoded_len(&self) -> Result<usize, PacketError> {
+        let mut bytes = Vec::with_capacity(16);
+        self.write(&mut bytes)?;
+        Ok(bytes.len())
+    }
+
+    fn write<W: Write>(&self, mut w: W) -> Result<(), PacketError> {
+        w.write_u32::<LittleEndian>(self.frame)?;
+        write_len(&mut w, self.commands.len(), "commands")?;
+        for command in self.commands.iter() {
+            command.write(&mut w)?;
+        }
+        Ok(())
+    }
+}
+
+/// A checksum of the game state after a particular game frame was simulated.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FrameChecksum {
+    pub frame: u32,
+    pub checksum: u32,
+}
+
+/// A datagram sent from one player to another. Every packet contains all of the sender's turns
+/// that the recipient hasn't acknowledged yet, so a lost packet will be covered by the next one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packet {
+    /// The player number of the sender.
+    pub player: u8,
+    /// The frame of the next turn the sender needs from the recipient. All turns before this have
+    /// been received.
+    pub ack: u32,
+    pub turns: Vec<Turn>,
+    pub checksums: Vec<FrameChecksum>,
+}
+
+impl Packet {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, PacketError> {
+        let mut bytes = Vec::with_capacity(64);
+        self.write(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Returns the number of bytes this packet takes up once encoded.
+    pub fn encoded_len(&self) -> Result<usize, PacketError> {
+        Ok(self.to_bytes()?.len())
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PacketError> {
+        Self::read(Cursor::new(bytes))
+    }
+
+    fn write<W: Write>(&self, mut w: W) -> Result<(), PacketError> {
+        w.write_u32::<LittleEndian>(PACKET_MAGIC)?;
+        w.write_u8(PROTOCOL_VERSION)?;
+        w.write_u8(self.player)?;
+        w.write_u32::<LittleEndian>(self.ack)?;
+
+        write_len(&mut w, self.turns.len(), "turns")?;
+        for turn in self.turns.iter() {
+            turn.write(&mut w)?;
+        }
+
+        write_len(&mut w, self.checksums.len(), "checksums")?;
+        for checksum in self.checksums.iter() {
+            w.write_u32::<LittleEndian>(checksum.frame)?;
+            w.write_u32::<LittleEndian>(checksum.checksum)?;
+        }
+
+        Ok(())
+    }
+
+    fn read<R: Read>(mut r: R) -> Result<Self, PacketError> {
+        let magic = r.read_u32::<LittleEndian>()?;
+        if magic != PACKET_MAGIC {
+            return Err(PacketError::InvalidHeader);
+        }
+        let version = r.read_u8()?;
+        if version != PROTOCOL_VERSION {
+            return Err(PacketError::UnsupportedVersion(version));
+        }
+        let player = r.read_u8()?;
+        let ack = r.read_u32::<LittleEndian>()?;
+
+        let num_turns = r.read_u8()?;
+        let mut turns = Vec::with_capacity(num_turns as usize);
+        for _ in 0..num_turns {
+            let frame = r.read_u32::<LittleEndian>()?;
+            let num_commands = r.read_u8()?;
+            let commands = (0..num_commands)
+                .map(|_| GameCommand::read(&mut r))
+                .collect::<Result<Vec<_>, _>>()?;
+            turns.push(Turn { frame, commands });
+        }
+
+        let num_checksums = r.read_u8()?;
+        let checksums = (0..num_checksums)
+            .map(|_| {
+                Ok(FrameChecksum {
+                    frame: r.read_u32::<LittleEndian>()?,
+                    checksum: r.read_u32::<LittleEndian>()?,
+                })
+            })
+            .collect::<Result<Vec<_>, PacketError>>()?;
+
+        Ok(Packet {
+            player,
+            ack,
+            turns,
+            checksums,
+        })
+    }
+}
+
+/// Writes the length of a list in the packet, which must fit in a u8.
+fn write_len<W: Write>(mut w: W, len: usize, field: &'static str) -> Result<(), PacketError> {
+    let len = u8::try_from(len).map_err(|_| PacketError::TooMany { field, count: len })?;
+    w.write_u8(len)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use claims::{assert_matches, assert_ok};
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let packet = Packet {
+            player: 3,
+            ack: 27,
+            turns: vec![
+                Turn {
+                    frame: 30,
+                    commands: vec![],
+                },
+                Turn {
+                    frame: 31,
+                    commands: vec![GameCommand::Leave],
+                },
+            ],
+            checksums: vec![FrameChecksum {
+                frame: 24,
+                checksum: 0xdeadbeef,
+            }],
+        };
+
+        let bytes = assert_ok!(packet.to_bytes());
+        let result = assert_ok!(Packet::from_bytes(&bytes));
+        assert_eq!(result, packet);
+    }
+
+    #[test]
+    fn too_many_entries() {
+        let packet = Packet {
+            player: 0,
+            ack: 0,
+            turns: vec![Turn {
+                frame: 0,
+                commands: vec![GameCommand::Leave; 256],
+            }],
+            checksums: vec![],
+        };
+        assert_matches!(
+            packet.to_bytes(),
+            Err(PacketError::TooMany {
+                field: "commands",
+                count: 256
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_packets() {
+        assert_matches!(
+            Packet::from_bytes(b"ABCD\x01\x00"),
+            Err(PacketError::InvalidHeader)
+        );
+        assert_matches!(
+            Packet::from_bytes(b"NBLS\x63\x00"),
+            Err(PacketError::UnsupportedVersion(0x63))
+        );
+        assert_matches!(Packet::from_bytes(b"NBLS\x03"), Err(PacketError::Io(_)));
+        assert_matches!(
+            Packet::from_bytes(b"NBLS\x03\x00\x00\x00\x00\x00\x01\x00\x00\x00\x00\x01\xff"),
+            Err(PacketError::UnknownCommand(0xff))
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use bevy::reflect::Reflect;
 
+use crate::random::LcgRand;
+
 #[allow(unused)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
 pub enum Race {
@@ -9,8 +11,7 @@ pub enum Race {
     Zerg,
 }
 
-#[allow(unused)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Reflect)]
 pub enum RaceSelection {
     #[default]
     Random,
@@ -28,3 +29,41 @@ impl From<Race> for RaceSelection {
         }
     }
 }
+
+impl RaceSelection {
+    /// Resolves this selection to a specific [Race], using `rand` to pick one if it is
+    /// [RaceSelection::Random]. This should be done with the synced RNG so that every client picks
+    /// the same race.
+    pub fn resolve(&self, rand: &mut LcgRand) -> Race {
+        match self {
+            RaceSelection::Random => match rand.in_range_u8(0, 2) {
+                // These match BW's race IDs
+                0 => Race::Zerg,
+                1 => Race::Terran,
+                _ => Race::Protoss,
+            },
+            RaceSelection::Protoss => Race::Protoss,
+            RaceSelection::Terran => Race::Terran,
+            RaceSelection::Zerg => Race::Zerg,
+        }
+    }
+
+    /// Returns the next selection in the list, wrapping around at the end.
+    pub fn next(&self) -> Self {
+        match self {
+            RaceSelection::Random => RaceSelection::Protoss,
+            RaceSelection::Protoss => RaceSelection::Terran,
+            RaceSelection::Terran => RaceSelection::Zerg,
+            RaceSelection::Zerg => RaceSelection::Random,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RaceSelection::Random => "Random",
+            RaceSelection::Protoss => "Protoss",
+            RaceSelection::Terran => "Terran",
+            RaceSelection::Zerg => "Zerg",
+        }
+    }
+}
@@ -29,6 +29,13 @@ impl LockedLcgRand {
         self.0.reseed(seed);
         info!("Seeded RNG with {seed}");
     }
+
+    /// Provides access to the internal [LcgRand] outside of the Fixed schedule. This should *ONLY*
+    /// be used during game initialization, where every client is guaranteed to make the same
+    /// calls in the same order!!!
+    pub fn i_know_what_im_doing_please_give_access(&mut self) -> &mut LcgRand {
+        &mut self.0
+    }
 }
 
 fn insert_lcg_rand(mut commands: Commands, mut locked_lcg_rand: ResMut<LockedLcgRand>) {
@@ -69,6 +76,11 @@ impl LcgRand {
         self.state = seed;
     }
 
+    /// Returns the current internal state of the generator.
+    pub fn state(&self) -> u32 {
+        self.state
+    }
+
     /// Generates a new random number as a [u32]. This value will be in the range `[0, 0x7fff]`.
     pub fn next_u32(&mut self) -> u32 {
         self.state = self.state.wrapping_mul(22695477).wrapping_add(1);
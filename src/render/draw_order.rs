@@ -0,0 +1,159 @@
+//! Ordering of sprites for drawing. BW draws sprites sorted by their elevation level, then by their
+//! Y position, then by the order they were created in. We reproduce that by sorting every
+//! [DrawOrder] entity each frame and translating its rank into a Z value. Images within a sprite
+//! are then placed just underneath that Z value based on their order in the sprite (see
+//! `update_construct_image_frames`).
+//!
+//! Z values are split into a few bands:
+//! - `0.0`: the terrain
+//! - `[SHADOW_Z_BASE, SPRITE_Z_BASE)`: shadows, so that they never draw over another sprite
+//! - `[SPRITE_Z_BASE, SPRITE_Z_BASE + SPRITE_Z_RANGE)`: sprites and their images
+
+use std::cmp::Ordering;
+
+use bevy::prelude::*;
+
+/// The maximum number of sprites we can give distinct Z values to. This matches BW's sprite limit,
+/// anything past it will be drawn in an arbitrary order.
+pub const MAX_ORDERED_SPRITES: usize = 2500;
+/// The amount of Z space used by all of the sprites.
+pub const SPRITE_Z_RANGE: f32 = 50.0;
+/// The lowest Z value used for shadows.
+pub const SHADOW_Z_BASE: f32 = 1.0;
+/// The Z value of the sprite that is drawn first.
+pub const SPRITE_Z_BASE: f32 = SHADOW_Z_BASE + SPRITE_Z_RANGE;
+/// The amount of Z space between each sprite.
+pub const SPRITE_Z_STEP: f32 = SPRITE_Z_RANGE / MAX_ORDERED_SPRITES as f32;
+/// The amount of Z space between each image within a sprite. This leaves room for 64 images in
+/// the top half of a sprite's Z space, with the bottom half being used for things drawn below all
+/// of its images (e.g. selection circles).
+pub const IMAGE_Z_STEP: f32 = SPRITE_Z_STEP / 128.0;
+/// Z offset (relative to a sprite) for things that should be drawn underneath all of the sprite's
+/// images, but still above any sprites ordered before it.
+pub const UNDERLAY_Z_OFFSET: f32 = -SPRITE_Z_STEP * 0.75;
+/// Z offset (relative to a sprite) for things that should be drawn over all of the sprite's images,
+/// but still underneath any sprites ordered after it (e.g. status bars).
+pub const OVERLAY_Z_OFFSET: f32 = SPRITE_Z_STEP * 0.125;
+
+/// Component that can be added to entities to make them sort like BW sprites. Entities will have
+/// their Z translation adjusted automatically.
+#[derive(Component, Debug, Copy, Clone, Default, Reflect)]
+pub struct DrawOrder {
+    /// The elevation level of the sprite, sprites with higher elevations are always drawn above
+    /// sprites with lower elevations (e.g. air units above ground units).
+    pub elevation: u8,
+    /// The order this entity was created in, used to break ties between sprites at the same
+    /// elevation and Y position. Assigned automatically when the component is added.
+    serial: u32,
+}
+
+impl DrawOrder {
+    pub fn new(elevation: u8) -> Self {
+        Self {
+            elevation,
+            serial: 0,
+        }
+    }
+}
+
+/// Counter used to assign [DrawOrder] serials in creation order.
+#[derive(Resource, Debug, Default)]
+struct NextDrawSerial(u32);
+
+pub fn plugin(app: &mut App) {
+    app.register_type::<DrawOrder>()
+        .init_resource::<NextDrawSerial>();
+    app.world_mut()
+        .register_component_hooks::<DrawOrder>()
+        .on_add(|mut world, entity, _component_id| {
+            let Some(mut next) = world.get_resource_mut::<NextDrawSerial>() else {
+                return;
+            };
+            let serial = next.0;
+            next.0 = next.0.wrapping_add(1);
+            if let Some(mut order) = world.get_mut::<DrawOrder>(entity) {
+                order.serial = serial;
+            }
+        });
+}
+
+/// Compares two sprites by the order they should be drawn in. Each sprite is paired with its Y
+/// translation, which is flipped relative to BW's coordinates (so lower values are drawn later).
+fn compare_draw_order(a: (&DrawOrder, f32), b: (&DrawOrder, f32)) -> Ordering {
+    a.0.elevation
+        .cmp(&b.0.elevation)
+        .then_with(|| b.1.total_cmp(&a.1))
+        .then_with(|| a.0.serial.cmp(&b.0.serial))
+}
+
+/// Returns the Z value for the sprite at `rank` in the draw order.
+fn z_for_rank(rank: usize) -> f32 {
+    SPRITE_Z_BASE + rank.min(MAX_ORDERED_SPRITES - 1) as f32 * SPRITE_Z_STEP
+}
+
+pub fn apply_draw_order(
+    mut query: Query<(Entity, &mut Transform, &DrawOrder)>,
+    mut sorted: Local<Vec<(Entity, DrawOrder, f32)>>,
+) {
+    sorted.clear();
+    sorted.extend(
+        query
+            .iter()
+            .map(|(entity, transform, &order)| (entity, order, transform.translation.y)),
+    );
+    sorted.sort_unstable_by(|a, b| compare_draw_order((&a.1, a.2), (&b.1, b.2)));
+
+    for (rank, &(entity, _, _)) in sorted.iter().enumerate() {
+        let Ok((_, mut transform, _)) = query.get_mut(entity) else {
+            continue;
+        };
+        let new_z = z_for_rank(rank);
+        if new_z != transform.translation.z {
+            transform.translation.z = new_z;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(elevation: u8, serial: u32) -> DrawOrder {
+        DrawOrder { elevation, serial }
+    }
+
+    #[test]
+    fn sprite_ordering() {
+        let ground = order(4, 0);
+        let ground_later = order(4, 1);
+        let air = order(12, 2);
+
+        // Elevation takes precedence over position
+        assert_eq!(
+            compare_draw_order((&air, 100.0), (&ground, -100.0)),
+            Ordering::Greater
+        );
+        // Lower on the screen is drawn later
+        assert_eq!(
+            compare_draw_order((&ground_later, 100.0), (&ground, -100.0)),
+            Ordering::Less
+        );
+        // Creation order breaks ties
+        assert_eq!(
+            compare_draw_order((&ground_later, 5.0), (&ground, 5.0)),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn z_bands() {
+        assert_eq!(z_for_rank(0), SPRITE_Z_BASE);
+        assert!(z_for_rank(MAX_ORDERED_SPRITES + 10) < SPRITE_Z_BASE + SPRITE_Z_RANGE);
+        // Images, underlays, and overlays of a sprite stay between the sprites ordered before and
+        // after it
+        assert!(z_for_rank(1) + UNDERLAY_Z_OFFSET > z_for_rank(0) + OVERLAY_Z_OFFSET);
+        assert!(z_for_rank(1) - 63.0 * IMAGE_Z_STEP > z_for_rank(1) + UNDERLAY_Z_OFFSET);
+        // Shadows stay within their band
+        assert!(z_for_rank(0) - SPRITE_Z_RANGE >= SHADOW_Z_BASE);
+    }
+}
@@ -0,0 +1,160 @@
+//! Selection of the visual effect used to draw an image, based on its [RenderStyle]. The effects
+//! themselves are implemented in `shaders/image_effects.wgsl`, which is shared by the materials
+//! used for SD and HD images.
+
+use crate::{
+    gamedata::RenderStyle, gameplay::players::PlayerRelation, render::draw_order::SPRITE_Z_RANGE,
+};
+
+/// How far shadows are moved back (in Z) from the image that would normally be drawn. This moves
+/// them out of the band sprites are sorted in (see `render::draw_order`), which puts shadows
+/// underneath every sprite (but still above the terrain), so they never draw over top of another
+/// construct.
+pub const SHADOW_Z_OFFSET: f32 = -SPRITE_Z_RANGE;
+
+/// A visual effect applied when drawing an image. The discriminants must match the `EFFECT_*`
+/// constants in the shader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageEffect {
+    #[default]
+    Normal = 0,
+    /// The image isn't drawn at all.
+    Hidden = 1,
+    /// A soft, partially transparent black. Overlapping shadows stack, making them darker (as in
+    /// BW).
+    Shadow = 2,
+    /// A cloaked image that is visible to the player viewing it (because they own it or are allied
+    /// with its owner).
+    Cloaked = 3,
+    /// A cloaked image that belongs to an enemy, which only shows as a faint distortion.
+    CloakDistortion = 4,
+    /// A blue tint, used for hallucinated units when viewed by their owner.
+    Hallucination = 5,
+    /// A bright flash, used when Protoss buildings warp in.
+    WarpFlash = 6,
+    /// A shimmering, translucent blue used for the EMP shockwave.
+    EmpShockwave = 7,
+    /// Only the outline of the image is drawn.
+    Outline = 8,
+    /// Only the bounding rectangle of the current frame is drawn.
+    BoundingRect = 9,
+}
+
+impl ImageEffect {
+    /// Returns the effect to draw an image with, given its render style and the relation of its
+    /// owner to the local player.
+    pub fn for_style(style: Option<RenderStyle>, relation: PlayerRelation) -> Self {
+        let Some(style) = style else {
+            return ImageEffect::Normal;
+        };
+
+        match style {
+            RenderStyle::Shadow => ImageEffect::Shadow,
+            // NOTE(tec27): BW picks between the cloak styles based on who is viewing the image, so
+            // we do the same rather than trusting the style the image was created with
+            RenderStyle::EnemyUnitCloak
+            | RenderStyle::OwnUnitCloak
+            | RenderStyle::AllyUnitCloak
+            | RenderStyle::OwnUnitCloak2
+            | RenderStyle::OwnUnitCloakDrawOnly => match relation {
+                PlayerRelation::Own | PlayerRelation::Ally => ImageEffect::Cloaked,
+                PlayerRelation::Enemy | PlayerRelation::Neutral => ImageEffect::CloakDistortion,
+            },
+            // BW crashes if it tries to draw one of these, we just skip drawing it instead
+            RenderStyle::Crash => ImageEffect::Hidden,
+            RenderStyle::EmpShockwave => ImageEffect::EmpShockwave,
+            RenderStyle::WarpFlash | RenderStyle::WarpFlash2 => ImageEffect::WarpFlash,
+            RenderStyle::Outline => ImageEffect::Outline,
+            RenderStyle::BoundingRect => ImageEffect::BoundingRect,
+            // Hallucinations only look different to the player that owns them, otherwise they'd be
+            // pretty useless
+            RenderStyle::Hallucination => match relation {
+                PlayerRelation::Own => ImageEffect::Hallucination,
+                _ => ImageEffect::Normal,
+            },
+            // TODO(tec27): UseRemapping should apply the image's remapping table (e.g. for flames)
+            // and PlayerSide should draw in the player's color
+            RenderStyle::OverlayOnTarget
+            | RenderStyle::UseRemapping
+            | RenderStyle::HpFloatDraw
+            | RenderStyle::PlayerSide => ImageEffect::Normal,
+        }
+    }
+
+    /// Returns the value that identifies this effect in the shader.
+    pub const fn shader_id(&self) -> u32 {
+        *self as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cloak_effects() {
+        for style in [
+            RenderStyle::EnemyUnitCloak,
+            RenderStyle::OwnUnitCloak,
+            RenderStyle::AllyUnitCloak,
+            RenderStyle::OwnUnitCloak2,
+            RenderStyle::OwnUnitCloakDrawOnly,
+        ] {
+            assert_eq!(
+                ImageEffect::for_style(Some(style), PlayerRelation::Own),
+                ImageEffect::Cloaked
+            );
+            assert_eq!(
+                ImageEffect::for_style(Some(style), PlayerRelation::Ally),
+                ImageEffect::Cloaked
+            );
+            assert_eq!(
+                ImageEffect::for_style(Some(style), PlayerRelation::Enemy),
+                ImageEffect::CloakDistortion
+            );
+        }
+    }
+
+    #[test]
+    fn hallucination_effects() {
+        assert_eq!(
+            ImageEffect::for_style(Some(RenderStyle::Hallucination), PlayerRelation::Own),
+            ImageEffect::Hallucination
+        );
+        assert_eq!(
+            ImageEffect::for_style(Some(RenderStyle::Hallucination), PlayerRelation::Enemy),
+            ImageEffect::Normal
+        );
+    }
+
+    #[test]
+    fn other_effects() {
+        let relation = PlayerRelation::Enemy;
+        assert_eq!(ImageEffect::for_style(None, relation), ImageEffect::Normal);
+        assert_eq!(
+            ImageEffect::for_style(Some(RenderStyle::Shadow), relation),
+            ImageEffect::Shadow
+        );
+        assert_eq!(
+            ImageEffect::for_style(Some(RenderStyle::Crash), relation),
+            ImageEffect::Hidden
+        );
+        assert_eq!(
+            ImageEffect::for_style(Some(RenderStyle::WarpFlash2), relation),
+            ImageEffect::WarpFlash
+        );
+        assert_eq!(
+            ImageEffect::for_style(Some(RenderStyle::EmpShockwave), relation),
+            ImageEffect::EmpShockwave
+        );
+        assert_eq!(
+            ImageEffect::for_style(Some(RenderStyle::Outline), relation),
+            ImageEffect::Outline
+        );
+        assert_eq!(
+            ImageEffect::for_style(Some(RenderStyle::BoundingRect), relation),
+            ImageEffect::BoundingRect
+        );
+        assert_eq!(ImageEffect::BoundingRect.shader_id(), 9);
+    }
+}
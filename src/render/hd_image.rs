@@ -0,0 +1,430 @@
+//! Rendering for HD construct images. Anims can have a `teamcolor` layer, which specifies areas of
+//! the image that should be tinted with the color of the player that owns it. Images are also drawn
+//! with the [ImageEffect] specified by their render style, and (if enabled) lit using their
+//! lighting layers (see [AnimLightingLayers]).
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{
+            AsBindGroup, Extent3d, ShaderRef, ShaderType, TextureDimension, TextureFormat,
+        },
+        view::NoFrustumCulling,
+    },
+    sprite::{Material2d, Material2dPlugin, Mesh2dHandle},
+};
+
+use crate::{
+    gamedata::{AnimLightingLayers, RenderStyle, TeamColorMask},
+    gameplay::{
+        constructs::{owner_of, ConstructImage, OwnedConstruct},
+        players::{PlayerColors, PlayerRelations},
+    },
+    settings::{AssetQuality, GameSettings},
+};
+
+use super::effects::ImageEffect;
+
+const HD_IMAGE_SHADER_PATH: &str = "shaders/hd_image.wgsl";
+
+/// The maximum number of dynamic lights that can affect HD images at once. Must match `MAX_LIGHTS`
+/// in the shader.
+const MAX_LIGHTS: usize = 8;
+/// The color of the light given off by images that use [RenderStyle::UseRemapping] (fire,
+/// explosions, etc.), with the intensity in the alpha channel.
+const REMAPPING_LIGHT_COLOR: LinearRgba = LinearRgba::new(1.0, 0.6, 0.3, 1.5);
+/// How far light from an image reaches, relative to the size of its current frame.
+const LIGHT_RADIUS_SCALE: f32 = 1.5;
+
+pub struct HdImagePlugin;
+
+impl Plugin for HdImagePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(Material2dPlugin::<HdImageMaterial>::default())
+            .add_systems(Startup, setup_hd_image_assets)
+            .add_systems(
+                Update,
+                (
+                    add_hd_image_materials,
+                    update_hd_image_materials,
+                    apply_lighting_setting.run_if(resource_changed::<GameSettings>),
+                    update_dynamic_lights.run_if(using_hd_lighting),
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn using_hd_lighting(settings: Res<GameSettings>) -> bool {
+    settings.hd_lighting && settings.asset_quality != AssetQuality::Standard
+}
+
+/// Uniform parameters for a [HdImageMaterial]. Must match the `HdImageParams` struct in the
+/// shader.
+#[derive(ShaderType, Debug, Clone, Copy, Default)]
+pub struct HdImageParams {
+    /// The area of the current frame within the layer textures (x, y, width, height).
+    pub frame: Vec4,
+    pub anchor: Vec2,
+    pub flip_x: u32,
+    /// The [ImageEffect] to draw with (see [ImageEffect::shader_id]).
+    pub effect: u32,
+    /// A color to multiply the final color by.
+    pub color: Vec4,
+    /// The color to tint the areas specified by the mask with (also used for outlines).
+    pub team_color: Vec4,
+    /// Whether to apply lighting (1) or not (0).
+    pub lighting: u32,
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct HdImageMaterial {
+    #[uniform(0)]
+    pub params: HdImageParams,
+    #[texture(1)]
+    #[sampler(2)]
+    pub diffuse: Handle<Image>,
+    #[texture(3)]
+    #[sampler(4)]
+    pub mask: Handle<Image>,
+    #[texture(5)]
+    #[sampler(6)]
+    pub emissive: Handle<Image>,
+    #[texture(7)]
+    #[sampler(8)]
+    pub normal: Handle<Image>,
+    #[texture(9)]
+    #[sampler(10)]
+    pub specular: Handle<Image>,
+    #[texture(11)]
+    #[sampler(12)]
+    pub ao_depth: Handle<Image>,
+    /// The current dynamic lights (see [HdImageAssets::lights]).
+    #[texture(13, sample_type = "float", filterable = false)]
+    pub lights: Handle<Image>,
+}
+
+impl Material2d for HdImageMaterial {
+    fn vertex_shader() -> ShaderRef {
+        HD_IMAGE_SHADER_PATH.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        HD_IMAGE_SHADER_PATH.into()
+    }
+}
+
+/// Assets shared by all HD images.
+#[derive(Resource, Debug)]
+struct HdImageAssets {
+    /// The unit quad that images are drawn with.
+    mesh: Mesh2dHandle,
+    /// A 1x1 texture that is fully transparent black, used for missing team color masks and
+    /// emissive/specular layers.
+    empty: Handle<Image>,
+    /// A 1x1 texture of a normal pointing straight out of the screen.
+    flat_normal: Handle<Image>,
+    /// A 1x1 texture with no ambient occlusion.
+    no_occlusion: Handle<Image>,
+    /// A `MAX_LIGHTS`x2 texture of the current dynamic lights. The first row contains the position
+    /// and radius of each light, the second contains its color and intensity. Lights with a radius
+    /// of 0 are unused.
+    lights: Handle<Image>,
+}
+
+fn solid_image(color: [u8; 4]) -> Image {
+    Image::new(
+        Extent3d {
+            width: 1,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        color.to_vec(),
+        TextureFormat::Rgba8Unorm,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+fn setup_hd_image_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let lights = Image::new(
+        Extent3d {
+            width: MAX_LIGHTS as u32,
+            height: 2,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        pack_lights(&[]),
+        TextureFormat::Rgba32Float,
+        RenderAssetUsages::default(),
+    );
+
+    commands.insert_resource(HdImageAssets {
+        mesh: meshes.add(Rectangle::new(1.0, 1.0)).into(),
+        empty: images.add(solid_image([0, 0, 0, 0])),
+        flat_normal: images.add(solid_image([128, 128, 255, 255])),
+        no_occlusion: images.add(solid_image([255, 255, 255, 255])),
+        lights: images.add(lights),
+    });
+}
+
+/// System that switches construct images (and anything else with a [TeamColorMask]) from being
+/// drawn as regular sprites to being drawn with a [HdImageMaterial].
+fn add_hd_image_materials(
+    mut commands: Commands,
+    query: Query<
+        (
+            Entity,
+            &Handle<Image>,
+            Option<&TeamColorMask>,
+            Option<&AnimLightingLayers>,
+        ),
+        (
+            Or<(With<ConstructImage>, With<TeamColorMask>)>,
+            Without<Handle<HdImageMaterial>>,
+        ),
+    >,
+    mut materials: ResMut<Assets<HdImageMaterial>>,
+    assets: Res<HdImageAssets>,
+    settings: Res<GameSettings>,
+) {
+    for (entity, diffuse, mask, lighting) in &query {
+        let layer = |get: fn(&AnimLightingLayers) -> &Option<Handle<Image>>,
+                     fallback: &Handle<Image>| {
+            lighting
+                .and_then(|l| get(l).clone())
+                .unwrap_or_else(|| fallback.clone())
+        };
+
+        let material = materials.add(HdImageMaterial {
+            params: HdImageParams {
+                color: Vec4::ONE,
+                team_color: Vec4::ONE,
+                lighting: settings.hd_lighting as u32,
+                ..default()
+            },
+            diffuse: diffuse.clone(),
+            mask: mask.map_or_else(|| assets.empty.clone(), |m| m.0.clone()),
+            emissive: layer(|l| &l.emissive, &assets.empty),
+            normal: layer(|l| &l.normal, &assets.flat_normal),
+            specular: layer(|l| &l.specular, &assets.empty),
+            ao_depth: layer(|l| &l.ao_depth, &assets.no_occlusion),
+            lights: assets.lights.clone(),
+        });
+        commands
+            .entity(entity)
+            .insert((
+                assets.mesh.clone(),
+                material,
+                // NOTE(tec27): The mesh gets resized to fit each frame in the shader, so its bounds
+                // aren't accurate for culling
+                NoFrustumCulling,
+            ))
+            // The diffuse texture is drawn by the material instead
+            .remove::<Handle<Image>>();
+    }
+}
+
+/// Returns the color that should be used to tint an image owned by `player`. Images without an
+/// owner aren't tinted.
+fn team_color_for(player_colors: &PlayerColors, owner: Option<u8>) -> Color {
+    owner
+        .and_then(|o| player_colors.get(o))
+        .map_or(Color::WHITE, |c| c.color())
+}
+
+/// System that updates the material for HD images when their frame or display settings change.
+fn update_hd_image_materials(
+    query: Query<
+        (
+            Entity,
+            &Handle<HdImageMaterial>,
+            &TextureAtlas,
+            &Sprite,
+            Option<&ConstructImage>,
+        ),
+        Or<(
+            Added<Handle<HdImageMaterial>>,
+            Changed<TextureAtlas>,
+            Changed<Sprite>,
+            Changed<ConstructImage>,
+        )>,
+    >,
+    q_parents: Query<&Parent>,
+    q_owners: Query<&OwnedConstruct>,
+    player_colors: PlayerColors,
+    player_relations: PlayerRelations,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+    mut materials: ResMut<Assets<HdImageMaterial>>,
+) {
+    for (entity, handle, atlas, sprite, image) in &query {
+        let Some(material) = materials.get_mut(handle) else {
+            continue;
+        };
+        let Some(rect) = layouts
+            .get(&atlas.layout)
+            .and_then(|layout| layout.textures.get(atlas.index))
+        else {
+            continue;
+        };
+
+        // TODO(tec27): Update this when ownership changes as well (e.g. from triggers giving units
+        // to another player)
+        let owner = owner_of(entity, &q_parents, &q_owners);
+        let effect = ImageEffect::for_style(
+            image.and_then(|i| i.render_style),
+            player_relations.relation_to_local(owner),
+        );
+        material.params = HdImageParams {
+            frame: Vec4::new(
+                rect.min.x as f32,
+                rect.min.y as f32,
+                rect.width() as f32,
+                rect.height() as f32,
+            ),
+            anchor: sprite.anchor.as_vec(),
+            flip_x: sprite.flip_x as u32,
+            effect: effect.shader_id(),
+            color: sprite.color.to_linear().to_vec4(),
+            team_color: team_color_for(&player_colors, owner).to_linear().to_vec4(),
+            lighting: material.params.lighting,
+        };
+    }
+}
+
+/// System that turns lighting on or off for all HD images when the setting changes.
+fn apply_lighting_setting(
+    settings: Res<GameSettings>,
+    mut materials: ResMut<Assets<HdImageMaterial>>,
+) {
+    let lighting = settings.hd_lighting as u32;
+    let changed = materials
+        .iter()
+        .filter(|(_, m)| m.params.lighting != lighting)
+        .map(|(id, _)| id)
+        .collect::<Vec<_>>();
+    for id in changed {
+        if let Some(material) = materials.get_mut(id) {
+            material.params.lighting = lighting;
+        }
+    }
+}
+
+/// A light that affects nearby HD images.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DynamicLight {
+    position: Vec2,
+    radius: f32,
+    /// The color of the light, with its intensity in the alpha channel.
+    color: LinearRgba,
+}
+
+/// Returns the (up to) `MAX_LIGHTS` lights closest to `center`.
+fn nearest_lights(mut lights: Vec<DynamicLight>, center: Vec2) -> Vec<DynamicLight> {
+    lights.sort_by(|a, b| {
+        a.position
+            .distance_squared(center)
+            .total_cmp(&b.position.distance_squared(center))
+    });
+    lights.truncate(MAX_LIGHTS);
+    lights
+}
+
+/// Packs lights into the data for the lights texture (see [HdImageAssets::lights]).
+fn pack_lights(lights: &[DynamicLight]) -> Vec<u8> {
+    let mut positions = [[0.0f32; 4]; MAX_LIGHTS];
+    let mut colors = [[0.0f32; 4]; MAX_LIGHTS];
+    for (i, light) in lights.iter().take(MAX_LIGHTS).enumerate() {
+        positions[i] = [light.position.x, light.position.y, light.radius, 0.0];
+        colors[i] = light.color.to_f32_array();
+    }
+
+    positions
+        .iter()
+        .chain(colors.iter())
+        .flatten()
+        .flat_map(|v| v.to_le_bytes())
+        .collect()
+}
+
+/// System that updates the dynamic lights from the images that give off light (fire, explosions,
+/// etc.).
+fn update_dynamic_lights(
+    q_sources: Query<(
+        &ConstructImage,
+        &TextureAtlas,
+        &GlobalTransform,
+        &InheritedVisibility,
+    )>,
+    q_camera: Query<&GlobalTransform, With<Camera2d>>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+    assets: Res<HdImageAssets>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    // TODO(tec27): Emissive layers (e.g. Protoss energy) could give off light as well
+    let lights = q_sources
+        .iter()
+        .filter(|(image, _, _, visibility)| {
+            image.render_style == Some(RenderStyle::UseRemapping) && visibility.get()
+        })
+        .filter_map(|(_, atlas, transform, _)| {
+            let rect = layouts.get(&atlas.layout)?.textures.get(atlas.index)?;
+            Some(DynamicLight {
+                position: transform.translation().truncate(),
+                radius: rect.width().max(rect.height()) as f32 * LIGHT_RADIUS_SCALE,
+                color: REMAPPING_LIGHT_COLOR,
+            })
+        })
+        .collect::<Vec<_>>();
+    let center = q_camera
+        .get_single()
+        .map_or(Vec2::ZERO, |t| t.translation().truncate());
+
+    let data = pack_lights(&nearest_lights(lights, center));
+    // Avoid re-uploading the texture if nothing has changed
+    if images.get(&assets.lights).is_some_and(|i| i.data != data) {
+        if let Some(image) = images.get_mut(&assets.lights) {
+            image.data = data;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn light(x: f32) -> DynamicLight {
+        DynamicLight {
+            position: Vec2::new(x, 0.0),
+            radius: 10.0,
+            color: LinearRgba::WHITE,
+        }
+    }
+
+    #[test]
+    fn light_selection() {
+        let lights = (0..20).map(|i| light(i as f32 * 10.0)).collect::<Vec<_>>();
+        let nearest = nearest_lights(lights, Vec2::new(100.0, 0.0));
+        assert_eq!(nearest.len(), MAX_LIGHTS);
+        assert_eq!(nearest[0], light(100.0));
+        assert!(nearest
+            .iter()
+            .all(|l| l.position.distance(Vec2::new(100.0, 0.0)) <= 40.0));
+    }
+
+    #[test]
+    fn light_packing() {
+        let data = pack_lights(&[light(5.0)]);
+        assert_eq!(data.len(), MAX_LIGHTS * 2 * 4 * 4);
+        let read = |i: usize| f32::from_le_bytes(data[i * 4..i * 4 + 4].try_into().unwrap());
+        assert_eq!((read(0), read(1), read(2)), (5.0, 0.0, 10.0));
+        // Unused lights have a radius of 0
+        assert_eq!(read(6), 0.0);
+        assert_eq!(read(MAX_LIGHTS * 4), 1.0);
+    }
+}
@@ -2,17 +2,27 @@ use bevy::{prelude::*, transform::TransformSystem};
 
 use crate::maps::position::apply_position_to_transform;
 
-use self::ysort::YSort;
+use self::{hd_image::HdImagePlugin, paletted::PalettedSpritePlugin, status_bar::StatusBarPlugin};
 
-pub mod ysort;
+pub mod draw_order;
+pub mod effects;
+pub mod hd_image;
+pub mod paletted;
+pub mod status_bar;
 
 pub struct RenderPlugin;
 
 impl Plugin for RenderPlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<YSort>().add_systems(
+        app.add_plugins((
+            draw_order::plugin,
+            HdImagePlugin,
+            PalettedSpritePlugin,
+            StatusBarPlugin,
+        ))
+        .add_systems(
             PostUpdate,
-            ysort::y_sort
+            draw_order::apply_draw_order
                 .before(TransformSystem::TransformPropagate)
                 // TODO(tec27): Add a custom schedule for this instead
                 .after(apply_position_to_transform),
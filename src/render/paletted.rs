@@ -0,0 +1,458 @@
+//! Rendering for SD images, which are stored as palette indices (see [GrpAsset]) and colored at
+//! render time using the current tileset's palette and a set of remapping tables.
+
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{
+            AsBindGroup, Extent3d, ShaderRef, ShaderType, TextureDimension, TextureFormat,
+        },
+        view::NoFrustumCulling,
+    },
+    sprite::{Material2d, Material2dPlugin, Mesh2dHandle},
+};
+
+use crate::{
+    gamedata::{
+        grp::GrpAsset,
+        palette::{PaletteAsset, PcxAsset, PALETTE_SIZE},
+    },
+    gameplay::{
+        constructs::{owner_of, ConstructImage, OwnedConstruct},
+        players::{PlayerColor, PlayerColors, PlayerRelations},
+    },
+    maps::{game_map::GameMapTileset, tileset_dark_table_path, tileset_palette_path},
+    settings::{AssetQuality, GameSettings},
+};
+
+use super::effects::ImageEffect;
+
+const PALETTED_SHADER_PATH: &str = "shaders/paletted.wgsl";
+
+/// The remapping table row that leaves palette indices unchanged.
+const IDENTITY_REMAP_ROW: u32 = 0;
+/// The first palette index that gets replaced with a player's team color.
+const TEAM_COLOR_START: usize = 8;
+/// How many palette indices are replaced with a player's team color.
+const TEAM_COLOR_COUNT: usize = 8;
+/// The row of the tileset's darkening table that BW remaps the colors underneath shadows with.
+const SHADOW_DARK_ROW: u32 = 17;
+/// The row of the tileset's darkening table used for cloaked images that the local player can see.
+/// This is lighter than shadows, so that the terrain underneath is still easy to make out.
+const CLOAK_DARK_ROW: u32 = 24;
+/// How much shadows darken what's behind them until the darkening table has loaded.
+const DEFAULT_SHADOW_DARKNESS: f32 = 0.5;
+/// How much cloaked images darken what's behind them until the darkening table has loaded.
+const DEFAULT_CLOAK_DARKNESS: f32 = 0.3;
+
+pub struct PalettedSpritePlugin;
+
+impl Plugin for PalettedSpritePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(Material2dPlugin::<PalettedMaterial>::default())
+            .add_systems(Startup, setup_sd_palette)
+            .add_systems(
+                Update,
+                (
+                    load_tileset_palette,
+                    update_sd_palette,
+                    add_paletted_materials,
+                    update_paletted_materials,
+                )
+                    .chain()
+                    .run_if(using_sd_graphics),
+            );
+    }
+}
+
+fn using_sd_graphics(settings: Res<GameSettings>) -> bool {
+    settings.asset_quality == AssetQuality::Standard
+}
+
+/// Uniform parameters for a [PalettedMaterial]. Must match the `PalettedParams` struct in the
+/// shader.
+#[derive(ShaderType, Debug, Clone, Copy, Default)]
+pub struct PalettedParams {
+    /// The area of the current frame within the indices texture (x, y, width, height).
+    pub frame: Vec4,
+    pub anchor: Vec2,
+    pub flip_x: u32,
+    /// Which row of the remapping table to use.
+    pub remap_row: u32,
+    /// A color to multiply the final color by.
+    pub color: Vec4,
+    /// The [ImageEffect] to draw with (see [ImageEffect::shader_id]).
+    pub effect: u32,
+    /// The color used for effects that draw outlines.
+    pub effect_color: Vec4,
+    /// How much to darken what's behind the image, for effects that remap the colors underneath
+    /// the image rather than drawing it (shadows and visible cloaked images).
+    pub darkness: f32,
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct PalettedMaterial {
+    #[uniform(0)]
+    pub params: PalettedParams,
+    #[texture(1, sample_type = "u_int")]
+    pub indices: Handle<Image>,
+    #[texture(2)]
+    pub palette: Handle<Image>,
+    #[texture(3, sample_type = "u_int")]
+    pub remap: Handle<Image>,
+}
+
+impl Material2d for PalettedMaterial {
+    fn vertex_shader() -> ShaderRef {
+        PALETTED_SHADER_PATH.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        PALETTED_SHADER_PATH.into()
+    }
+}
+
+/// The textures shared by all SD images for looking up their colors.
+#[derive(Resource, Debug)]
+pub struct SdPalette {
+    /// The current palette, as a `PALETTE_SIZE`x1 texture.
+    pub palette: Handle<Image>,
+    /// The remapping tables, as a `PALETTE_SIZE`xN texture of palette indices. Row 0 is the
+    /// identity mapping, and each row after that is the team color mapping for a [PlayerColor].
+    pub remap: Handle<Image>,
+    /// The unit quad that all SD images are drawn with.
+    mesh: Mesh2dHandle,
+    /// The palette of the current map's tileset, if one has been requested.
+    tileset_palette: Option<Handle<PaletteAsset>>,
+    /// The table that maps [PlayerColor]s to palette indices.
+    team_colors: Handle<PcxAsset>,
+    /// The darkening table of the current map's tileset, if one has been requested.
+    tileset_dark_table: Option<Handle<PcxAsset>>,
+    /// How much shadows darken what's behind them, derived from the darkening table.
+    shadow_darkness: f32,
+    /// How much visible cloaked images darken what's behind them, derived from the darkening table.
+    cloak_darkness: f32,
+}
+
+impl SdPalette {
+    /// Returns how much an image drawn with `effect` darkens what's behind it.
+    fn darkness(&self, effect: ImageEffect) -> f32 {
+        match effect {
+            ImageEffect::Shadow => self.shadow_darkness,
+            ImageEffect::Cloaked => self.cloak_darkness,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Returns the row of the remapping table that applies the team color for `color`.
+fn team_color_remap_row(color: PlayerColor) -> u32 {
+    color as u32 + 1
+}
+
+/// Returns how much remapping colors through `row` of the darkening table `dark` darkens them on
+/// average (0 leaves them unchanged, 1 makes them black). BW remaps the palette indices of what's
+/// already been drawn, which we can't do with full color terrain, so this is used to darken what's
+/// behind the image by the same amount instead.
+fn darkening_amount(dark: &PcxAsset, palette: &PaletteAsset, row: u32) -> Option<f32> {
+    let row = row.min(dark.height.checked_sub(1)?);
+    let luminance = |index: u8| {
+        palette
+            .colors
+            .get(index as usize)
+            .map_or(0.0, |&[r, g, b]| {
+                0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32
+            })
+    };
+    let (mut original, mut darkened) = (0.0, 0.0);
+    for i in 0..PALETTE_SIZE.min(dark.width as usize) {
+        original += luminance(i as u8);
+        darkened += luminance(dark.get(i as u32, row)?);
+    }
+    (original > 0.0).then(|| (1.0 - darkened / original).clamp(0.0, 1.0))
+}
+
+fn setup_sd_palette(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    asset_server: Res<AssetServer>,
+    settings: Res<GameSettings>,
+) {
+    if settings.asset_quality != AssetQuality::Standard {
+        return;
+    }
+
+    // Until the actual palette loads, we use a greyscale one so that things are at least visible
+    let palette = Image::new(
+        Extent3d {
+            width: PALETTE_SIZE as u32,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        (0..PALETTE_SIZE)
+            .flat_map(|i| [i as u8, i as u8, i as u8, 255])
+            .collect(),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    let rows = PlayerColor::ALL.len() + 1;
+    let remap = Image::new(
+        Extent3d {
+            width: PALETTE_SIZE as u32,
+            height: rows as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        (0..rows)
+            .flat_map(|_| (0..PALETTE_SIZE).map(|i| i as u8))
+            .collect(),
+        TextureFormat::R8Uint,
+        RenderAssetUsages::default(),
+    );
+
+    commands.insert_resource(SdPalette {
+        palette: images.add(palette),
+        remap: images.add(remap),
+        mesh: meshes.add(Rectangle::new(1.0, 1.0)).into(),
+        tileset_palette: None,
+        team_colors: asset_server.load("casc-extracted/game/tunit.pcx"),
+        tileset_dark_table: None,
+        shadow_darkness: DEFAULT_SHADOW_DARKNESS,
+        cloak_darkness: DEFAULT_CLOAK_DARKNESS,
+    });
+}
+
+/// System that loads the palette and darkening table for the tileset of a newly loaded map.
+fn load_tileset_palette(
+    q_tileset: Query<&GameMapTileset, Added<GameMapTileset>>,
+    mut sd_palette: ResMut<SdPalette>,
+    asset_server: Res<AssetServer>,
+) {
+    if let Some(tileset) = q_tileset.iter().last() {
+        sd_palette.tileset_palette = Some(asset_server.load(tileset_palette_path(tileset.0)));
+        sd_palette.tileset_dark_table = Some(asset_server.load(tileset_dark_table_path(tileset.0)));
+    }
+}
+
+/// System that copies the palette and team color tables into the shared textures once they've
+/// loaded, and calculates how much shadows and cloaked images darken things from the darkening
+/// table.
+fn update_sd_palette(
+    mut palette_events: EventReader<AssetEvent<PaletteAsset>>,
+    mut pcx_events: EventReader<AssetEvent<PcxAsset>>,
+    mut sd_palette: ResMut<SdPalette>,
+    palettes: Res<Assets<PaletteAsset>>,
+    pcxs: Res<Assets<PcxAsset>>,
+    mut images: ResMut<Assets<Image>>,
+    mut q_sprites: Query<&mut Sprite, With<Handle<PalettedMaterial>>>,
+) {
+    let pcxs_changed = pcx_events
+        .read()
+        .filter_map(|e| match e {
+            AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let palette_changed = sd_palette.is_changed()
+        || palette_events.read().any(|e| {
+            matches!(
+                e,
+                AssetEvent::LoadedWithDependencies { .. } | AssetEvent::Modified { .. }
+            )
+        });
+    let dark_table_changed = sd_palette
+        .tileset_dark_table
+        .as_ref()
+        .is_some_and(|h| pcxs_changed.contains(&h.id()));
+    if palette_changed || dark_table_changed {
+        let palette = sd_palette
+            .tileset_palette
+            .as_ref()
+            .and_then(|handle| palettes.get(handle));
+        let dark_table = sd_palette
+            .tileset_dark_table
+            .as_ref()
+            .and_then(|handle| pcxs.get(handle));
+        if let (Some(palette), Some(dark_table)) = (palette, dark_table) {
+            let shadow = darkening_amount(dark_table, palette, SHADOW_DARK_ROW)
+                .unwrap_or(DEFAULT_SHADOW_DARKNESS);
+            let cloak = darkening_amount(dark_table, palette, CLOAK_DARK_ROW)
+                .unwrap_or(DEFAULT_CLOAK_DARKNESS);
+            if shadow != sd_palette.shadow_darkness || cloak != sd_palette.cloak_darkness {
+                let sd_palette = sd_palette.bypass_change_detection();
+                sd_palette.shadow_darkness = shadow;
+                sd_palette.cloak_darkness = cloak;
+                // Make sure materials that were already created pick up the new values
+                for mut sprite in &mut q_sprites {
+                    sprite.set_changed();
+                }
+            }
+        }
+    }
+
+    if palette_changed {
+        let palette = sd_palette
+            .tileset_palette
+            .as_ref()
+            .and_then(|handle| palettes.get(handle));
+        if let (Some(palette), Some(image)) = (palette, images.get_mut(&sd_palette.palette)) {
+            image.data = palette
+                .colors
+                .iter()
+                .flat_map(|&[r, g, b]| [r, g, b, 255])
+                .collect();
+        }
+    }
+
+    if pcxs_changed.contains(&sd_palette.team_colors.id()) {
+        let team_colors = pcxs.get(&sd_palette.team_colors);
+        if let (Some(team_colors), Some(image)) = (team_colors, images.get_mut(&sd_palette.remap)) {
+            for color in PlayerColor::ALL {
+                let row = team_color_remap_row(color) as usize * PALETTE_SIZE;
+                for i in 0..TEAM_COLOR_COUNT {
+                    let x = (color as usize * TEAM_COLOR_COUNT + i) as u32;
+                    if let Some(index) = team_colors.get(x, 0) {
+                        image.data[row + TEAM_COLOR_START + i] = index;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// System that adds a [PalettedMaterial] to images whose GRP has finished loading.
+fn add_paletted_materials(
+    mut commands: Commands,
+    query: Query<(Entity, &Handle<GrpAsset>), Without<Handle<PalettedMaterial>>>,
+    grp_assets: Res<Assets<GrpAsset>>,
+    mut materials: ResMut<Assets<PalettedMaterial>>,
+    sd_palette: Res<SdPalette>,
+) {
+    for (entity, handle) in &query {
+        let Some(grp) = grp_assets.get(handle) else {
+            continue;
+        };
+        let material = materials.add(PalettedMaterial {
+            params: PalettedParams {
+                color: Vec4::ONE,
+                effect_color: Vec4::ONE,
+                ..default()
+            },
+            indices: grp.indices.clone(),
+            palette: sd_palette.palette.clone(),
+            remap: sd_palette.remap.clone(),
+        });
+        commands.entity(entity).insert((
+            sd_palette.mesh.clone(),
+            material,
+            // NOTE(tec27): The mesh gets resized to fit each frame in the shader, so its bounds
+            // aren't accurate for culling
+            NoFrustumCulling,
+        ));
+    }
+}
+
+/// System that updates the material for SD images when their frame or display settings change.
+fn update_paletted_materials(
+    query: Query<
+        (
+            Entity,
+            &Handle<PalettedMaterial>,
+            &TextureAtlas,
+            &Sprite,
+            Option<&ConstructImage>,
+        ),
+        Or<(
+            Added<Handle<PalettedMaterial>>,
+            Changed<TextureAtlas>,
+            Changed<Sprite>,
+            Changed<ConstructImage>,
+        )>,
+    >,
+    q_parents: Query<&Parent>,
+    q_owners: Query<&OwnedConstruct>,
+    player_colors: PlayerColors,
+    player_relations: PlayerRelations,
+    sd_palette: Res<SdPalette>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+    mut materials: ResMut<Assets<PalettedMaterial>>,
+) {
+    for (entity, handle, atlas, sprite, image) in &query {
+        let Some(material) = materials.get_mut(handle) else {
+            continue;
+        };
+        let Some(rect) = layouts
+            .get(&atlas.layout)
+            .and_then(|layout| layout.textures.get(atlas.index))
+        else {
+            continue;
+        };
+
+        let owner = owner_of(entity, &q_parents, &q_owners);
+        let color = owner.and_then(|o| player_colors.get(o));
+        let effect = ImageEffect::for_style(
+            image.and_then(|i| i.render_style),
+            player_relations.relation_to_local(owner),
+        );
+
+        material.params = PalettedParams {
+            frame: Vec4::new(
+                rect.min.x as f32,
+                rect.min.y as f32,
+                rect.width() as f32,
+                rect.height() as f32,
+            ),
+            anchor: sprite.anchor.as_vec(),
+            flip_x: sprite.flip_x as u32,
+            remap_row: color.map_or(IDENTITY_REMAP_ROW, team_color_remap_row),
+            color: sprite.color.to_linear().to_vec4(),
+            effect: effect.shader_id(),
+            effect_color: color
+                .map_or(Color::WHITE, |c| c.color())
+                .to_linear()
+                .to_vec4(),
+            darkness: sd_palette.darkness(effect),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remap_rows() {
+        assert_eq!(team_color_remap_row(PlayerColor::Red), 1);
+        assert_eq!(
+            team_color_remap_row(PlayerColor::Azure) as usize,
+            PlayerColor::ALL.len()
+        );
+        assert_ne!(team_color_remap_row(PlayerColor::Red), IDENTITY_REMAP_ROW);
+    }
+
+    #[test]
+    fn darkening() {
+        // A greyscale palette, where each row of the table halves the brightness of the last
+        let palette = PaletteAsset {
+            colors: (0..PALETTE_SIZE).map(|i| [i as u8; 3]).collect(),
+        };
+        let dark = PcxAsset {
+            width: PALETTE_SIZE as u32,
+            height: 2,
+            pixels: (0..PALETTE_SIZE)
+                .map(|i| i as u8)
+                .chain((0..PALETTE_SIZE).map(|i| (i / 2) as u8))
+                .collect(),
+            palette: palette.clone(),
+        };
+
+        assert_eq!(darkening_amount(&dark, &palette, 0), Some(0.0));
+        let half = darkening_amount(&dark, &palette, 1).unwrap();
+        assert!((half - 0.5).abs() < 0.01, "{half}");
+        // Rows past the end of the table use the last row
+        assert_eq!(darkening_amount(&dark, &palette, 17), Some(half));
+    }
+}
@@ -0,0 +1,55 @@
+//! Rendering for BW-style segmented status bars (e.g. health, shields, and energy), drawn as a
+//! single quad per construct.
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderRef, ShaderType},
+    sprite::{Material2d, Material2dPlugin},
+};
+
+const STATUS_BAR_SHADER_PATH: &str = "shaders/status_bar.wgsl";
+
+/// The maximum number of rows a status bar can have.
+pub const MAX_STATUS_BAR_ROWS: usize = 3;
+
+pub struct StatusBarPlugin;
+
+impl Plugin for StatusBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(Material2dPlugin::<StatusBarMaterial>::default());
+    }
+}
+
+/// Uniform parameters for a [StatusBarMaterial]. Must match the `StatusBarParams` struct in the
+/// shader.
+#[derive(ShaderType, Debug, Clone, Copy, Default, PartialEq)]
+pub struct StatusBarParams {
+    /// The size of the whole bar, in pixels.
+    pub size: Vec2,
+    /// The width of a single pixel of the bar in SD graphics, in pixels.
+    pub scale: f32,
+    /// How many segments each row is split into.
+    pub segments: u32,
+    /// How many rows the bar has (at most [MAX_STATUS_BAR_ROWS]).
+    pub rows: u32,
+    /// How many segments of each row are filled in.
+    pub filled: UVec4,
+    /// The color of the filled segments of each row.
+    pub colors: [Vec4; MAX_STATUS_BAR_ROWS],
+}
+
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct StatusBarMaterial {
+    #[uniform(0)]
+    pub params: StatusBarParams,
+}
+
+impl Material2d for StatusBarMaterial {
+    fn vertex_shader() -> ShaderRef {
+        STATUS_BAR_SHADER_PATH.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        STATUS_BAR_SHADER_PATH.into()
+    }
+}
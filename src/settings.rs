@@ -1,7 +1,19 @@
-use bevy::{prelude::*, window::WindowMode};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use bevy::{
+    audio::Volume,
+    prelude::*,
+    window::{PrimaryWindow, WindowMode, WindowMoved},
+};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::input::Keybindings;
 
-#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, Reflect)]
+#[derive(Copy, Clone, Debug, PartialEq, Default, Serialize, Deserialize, Reflect)]
 #[serde(rename_all = "camelCase")]
 pub enum NeobroodWindowMode {
     Windowed,
@@ -108,7 +120,7 @@ const fn default_volume() -> f32 {
 }
 
 #[allow(unused)]
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Reflect)]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize, Reflect)]
 #[serde(rename_all = "camelCase")]
 pub struct Volumes {
     #[serde(default = "default_volume")]
@@ -129,6 +141,31 @@ impl Default for Volumes {
     }
 }
 
+const fn default_scroll_speed() -> f32 {
+    3000.0
+}
+
+/// How fast the camera scrolls, in pixels per second (at the default zoom level).
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize, Reflect)]
+#[serde(rename_all = "camelCase")]
+pub struct ScrollSpeeds {
+    /// The speed when the mouse is at the edge of the screen.
+    #[serde(default = "default_scroll_speed")]
+    pub edge: f32,
+    /// The speed when using the scroll keys (the arrow keys by default).
+    #[serde(default = "default_scroll_speed")]
+    pub keyboard: f32,
+}
+
+impl Default for ScrollSpeeds {
+    fn default() -> Self {
+        Self {
+            edge: default_scroll_speed(),
+            keyboard: default_scroll_speed(),
+        }
+    }
+}
+
 #[derive(
     Debug,
     Clone,
@@ -159,20 +196,290 @@ impl AudioQuality {
     }
 }
 
-// TODO(tec27): Write a way to configure these ingame and save them to the file
-#[derive(Resource, Clone, Copy, Debug, Default, Serialize, Deserialize, Reflect)]
+#[derive(Resource, Clone, Debug, PartialEq, Default, Serialize, Deserialize, Reflect)]
 #[serde(rename_all = "camelCase")]
 pub struct GameSettings {
     #[serde(default)]
     pub window_mode: NeobroodWindowMode,
     pub window_size: Option<(u32, u32)>,
+    /// The position of the window on the screen when the game was last closed.
+    pub window_position: Option<(i32, i32)>,
     #[serde(default)]
     pub asset_quality: AssetQuality,
     #[serde(default)]
     pub asset_pack: AssetPack,
+    /// Whether to light HD graphics using their normal, specular, and emissive layers. This has no
+    /// effect when using SD graphics.
+    #[serde(default)]
+    pub hd_lighting: bool,
+    /// Whether to show status bars (health, shields, and energy) for every construct, rather than
+    /// just selected or hovered ones.
+    #[serde(default)]
+    pub always_show_status_bars: bool,
+    #[serde(default)]
+    pub scroll_speeds: ScrollSpeeds,
 
     #[serde(default)]
     pub volumes: Volumes,
     #[serde(default)]
     pub audio_quality: AudioQuality,
+
+    #[serde(default)]
+    pub keybindings: Keybindings,
+}
+
+impl GameSettings {
+    /// Returns whether changing from these settings to `other` requires restarting the game to
+    /// take effect.
+    pub fn needs_restart_for(&self, other: &GameSettings) -> bool {
+        self.asset_quality != other.asset_quality
+            || self.asset_pack != other.asset_pack
+            || self.audio_quality != other.audio_quality
+    }
+
+    /// Applies all of the settings from `other` that can be changed while the game is running.
+    pub fn apply_live(&mut self, other: &GameSettings) {
+        let asset_quality = self.asset_quality;
+        let asset_pack = self.asset_pack;
+        let audio_quality = self.audio_quality;
+        *self = GameSettings {
+            asset_quality,
+            asset_pack,
+            audio_quality,
+            ..other.clone()
+        };
+    }
+}
+
+/// The name of the settings file, which is stored in the user's `Documents/Starcraft` directory.
+pub const SETTINGS_FILE_NAME: &str = "neobrood-settings.json";
+
+#[derive(Error, Debug)]
+pub enum SettingsError {
+    #[error("failed to access settings file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to parse settings: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Loads the settings stored at `path`.
+pub fn load_settings(path: &Path) -> Result<GameSettings, SettingsError> {
+    let file = File::open(path)?;
+    Ok(serde_json::from_reader(BufReader::new(file))?)
+}
+
+/// Saves `settings` to `path`. The settings are written to a temporary file first which then
+/// replaces the existing file, so a failed write never leaves a partially written settings file.
+pub fn save_settings(settings: &GameSettings, path: &Path) -> Result<(), SettingsError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let temp_path = path.with_extension("json.tmp");
+    {
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+        serde_json::to_writer_pretty(&mut writer, settings)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+    }
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+/// Resource that specifies where the settings are stored, and what was last saved there. This is
+/// not present if settings can't be saved.
+#[derive(Resource, Debug)]
+pub struct SettingsFile {
+    pub path: PathBuf,
+    pub saved: GameSettings,
+}
+
+/// Event that saves the included settings to the [SettingsFile].
+#[derive(Event, Debug, Clone)]
+pub struct SaveSettingsEvent(pub GameSettings);
+
+/// Plugin that saves settings to disk and applies changes to them to the window and audio.
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SaveSettingsEvent>()
+            .add_systems(
+                Update,
+                (
+                    apply_window_settings.run_if(resource_changed::<GameSettings>),
+                    apply_global_volume.run_if(resource_changed::<GameSettings>),
+                    write_settings.run_if(resource_exists::<SettingsFile>),
+                    watch_settings_file.run_if(resource_exists::<SettingsFile>),
+                ),
+            )
+            .add_systems(
+                Last,
+                save_window_position.run_if(resource_exists::<SettingsFile>),
+            );
+    }
+}
+
+fn write_settings(
+    mut events: EventReader<SaveSettingsEvent>,
+    mut settings_file: ResMut<SettingsFile>,
+) {
+    let Some(SaveSettingsEvent(settings)) = events.read().last() else {
+        return;
+    };
+
+    match save_settings(settings, &settings_file.path) {
+        Ok(()) => {
+            info!("Saved settings to {:?}", settings_file.path);
+            settings_file.saved = settings.clone();
+        }
+        Err(e) => error!("Failed to save settings to {:?}: {e}", settings_file.path),
+    }
+}
+
+/// How often the settings file is checked for changes made outside of the game.
+const SETTINGS_WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+struct SettingsWatchState {
+    timer: Timer,
+    last_modified: Option<SystemTime>,
+}
+
+impl Default for SettingsWatchState {
+    fn default() -> Self {
+        Self {
+            timer: Timer::new(SETTINGS_WATCH_INTERVAL, TimerMode::Repeating),
+            last_modified: None,
+        }
+    }
+}
+
+/// Applies changes made to the settings file while the game is running (e.g. by editing it by
+/// hand). If the file can't be loaded, the current settings are kept.
+fn watch_settings_file(
+    mut state: Local<SettingsWatchState>,
+    time: Res<Time>,
+    mut settings_file: ResMut<SettingsFile>,
+    mut settings: ResMut<GameSettings>,
+) {
+    if !state.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Ok(modified) = fs::metadata(&settings_file.path).and_then(|m| m.modified()) else {
+        return;
+    };
+    let last_modified = state.last_modified.replace(modified);
+    if last_modified.is_none() || last_modified == Some(modified) {
+        return;
+    }
+
+    match load_settings(&settings_file.path) {
+        // This is usually a file we just saved ourselves
+        Ok(loaded) if loaded == settings_file.saved => {}
+        Ok(loaded) => {
+            info!("Settings file changed, applying the new settings");
+            if settings.needs_restart_for(&loaded) {
+                warn!("Some of the changed settings will only apply after restarting the game");
+            }
+            settings.apply_live(&loaded);
+            settings_file.saved = loaded;
+        }
+        Err(e) => error!("Failed to reload settings file, keeping the current settings: {e}"),
+    }
+}
+
+/// Keeps track of where the window is, and saves it to the settings file when the game exits so
+/// that it can be restored on the next launch.
+fn save_window_position(
+    mut position: Local<Option<IVec2>>,
+    mut moved_events: EventReader<WindowMoved>,
+    mut exit_events: EventReader<AppExit>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    mut settings_file: ResMut<SettingsFile>,
+) {
+    for event in moved_events.read() {
+        if primary_window.contains(event.window) {
+            *position = Some(event.position);
+        }
+    }
+    if exit_events.is_empty() {
+        return;
+    }
+    exit_events.clear();
+
+    let Some(position) = *position else {
+        return;
+    };
+    let mut settings = settings_file.saved.clone();
+    settings.window_position = Some((position.x, position.y));
+    match save_settings(&settings, &settings_file.path) {
+        Ok(()) => settings_file.saved = settings,
+        Err(e) => error!("Failed to save window position: {e}"),
+    }
+}
+
+fn apply_window_settings(
+    settings: Res<GameSettings>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = window.get_single_mut() else {
+        return;
+    };
+
+    let mode = settings.window_mode.into();
+    if window.mode != mode {
+        window.mode = mode;
+    }
+    if let Some((width, height)) = settings.window_size {
+        let (width, height) = (width as f32, height as f32);
+        if window.resolution.width() != width || window.resolution.height() != height {
+            window.resolution.set(width, height);
+        }
+    }
+}
+
+fn apply_global_volume(settings: Res<GameSettings>, mut global_volume: ResMut<GlobalVolume>) {
+    let volume = Volume::new(settings.volumes.global);
+    if global_volume.volume.get() != volume.get() {
+        global_volume.volume = volume;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_and_load() {
+        let dir = std::env::temp_dir().join(format!("neobrood-settings-{}", std::process::id()));
+        let path = dir.join(SETTINGS_FILE_NAME);
+        let settings = GameSettings {
+            window_position: Some((-20, 300)),
+            hd_lighting: true,
+            ..default()
+        };
+
+        save_settings(&settings, &path).unwrap();
+        let loaded = load_settings(&path).unwrap();
+        assert_eq!(loaded.window_position, Some((-20, 300)));
+        assert!(loaded.hd_lighting);
+        assert!(!path.with_extension("json.tmp").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn live_settings() {
+        let mut live = GameSettings::default();
+        let changed = GameSettings {
+            asset_quality: AssetQuality::Standard,
+            always_show_status_bars: true,
+            ..default()
+        };
+        assert!(live.needs_restart_for(&changed));
+
+        live.apply_live(&changed);
+        assert!(live.always_show_status_bars);
+        assert_eq!(live.asset_quality, AssetQuality::High);
+        assert!(live.needs_restart_for(&changed));
+    }
 }
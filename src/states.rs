@@ -7,11 +7,16 @@ pub enum AppState {
     /// State that shows a menu for selecting options and starting a game.
     #[default]
     Menu,
+    /// State that shows the lobby for configuring the players in a game on the selected map.
+    Lobby,
     /// Transitory state that ensures the necessary game resources are loaded before a game starts.
     /// This will transition to `InGame` once the resources are ready.
     PreGame,
     /// State that runs actual gameplay.
     InGame,
+    /// State that shows which game files failed to load (preventing a game from starting), before
+    /// returning to the `Menu`.
+    LoadError,
 }
 
 /// Marker component for entities that should be despawned when exiting the `AppState::InGame`